@@ -0,0 +1,131 @@
+use crate::{conventions, AcronymStyle};
+
+/// Acronyms we know how to split out of a run of capitals, e.g. `XMLHTTP`.
+const KNOWN_ACRONYMS: &[&str] = &[
+    "API", "CSS", "CSV", "DOM", "HTML", "HTTP", "HTTPS", "ID", "JSON", "JWT",
+    "SQL", "SVG", "UI", "URI", "URL", "UUID", "XML",
+];
+
+/// `s`, a name with acronyms in it like `XMLHTTPRequest`, in kebab-case,
+/// splitting its runs of capitals the way `--acronym-style` says
+pub fn acronym_to_kebab(s: &str) -> String {
+    acronym_to_kebab_as(s, conventions::get().acronyms)
+}
+
+/// `s` in kebab-case, splitting its runs of capitals the way `style` says
+pub fn acronym_to_kebab_as(s: &str, style: AcronymStyle) -> String {
+    acronym_words(s)
+        .iter()
+        .flat_map(|word| match style {
+            AcronymStyle::Dictionary => split_acronym_run(word),
+            AcronymStyle::First => split_acronym_run_shortest(word),
+            AcronymStyle::Last => vec![word.as_str()],
+        })
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Splits a name at its case changes, keeping each run of capitals whole:
+/// `XMLHTTPRequest` is `XMLHTTP`, `Request`
+fn acronym_words(s: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let chars: Vec<char> = s.chars().collect();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && !current.is_empty() {
+            let prev = chars[i - 1];
+            let next_is_lower =
+                chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            // Start a new word after a lowercase letter, or at the last
+            // capital of a run when it begins a lowercase word (`XMLParser`)
+            if !prev.is_uppercase() || next_is_lower {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// Splits a run of capitals into known acronyms (`XMLHTTP` -> `XML`, `HTTP`).
+/// Runs that can't be fully segmented are kept as a single word.
+fn split_acronym_run(word: &str) -> Vec<&str> {
+    fn segment<'a>(run: &'a str, out: &mut Vec<&'a str>) -> bool {
+        if run.is_empty() {
+            return true;
+        }
+        // Prefer the longest match so `HTTPS` wins over `HTTP`
+        for len in (2..=run.len()).rev() {
+            if KNOWN_ACRONYMS.contains(&&run[..len]) {
+                out.push(&run[..len]);
+                if segment(&run[len..], out) {
+                    return true;
+                }
+                out.pop();
+            }
+        }
+        false
+    }
+
+    if word.len() < 2 || !word.chars().all(|c| c.is_ascii_uppercase()) {
+        return vec![word];
+    }
+    let mut parts = Vec::new();
+    if segment(word, &mut parts) {
+        parts
+    } else {
+        vec![word]
+    }
+}
+
+/// Splits a run of capitals into the shortest known acronyms, one letter at
+/// a time where none fits (`HTTPS` -> `HTTP`, `S`)
+fn split_acronym_run_shortest(word: &str) -> Vec<&str> {
+    if !word.chars().all(|c| c.is_ascii_uppercase()) {
+        return vec![word];
+    }
+    let mut parts = Vec::new();
+    let mut rest = word;
+    while !rest.is_empty() {
+        let len = (2..=rest.len())
+            .find(|&len| KNOWN_ACRONYMS.contains(&&rest[..len]))
+            .unwrap_or(1);
+        parts.push(&rest[..len]);
+        rest = &rest[len..];
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_acronym_styles() {
+        let styles = |name| {
+            [
+                AcronymStyle::Dictionary,
+                AcronymStyle::First,
+                AcronymStyle::Last,
+            ]
+            .map(|style| acronym_to_kebab_as(name, style))
+        };
+        assert_eq!(
+            styles("HTTPSProxy"),
+            ["https-proxy", "http-s-proxy", "https-proxy"]
+        );
+        assert_eq!(
+            styles("XMLHTTPRequest"),
+            ["xml-http-request", "xml-http-request", "xmlhttp-request"]
+        );
+        assert_eq!(
+            styles("ABCWidget"),
+            ["abc-widget", "a-b-c-widget", "abc-widget"]
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// An import prefix that stands for a directory, like `@components` for
+/// `src/components`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alias {
+    pub prefix: String,
+    pub dir: PathBuf,
+}
+
+/// The directory aliases of the project at `root`: the `paths` of its
+/// `tsconfig.json` or `jsconfig.json` that map `prefix/*` to a directory,
+/// then each `PREFIX=DIR` in `extra`, with `DIR` relative to `root`
+pub fn load(root: &Path, extra: &[String]) -> Result<Vec<Alias>> {
+    let mut aliases = Vec::new();
+    for name in ["tsconfig.json", "jsconfig.json"] {
+        let path = root.join(name);
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let config: Value = serde_json::from_str(&strip_jsonc(&content))
+            .with_context(|| format!("Malformed {}", path.display()))?;
+        aliases.extend(from_config(root, &config));
+        break;
+    }
+    for alias in extra {
+        let Some((prefix, dir)) = alias.split_once('=') else {
+            anyhow::bail!("Invalid alias '{}': expected PREFIX=DIR", alias);
+        };
+        aliases.push(Alias {
+            prefix: prefix.trim_end_matches('/').to_string(),
+            dir: crate::resolve::normalize(&root.join(dir)),
+        });
+    }
+    Ok(aliases)
+}
+
+/// `compilerOptions.paths` entries like `"@components/*":
+/// ["src/components/*"]`, relative to `compilerOptions.baseUrl`
+fn from_config(root: &Path, config: &Value) -> Vec<Alias> {
+    let options = config.get("compilerOptions");
+    let base = options
+        .and_then(|o| o.get("baseUrl"))
+        .and_then(Value::as_str)
+        .unwrap_or(".");
+    let Some(paths) = options
+        .and_then(|o| o.get("paths"))
+        .and_then(Value::as_object)
+    else {
+        return Vec::new();
+    };
+
+    let mut aliases = Vec::new();
+    for (pattern, targets) in paths {
+        let target = targets.get(0).and_then(Value::as_str);
+        let prefix = pattern.strip_suffix("/*");
+        let dir = target.and_then(|t| t.strip_suffix("/*"));
+        if let (Some(prefix), Some(dir)) = (prefix, dir) {
+            aliases.push(Alias {
+                prefix: prefix.to_string(),
+                dir: crate::resolve::normalize(&root.join(base).join(dir)),
+            });
+        }
+    }
+    aliases
+}
+
+/// Drops the comments and trailing commas `tsconfig.json` allows, leaving
+/// strings alone
+pub fn strip_jsonc(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                out.push(c);
+                while let Some(c) = chars.next() {
+                    out.push(c);
+                    match c {
+                        '\\' => out.extend(chars.next()),
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                while chars.peek().is_some_and(|&c| c != '\n') {
+                    chars.next();
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut last = ' ';
+                for c in chars.by_ref() {
+                    if last == '*' && c == '/' {
+                        break;
+                    }
+                    last = c;
+                }
+            }
+            '}' | ']' => {
+                let end = out.trim_end().len();
+                if out[..end].ends_with(',') {
+                    out.truncate(end - 1);
+                }
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_tsconfig_aliases() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::write(
+            root.join("tsconfig.json"),
+            r#"{
+  // Comments and trailing commas are allowed
+  "compilerOptions": {
+    "baseUrl": "./src",
+    "paths": {
+      "@components/*": ["components/*"], /* "~/*": ["*"] */
+      "@config": ["config/index.ts"],
+      "~/*": ["./*",],
+    },
+  },
+}"#,
+        )?;
+
+        let aliases = load(root, &["#lib=packages/lib/".to_string()])?;
+        assert_eq!(
+            aliases,
+            [
+                Alias {
+                    prefix: "@components".into(),
+                    dir: root.join("src/components"),
+                },
+                Alias {
+                    prefix: "~".into(),
+                    dir: root.join("src"),
+                },
+                Alias {
+                    prefix: "#lib".into(),
+                    dir: root.join("packages/lib"),
+                },
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,245 @@
+use crate::acronyms::acronym_to_kebab_as;
+use crate::messages::{text, Msg};
+use crate::plan::Plan;
+use crate::report::Aborted;
+use crate::{brands, conventions};
+use crate::{
+    confirm, convert_name_as, locale, pascal_to_kebab, pascal_to_kebab_smart,
+    scanner, AcronymStyle, NameStyle, Options,
+};
+use anyhow::Result;
+use clap::ValueEnum;
+use regex::Regex;
+use std::path::PathBuf;
+
+/// A planned rename whose new name depends on a guess, like where the words
+/// in `XMLHTTPRequest` or `Vector3D` begin
+#[derive(Debug, PartialEq, Eq)]
+pub struct Ambiguous {
+    pub path: PathBuf,
+    pub chosen: String,
+    /// What made it a guess: acronym runs, digits, single letters
+    pub reasons: Vec<&'static str>,
+    /// What the other readings of the name would have converted to
+    pub alternatives: Vec<String>,
+}
+
+/// The renames in `plan` whose new names were guessed. Names that were
+/// numbered to avoid a collision are left out, as they're already reported.
+pub fn find(plan: &Plan) -> Vec<Ambiguous> {
+    let mut found = Vec::new();
+    for rename in &plan.renames {
+        let (Some(from), Some(to)) = (
+            rename.from.file_name().and_then(|n| n.to_str()),
+            rename.to.file_name().and_then(|n| n.to_str()),
+        ) else {
+            continue;
+        };
+        let style = scanner::name_style(&rename.from, rename.from.is_dir());
+        if convert_name_as(from, style) != to {
+            continue;
+        }
+        if let Some((reasons, alternatives)) = name_alternatives(from, style) {
+            found.push(Ambiguous {
+                path: rename.from.clone(),
+                chosen: to.to_string(),
+                reasons,
+                alternatives,
+            });
+        }
+    }
+    found
+}
+
+/// Why converting `name` takes a guess, and what else it could become
+fn name_alternatives(
+    name: &str,
+    style: NameStyle,
+) -> Option<(Vec<&'static str>, Vec<String>)> {
+    let chosen = convert_name_as(name, style);
+    let parts: Vec<&str> = name.split('.').collect();
+    let chosen_parts: Vec<&str> = chosen.split('.').collect();
+    let mut reasons = Vec::new();
+    let mut alternatives = Vec::new();
+
+    for (i, part) in parts.iter().enumerate() {
+        // Parts the conversion leaves alone, like the `mp4` of
+        // `IntroVideo.mp4`, weren't guessed at
+        if locale::canonical(part).is_some() || chosen_parts[i] == *part {
+            continue;
+        }
+        let words: Vec<&str> = match style {
+            NameStyle::Kebab => vec![part],
+            NameStyle::Snake => part.split('_').collect(),
+        };
+        for (j, word) in words.iter().enumerate() {
+            let Some((why, others)) = word_alternatives(word) else {
+                continue;
+            };
+            for reason in why {
+                if !reasons.contains(&reason) {
+                    reasons.push(reason);
+                }
+            }
+            for other in others {
+                let mut new_parts = chosen_parts.clone();
+                let joined;
+                if style == NameStyle::Snake {
+                    let mut new_words: Vec<String> = chosen_parts[i]
+                        .split('_')
+                        .map(str::to_string)
+                        .collect();
+                    new_words[j] = other.replace('-', "_");
+                    joined = new_words.join("_");
+                } else {
+                    joined = other;
+                }
+                new_parts[i] = &joined;
+                let alternative = new_parts.join(".");
+                if alternative != chosen && !alternatives.contains(&alternative)
+                {
+                    alternatives.push(alternative);
+                }
+            }
+        }
+    }
+    (!alternatives.is_empty()).then_some((reasons, alternatives))
+}
+
+/// Why converting one word of a name takes a guess, and the kebab-case
+/// forms its other readings give
+fn word_alternatives(word: &str) -> Option<(Vec<&'static str>, Vec<String>)> {
+    // Brand names convert the way they're known
+    if brands::split(word, &conventions::get().brands).is_some() {
+        return None;
+    }
+    let chosen = pascal_to_kebab_smart(word);
+    let mut reasons = Vec::new();
+    let mut others = Vec::new();
+
+    let chars: Vec<char> = word.chars().collect();
+    if chars
+        .windows(2)
+        .any(|w| w[0].is_uppercase() && w[1].is_uppercase())
+    {
+        reasons.push("run of capitals");
+        // What the other --acronym-style choices give, and every capital
+        // taken as a word of its own
+        for style in AcronymStyle::value_variants() {
+            others.push(acronym_to_kebab_as(word, *style));
+        }
+        others.push(pascal_to_kebab(word));
+    }
+
+    if chosen.chars().any(|c| c.is_ascii_digit())
+        && chosen.chars().any(|c| c.is_alphabetic())
+    {
+        reasons.push("digits");
+        let before = Regex::new(r"([\p{Alphabetic}])(\d)").unwrap();
+        let after = Regex::new(r"(\d)-").unwrap();
+        let split = before.replace_all(&chosen, "$1-$2").to_string();
+        others.push(split.clone());
+        others.push(after.replace_all(&chosen, "$1").to_string());
+        others.push(after.replace_all(&split, "$1").to_string());
+    }
+
+    let words: Vec<&str> = chosen.split('-').collect();
+    // A letter after a number, like the `d` of `vector3-d`, is down to
+    // the digits
+    let single = |i: usize| {
+        let mut chars = words[i].chars();
+        chars.next().is_some_and(char::is_alphabetic)
+            && chars.next().is_none()
+            && (i == 0 || !words[i - 1].ends_with(|c: char| c.is_ascii_digit()))
+    };
+    let singles: Vec<usize> = (0..words.len()).filter(|&i| single(i)).collect();
+    if words.len() > 1 && !singles.is_empty() {
+        reasons.push("single-letter word");
+        for i in singles {
+            let word = words[i];
+            // Joined to the word it most likely belongs with
+            let mut joined = words.clone();
+            let merged = match i {
+                0 => format!("{}{}", word, words[1]),
+                _ => format!("{}{}", words[i - 1], word),
+            };
+            let at = i.saturating_sub(1);
+            joined.splice(at..at + 2, [merged.as_str()]);
+            others.push(joined.join("-"));
+        }
+    }
+
+    let mut seen = vec![chosen];
+    others.retain(|other| {
+        let new = !seen.contains(other);
+        seen.push(other.clone());
+        new
+    });
+    (!others.is_empty()).then_some((reasons, others))
+}
+
+/// Lists the guessed names in `plan` before they're applied and, on a
+/// terminal, asks whether to go ahead unless `--yes` was given
+pub fn review(plan: &Plan, opts: &Options) -> Result<()> {
+    let ambiguous = find(plan);
+    if ambiguous.is_empty() {
+        return Ok(());
+    }
+    print(&ambiguous, opts);
+    if opts.yes || !std::io::IsTerminal::is_terminal(&std::io::stdin()) {
+        return Ok(());
+    }
+    if confirm(&text(Msg::ApplyGuesses, &[]))? {
+        return Ok(());
+    }
+    Err(Aborted(text(Msg::GuessesDeclined, &[])).into())
+}
+
+pub fn print(ambiguous: &[Ambiguous], opts: &Options) {
+    opts.log(format_args!("{}", text(Msg::GuessedNames, &[])));
+    for name in ambiguous {
+        opts.log(format_args!(
+            "{}",
+            text(
+                Msg::GuessedName,
+                &[
+                    &opts.display.show(&name.path),
+                    &name.chosen,
+                    &name.reasons.join(", "),
+                    &name.alternatives.join(", ")
+                ]
+            )
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ambiguous_names() {
+        let alternatives = |name| name_alternatives(name, NameStyle::Kebab);
+        assert_eq!(
+            alternatives("XMLHTTPRequest.ts"),
+            Some((
+                vec!["run of capitals"],
+                vec![
+                    "xmlhttp-request.ts".to_string(),
+                    "x-m-l-h-t-t-p-request.ts".to_string()
+                ]
+            ))
+        );
+        assert_eq!(
+            alternatives("Vector3D.ts"),
+            Some((vec!["digits"], vec!["vector-3d.ts".to_string()]))
+        );
+        assert_eq!(
+            alternatives("GetX.ts"),
+            Some((vec!["single-letter word"], vec!["getx.ts".to_string()]))
+        );
+        assert_eq!(alternatives("UserCard.ts"), None);
+        assert_eq!(alternatives("IntroVideo.mp4"), None);
+        assert_eq!(alternatives("Messages.en-US.json"), None);
+    }
+}
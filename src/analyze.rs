@@ -0,0 +1,170 @@
+use crate::plan::Plan;
+use crate::report::Report;
+use crate::{detect_case, encoding, needs_conversion_as, scanner, Case};
+use crate::{relative_to, Options};
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How many directories `print` lists
+const TOP_DIRS: usize = 10;
+
+/// The shape of a tree's naming, for scoping a migration with `--analyze`
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Analysis {
+    /// Files by the case their name is in
+    pub cases: BTreeMap<&'static str, usize>,
+    /// Directories by how many of the names directly in them need
+    /// converting, most first
+    pub dirs: Vec<(PathBuf, usize)>,
+    /// Lines `-i` would rewrite, and the files they're in
+    pub references: usize,
+    pub files_to_update: usize,
+}
+
+/// What `--analyze` calls the case of `name`, going by its stem
+fn case_name(name: &str) -> &'static str {
+    let stem = name.split('.').next().filter(|s| !s.is_empty());
+    let stem = stem.unwrap_or(name);
+    match detect_case(stem) {
+        Case::Pascal => "PascalCase",
+        Case::Camel => "camelCase",
+        Case::Acronym => "ACRONYM",
+        Case::Kebab if stem.contains('_') => "snake_case",
+        Case::Kebab => "kebab-case",
+    }
+}
+
+/// Counts the cases of the names under `dir`, where the names that need
+/// converting are, and roughly how many references would change, without
+/// planning any renames
+pub fn analyze(
+    dir: &Path,
+    opts: &Options,
+    report: &mut Report,
+) -> Result<Analysis> {
+    let mut analysis = Analysis::default();
+    let mut dirs: BTreeMap<PathBuf, usize> = BTreeMap::new();
+    for entry in crate::walk(dir, opts, report)?
+        .iter()
+        .filter(|e| e.depth() > 0)
+    {
+        let Some(name) = entry.file_name().to_str() else {
+            continue;
+        };
+        let is_dir = entry.file_type().is_dir();
+        if !is_dir {
+            *analysis.cases.entry(case_name(name)).or_default() += 1;
+        }
+        let style = scanner::name_style(entry.path(), is_dir);
+        if needs_conversion_as(name, style)
+            && scanner::pinned(entry.path(), is_dir).is_none()
+        {
+            let parent = entry.path().parent().unwrap_or(dir);
+            *dirs
+                .entry(relative_to(parent, dir).to_path_buf())
+                .or_default() += 1;
+        }
+    }
+    analysis.dirs = dirs.into_iter().collect();
+    analysis
+        .dirs
+        .sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    // Only what the names are now is known, so this counts the references
+    // that aren't converted, not the ones a rename would leave behind
+    let plan = Plan::default();
+    for entry in crate::source_files(dir, opts, report)? {
+        let path = entry.path();
+        let Some((content, _)) =
+            fs::read(path).ok().and_then(|b| encoding::decode(&b))
+        else {
+            continue;
+        };
+        let (converted, _) =
+            scanner::convert_file(path, &content, &opts.scan, &plan, &plan);
+        let changes = content
+            .lines()
+            .zip(converted.lines())
+            .filter(|(old, new)| old != new)
+            .count();
+        if changes > 0 {
+            analysis.references += changes;
+            analysis.files_to_update += 1;
+        }
+    }
+    Ok(analysis)
+}
+
+impl Analysis {
+    pub fn print(&self) {
+        let files: usize = self.cases.values().sum();
+        println!("{} files by case:", files);
+        let mut cases: Vec<_> = self.cases.iter().collect();
+        cases.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        for (case, count) in cases {
+            println!("  {:<12} {}", case, count);
+        }
+        let names: usize = self.dirs.iter().map(|(_, n)| n).sum();
+        println!("{} names to convert", names);
+        if !self.dirs.is_empty() {
+            println!("Directories with the most:");
+        }
+        for (dir, count) in self.dirs.iter().take(TOP_DIRS) {
+            let dir = match dir.as_os_str().is_empty() {
+                true => Path::new("."),
+                false => dir,
+            };
+            println!("  {:<5} {}", count, dir.display());
+        }
+        println!(
+            "About {} references to update in {} files",
+            self.references, self.files_to_update
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_analyze() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src/Components"))?;
+        fs::write(root.join("src/Components/UserCard.tsx"), "")?;
+        fs::write(root.join("src/Components/XMLParser.ts"), "")?;
+        fs::write(root.join("src/userStore.ts"), "")?;
+        fs::write(root.join("src/api_client.ts"), "")?;
+        fs::write(
+            root.join("src/index.ts"),
+            "import card from './Components/UserCard';\n",
+        )?;
+
+        let analysis =
+            analyze(root, &Options::default(), &mut Report::default())?;
+        let cases: Vec<_> = analysis.cases.into_iter().collect();
+        assert_eq!(
+            cases,
+            [
+                ("ACRONYM", 1),
+                ("PascalCase", 1),
+                ("camelCase", 1),
+                ("kebab-case", 1),
+                ("snake_case", 1),
+            ]
+        );
+        assert_eq!(
+            analysis.dirs,
+            [
+                (PathBuf::from("src"), 2),
+                (PathBuf::from("src/Components"), 2)
+            ]
+        );
+        assert_eq!((analysis.references, analysis.files_to_update), (1, 1));
+        Ok(())
+    }
+}
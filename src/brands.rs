@@ -0,0 +1,166 @@
+use anyhow::{bail, Result};
+
+/// Names whose usual lowercase form isn't what splitting them at their
+/// capitals gives: `github`, not `git-hub`
+pub const BUILTIN: &[(&str, &str)] = &[
+    ("CoffeeScript", "coffeescript"),
+    ("DevOps", "devops"),
+    ("DynamoDB", "dynamodb"),
+    ("FastAPI", "fastapi"),
+    ("GitHub", "github"),
+    ("GitLab", "gitlab"),
+    ("GraphQL", "graphql"),
+    ("JavaScript", "javascript"),
+    ("LinkedIn", "linkedin"),
+    ("MongoDB", "mongodb"),
+    ("MySQL", "mysql"),
+    ("NodeJS", "nodejs"),
+    ("OAuth", "oauth"),
+    ("OpenAPI", "openapi"),
+    ("PayPal", "paypal"),
+    ("PostgreSQL", "postgresql"),
+    ("PowerShell", "powershell"),
+    ("PyTorch", "pytorch"),
+    ("SQLite", "sqlite"),
+    ("TensorFlow", "tensorflow"),
+    ("TypeScript", "typescript"),
+    ("VSCode", "vscode"),
+    ("WebAssembly", "webassembly"),
+    ("WebGL", "webgl"),
+    ("WebRTC", "webrtc"),
+    ("WebSocket", "websocket"),
+    ("WordPress", "wordpress"),
+    ("YouTube", "youtube"),
+    ("iOS", "ios"),
+    ("iPad", "ipad"),
+    ("iPadOS", "ipados"),
+    ("iPhone", "iphone"),
+    ("jQuery", "jquery"),
+    ("macOS", "macos"),
+    ("tvOS", "tvos"),
+    ("watchOS", "watchos"),
+];
+
+/// A brand name as written in names, and the form it converts to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Brand {
+    pub name: String,
+    pub form: String,
+}
+
+/// The brands to use: the built-in ones unless `builtin` is false, with
+/// those from `--brand NAME[=FORM]` added or taking their place
+pub fn load(specs: &[String], builtin: bool) -> Result<Vec<Brand>> {
+    let mut brands: Vec<Brand> = match builtin {
+        true => BUILTIN
+            .iter()
+            .map(|(name, form)| Brand {
+                name: name.to_string(),
+                form: form.to_string(),
+            })
+            .collect(),
+        false => Vec::new(),
+    };
+    for spec in specs {
+        let (name, form) = match spec.split_once('=') {
+            Some((name, form)) => (name, form.to_string()),
+            None => (spec.as_str(), spec.to_lowercase()),
+        };
+        if name.is_empty() || form.is_empty() {
+            bail!("Invalid --brand {:?}: expected NAME or NAME=FORM", spec);
+        }
+        if form.chars().any(char::is_uppercase) {
+            bail!("Invalid --brand {:?}: {:?} isn't lowercase", spec, form);
+        }
+        brands.retain(|b| !b.name.eq_ignore_ascii_case(name));
+        brands.push(Brand {
+            name: name.to_string(),
+            form,
+        });
+    }
+    // Longest first, so `iPadOS` wins over `iPad`
+    brands.sort_by_key(|b| std::cmp::Reverse(b.name.len()));
+    Ok(brands)
+}
+
+/// Part of a word split around the brands in it
+#[derive(Debug, PartialEq, Eq)]
+pub enum Segment<'a> {
+    Brand(&'a str),
+    Text(&'a str),
+}
+
+/// Splits `word` around the brand names in it, or returns `None` if there
+/// are none. A brand has to start a word, so it's matched at the start or
+/// where it begins with a capital, and mustn't run on into lowercase
+/// letters: `WebSockets` isn't `WebSocket`.
+pub fn split<'a>(
+    word: &'a str,
+    brands: &'a [Brand],
+) -> Option<Vec<Segment<'a>>> {
+    let mut segments = Vec::new();
+    let mut text_start = 0;
+    let mut i = 0;
+    while i < word.len() {
+        let rest = &word[i..];
+        let found = brands.iter().find(|brand| {
+            rest.starts_with(&brand.name)
+                && (i == 0 || brand.name.starts_with(char::is_uppercase))
+                && !rest[brand.name.len()..].starts_with(char::is_lowercase)
+        });
+        match found {
+            Some(brand) => {
+                if text_start < i {
+                    segments.push(Segment::Text(&word[text_start..i]));
+                }
+                segments.push(Segment::Brand(&brand.form));
+                i += brand.name.len();
+                text_start = i;
+            }
+            None => i += rest.chars().next().map_or(1, char::len_utf8),
+        }
+    }
+    if segments.is_empty() {
+        return None;
+    }
+    if text_start < word.len() {
+        segments.push(Segment::Text(&word[text_start..]));
+    }
+    Some(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_brand_segments() -> Result<()> {
+        let brands = load(&["ChatGPT".to_string()], true)?;
+        assert_eq!(
+            split("UseGitHubAPI", &brands),
+            Some(vec![
+                Segment::Text("Use"),
+                Segment::Brand("github"),
+                Segment::Text("API")
+            ])
+        );
+        assert_eq!(
+            split("iPadOSWidget", &brands),
+            Some(vec![Segment::Brand("ipados"), Segment::Text("Widget")])
+        );
+        assert_eq!(split("WebSocketsServer", &brands), None);
+        assert_eq!(split("UseiOS", &brands), None);
+        assert_eq!(
+            split("ChatGPTClient", &brands),
+            Some(vec![Segment::Brand("chatgpt"), Segment::Text("Client")])
+        );
+
+        let brands = load(&["GitHub=git-hub".to_string()], true)?;
+        assert_eq!(
+            split("GitHub", &brands),
+            Some(vec![Segment::Brand("git-hub")])
+        );
+        assert!(load(&["Foo=Bar".to_string()], true).is_err());
+        Ok(())
+    }
+}
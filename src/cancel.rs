@@ -0,0 +1,112 @@
+use anyhow::Result;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Exit code after Ctrl-C or SIGTERM, as shells report for SIGINT
+pub const EXIT_CANCELLED: i32 = 130;
+
+#[cfg(not(test))]
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+
+// Tests run side by side on their own threads, so cancelling one must
+// leave the others running
+#[cfg(test)]
+thread_local! {
+    static CANCELLED: AtomicBool = const { AtomicBool::new(false) };
+    static COUNTDOWN: std::cell::Cell<Option<usize>> =
+        const { std::cell::Cell::new(None) };
+}
+
+/// Returned from a checkpoint once the run has been cancelled
+#[derive(Debug)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// Traps Ctrl-C and SIGTERM so the run stops between file operations
+/// instead of in the middle of one. A second signal exits immediately.
+pub fn install() -> Result<()> {
+    ctrlc::set_handler(|| {
+        if cancel() {
+            std::process::exit(EXIT_CANCELLED);
+        }
+        eprintln!("\nStopping after the current file (press Ctrl-C again to quit now)");
+    })?;
+    Ok(())
+}
+
+/// Fails with `Cancelled` if a signal arrived. Called between operations,
+/// where stopping leaves every file whole.
+pub fn checkpoint() -> Result<()> {
+    #[cfg(test)]
+    COUNTDOWN.with(|countdown| match countdown.get() {
+        Some(0) => {
+            countdown.set(None);
+            cancel();
+        }
+        left => countdown.set(left.map(|n| n - 1)),
+    });
+    if is_set() {
+        return Err(Cancelled.into());
+    }
+    Ok(())
+}
+
+pub fn is_cancelled(err: &anyhow::Error) -> bool {
+    err.is::<Cancelled>()
+}
+
+/// Marks the run cancelled, returning whether it already was
+fn cancel() -> bool {
+    #[cfg(not(test))]
+    return CANCELLED.swap(true, Ordering::SeqCst);
+    #[cfg(test)]
+    CANCELLED.with(|cancelled| cancelled.swap(true, Ordering::SeqCst))
+}
+
+fn is_set() -> bool {
+    #[cfg(not(test))]
+    return CANCELLED.load(Ordering::SeqCst);
+    #[cfg(test)]
+    CANCELLED.with(|cancelled| cancelled.load(Ordering::SeqCst))
+}
+
+/// Lets `n` more checkpoints on this thread pass, then cancels the run as
+/// a signal arriving then would
+#[cfg(test)]
+pub fn cancel_after(n: usize) {
+    COUNTDOWN.with(|countdown| countdown.set(Some(n)));
+}
+
+/// Lets the next run on this thread go ahead
+#[cfg(test)]
+pub fn reset() {
+    CANCELLED.with(|cancelled| cancelled.store(false, Ordering::SeqCst));
+    COUNTDOWN.with(|countdown| countdown.set(None));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint_after_a_signal() {
+        assert!(checkpoint().is_ok());
+        assert!(!cancel());
+        let err = checkpoint().unwrap_err();
+        assert!(is_cancelled(&err));
+        assert!(!is_cancelled(&anyhow::anyhow!("Failed to read file")));
+
+        reset();
+        cancel_after(1);
+        assert!(checkpoint().is_ok());
+        assert!(is_cancelled(&checkpoint().unwrap_err()));
+        reset();
+    }
+}
@@ -0,0 +1,269 @@
+use crate::plan::Plan;
+use crate::scanner::{self, rewrite_file_path, splice, ScanOptions, Scanner};
+use anyhow::{bail, Context, Result};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where GitHub and GitLab look for a CODEOWNERS file, in the order they do
+const LOCATIONS: [&str; 3] =
+    [".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+/// The paths a team owns according to a CODEOWNERS file, for `--owned-by`
+#[derive(Debug)]
+pub struct Owners {
+    /// Each rule's pattern and whether the team is among its owners, in
+    /// file order. The last rule matching a path decides who owns it.
+    rules: Vec<(GlobSet, bool)>,
+    /// The processed directory, relative to the CODEOWNERS file's repository
+    prefix: PathBuf,
+}
+
+impl Owners {
+    /// Reads the CODEOWNERS file for `root`, looking in `root` and then in
+    /// each directory above it
+    pub fn load(root: &Path, team: &str) -> Result<Self> {
+        let root = fs::canonicalize(root)
+            .with_context(|| format!("Failed to resolve {}", root.display()))?;
+        for repo in root.ancestors() {
+            for location in LOCATIONS {
+                let path = repo.join(location);
+                let Ok(content) = fs::read_to_string(&path) else {
+                    continue;
+                };
+                let prefix = root.strip_prefix(repo).unwrap_or(Path::new(""));
+                return parse(&content, team, prefix).with_context(|| {
+                    format!("Failed to read {}", path.display())
+                });
+            }
+        }
+        bail!(
+            "--owned-by needs a CODEOWNERS file, and there's none in {} or \
+             above it",
+            root.display()
+        )
+    }
+
+    /// Whether the team owns `rel_path`, relative to the processed directory
+    pub fn owns(&self, rel_path: &Path) -> bool {
+        let path = self.prefix.join(rel_path);
+        self.rules
+            .iter()
+            .rev()
+            .find(|(pattern, _)| pattern.is_match(&path))
+            .is_some_and(|&(_, owned)| owned)
+    }
+}
+
+/// CODEOWNERS files, for the paths their rules give owners to
+pub struct CodeownersScanner;
+
+impl Scanner for CodeownersScanner {
+    fn matches(&self, path: &Path) -> bool {
+        path.file_name().is_some_and(|name| name == "CODEOWNERS")
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_paths(path, content, scanner::converted_path)
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_paths(path, content, |found| plan.target_of(found))
+    }
+
+    /// Rules anchored to the repository, by a `/` at their start or in
+    /// their middle, whose path leads nowhere. Other patterns match at any
+    /// depth.
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> Vec<String> {
+        let repo = repo_root(path);
+        pattern_regex()
+            .captures_iter(content)
+            .filter(|caps| {
+                let file = caps[2].trim_end_matches('/');
+                (caps.get(1).is_some() || file.contains('/'))
+                    && scanner::leads_nowhere(repo, file, &[])
+            })
+            .map(|caps| caps[0].trim_start().to_string())
+            .collect()
+    }
+
+    fn pins_name(&self, path: &Path) -> Option<&'static str> {
+        self.matches(path)
+            .then_some("GitHub and GitLab look for it by this name")
+    }
+}
+
+/// The repository a CODEOWNERS file at `path` belongs to, which its
+/// patterns are relative to
+fn repo_root(path: &Path) -> &Path {
+    let depth = LOCATIONS
+        .iter()
+        .find(|location| path.ends_with(location))
+        .map_or(1, |location| Path::new(location).components().count());
+    path.ancestors().nth(depth).unwrap_or(Path::new(""))
+}
+
+/// The pattern each rule starts with, and the `/` anchoring it
+fn pattern_regex() -> Regex {
+    Regex::new(r"(?m)^[ \t]*(/)?([^\s#\[/][^\s]*)").unwrap()
+}
+
+/// Rewrites the path each rule starts with, like `/web/Components/`,
+/// relative to the repository. Only the parts that exist there are
+/// touched, so globs like `*.css` are left alone.
+fn rewrite_paths(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let repo = repo_root(path);
+    let mut edits = Vec::new();
+    for caps in pattern_regex().captures_iter(content) {
+        let file = caps.get(2).unwrap();
+        edits.extend(rewrite_file_path(
+            repo,
+            file.as_str(),
+            file.start(),
+            &[],
+            &rename,
+        ));
+    }
+    splice(content, edits)
+}
+
+fn parse(content: &str, team: &str, prefix: &Path) -> Result<Owners> {
+    let mut rules = Vec::new();
+    for line in content.lines() {
+        let line = line.split_once(" #").map_or(line, |(rule, _)| rule).trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with('[') {
+            continue;
+        }
+        let mut fields = line.split_whitespace();
+        let Some(pattern) = fields.next() else {
+            continue;
+        };
+        let owned = fields.any(|owner| {
+            owner
+                .trim_start_matches('@')
+                .eq_ignore_ascii_case(team.trim_start_matches('@'))
+        });
+        rules.push((compile(pattern)?, owned));
+    }
+    Ok(Owners {
+        rules,
+        prefix: prefix.to_path_buf(),
+    })
+}
+
+/// Compiles a CODEOWNERS pattern, which follows `.gitignore` rules: it's
+/// anchored to the repository if it has a `/` other than at the end, and a
+/// directory it matches owns everything under it, except with `dir/*`
+fn compile(pattern: &str) -> Result<GlobSet> {
+    let trimmed = pattern.trim_start_matches('/').trim_end_matches('/');
+    let anchored = pattern.trim_end_matches('/').contains('/');
+    let base = match anchored {
+        true => trimmed.to_string(),
+        false => format!("**/{}", trimmed),
+    };
+    let mut globs = vec![base.clone()];
+    if !trimmed.ends_with("/*") {
+        globs.push(format!("{}/**", base));
+    }
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        builder.add(
+            GlobBuilder::new(&glob)
+                .literal_separator(true)
+                .build()
+                .with_context(|| format!("Invalid pattern: {}", pattern))?,
+        );
+    }
+    Ok(builder.build()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_owned_paths() -> Result<()> {
+        let content = "\
+# Default owners
+*                   @org/platform
+/web/               @org/frontend
+*.css               @org/frontend @org/design
+/web/api/           @org/backend   # not ours
+/docs/*             @org/Frontend
+";
+        let owners = parse(content, "@org/frontend", Path::new(""))?;
+        let owns = |path: &str| owners.owns(Path::new(path));
+        assert!(owns("web/src/UserCard.ts"));
+        assert!(owns("web"));
+        assert!(owns("server/Theme.css"));
+        assert!(owns("docs/GettingStarted.md"));
+        assert!(!owns("docs/guides/Setup.md"));
+        assert!(!owns("web/api/Client.ts"));
+        assert!(!owns("server/Main.ts"));
+
+        let owners = parse(content, "org/frontend", Path::new("web"))?;
+        assert!(owners.owns(Path::new("src/UserCard.ts")));
+        assert!(!owners.owns(Path::new("api/Client.ts")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_codeowners_paths() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join(".github"))?;
+        fs::create_dir_all(root.join("web/Components"))?;
+        fs::write(root.join("web/Components/UserCard.ts"), "")?;
+
+        let content = "\
+# Components
+*.css                             @org/design
+/web/Components/                  @org/frontend
+web/Components/UserCard.ts        @org/frontend
+";
+        let path = root.join(".github/CODEOWNERS");
+        assert!(CodeownersScanner.matches(&path));
+        assert!(scanner::pinned(&path, false).is_some());
+        let (new_content, changes) =
+            CodeownersScanner.convert(&path, content, &ScanOptions::default());
+        assert_eq!(
+            new_content,
+            content
+                .replace("Components/", "components/")
+                .replace("UserCard", "user-card")
+        );
+        assert_eq!(changes, 3);
+
+        let content = "\
+/web/Components/                  @org/frontend
+/web/Removed/                     @org/frontend
+Gone                              @org/frontend
+web/Gone.ts                       @org/frontend
+";
+        let orphans =
+            CodeownersScanner.orphans(&path, content, &ScanOptions::default());
+        assert_eq!(orphans, ["/web/Removed/", "web/Gone.ts"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+use crate::brands::{self, Brand};
+use crate::{AcronymStyle, NameStyle, NumberStyle};
+use std::sync::OnceLock;
+
+/// How names are split into words, from the command line. Names are
+/// converted in too many places to pass these around, and they can't
+/// change during a run, so they're set once at startup.
+#[derive(Debug, Clone)]
+pub struct Conventions {
+    pub acronyms: AcronymStyle,
+    pub numbers: NumberStyle,
+    /// Longest first
+    pub brands: Vec<Brand>,
+    /// The case C and C++ files are named in
+    pub c_style: NameStyle,
+    /// Whether Java and Kotlin files and packages are renamed
+    pub force_java: bool,
+}
+
+impl Default for Conventions {
+    fn default() -> Self {
+        Self {
+            acronyms: AcronymStyle::default(),
+            numbers: NumberStyle::default(),
+            brands: brands::load(&[], true).unwrap_or_default(),
+            c_style: NameStyle::Snake,
+            force_java: false,
+        }
+    }
+}
+
+static CONVENTIONS: OnceLock<Conventions> = OnceLock::new();
+
+/// Sets the conventions for the rest of the run. Only the first call counts.
+pub fn set(conventions: Conventions) {
+    let _ = CONVENTIONS.set(conventions);
+}
+
+/// The conventions in force, or the defaults if none were set
+pub fn get() -> &'static Conventions {
+    CONVENTIONS.get_or_init(Conventions::default)
+}
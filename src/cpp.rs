@@ -0,0 +1,274 @@
+use crate::plan::Plan;
+use crate::scanner::{self, rewrite_file_path, splice, ScanOptions, Scanner};
+use crate::{conventions, NameStyle};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// C and C++ sources and headers
+const EXTENSIONS: [&str; 12] = [
+    "c", "h", "cc", "cpp", "cxx", "c++", "hh", "hpp", "hxx", "h++", "inl",
+    "ipp",
+];
+
+/// The CMake commands whose arguments are paths, and how many arguments
+/// come first that aren't, like the target's name
+const CMAKE_COMMANDS: [(&str, usize); 6] = [
+    ("add_executable", 1),
+    ("add_library", 1),
+    ("target_sources", 1),
+    ("target_include_directories", 1),
+    ("include_directories", 0),
+    ("add_subdirectory", 0),
+];
+
+/// C and C++ files, named in `--c-style`, with their quoted `#include`s,
+/// and the source lists in `CMakeLists.txt`
+pub struct CppScanner;
+
+impl Scanner for CppScanner {
+    fn matches(&self, path: &Path) -> bool {
+        is_cmake_lists(path)
+            || path
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|ext| EXTENSIONS.contains(&ext))
+    }
+
+    fn name_style(&self) -> NameStyle {
+        conventions::get().c_style
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite(path, content, scanner::converted_path)
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite(path, content, |found| plan.target_of(found))
+    }
+
+    /// Includes found in no directory above the file, when the directory
+    /// they start in is there, and CMake paths that lead nowhere. An
+    /// include of a bare file name may be on an include path, so it isn't
+    /// taken to be missing.
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> Vec<String> {
+        let dir = path.parent().unwrap_or(Path::new(""));
+        if is_cmake_lists(path) {
+            return cmake_paths(content)
+                .into_iter()
+                .map(|(file, _)| file)
+                // `PRIVATE`, `STATIC` and the other keywords
+                .filter(|file| {
+                    file.chars().any(|c| !c.is_ascii_uppercase() && c != '_')
+                })
+                .filter(|file| scanner::leads_nowhere(dir, file, &[]))
+                .map(str::to_string)
+                .collect();
+        }
+        include_regex()
+            .captures_iter(content)
+            .map(|caps| caps[1].to_string())
+            .filter(|file| {
+                file.split_once('/').is_some_and(|(first, _)| {
+                    dir.ancestors().any(|base| base.join(first).is_dir())
+                }) && dir
+                    .ancestors()
+                    .all(|base| scanner::leads_nowhere(base, file, &[]))
+            })
+            .collect()
+    }
+
+    fn pins_name(&self, path: &Path) -> Option<&'static str> {
+        is_cmake_lists(path).then_some("CMake looks for it by this name")
+    }
+}
+
+fn is_cmake_lists(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name == "CMakeLists.txt")
+}
+
+fn rewrite(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    match is_cmake_lists(path) {
+        true => rewrite_cmake_paths(path, content, rename),
+        false => rewrite_includes(path, content, rename),
+    }
+}
+
+/// `#include "Utils/StringHelpers.hpp"`
+fn include_regex() -> Regex {
+    Regex::new(r#"(?m)^[ \t]*#[ \t]*include[ \t]*"([^"\n]+)""#).unwrap()
+}
+
+/// Rewrites `#include "Utils/StringHelpers.hpp"`. `<...>` includes are
+/// the system's and left alone. Include paths aren't known here, so each
+/// is tried against the file's directory and then each directory above it.
+fn rewrite_includes(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut edits = Vec::new();
+    for caps in include_regex().captures_iter(content) {
+        let file = caps.get(1).unwrap();
+        // The first directory the include exists in
+        let Some(base) = dir
+            .ancestors()
+            .find(|base| base.join(file.as_str()).exists())
+        else {
+            continue;
+        };
+        edits.extend(rewrite_file_path(
+            base,
+            file.as_str(),
+            file.start(),
+            &[],
+            &rename,
+        ));
+    }
+    splice(content, edits)
+}
+
+/// Rewrites the paths in a `CMakeLists.txt`, relative to its directory
+fn rewrite_cmake_paths(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut edits = Vec::new();
+    for (file, start) in cmake_paths(content) {
+        edits.extend(rewrite_file_path(dir, file, start, &[], &rename));
+    }
+    splice(content, edits)
+}
+
+/// The arguments of `add_executable(App Src/Main.cpp)` and the other
+/// `CMAKE_COMMANDS`, with where each starts, less any
+/// `${CMAKE_CURRENT_SOURCE_DIR}/` in front. Arguments with any other
+/// variable are left out.
+fn cmake_paths(content: &str) -> Vec<(&str, usize)> {
+    let command = Regex::new(r"(?i)\b([a-z_]+)\s*\(([^)]*)\)").unwrap();
+    let argument = Regex::new(r#""([^"]*)"|([^\s"]+)"#).unwrap();
+
+    let mut paths = Vec::new();
+    for caps in command.captures_iter(content) {
+        let name = caps[1].to_ascii_lowercase();
+        let Some(&(_, skip)) = CMAKE_COMMANDS.iter().find(|(c, _)| *c == name)
+        else {
+            continue;
+        };
+        let args = caps.get(2).unwrap();
+        for arg in argument.captures_iter(args.as_str()).skip(skip) {
+            let arg = arg.iter().skip(1).flatten().next().unwrap();
+            let text = arg.as_str();
+            let file =
+                ["${CMAKE_CURRENT_SOURCE_DIR}/", "${CMAKE_CURRENT_LIST_DIR}/"]
+                    .iter()
+                    .find_map(|prefix| text.strip_prefix(prefix))
+                    .unwrap_or(text);
+            if file.contains("${") || file.starts_with('/') {
+                continue;
+            }
+            paths.push((file, args.start() + arg.end() - file.len()));
+        }
+    }
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_cpp_includes_and_cmake() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("Engine/Utils"))?;
+        fs::write(root.join("Engine/Utils/StringHelpers.hpp"), "")?;
+        fs::write(root.join("Engine/GameLoop.cpp"), "")?;
+        fs::write(root.join("Engine/CMakeLists.txt"), "")?;
+
+        let content = "\
+#include <Windows.h>
+#include \"Utils/StringHelpers.hpp\"
+#  include \"Engine/Utils/StringHelpers.hpp\"
+";
+        let path = root.join("Engine/GameLoop.cpp");
+        assert!(CppScanner.matches(&path));
+        assert_eq!(CppScanner.name_style(), NameStyle::Snake);
+        let (new_content, changes) =
+            CppScanner.convert(&path, content, &ScanOptions::default());
+        assert_eq!(
+            new_content,
+            "\
+#include <Windows.h>
+#include \"utils/string_helpers.hpp\"
+#  include \"engine/utils/string_helpers.hpp\"
+"
+        );
+        assert_eq!(changes, 5);
+
+        let cmake = "\
+add_subdirectory(Engine)
+add_executable(Engine ${CMAKE_CURRENT_SOURCE_DIR}/Engine/GameLoop.cpp)
+target_sources(Engine PRIVATE \"Engine/Utils/StringHelpers.hpp\" ${EXTRA})
+";
+        let path = root.join("CMakeLists.txt");
+        assert!(CppScanner.pins_name(&path).is_some());
+        let (new_content, changes) =
+            CppScanner.convert(&path, cmake, &ScanOptions::default());
+        assert_eq!(
+            new_content,
+            "\
+add_subdirectory(engine)
+add_executable(Engine ${CMAKE_CURRENT_SOURCE_DIR}/engine/game_loop.cpp)
+target_sources(Engine PRIVATE \"engine/utils/string_helpers.hpp\" ${EXTRA})
+"
+        );
+        assert_eq!(changes, 6);
+        let orphans = CppScanner.orphans(
+            &path,
+            &cmake.replace("GameLoop", "Gone"),
+            &ScanOptions::default(),
+        );
+        assert_eq!(orphans, ["Engine/Gone.cpp"]);
+
+        let content = "\
+#include \"Utils/StringHelpers.hpp\"
+#include \"Utils/Gone.hpp\"
+#include \"config.h\"
+#include \"fmt/core.h\"
+";
+        let orphans = CppScanner.orphans(
+            &root.join("Engine/GameLoop.cpp"),
+            content,
+            &ScanOptions::default(),
+        );
+        assert_eq!(orphans, ["Utils/Gone.hpp"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,222 @@
+use crate::plan::Plan;
+use crate::scanner::{self, rewrite_file_path, splice, ScanOptions, Scanner};
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// MSBuild project and import files, and the solutions that list projects
+const PROJECT_EXTENSIONS: [&str; 6] =
+    ["csproj", "vbproj", "fsproj", "props", "targets", "sln"];
+
+/// C# sources. Nothing in them refers to other files by path, but their
+/// classes are usually named after the file, which a rename breaks.
+pub struct CSharpScanner;
+
+impl Scanner for CSharpScanner {
+    fn matches(&self, path: &Path) -> bool {
+        path.extension().is_some_and(|e| e == "cs")
+    }
+
+    fn convert(
+        &self,
+        _path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        (content.to_string(), 0)
+    }
+
+    fn rename_caveat(&self, path: &Path, to: &Path) -> Option<String> {
+        let content = fs::read_to_string(path).ok()?;
+        let stem = path.file_stem()?.to_str()?;
+        let type_regex =
+            Regex::new(r"\b(class|struct|record|interface|enum)\s+(\w+)")
+                .unwrap();
+        let caps = type_regex
+            .captures_iter(&content)
+            .find(|caps| &caps[2] == stem)?;
+        Some(format!(
+            "{} '{}' no longer matches its file's name, '{}'",
+            &caps[1],
+            stem,
+            to.file_name()?.to_string_lossy()
+        ))
+    }
+}
+
+/// MSBuild projects (`.csproj` and the like), whose items name the files
+/// they build, as in `<Compile Include="Models\UserProfile.cs" />`, and
+/// solutions, which name their projects' files
+pub struct MsBuildScanner;
+
+impl Scanner for MsBuildScanner {
+    fn matches(&self, path: &Path) -> bool {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|ext| PROJECT_EXTENSIONS.contains(&ext))
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_items(path, content, scanner::converted_path)
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_items(path, content, |found| plan.target_of(found))
+    }
+
+    /// Items a project includes, and projects a solution lists, that
+    /// aren't there. `Update` and `Remove` only narrow what's included, so
+    /// a missing one breaks nothing.
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> Vec<String> {
+        let dir = path.parent().unwrap_or(Path::new(""));
+        items(path, content)
+            .into_iter()
+            .filter(|&(_, _, narrows)| !narrows)
+            .map(|(item, _, _)| item)
+            .filter(|item| {
+                scanner::leads_nowhere(dir, &item.replace('\\', "/"), &[])
+            })
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+/// Rewrites the paths in the `Include`, `Update` and `Remove` attributes
+/// of a project's items, and the quoted project paths in a solution, that
+/// name a file or directory, relative to the file they're in. Either slash
+/// can separate them, and globs and paths built from properties are left
+/// alone.
+fn rewrite_items(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut edits = Vec::new();
+    for (item, start, _) in items(path, content) {
+        // Backslashes and slashes are both one byte, so the edits for the
+        // slashed path line up with the item as written
+        let file = item.replace('\\', "/");
+        edits.extend(rewrite_file_path(dir, &file, start, &[], &rename));
+    }
+    splice(content, edits)
+}
+
+/// The item paths in a project, or the project paths in a solution, with
+/// where each starts and whether it's from an `Update` or `Remove`
+fn items<'a>(path: &Path, content: &'a str) -> Vec<(&'a str, usize, bool)> {
+    let attribute = match path.extension().is_some_and(|e| e == "sln") {
+        true => r#"(?m)^Project\([^)]*\)\s*=\s*"[^"]*",\s*"([^"]*)""#,
+        false => r#"\b(Include|Update|Remove)\s*=\s*"([^"]*)""#,
+    };
+    let attribute = Regex::new(attribute).unwrap();
+
+    let mut found = Vec::new();
+    for caps in attribute.captures_iter(content) {
+        let narrows = caps.len() > 2 && &caps[1] != "Include";
+        let value = caps.get(caps.len() - 1).unwrap();
+        let mut start = value.start();
+        for item in value.as_str().split(';') {
+            let item_start = start + item.len() - item.trim_start().len();
+            start += item.len() + 1;
+            let item = item.trim();
+            if item.is_empty() || item.contains(['*', '$', '@', '%', ':']) {
+                continue;
+            }
+            found.push((item, item_start, narrows));
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_msbuild_items() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("App/Models"))?;
+        fs::create_dir_all(root.join("App/Assets"))?;
+        fs::write(
+            root.join("App/Models/UserProfile.cs"),
+            "public class UserProfile {}",
+        )?;
+        fs::write(root.join("App/Assets/SiteLogo.png"), "")?;
+        fs::write(root.join("App/App.csproj"), "")?;
+
+        let project = r#"<Project Sdk="Microsoft.NET.Sdk">
+  <ItemGroup>
+    <Compile Include="Models\UserProfile.cs" />
+    <Compile Remove="Legacy\**\*.cs" />
+    <Content Include="Assets/SiteLogo.png;Models\Missing.cs">
+      <CopyToOutputDirectory>Always</CopyToOutputDirectory>
+    </Content>
+    <None Update="$(ProjectDir)Assets\SiteLogo.png" />
+  </ItemGroup>
+</Project>
+"#;
+        let (converted, changes) = MsBuildScanner.convert(
+            &root.join("App/App.csproj"),
+            project,
+            &ScanOptions::default(),
+        );
+        assert_eq!(
+            converted,
+            project
+                .replace(r"Models\UserProfile", r"models\user-profile")
+                .replace("Assets/SiteLogo", "assets/site-logo")
+                .replace(r";Models\Missing", r";models\Missing")
+        );
+        assert_eq!(changes, 5);
+        let orphans = MsBuildScanner.orphans(
+            &root.join("App/App.csproj"),
+            project,
+            &ScanOptions::default(),
+        );
+        assert_eq!(orphans, [r"Models\Missing.cs"]);
+
+        let solution = "\
+Project(\"{FAE04EC0}\") = \"App\", \"App\\App.csproj\", \"{1A2B}\"
+EndProject
+";
+        let (converted, _) = MsBuildScanner.convert(
+            &root.join("Game.sln"),
+            solution,
+            &ScanOptions::default(),
+        );
+        assert!(converted.contains(r#""app\app.csproj""#));
+
+        let caveat = CSharpScanner.rename_caveat(
+            &root.join("App/Models/UserProfile.cs"),
+            &root.join("App/Models/user-profile.cs"),
+        );
+        assert_eq!(
+            caveat.as_deref(),
+            Some(
+                "class 'UserProfile' no longer matches its file's name, \
+                 'user-profile.cs'"
+            )
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,405 @@
+use crate::messages::{text, Msg};
+use crate::report::{Report, WarningKind};
+use crate::scanner::splice;
+use crate::{convert_name, encoding, Options};
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Files whose selectors define classes
+const STYLESHEETS: [&str; 4] = ["css", "scss", "sass", "less"];
+/// Files with `<style>` blocks
+const STYLED: [&str; 5] = ["html", "htm", "vue", "svelte", "astro"];
+/// Files whose `class` attributes and scripts use classes
+const TEMPLATES: [&str; 11] = [
+    "html", "htm", "vue", "svelte", "astro", "jsx", "tsx", "js", "ts", "mjs",
+    "cjs",
+];
+
+/// Where a class name is written in one file
+#[derive(Debug, Default)]
+struct Sites {
+    /// In selectors
+    defined: Vec<(Range<usize>, String)>,
+    /// In `class` attributes, `class:` directives and `classList` calls
+    used: Vec<(Range<usize>, String)>,
+    /// Every word, for telling whether a class is written anywhere else
+    words: HashMap<String, usize>,
+    /// Whether selectors build class names from their parent's, like
+    /// `&__title`, which renaming the parent would silently change
+    concatenates: bool,
+}
+
+/// The class renames a run makes, and the classes that keep their names
+/// with the reason
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ClassPlan {
+    pub renames: BTreeMap<String, String>,
+    pub kept: Vec<(PathBuf, String, String)>,
+}
+
+/// `class` in kebab-case. Each part between `-` and `_` is converted on its
+/// own, so BEM names keep their separators.
+fn convert_class(class: &str) -> String {
+    let mut converted = String::with_capacity(class.len() + 4);
+    let mut part = String::new();
+    for c in class.chars() {
+        if c == '-' || c == '_' {
+            converted.push_str(&convert_name(&part));
+            converted.push(c);
+            part.clear();
+        } else {
+            part.push(c);
+        }
+    }
+    converted.push_str(&convert_name(&part));
+    converted
+}
+
+fn extension(path: &Path) -> &str {
+    path.extension().and_then(|e| e.to_str()).unwrap_or("")
+}
+
+fn regex(cell: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+    cell.get_or_init(|| Regex::new(pattern).unwrap())
+}
+
+/// `css` with comments and strings blanked out, keeping every offset
+fn mask(css: &str) -> String {
+    static HIDDEN: OnceLock<Regex> = OnceLock::new();
+    let hidden =
+        regex(&HIDDEN, r#"(?s)/\*.*?\*/|//[^\n]*|"[^"\n]*"|'[^'\n]*'"#);
+    let mut masked = css.to_string();
+    for found in hidden.find_iter(css) {
+        // Blanked byte by byte so multi-byte characters don't shift anything
+        let blank = " ".repeat(found.len());
+        masked.replace_range(found.range(), &blank);
+    }
+    masked
+}
+
+/// The classes defined by the selectors in `css`, which starts at byte
+/// `offset` of its file
+fn scan_selectors(css: &str, offset: usize, sites: &mut Sites) {
+    static PRELUDE: OnceLock<Regex> = OnceLock::new();
+    static CLASS: OnceLock<Regex> = OnceLock::new();
+    static CONCAT: OnceLock<Regex> = OnceLock::new();
+    let prelude = regex(&PRELUDE, r"[^{};]*\{");
+    let class = regex(&CLASS, r"\.(-?[A-Za-z_][\w-]*)");
+    let concat = regex(&CONCAT, r"&[\w-]");
+
+    let masked = mask(css);
+    for selector in prelude.find_iter(&masked) {
+        let text = selector.as_str();
+        if text.trim_start().starts_with('@') {
+            continue;
+        }
+        sites.concatenates |= concat.is_match(text);
+        for caps in class.captures_iter(text) {
+            let name = caps.get(1).unwrap();
+            let start = offset + selector.start() + name.start();
+            sites
+                .defined
+                .push((start..start + name.len(), name.as_str().to_string()));
+        }
+    }
+    // Words in comments don't stop a class from being renamed
+    count_words(&masked, &mut sites.words);
+}
+
+fn count_words(text: &str, words: &mut HashMap<String, usize>) {
+    static WORD: OnceLock<Regex> = OnceLock::new();
+    for word in regex(&WORD, r"-?[A-Za-z_][\w-]*").find_iter(text) {
+        *words.entry(word.as_str().to_string()).or_default() += 1;
+    }
+}
+
+/// The classes used in `content`'s `class` and `className` attributes,
+/// Svelte `class:` directives and `classList` calls
+fn scan_usages(content: &str, sites: &mut Sites) {
+    static ATTRIBUTE: OnceLock<Regex> = OnceLock::new();
+    static DIRECTIVE: OnceLock<Regex> = OnceLock::new();
+    static CLASS_LIST: OnceLock<Regex> = OnceLock::new();
+    static STRING: OnceLock<Regex> = OnceLock::new();
+    static TOKEN: OnceLock<Regex> = OnceLock::new();
+    let attribute = regex(
+        &ATTRIBUTE,
+        r#"\b(?:class|className)\s*=\s*(?:"([^"]*)"|'([^']*)'|\{\s*(?:"([^"]*)"|'([^']*)'|`([^`$]*)`)\s*\})"#,
+    );
+    let directive = regex(&DIRECTIVE, r"\bclass:([\w-]+)");
+    let class_list = regex(
+        &CLASS_LIST,
+        r"\bclassList\.(?:add|remove|toggle|contains|replace)\(([^)]*)\)",
+    );
+    let string = regex(&STRING, r#"'([^'\n]*)'|"([^"\n]*)""#);
+    let token = regex(&TOKEN, r"\S+");
+
+    // Space-separated class lists, with where each starts
+    let mut lists = Vec::new();
+    for caps in attribute.captures_iter(content) {
+        let list = caps.iter().skip(1).flatten().next().unwrap();
+        lists.push((list.start(), list.as_str()));
+    }
+    for caps in class_list.captures_iter(content) {
+        let args = caps.get(1).unwrap();
+        for arg in string.captures_iter(args.as_str()) {
+            let arg = arg.iter().skip(1).flatten().next().unwrap();
+            lists.push((args.start() + arg.start(), arg.as_str()));
+        }
+    }
+    for (list_start, list) in lists {
+        for word in token.find_iter(list) {
+            let start = list_start + word.start();
+            sites
+                .used
+                .push((start..start + word.len(), word.as_str().to_string()));
+        }
+    }
+    for caps in directive.captures_iter(content) {
+        let name = caps.get(1).unwrap();
+        sites.used.push((name.range(), name.as_str().to_string()));
+    }
+}
+
+/// Where classes are defined and used in the file at `path`
+fn scan_file(path: &Path, content: &str) -> Sites {
+    static STYLE: OnceLock<Regex> = OnceLock::new();
+    let ext = extension(path);
+    let mut sites = Sites::default();
+    if STYLESHEETS.contains(&ext) {
+        scan_selectors(content, 0, &mut sites);
+        return sites;
+    }
+    let mut outside_styles = content.to_string();
+    if STYLED.contains(&ext) {
+        let style = regex(&STYLE, r"(?is)<style[^>]*>(.*?)</style>");
+        for caps in style.captures_iter(content) {
+            let css = caps.get(1).unwrap();
+            scan_selectors(css.as_str(), css.start(), &mut sites);
+            outside_styles.replace_range(css.range(), &" ".repeat(css.len()));
+        }
+    }
+    scan_usages(&outside_styles, &mut sites);
+    count_words(&outside_styles, &mut sites.words);
+    sites
+}
+
+fn is_scanned(path: &Path) -> bool {
+    let ext = extension(path);
+    STYLESHEETS.contains(&ext) || TEMPLATES.contains(&ext)
+}
+
+/// Reads the stylesheets and templates under `dir`
+fn read_files(
+    dir: &Path,
+    opts: &Options,
+    report: &mut Report,
+) -> Result<Vec<(PathBuf, String, Sites)>> {
+    let mut files = Vec::new();
+    for entry in crate::walk(dir, opts, report)? {
+        let path = entry.path();
+        if !entry.file_type().is_file() || !is_scanned(path) {
+            continue;
+        }
+        let Some((content, _)) =
+            fs::read(path).ok().and_then(|b| encoding::decode(&b))
+        else {
+            continue;
+        };
+        let sites = scan_file(path, &content);
+        files.push((path.to_path_buf(), content, sites));
+    }
+    Ok(files)
+}
+
+/// Works out which classes defined under `dir` to convert. A class is only
+/// renamed when every place its name is written is one kebabify rewrites,
+/// so a class also used from a CSS module, a script or a selector built
+/// with `&` keeps its name rather than being renamed in some places only.
+fn plan_classes(files: &[(PathBuf, String, Sites)]) -> ClassPlan {
+    let mut defined: BTreeMap<&str, &Path> = BTreeMap::new();
+    let mut sites: HashMap<&str, usize> = HashMap::new();
+    let mut words: HashMap<&str, usize> = HashMap::new();
+    let mut concatenated = HashMap::new();
+    for (path, _, file) in files {
+        for (_, name) in &file.defined {
+            defined.entry(name).or_insert(path);
+            if file.concatenates {
+                concatenated.insert(name.as_str(), path);
+            }
+        }
+        for (_, name) in file.defined.iter().chain(&file.used) {
+            *sites.entry(name).or_default() += 1;
+        }
+        for (word, count) in &file.words {
+            *words.entry(word).or_default() += count;
+        }
+    }
+
+    let mut plan = ClassPlan::default();
+    let mut targets: HashMap<String, Vec<&str>> = HashMap::new();
+    for (&class, path) in &defined {
+        let converted = convert_class(class);
+        if converted == class {
+            continue;
+        }
+        let keep = |reason: String| (path.to_path_buf(), class.into(), reason);
+        if let Some(path) = concatenated.get(class) {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            plan.kept.push(keep(format!(
+                "{} builds class names from its selectors with &, which \
+                 renaming it would change",
+                name
+            )));
+        } else if words.get(class) > sites.get(class) {
+            plan.kept.push(keep(
+                "also written somewhere it can't be renamed safely, like a \
+                 script or a CSS module"
+                    .to_string(),
+            ));
+        } else if words.contains_key(converted.as_str()) {
+            plan.kept
+                .push(keep(format!("'{}' is already used", converted)));
+        } else {
+            targets.entry(converted).or_default().push(class);
+        }
+    }
+    for (converted, classes) in targets {
+        if classes.len() == 1 {
+            plan.renames.insert(classes[0].to_string(), converted);
+            continue;
+        }
+        for class in classes {
+            plan.kept.push((
+                defined[class].to_path_buf(),
+                class.to_string(),
+                format!("other classes would also become '{}'", converted),
+            ));
+        }
+    }
+    plan.kept.sort();
+    plan
+}
+
+fn warn_kept(plan: &ClassPlan, report: &mut Report) {
+    for (path, class, reason) in &plan.kept {
+        report.warn(
+            path,
+            WarningKind::ClassKept,
+            format!("class '{}' kept: {}", class, reason),
+        );
+    }
+}
+
+/// Converts the CSS class names under `dir` to kebab-case, in their
+/// selectors and everywhere they're used, for `--css-classes`
+pub fn run(dir: &Path, opts: &Options, report: &mut Report) -> Result<()> {
+    let files = read_files(dir, opts, report)?;
+    let plan = plan_classes(&files);
+    warn_kept(&plan, report);
+    for (from, to) in &plan.renames {
+        opts.log(format_args!("{}", text(Msg::RenamingClass, &[from, to])));
+    }
+    for (path, content, sites) in files {
+        crate::cancel::checkpoint()?;
+        let edits: Vec<_> = sites
+            .defined
+            .into_iter()
+            .chain(sites.used)
+            .filter_map(|(range, name)| Some((range, plan.renames.get(&name)?)))
+            .map(|(range, to)| (range, to.clone()))
+            .collect();
+        if edits.is_empty() {
+            continue;
+        }
+        let (new_content, changes) = splice(&content, edits);
+        opts.log(format_args!(
+            "{}",
+            text(Msg::UpdatedClasses, &[&changes, &opts.display.show(&path)])
+        ));
+        let bytes = fs::read(&path).with_context(|| {
+            format!("Failed to read file: {}", opts.display.show(&path))
+        })?;
+        let Some((_, encoding)) = encoding::decode(&bytes) else {
+            continue;
+        };
+        let result = crate::write_content(
+            &path,
+            bytes,
+            &new_content,
+            encoding,
+            opts,
+            report,
+        );
+        report.check(&path, result)?;
+    }
+    crate::write_back::flush(opts, report)
+}
+
+/// Prints the class renames `run` would make, for `--dry-run`
+pub fn preview(dir: &Path, opts: &Options, report: &mut Report) -> Result<()> {
+    let plan = plan_classes(&read_files(dir, opts, report)?);
+    warn_kept(&plan, report);
+    for (from, to) in &plan.renames {
+        opts.log(format_args!("{}", text(Msg::WouldRenameClass, &[from, to])));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_plan_classes() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::write(
+            root.join("app.css"),
+            "/* .Unused */\n\
+             .UserCard, .UserCard:hover { color: red; }\n\
+             .navBar > .isActive { background: url('a.Png'); }\n\
+             .Title-Big__Main { }\n\
+             .userList {}\n\
+             .user-list {}\n",
+        )?;
+        fs::write(root.join("card.scss"), ".Card { &__Body { } }\n")?;
+        fs::write(
+            root.join("App.jsx"),
+            "<div className=\"UserCard Title-Big__Main\">\n\
+             <nav className={styles.navBar} class:isActive={on} />\n",
+        )?;
+        fs::write(
+            root.join("page.vue"),
+            "<template><p :class=\"x\" class='isActive'>Card</p></template>\n\
+             <style>.SideBar { }</style>\n",
+        )?;
+
+        let files =
+            read_files(root, &Options::default(), &mut Report::default())?;
+        let plan = plan_classes(&files);
+        let renames: Vec<_> = plan
+            .renames
+            .iter()
+            .map(|(f, t)| (f.as_str(), t.as_str()))
+            .collect();
+        assert_eq!(
+            renames,
+            [
+                ("SideBar", "side-bar"),
+                ("Title-Big__Main", "title-big__main"),
+                ("UserCard", "user-card"),
+                ("isActive", "is-active"),
+            ]
+        );
+        let kept: Vec<_> =
+            plan.kept.iter().map(|(_, c, _)| c.as_str()).collect();
+        assert_eq!(kept, ["navBar", "userList", "Card"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,329 @@
+use crate::plan::Plan;
+use crate::resolve::resolve_specifier;
+use crate::scanner::splice;
+use clap::ValueEnum;
+use regex::Regex;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// What to do with `import Card from './UserCard'` when `UserCard.tsx` is
+/// renamed and default-exports an identifier named after it
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DefaultImports {
+    /// Leave the names they're imported under alone
+    #[default]
+    Keep,
+    /// Leave them alone and warn about each one not in `--import-case`
+    Flag,
+    /// Rename them, and their uses in the importing file, to the exported
+    /// identifier in `--import-case`
+    Rewrite,
+}
+
+/// The case default imports are named in
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImportCase {
+    /// `UserCard`, as components usually are
+    #[default]
+    Pascal,
+    /// `userCard`
+    Camel,
+}
+
+/// A default import whose local name doesn't follow the convention
+#[derive(Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    pub line: usize,
+    pub local: String,
+    pub wanted: String,
+    /// Why it can't be renamed, if it can't
+    pub blocked: Option<&'static str>,
+}
+
+fn import_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(concat!(
+            r"\bimport\s+([A-Za-z_$][\w$]*)\s*",
+            r"(?:,\s*(?:\{[^}]*\}|\*\s*as\s+[\w$]+)\s*)?",
+            r#"from\s*["']([^"'\n]+)["']"#,
+        ))
+        .unwrap()
+    })
+}
+
+fn export_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(concat!(
+            r"\bexport\s+default\s+(?:abstract\s+)?",
+            r"(?:(?:async\s+)?function\s*\*?\s*|class\s+)?([A-Za-z_$][\w$]*)",
+            r"|\bexport\s*\{[^}]*?\b([A-Za-z_$][\w$]*)\s+as\s+default\b",
+        ))
+        .unwrap()
+    })
+}
+
+/// The identifier the file at `path` default-exports, if it's named after
+/// the file, like `UserCard` in `UserCard.tsx`
+fn exported_name(path: &Path) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    let caps = export_regex().captures(&content)?;
+    let name = caps.iter().skip(1).flatten().next()?.as_str();
+    let stem = path.file_name()?.to_str()?.split('.').next()?;
+    let squash = |s: &str| s.replace(['-', '_'], "").to_lowercase();
+    let keyword = ["function", "class", "async"].contains(&name);
+    (!keyword && squash(name) == squash(stem)).then(|| name.to_string())
+}
+
+/// `name` in `case`, keeping it as it is if it already is
+fn in_case(name: &str, case: ImportCase) -> String {
+    let first_upper = name.starts_with(|c: char| c.is_ascii_uppercase());
+    if !name.contains('_') && first_upper == (case == ImportCase::Pascal) {
+        return name.to_string();
+    }
+    let words = crate::convert_name(name);
+    let mut words = words.split(['-', '_']).filter(|w| !w.is_empty());
+    let mut out = match case {
+        ImportCase::Pascal => String::new(),
+        ImportCase::Camel => words.next().unwrap_or_default().to_string(),
+    };
+    for word in words {
+        let mut chars = word.chars();
+        out.extend(chars.next().map(|c| c.to_ascii_uppercase()));
+        out.push_str(chars.as_str());
+    }
+    out
+}
+
+/// `content` with its comments and the text of its strings blanked out,
+/// so what's left is code. Expressions in template literals are kept.
+fn code_only(content: &str) -> String {
+    let bytes = content.as_bytes();
+    let mut out = bytes.to_vec();
+    let mut blank = |range: Range<usize>| {
+        for b in &mut out[range] {
+            if *b != b'\n' {
+                *b = b' ';
+            }
+        }
+    };
+    let find = |from: usize, needle: &[u8]| {
+        bytes[from.min(bytes.len())..]
+            .windows(needle.len())
+            .position(|w| w == needle)
+            .map_or(bytes.len(), |i| from + i + needle.len())
+    };
+    // Open braces in each template expression being read, innermost last
+    let mut templates: Vec<usize> = Vec::new();
+    let mut at = 0;
+    while at < bytes.len() {
+        let start = at;
+        let in_template = match bytes[at] {
+            b'/' if bytes.get(at + 1) == Some(&b'/') => {
+                // The newline itself is left as it is
+                at = bytes[at..]
+                    .iter()
+                    .position(|&b| b == b'\n')
+                    .map_or(bytes.len(), |i| at + i);
+                false
+            }
+            b'/' if bytes.get(at + 1) == Some(&b'*') => {
+                at = find(at + 2, b"*/");
+                false
+            }
+            quote @ (b'\'' | b'"') => {
+                at += 1;
+                while at < bytes.len() && ![quote, b'\n'].contains(&bytes[at]) {
+                    at += 1 + (bytes[at] == b'\\') as usize;
+                }
+                at += 1;
+                false
+            }
+            b'`' => true,
+            b'}' if templates.last() == Some(&0) => {
+                templates.pop();
+                true
+            }
+            b'{' | b'}' => {
+                if let Some(open) = templates.last_mut() {
+                    match bytes[at] {
+                        b'{' => *open += 1,
+                        _ => *open -= 1,
+                    }
+                }
+                at += 1;
+                continue;
+            }
+            _ => {
+                at += 1;
+                continue;
+            }
+        };
+        if in_template {
+            at += 1;
+            while at < bytes.len() && bytes[at] != b'`' {
+                if bytes[at..].starts_with(b"${") {
+                    templates.push(0);
+                    break;
+                }
+                at += 1 + (bytes[at] == b'\\') as usize;
+            }
+            blank(start + 1..at.min(bytes.len()));
+            at += 1 + (bytes.get(at) == Some(&b'$')) as usize;
+            continue;
+        }
+        blank(start..at.min(bytes.len()));
+    }
+    String::from_utf8(out).unwrap_or_default()
+}
+
+/// Where `name` is used as an identifier in `code`, leaving out properties
+/// like `props.name`
+fn uses(code: &str, name: &str) -> Vec<Range<usize>> {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+    code.match_indices(name)
+        .map(|(at, _)| at..at + name.len())
+        .filter(|range| {
+            let before = &code[..range.start];
+            let after = code[range.end..].chars().next();
+            !before.ends_with(is_ident)
+                && (!before.ends_with('.') || before.ends_with("..."))
+                && !after.is_some_and(is_ident)
+        })
+        .collect()
+}
+
+/// The default imports in the file at `path` of files `plan` renames that
+/// are named after them, whose local name isn't the exported one in `case`
+pub fn mismatches(
+    path: &Path,
+    content: &str,
+    plan: &Plan,
+    case: ImportCase,
+) -> Vec<Mismatch> {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let code = code_only(content);
+    let reexport = Regex::new(r"\bexport\s*\{[^}]*\}").unwrap();
+    let mut found: Vec<Mismatch> = Vec::new();
+    for caps in import_regex().captures_iter(content) {
+        let whole = caps.get(0).unwrap();
+        if !code[whole.start()..].starts_with("import") {
+            continue;
+        }
+        let Some(target) = resolve_specifier(dir, &caps[2]) else {
+            continue;
+        };
+        let target = target.path();
+        if plan.target_of(target).is_none() {
+            continue;
+        }
+        let Some(exported) = exported_name(target) else {
+            continue;
+        };
+        let (local, wanted) = (&caps[1], in_case(&exported, case));
+        if local == wanted || found.iter().any(|m| m.local == local) {
+            continue;
+        }
+        let blocked = if !uses(&code, &wanted).is_empty() {
+            Some("the name is already used in this file")
+        } else if reexport
+            .find_iter(&code)
+            .any(|list| !uses(list.as_str(), local).is_empty())
+        {
+            Some("it's exported under that name")
+        } else {
+            None
+        };
+        found.push(Mismatch {
+            line: content[..whole.start()].matches('\n').count() + 1,
+            local: local.to_string(),
+            wanted,
+            blocked,
+        });
+    }
+    found
+}
+
+/// Renames the default imports `mismatches` finds in `content`, and their
+/// uses in it, to the exported identifier in `case`
+pub fn rewrite(
+    path: &Path,
+    content: &str,
+    plan: &Plan,
+    case: ImportCase,
+) -> (String, usize) {
+    let found = mismatches(path, content, plan, case);
+    if found.is_empty() {
+        return (content.to_string(), 0);
+    }
+    let code = code_only(content);
+    let edits = found
+        .iter()
+        .filter(|m| m.blocked.is_none())
+        .flat_map(|m| {
+            uses(&code, &m.local)
+                .into_iter()
+                .map(|range| (range, m.wanted.clone()))
+        })
+        .collect();
+    splice(content, edits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_rewrite_default_imports() -> anyhow::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::write(
+            root.join("UserCard.tsx"),
+            "export default function UserCard() {}\n",
+        )?;
+        fs::write(
+            root.join("XMLParser.ts"),
+            "export default class XMLParser {}\n",
+        )?;
+        fs::write(root.join("Theme.ts"), "export default { dark: true };\n")?;
+        let mut plan = Plan::default();
+        for (from, to) in [
+            ("UserCard.tsx", "user-card.tsx"),
+            ("XMLParser.ts", "xml-parser.ts"),
+            ("Theme.ts", "theme.ts"),
+        ] {
+            plan.push(root.join(from), root.join(to));
+        }
+        let content = "\
+import Card, { type Props } from './UserCard';
+import parser from './XMLParser';
+import theme from './Theme';
+// Card is rendered below
+const label = `Card: ${Card.name}`;
+export const App = (props: Props) => <Card {...props} card={props.Card} />;
+";
+        let main = root.join("main.tsx");
+        let (rewritten, changes) =
+            rewrite(&main, content, &plan, ImportCase::Pascal);
+        assert_eq!(changes, 4);
+        assert_eq!(
+            rewritten,
+            content
+                .replace("import Card,", "import UserCard,")
+                .replace("${Card.", "${UserCard.")
+                .replace("<Card ", "<UserCard ")
+                .replace("import parser", "import XMLParser")
+        );
+
+        let (_, changes) = rewrite(&main, content, &plan, ImportCase::Camel);
+        assert_eq!(changes, 4);
+        let content = "import Card from './UserCard';\nexport { Card };\n";
+        let found = mismatches(&main, content, &plan, ImportCase::Pascal);
+        assert_eq!(found[0].blocked, Some("it's exported under that name"));
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+use crate::plan::Plan;
+use crate::scanner::splice;
+use clap::ValueEnum;
+use regex::Regex;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// What to do with `describe('UserCard', ...)` blocks named after a test
+/// file that's renamed
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DescribeBlocks {
+    /// Leave them as they are
+    #[default]
+    Keep,
+    /// Leave them as they are and warn about each one
+    Flag,
+    /// Rename them along with the file
+    Rewrite,
+}
+
+/// Whether `path` is a test file, like `UserCard.test.ts` or a file in
+/// `__tests__`
+fn is_test_file(path: &Path) -> bool {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    name.contains(".test.")
+        || name.contains(".spec.")
+        || path
+            .parent()
+            .and_then(Path::file_name)
+            .is_some_and(|dir| dir == "__tests__")
+}
+
+/// The part of a file name before its first dot, which test suites are
+/// usually named after
+fn base_name(path: &Path) -> Option<&str> {
+    let name = path.file_name()?.to_str()?;
+    name.split('.').next().filter(|base| !base.is_empty())
+}
+
+fn describe_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(concat!(
+            r"\b(?:describe|suite|context)(?:\.\w+)*\(\s*",
+            r#"(?:'([^'\n]*)'|"([^"\n]*)"|`([^`\n]*)`)"#,
+        ))
+        .unwrap()
+    })
+}
+
+/// Renames the `describe`, `suite` and `context` blocks in the test file at
+/// `path` whose title is its old base name, if `plan` renames it. Blocks
+/// with any other title are left alone.
+pub fn rewrite(path: &Path, content: &str, plan: &Plan) -> (String, usize) {
+    let target = plan.target_of(path);
+    let (Some(old), Some(new)) =
+        (base_name(path), target.as_deref().and_then(base_name))
+    else {
+        return (content.to_string(), 0);
+    };
+    if old == new || !is_test_file(path) {
+        return (content.to_string(), 0);
+    }
+    let edits = describe_regex()
+        .captures_iter(content)
+        .filter_map(|caps| caps.iter().skip(1).flatten().next())
+        .filter(|title| title.as_str() == old)
+        .map(|title| (title.range(), new.to_string()))
+        .collect();
+    splice(content, edits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_rewrite_describe_blocks() {
+        let mut plan = Plan::default();
+        plan.push(
+            PathBuf::from("src/UserCard.test.tsx"),
+            PathBuf::from("src/user-card.test.tsx"),
+        );
+        let content = "\
+describe('UserCard', () => {
+  describe.each(cases)(\"UserCard\", () => {});
+  describe('UserCard rendering', () => {});
+  it('UserCard', () => {});
+});
+";
+        let (rewritten, changes) =
+            rewrite(Path::new("src/UserCard.test.tsx"), content, &plan);
+        assert_eq!(changes, 1);
+        assert_eq!(
+            rewritten,
+            content.replacen("describe('UserCard'", "describe('user-card'", 1)
+        );
+        // Only test files, and only when they're renamed
+        let (_, changes) =
+            rewrite(Path::new("src/UserCard.tsx"), content, &plan);
+        assert_eq!(changes, 0);
+        let (_, changes) =
+            rewrite(Path::new("src/Other.test.tsx"), content, &plan);
+        assert_eq!(changes, 0);
+    }
+}
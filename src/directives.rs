@@ -0,0 +1,71 @@
+/// Anywhere in a file, keeps every reference in it as written
+pub const IGNORE_FILE: &str = "kebabify-ignore-file";
+/// Keeps the references on the line after the one it's on as written
+pub const IGNORE_NEXT_LINE: &str = "kebabify-ignore-next-line";
+
+/// Undoes the rewrites in `new` that inline directives in `old` opt out of.
+/// Directives are found by name in any comment syntax, like
+/// `// kebabify-ignore-next-line` or `# kebabify-ignore-file`. Rewrites
+/// never add or remove lines, so lines are matched up by position. Returns
+/// the content and the number of changes left.
+pub fn apply(old: &str, (new, changes): (String, usize)) -> (String, usize) {
+    if changes == 0 || !old.contains("kebabify-ignore") {
+        return (new, changes);
+    }
+    if old.contains(IGNORE_FILE) {
+        return (old.to_string(), 0);
+    }
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    if old_lines.len() != new_lines.len() {
+        return (new, changes);
+    }
+
+    let mut restored = 0;
+    let mut result = String::with_capacity(new.len());
+    for (i, (old_line, new_line)) in
+        old_lines.iter().zip(&new_lines).enumerate()
+    {
+        let ignored = i > 0 && old_lines[i - 1].contains(IGNORE_NEXT_LINE);
+        if ignored && old_line != new_line {
+            restored += 1;
+            result.push_str(old_line);
+        } else {
+            result.push_str(new_line);
+        }
+    }
+    // A restored line had at least one change; any more were its own
+    let changes = if result == old { 0 } else { changes - restored };
+    (result, changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_directives() {
+        let old = "import a from './Lib/A';
+// kebabify-ignore-next-line
+const route = require('./Routes/Home');
+import b from './Lib/B';
+";
+        let new = old
+            .replace("/Lib/", "/lib/")
+            .replace("/Routes/Home", "/routes/home");
+        let (result, changes) = apply(old, (new, 3));
+        assert_eq!(
+            result,
+            "import a from './lib/A';
+// kebabify-ignore-next-line
+const route = require('./Routes/Home');
+import b from './lib/B';
+"
+        );
+        assert_eq!(changes, 2);
+
+        let old = format!("/* {} */\n{}", IGNORE_FILE, old);
+        let new = old.replace("/Lib/", "/lib/");
+        assert_eq!(apply(&old, (new, 2)), (old.clone(), 0));
+    }
+}
@@ -0,0 +1,77 @@
+use std::path::{Path, PathBuf};
+
+/// Formats paths for terminal output and reports relative to a fixed base
+/// directory, so the same run prints the same paths on every machine
+#[derive(Debug, Default, Clone)]
+pub struct PathDisplay {
+    base: Option<PathBuf>,
+}
+
+impl PathDisplay {
+    pub fn new(base: &Path) -> Self {
+        Self {
+            base: std::path::absolute(base).ok(),
+        }
+    }
+
+    /// Uses the enclosing repository root of `path` as the base, falling
+    /// back to `path` itself when it isn't inside a repository
+    pub fn for_root(path: &Path) -> Self {
+        let root = std::path::absolute(path).unwrap_or(path.to_path_buf());
+        let repo = root
+            .ancestors()
+            .find(|dir| dir.join(".git").exists())
+            .unwrap_or(&root);
+        Self::new(repo)
+    }
+
+    /// Paths outside the base are shown in full
+    pub fn show(&self, path: &Path) -> String {
+        let Some(base) = &self.base else {
+            return path.display().to_string();
+        };
+        let Ok(absolute) = std::path::absolute(path) else {
+            return path.display().to_string();
+        };
+
+        match absolute.strip_prefix(base) {
+            Ok(rel) if rel.as_os_str().is_empty() => ".".to_string(),
+            Ok(rel) => rel.to_string_lossy().replace('\\', "/"),
+            Err(_) => absolute.display().to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_show_relative_to_base() {
+        let display = PathDisplay::new(Path::new("/repo"));
+        assert_eq!(display.show(Path::new("/repo/src/App.ts")), "src/App.ts");
+        assert_eq!(display.show(Path::new("/repo")), ".");
+        assert_eq!(
+            display.show(Path::new("/elsewhere/a.ts")),
+            "/elsewhere/a.ts"
+        );
+    }
+
+    #[test]
+    fn test_for_root_finds_repository() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let repo = temp_dir.path();
+        fs::create_dir_all(repo.join(".git"))?;
+        fs::create_dir_all(repo.join("web/src"))?;
+
+        let display = PathDisplay::for_root(&repo.join("web"));
+        assert_eq!(
+            display.show(&repo.join("web/src/App.ts")),
+            "web/src/App.ts"
+        );
+        Ok(())
+    }
+}
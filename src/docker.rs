@@ -0,0 +1,268 @@
+use crate::plan::Plan;
+use crate::scanner::{self, rewrite_file_path, splice, ScanOptions, Scanner};
+use regex::Regex;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Dockerfiles and Compose files, for the paths they copy and mount
+pub struct DockerScanner;
+
+impl Scanner for DockerScanner {
+    fn matches(&self, path: &Path) -> bool {
+        is_dockerfile(path) || is_compose_file(path)
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_paths(path, content, scanner::converted_path)
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_paths(path, content, |found| plan.target_of(found))
+    }
+
+    /// Host paths that lead nowhere. A Dockerfile's build context may be
+    /// any directory above it, so its sources have to lead nowhere from
+    /// each of them. URLs and named volumes aren't host paths.
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> Vec<String> {
+        let dockerfile = is_dockerfile(path);
+        host_paths(path, content)
+            .into_iter()
+            .map(|(base, range)| (base, &content[range]))
+            .filter(|(_, file)| {
+                file.contains(['/', '.']) && !file.contains(':')
+            })
+            .filter(|(base, file)| match dockerfile {
+                true => base
+                    .ancestors()
+                    .all(|base| scanner::leads_nowhere(base, file, &[])),
+                false => scanner::leads_nowhere(base, file, &[]),
+            })
+            .map(|(_, file)| file.to_string())
+            .collect()
+    }
+
+    fn pins_name(&self, path: &Path) -> Option<&'static str> {
+        is_dockerfile(path)
+            .then_some("`docker build` looks for the Dockerfile by this name")
+    }
+}
+
+fn file_name(path: &Path) -> &str {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default()
+}
+
+/// `Dockerfile`, `Dockerfile.dev`, `Containerfile` and `app.dockerfile`
+fn is_dockerfile(path: &Path) -> bool {
+    let name = file_name(path);
+    ["Dockerfile", "Containerfile"].iter().any(|base| {
+        name == *base
+            || name.strip_prefix(base).is_some_and(|s| s.starts_with('.'))
+    }) || name.ends_with(".dockerfile")
+}
+
+/// `docker-compose.yml`, `compose.yaml`, `docker-compose.prod.yml`, ...
+fn is_compose_file(path: &Path) -> bool {
+    Regex::new(r"^(?:docker-)?compose(?:\.[\w-]+)?\.ya?ml$")
+        .unwrap()
+        .is_match(file_name(path))
+}
+
+fn rewrite_paths(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let mut edits = Vec::new();
+    for (base, range) in host_paths(path, content) {
+        let file = &content[range.clone()];
+        edits.extend(rewrite_file_path(&base, file, range.start, &[], &rename));
+    }
+    splice(content, edits)
+}
+
+/// The host paths in a Dockerfile or Compose file, each with the directory
+/// it's relative to
+fn host_paths(path: &Path, content: &str) -> Vec<(PathBuf, Range<usize>)> {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    if is_dockerfile(path) {
+        copy_sources(content)
+            .into_iter()
+            .map(|range| (dir.to_path_buf(), range))
+            .collect()
+    } else {
+        compose_paths(dir, content)
+    }
+}
+
+/// Sources of `COPY` and `ADD` instructions, in shell or JSON form. They're
+/// relative to the build context, taken to be the Dockerfile's directory.
+/// Copies from another stage or image (`--from`) are skipped.
+fn copy_sources(content: &str) -> Vec<Range<usize>> {
+    let instruction =
+        Regex::new(r"(?mi)^[ \t]*(?:COPY|ADD)[ \t]+(.*)$").unwrap();
+    let json_string = Regex::new(r#""([^"]*)""#).unwrap();
+    let word = Regex::new(r"\S+").unwrap();
+
+    let mut sources = Vec::new();
+    for caps in instruction.captures_iter(content) {
+        let args = caps.get(1).unwrap();
+        if args.as_str().contains("--from") {
+            continue;
+        }
+        let mut words: Vec<Range<usize>> = if args.as_str().starts_with('[') {
+            json_string
+                .captures_iter(args.as_str())
+                .map(|c| c.get(1).unwrap().range())
+                .collect()
+        } else {
+            word.find_iter(args.as_str())
+                .map(|m| m.range())
+                .filter(|r| !args.as_str()[r.clone()].starts_with("--"))
+                .collect()
+        };
+        // The last argument is the destination in the image
+        words.pop();
+        sources.extend(
+            words
+                .into_iter()
+                .map(|r| args.start() + r.start..args.start() + r.end),
+        );
+    }
+    sources
+}
+
+/// Host paths in a Compose file, each with the directory it's relative to:
+/// `build`, `context`, `env_file` and `extends` `file` entries and bind
+/// mounts are relative to the Compose file, `dockerfile` to the last
+/// `context` before it
+fn compose_paths(dir: &Path, content: &str) -> Vec<(PathBuf, Range<usize>)> {
+    let key = Regex::new(
+        r#"(?m)^[ \t-]*(build|context|dockerfile|env_file|file|source)[ \t]*:[ \t]*["']?([^"'#\s]+)"#,
+    )
+    .unwrap();
+    // `- ./Src:/app/src:ro` in `volumes`, or a relative `env_file` item
+    let item =
+        Regex::new(r#"(?m)^[ \t]*-[ \t]*["']?(\.{1,2}/[^:"'#\s]*)"#).unwrap();
+
+    let mut paths = Vec::new();
+    let mut context = dir.to_path_buf();
+    for caps in key.captures_iter(content) {
+        let value = caps.get(2).unwrap();
+        let base = match &caps[1] {
+            "dockerfile" => context.clone(),
+            "context" => {
+                context = dir.join(value.as_str());
+                dir.to_path_buf()
+            }
+            _ => dir.to_path_buf(),
+        };
+        paths.push((base, value.range()));
+    }
+    for caps in item.captures_iter(content) {
+        paths.push((dir.to_path_buf(), caps.get(1).unwrap().range()));
+    }
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_dockerfile_and_compose() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("Src/Config"))?;
+        fs::create_dir_all(root.join("Docker"))?;
+        fs::write(root.join("Src/AppServer.ts"), "")?;
+        fs::write(root.join("Docker/Dockerfile.Dev"), "")?;
+
+        let dockerfile = "\
+FROM node AS build
+COPY --chown=node Src/AppServer.ts Src/Config/ /app/Src/
+ADD [\"Src/AppServer.ts\", \"/app/\"]
+COPY --from=build /app/Src/AppServer.ts /srv/
+";
+        let (new_content, changes) = DockerScanner.convert(
+            &root.join("Dockerfile"),
+            dockerfile,
+            &ScanOptions::default(),
+        );
+        assert_eq!(
+            new_content,
+            "\
+FROM node AS build
+COPY --chown=node src/app-server.ts src/config/ /app/Src/
+ADD [\"src/app-server.ts\", \"/app/\"]
+COPY --from=build /app/Src/AppServer.ts /srv/
+"
+        );
+        assert_eq!(changes, 6);
+
+        let compose = "\
+services:
+  app:
+    build:
+      context: ./Docker
+      dockerfile: Dockerfile.Dev
+    volumes:
+      - ./Src:/app/src:ro
+      - data:/var/lib/data
+";
+        let (new_content, _) = DockerScanner.convert(
+            &root.join("docker-compose.yml"),
+            compose,
+            &ScanOptions::default(),
+        );
+        assert_eq!(
+            new_content,
+            "\
+services:
+  app:
+    build:
+      context: ./docker
+      dockerfile: Dockerfile.Dev
+    volumes:
+      - ./src:/app/src:ro
+      - data:/var/lib/data
+"
+        );
+        assert!(scanner::pinned(&root.join("Docker/Dockerfile.Dev"), false)
+            .is_some());
+
+        let orphans = DockerScanner.orphans(
+            &root.join("docker-compose.yml"),
+            &compose.replace("Dockerfile.Dev", "Dockerfile.Gone"),
+            &ScanOptions::default(),
+        );
+        assert_eq!(orphans, ["Dockerfile.Gone"]);
+        let orphans = DockerScanner.orphans(
+            &root.join("Docker/Dockerfile.Dev"),
+            "COPY Src/AppServer.ts Src/Gone.ts /app/\n",
+            &ScanOptions::default(),
+        );
+        assert_eq!(orphans, ["Src/Gone.ts"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,515 @@
+use crate::plan::Plan;
+use crate::scanner::{
+    self, rewrite_config_paths, rewrite_file_path, splice, JsScanner,
+    ScanOptions, Scanner,
+};
+use regex::Regex;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Extensions documentation pages are named without
+const PAGE_EXTENSIONS: &[&str] = &["md", "mdx"];
+
+/// Documentation site configs: Docusaurus's `docusaurus.config.*` and
+/// `sidebars.*`, VuePress's `.vuepress/config.*` and Astro's
+/// `astro.config.*` and content collections config. Sidebars name pages
+/// by path, and a page that's been renamed out from under one disappears
+/// from the navigation without an error.
+pub struct DocsScanner;
+
+impl Scanner for DocsScanner {
+    fn matches(&self, path: &Path) -> bool {
+        config_kind(path).is_some()
+    }
+
+    fn converts_unresolved(&self) -> bool {
+        true
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        scan: &ScanOptions,
+    ) -> (String, usize) {
+        let (content, paths) =
+            rewrite_paths(path, content, scanner::converted_path);
+        let (content, imports) = JsScanner.convert(path, &content, scan);
+        (content, paths + imports)
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        scan: &ScanOptions,
+    ) -> (String, usize) {
+        let (content, paths) =
+            rewrite_paths(path, content, |found| plan.target_of(found));
+        let (content, imports) =
+            JsScanner.follow_plan(path, &content, plan, scan);
+        (content, paths + imports)
+    }
+
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        scan: &ScanOptions,
+    ) -> Vec<String> {
+        JsScanner.orphans(path, content, scan)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Config {
+    Docusaurus,
+    Sidebars,
+    VuePress,
+    Astro,
+    /// `src/content/config.*` or `src/content.config.*`
+    Collections,
+}
+
+fn config_kind(path: &Path) -> Option<Config> {
+    let name = path.file_name()?.to_str()?;
+    let (stem, extension) = name.rsplit_once('.')?;
+    let script =
+        matches!(extension, "js" | "cjs" | "mjs" | "ts" | "cts" | "mts");
+    let parent = path.parent().and_then(Path::file_name);
+    let kind = match stem {
+        "docusaurus.config" => Config::Docusaurus,
+        "sidebars" if extension == "json" => return Some(Config::Sidebars),
+        "sidebars" => Config::Sidebars,
+        "astro.config" => Config::Astro,
+        "content.config" => Config::Collections,
+        "config" if parent.is_some_and(|p| p == ".vuepress") => {
+            Config::VuePress
+        }
+        "config" if parent.is_some_and(|p| p == "content") => {
+            Config::Collections
+        }
+        _ => return None,
+    };
+    script.then_some(kind)
+}
+
+/// Rewrites the config's paths: those relative to it, the pages a
+/// sidebar names by doc ID or by site path, and an Astro collection's
+/// `base` and the collection directories `collections` names
+fn rewrite_paths(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let Some(kind) = config_kind(path) else {
+        return (content.to_string(), 0);
+    };
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut edits = Vec::new();
+    match kind {
+        Config::Docusaurus | Config::Astro => {
+            // Bare directory names like `path: 'Docs'`
+            for range in setting_values(content, &["path", "srcDir", "base"]) {
+                let value = &content[range.clone()];
+                if !value.contains(['/', '.']) {
+                    edits.extend(rewrite_file_path(
+                        dir,
+                        value,
+                        range.start,
+                        &[],
+                        &rename,
+                    ));
+                }
+            }
+        }
+        Config::Sidebars => {
+            let bases = docs_dirs(dir);
+            for range in string_values(content) {
+                let id = &content[range.clone()];
+                // Labels and the like can share a name with a directory, so
+                // only strings that name a page count
+                let found = bases.iter().find(|base| {
+                    PAGE_EXTENSIONS
+                        .iter()
+                        .any(|e| base.join(format!("{}.{}", id, e)).is_file())
+                });
+                if let Some(base) = found {
+                    edits.extend(rewrite_file_path(
+                        base,
+                        id,
+                        range.start,
+                        PAGE_EXTENSIONS,
+                        &rename,
+                    ));
+                }
+            }
+        }
+        Config::VuePress => {
+            // Site paths are relative to the docs directory `.vuepress` is in
+            let source = dir.parent().unwrap_or(dir);
+            for range in string_values(content) {
+                let Some(page) = content[range.clone()].strip_prefix('/')
+                else {
+                    continue;
+                };
+                let first = page.split('/').next().unwrap_or(page);
+                if first.is_empty() || !source.join(first).exists() {
+                    continue;
+                }
+                edits.extend(rewrite_file_path(
+                    source,
+                    page,
+                    range.start + 1,
+                    PAGE_EXTENSIONS,
+                    &rename,
+                ));
+            }
+        }
+        Config::Collections => {
+            let root =
+                dir.ancestors().find(|d| has_astro_config(d)).unwrap_or(dir);
+            let collections = match dir.file_name() {
+                Some(name) if name == "content" => dir.to_path_buf(),
+                _ => dir.join("content"),
+            };
+            // Loader bases are relative to the project, not the config
+            let (content, bases) =
+                rewrite_config_paths(&root.join("config"), content, &rename);
+            let (content, keys) =
+                rewrite_collections(&collections, &content, &rename);
+            return (content, bases + keys);
+        }
+    }
+    let (content, pages) = splice(content, edits);
+    let (content, paths) = rewrite_config_paths(path, &content, &rename);
+    (content, pages + paths)
+}
+
+fn has_astro_config(dir: &Path) -> bool {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return false;
+    };
+    entries.filter_map(|e| e.ok()).any(|e| {
+        let name = e.file_name();
+        name.to_string_lossy().starts_with("astro.config.")
+    })
+}
+
+/// The directories a Docusaurus sidebar's doc IDs are relative to: the
+/// docs plugins' `path`s in `docusaurus.config.*` next to it, and `docs`
+fn docs_dirs(dir: &Path) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    let configs = fs::read_dir(dir).into_iter().flatten().filter_map(|e| {
+        let path = e.ok()?.path();
+        (config_kind(&path) == Some(Config::Docusaurus)).then_some(path)
+    });
+    for config in configs {
+        let content = fs::read_to_string(&config).unwrap_or_default();
+        for range in setting_values(&content, &["path"]) {
+            let value = content[range].trim_start_matches("./");
+            dirs.push(dir.join(value));
+        }
+    }
+    dirs.push(dir.join("docs"));
+    dirs
+}
+
+/// Where each quoted string in `content` is, without its quotes
+fn string_values(content: &str) -> Vec<Range<usize>> {
+    let string = Regex::new(r#""([^"\n]*)"|'([^'\n]*)'|`([^`$\n]*)`"#).unwrap();
+    string
+        .captures_iter(content)
+        .map(|caps| caps.iter().skip(1).flatten().next().unwrap().range())
+        .collect()
+}
+
+/// Where the strings set for any of `keys` are, without their quotes
+fn setting_values(content: &str, keys: &[&str]) -> Vec<Range<usize>> {
+    let setting =
+        Regex::new(r#"\b(\w+)["']?\s*:\s*["'`]([^"'`\n]*)["'`]"#).unwrap();
+    setting
+        .captures_iter(content)
+        .filter(|caps| keys.contains(&&caps[1]))
+        .map(|caps| caps.get(2).unwrap().range())
+        .collect()
+}
+
+/// Rewrites the keys of `export const collections = { Blog, 'Docs': docs }`,
+/// which Astro looks for as directories in `src/content`. A shorthand
+/// `Blog` becomes `blog: Blog`.
+fn rewrite_collections(
+    collections: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let object = Regex::new(r"collections\s*=\s*\{([^}]*)\}").unwrap();
+    let entry =
+        Regex::new(r#"(?:^|,)\s*(?:["']([^"'\n]+)["']|(\w+))\s*(:)?"#).unwrap();
+    let Some(body) = object.captures(content).and_then(|caps| caps.get(1))
+    else {
+        return (content.to_string(), 0);
+    };
+
+    let mut edits = Vec::new();
+    for caps in entry.captures_iter(body.as_str()) {
+        let key = caps.get(1).or(caps.get(2)).unwrap();
+        let dir = collections.join(key.as_str());
+        if !dir.is_dir() {
+            continue;
+        }
+        let new = rename(&dir);
+        let Some(new) = new.as_deref().and_then(Path::file_name) else {
+            continue;
+        };
+        let new = new.to_string_lossy();
+        if new == key.as_str() {
+            continue;
+        }
+        let quoted = caps.get(1).is_some();
+        let replacement = match (quoted, caps.get(3).is_some()) {
+            (true, _) => new.to_string(),
+            (false, true) if is_identifier(&new) => new.to_string(),
+            (false, true) => format!("'{}'", new),
+            (false, false) => format!("'{}': {}", new, key.as_str()),
+        };
+        let start = body.start() + key.start();
+        edits.push((start..start + key.len(), replacement));
+    }
+    splice(content, edits)
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.chars()
+        .all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// Markdown and MDX pages, for the `slug` and `image` fields of their
+/// front matter that name files
+pub struct FrontMatterScanner;
+
+impl Scanner for FrontMatterScanner {
+    fn matches(&self, path: &Path) -> bool {
+        path.extension().is_some_and(|e| e == "md" || e == "mdx")
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_front_matter(path, content, scanner::converted_path)
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_front_matter(path, content, |found| plan.target_of(found))
+    }
+}
+
+/// Rewrites `slug: /Guides/GettingStarted` and `image: ./Img/Hero.png`.
+/// A relative path is relative to the page; one starting with `/` is
+/// looked up from the page's directory upwards, and in the `static` and
+/// `public` directories there, where sites serve assets from. A slug
+/// that doesn't name a page on disk is a URL of its own and is left alone.
+fn rewrite_front_matter(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let Some(body) = content.strip_prefix("---\n") else {
+        return (content.to_string(), 0);
+    };
+    let Some(end) = body.find("\n---") else {
+        return (content.to_string(), 0);
+    };
+    let field = Regex::new(
+        r#"(?m)^(slug|image)[ \t]*:[ \t]*["']?([^"'#\s][^"'#\n]*?)["']?[ \t]*$"#,
+    )
+    .unwrap();
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let offset = content.len() - body.len();
+
+    let mut edits = Vec::new();
+    for caps in field.captures_iter(&body[..end]) {
+        let value = caps.get(2).unwrap();
+        if value.as_str().contains("://") {
+            continue;
+        }
+        let extensions = match &caps[1] {
+            "slug" => PAGE_EXTENSIONS,
+            _ => &[],
+        };
+        let (file, start, bases) = match value.as_str().strip_prefix('/') {
+            Some(file) => {
+                let bases = dir
+                    .ancestors()
+                    .flat_map(|a| {
+                        [a.to_path_buf(), a.join("static"), a.join("public")]
+                    })
+                    .collect();
+                (file, value.start() + 1, bases)
+            }
+            None => (value.as_str(), value.start(), vec![dir.to_path_buf()]),
+        };
+        let exists = |base: &PathBuf| {
+            let path = base.join(file);
+            path.exists()
+                || extensions.iter().any(|e| path.with_extension(e).is_file())
+        };
+        let Some(base) = bases.iter().find(|base| exists(base)) else {
+            continue;
+        };
+        edits.extend(rewrite_file_path(
+            base,
+            file,
+            offset + start,
+            extensions,
+            &rename,
+        ));
+    }
+    splice(content, edits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_docusaurus_sidebars() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("Docs/Guides"))?;
+        fs::write(root.join("Docs/Intro.md"), "")?;
+        fs::write(root.join("Docs/Guides/GettingStarted.mdx"), "")?;
+        let config = r#"module.exports = {
+  presets: [['classic', { docs: { path: 'Docs', sidebarPath: './sidebars.js' } }]],
+};
+"#;
+        fs::write(root.join("docusaurus.config.js"), config)?;
+        fs::write(root.join("sidebars.js"), "")?;
+
+        let path = root.join("docusaurus.config.js");
+        assert!(DocsScanner.matches(&path));
+        let (new_config, _) =
+            DocsScanner.convert(&path, config, &ScanOptions::default());
+        assert!(new_config.contains("path: 'docs'"));
+
+        let sidebars = r#"module.exports = {
+  docs: ['Intro', { type: 'category', label: 'Guides', items: ['Guides/GettingStarted'] }],
+};
+"#;
+        let (new_sidebars, changes) = DocsScanner.convert(
+            &root.join("sidebars.js"),
+            sidebars,
+            &ScanOptions::default(),
+        );
+        assert_eq!(
+            new_sidebars,
+            r#"module.exports = {
+  docs: ['intro', { type: 'category', label: 'Guides', items: ['guides/getting-started'] }],
+};
+"#
+        );
+        assert_eq!(changes, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_vuepress_and_astro_configs() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("Docs/.vuepress"))?;
+        fs::create_dir_all(root.join("Docs/Guide"))?;
+        fs::write(root.join("Docs/Guide/QuickStart.md"), "")?;
+        let vuepress = "export default { themeConfig: { sidebar: ['/Guide/', '/Guide/QuickStart.md', '/Guide/QuickStart'] } }\n";
+        let path = root.join("Docs/.vuepress/config.ts");
+        assert!(DocsScanner.matches(&path));
+        let (new_vuepress, _) =
+            DocsScanner.convert(&path, vuepress, &ScanOptions::default());
+        assert_eq!(
+            new_vuepress,
+            "export default { themeConfig: { sidebar: ['/guide/', '/guide/quick-start.md', '/guide/quick-start'] } }\n"
+        );
+
+        fs::write(root.join("astro.config.mjs"), "")?;
+        fs::create_dir_all(root.join("src/content/BlogPosts"))?;
+        fs::create_dir_all(root.join("src/Data/Authors"))?;
+        let collections = r#"const blog = defineCollection({ type: 'content' });
+const authors = defineCollection({
+  loader: glob({ pattern: '**/*.json', base: './src/Data/Authors' }),
+});
+export const collections = { BlogPosts: blog, authors };
+"#;
+        let path = root.join("src/content/config.ts");
+        assert!(DocsScanner.matches(&path));
+        let (new_collections, _) =
+            DocsScanner.convert(&path, collections, &ScanOptions::default());
+        assert_eq!(
+            new_collections,
+            r#"const blog = defineCollection({ type: 'content' });
+const authors = defineCollection({
+  loader: glob({ pattern: '**/*.json', base: './src/data/authors' }),
+});
+export const collections = { 'blog-posts': blog, authors };
+"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_front_matter() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("docs/Guides/Img"))?;
+        fs::create_dir_all(root.join("static/Img"))?;
+        fs::write(root.join("docs/Guides/GettingStarted.md"), "")?;
+        fs::write(root.join("docs/Guides/Img/Hero.png"), "")?;
+        fs::write(root.join("static/Img/Social.png"), "")?;
+
+        let page = "---
+title: Getting Started
+slug: /Guides/GettingStarted
+image: ./Img/Hero.png
+---
+
+See ./Img/Hero.png for slug: /Guides/GettingStarted.
+";
+        let path = root.join("docs/Guides/Overview.md");
+        assert!(FrontMatterScanner.matches(&path));
+        let (new_page, changes) =
+            FrontMatterScanner.convert(&path, page, &ScanOptions::default());
+        assert_eq!(
+            new_page,
+            "---
+title: Getting Started
+slug: /guides/getting-started
+image: ./img/hero.png
+---
+
+See ./Img/Hero.png for slug: /Guides/GettingStarted.
+"
+        );
+        assert_eq!(changes, 4);
+
+        let page = "---\nimage: '/Img/Social.png'\nslug: /Custom/Url\n---\n";
+        let (new_page, _) =
+            FrontMatterScanner.convert(&path, page, &ScanOptions::default());
+        assert_eq!(
+            new_page,
+            "---\nimage: '/img/social.png'\nslug: /Custom/Url\n---\n"
+        );
+        Ok(())
+    }
+}
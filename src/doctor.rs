@@ -0,0 +1,243 @@
+use crate::report::Report;
+use crate::resolve::{is_relative_specifier, RESOLVE_EXTENSIONS};
+use crate::scanner::scanner_for;
+use crate::{convert_name, encoding, import_regex, Options};
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::DirEntry;
+
+/// A latent problem found by `kebabify doctor`
+#[derive(Debug, PartialEq, Eq)]
+pub struct Finding {
+    pub path: PathBuf,
+    pub problem: String,
+    pub fix: String,
+}
+
+/// Scans `dir` for problems that would break or complicate a rename, without
+/// changing anything. Returns how many problems were found.
+pub fn run(dir: &Path, opts: &Options, report: &mut Report) -> Result<usize> {
+    let entries = crate::walk(dir, opts, report)?;
+    let mut findings = case_collisions(&entries);
+    findings.extend(conversion_collisions(&entries));
+
+    for entry in crate::source_files(dir, opts, report)? {
+        let Ok(bytes) = fs::read(entry.path()) else {
+            continue;
+        };
+        let Some((content, _)) = encoding::decode(&bytes) else {
+            continue;
+        };
+        let base_dir = entry.path().parent().unwrap_or(Path::new("."));
+
+        for caps in import_regex().captures_iter(&content) {
+            let spec = &caps[2];
+            if let Some(fixed) = fix_import_case(base_dir, spec) {
+                findings.push(Finding {
+                    path: entry.path().to_path_buf(),
+                    problem: format!(
+                        "'{}' only matches files on disk case-insensitively \
+                         (breaks on Linux)",
+                        spec
+                    ),
+                    fix: format!("import '{}' instead", fixed),
+                });
+            }
+        }
+        if let Some(scanner) = scanner_for(entry.path()) {
+            findings.extend(scanner.findings(entry.path(), &content));
+        }
+    }
+
+    print_findings(&findings, opts);
+    Ok(findings.len())
+}
+
+pub fn print_findings(findings: &[Finding], opts: &Options) {
+    for finding in findings {
+        println!("{}: {}", opts.display.show(&finding.path), finding.problem);
+        println!("  fix: {}", finding.fix);
+    }
+    if findings.is_empty() {
+        println!("No problems found");
+    }
+}
+
+/// Siblings whose names differ only by case. They can't coexist on
+/// case-insensitive filesystems (macOS, Windows).
+pub fn case_collisions(entries: &[DirEntry]) -> Vec<Finding> {
+    group_siblings(entries, |name| name.to_lowercase())
+        .into_iter()
+        .map(|(dir, names)| Finding {
+            path: dir,
+            problem: format!("names differ only by case: {}", names.join(", ")),
+            fix: "rename or merge all but one of them".to_string(),
+        })
+        .collect()
+}
+
+/// Siblings that would end up with the same name once converted, e.g.
+/// `UserCard.ts` next to `user-card.ts`
+pub fn conversion_collisions(entries: &[DirEntry]) -> Vec<Finding> {
+    group_siblings(entries, convert_name)
+        .into_iter()
+        // Names that only differ by case are already reported as such
+        .filter(|(_, names)| {
+            names.iter().any(|n| !n.eq_ignore_ascii_case(&names[0]))
+        })
+        .map(|(dir, names)| Finding {
+            path: dir,
+            problem: format!(
+                "{} would all be renamed to {}",
+                names.join(", "),
+                convert_name(&names[0])
+            ),
+            fix: "rename one of them by hand before running kebabify"
+                .to_string(),
+        })
+        .collect()
+}
+
+/// Groups sibling names by `key` and returns every group with more than one
+/// distinct name, keyed by parent directory
+fn group_siblings(
+    entries: &[DirEntry],
+    key: impl Fn(&str) -> String,
+) -> Vec<(PathBuf, Vec<String>)> {
+    let mut groups: BTreeMap<(PathBuf, String), Vec<String>> = BTreeMap::new();
+    for entry in entries.iter().filter(|e| e.depth() > 0) {
+        let (Some(parent), Some(name)) =
+            (entry.path().parent(), entry.file_name().to_str())
+        else {
+            continue;
+        };
+        let names =
+            groups.entry((parent.to_path_buf(), key(name))).or_default();
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    }
+
+    groups
+        .into_iter()
+        .filter(|(_, names)| names.len() > 1)
+        .map(|((dir, _), mut names)| {
+            names.sort();
+            (dir, names)
+        })
+        .collect()
+}
+
+/// Checks a relative specifier against the exact names on disk. Returns the
+/// correctly cased specifier if the import only resolves case-insensitively,
+/// which works on macOS and Windows but breaks on Linux.
+pub fn fix_import_case(base_dir: &Path, spec: &str) -> Option<String> {
+    if !is_relative_specifier(spec) {
+        return None;
+    }
+
+    let segments: Vec<&str> = spec.split('/').collect();
+    let mut dir = base_dir.to_path_buf();
+    let mut fixed = Vec::with_capacity(segments.len());
+    let mut mismatched = false;
+
+    for (i, segment) in segments.iter().enumerate() {
+        if matches!(*segment, "." | ".." | "") {
+            dir.push(segment);
+            fixed.push(segment.to_string());
+            continue;
+        }
+
+        let is_last = i == segments.len() - 1;
+        let (actual, exact) = match find_entry(&dir, segment) {
+            Some(found) => found,
+            None if is_last => find_with_extension(&dir, segment)?,
+            None => return None,
+        };
+        mismatched |= !exact;
+        dir.push(&actual);
+        fixed.push(actual);
+    }
+
+    mismatched.then(|| fixed.join("/"))
+}
+
+/// Finds `name` in `dir`, preferring an exact match over a case-insensitive
+/// one, then the first case-insensitive match in name order. Returns the
+/// name as it is on disk.
+fn find_entry(dir: &Path, name: &str) -> Option<(String, bool)> {
+    let mut insensitive: Option<String> = None;
+    for entry in fs::read_dir(dir).ok()?.filter_map(|e| e.ok()) {
+        let Ok(actual) = entry.file_name().into_string() else {
+            continue;
+        };
+        if actual == name {
+            return Some((actual, true));
+        }
+        if actual.eq_ignore_ascii_case(name)
+            && insensitive.as_ref().is_none_or(|other| actual < *other)
+        {
+            insensitive = Some(actual);
+        }
+    }
+    insensitive.map(|actual| (actual, false))
+}
+
+/// Like `find_entry`, but for specifiers that omit the file's extension.
+/// The extension is stripped again from the returned name.
+fn find_with_extension(dir: &Path, stem: &str) -> Option<(String, bool)> {
+    RESOLVE_EXTENSIONS.iter().find_map(|ext| {
+        let (actual, exact) = find_entry(dir, &format!("{}.{}", stem, ext))?;
+        let actual_stem = actual.rsplit_once('.')?.0.to_string();
+        Some((actual_stem, exact))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use tempfile::TempDir;
+    use walkdir::WalkDir;
+
+    #[test]
+    fn test_collisions() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::write(root.join("UserCard.ts"), "")?;
+        fs::write(root.join("user-card.ts"), "")?;
+        fs::write(root.join("Other.ts"), "")?;
+
+        let entries: Vec<_> = WalkDir::new(root)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .collect();
+        let found = conversion_collisions(&entries);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].problem.contains("UserCard.ts, user-card.ts"));
+        assert!(case_collisions(&entries).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_fix_import_case() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir(root.join("Components"))?;
+        fs::write(root.join("Components/Button.svelte"), "")?;
+
+        assert_eq!(
+            fix_import_case(root, "./components/button.svelte"),
+            Some("./Components/Button.svelte".to_string())
+        );
+        assert_eq!(
+            fix_import_case(root, "./components/button"),
+            Some("./Components/Button".to_string())
+        );
+        assert_eq!(fix_import_case(root, "./Components/Button"), None);
+        assert_eq!(fix_import_case(root, "./Missing"), None);
+        Ok(())
+    }
+}
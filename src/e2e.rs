@@ -0,0 +1,190 @@
+use crate::plan::Plan;
+use crate::scanner::{
+    self, rewrite_config_paths, rewrite_file_path, splice, JsScanner,
+    ScanOptions, Scanner,
+};
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Cypress and Playwright configs, for the paths of their settings like
+/// `specPattern`, `fixturesFolder`, `testDir` and `snapshotDir`
+pub struct E2eScanner;
+
+impl Scanner for E2eScanner {
+    fn matches(&self, path: &Path) -> bool {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let Some((stem, extension)) = name.rsplit_once('.') else {
+            return false;
+        };
+        let script =
+            matches!(extension, "js" | "cjs" | "mjs" | "ts" | "cts" | "mts");
+        match stem {
+            "cypress.config" | "playwright.config" | "playwright-ct.config" => {
+                script
+            }
+            _ => name == "cypress.json",
+        }
+    }
+
+    fn converts_unresolved(&self) -> bool {
+        true
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        scan: &ScanOptions,
+    ) -> (String, usize) {
+        let (content, paths) =
+            rewrite_config_paths(path, content, scanner::converted_path);
+        let (content, imports) = JsScanner.convert(path, &content, scan);
+        (content, paths + imports)
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        scan: &ScanOptions,
+    ) -> (String, usize) {
+        let (content, paths) =
+            rewrite_config_paths(path, content, |found| plan.target_of(found));
+        let (content, imports) =
+            JsScanner.follow_plan(path, &content, plan, scan);
+        (content, paths + imports)
+    }
+
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        scan: &ScanOptions,
+    ) -> Vec<String> {
+        JsScanner.orphans(path, content, scan)
+    }
+}
+
+/// Extensions `cy.fixture` tries, in order, when a fixture is named
+/// without one
+const FIXTURE_EXTENSIONS: &[&str] = &[
+    "json", "js", "coffee", "html", "txt", "csv", "png", "jpg", "jpeg", "gif",
+    "tif", "tiff", "zip",
+];
+
+/// Rewrites the fixtures named by `cy.fixture('Users/AdminUser.json')` in
+/// a test at `path`. They're relative to the `fixturesFolder` of the
+/// closest `cypress.config.*` above it, `cypress/fixtures` by default.
+pub fn rewrite_fixtures(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    if !content.contains("cy.fixture(") {
+        return (content.to_string(), 0);
+    }
+    let Some(folder) = fixtures_folder(path) else {
+        return (content.to_string(), 0);
+    };
+    let call = Regex::new(r#"cy\.fixture\(\s*["'`]([^"'`$\n]+)["'`]"#).unwrap();
+
+    let mut edits = Vec::new();
+    for caps in call.captures_iter(content) {
+        let file = caps.get(1).unwrap();
+        edits.extend(rewrite_file_path(
+            &folder,
+            file.as_str(),
+            file.start(),
+            FIXTURE_EXTENSIONS,
+            &rename,
+        ));
+    }
+    splice(content, edits)
+}
+
+/// The fixtures folder of the Cypress project a test at `path` is in, or
+/// `None` if it isn't in one or fixtures are turned off
+fn fixtures_folder(path: &Path) -> Option<PathBuf> {
+    let setting =
+        Regex::new(r#"fixturesFolder["']?\s*:\s*(?:["']([^"']*)["']|false)"#)
+            .unwrap();
+    for dir in path.ancestors().skip(1) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+        let config =
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .find(|config| {
+                    let name = config.file_name().and_then(|n| n.to_str());
+                    name.is_some_and(|n| {
+                        n.starts_with("cypress.config.") || n == "cypress.json"
+                    })
+                });
+        let Some(config) = config else {
+            continue;
+        };
+        let content = fs::read_to_string(config).unwrap_or_default();
+        return match setting.captures(&content) {
+            Some(caps) => Some(dir.join(caps.get(1)?.as_str())),
+            None => Some(dir.join("cypress/fixtures")),
+        };
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_e2e_config_and_fixtures() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("Cypress/Integration"))?;
+        fs::create_dir_all(root.join("Cypress/Fixtures/Users"))?;
+        fs::write(root.join("Cypress/Fixtures/Users/AdminUser.json"), "{}")?;
+        let config = r#"import { defineConfig } from 'cypress';
+
+export default defineConfig({
+  fixturesFolder: 'Cypress/Fixtures',
+  e2e: { specPattern: 'Cypress/Integration/**/*.cy.{js,ts}' },
+});
+"#;
+        fs::write(root.join("cypress.config.ts"), config)?;
+
+        let path = root.join("cypress.config.ts");
+        assert!(E2eScanner.matches(&path));
+        let (new_config, changes) =
+            E2eScanner.convert(&path, config, &ScanOptions::default());
+        assert_eq!(changes, 4);
+        assert!(new_config.contains("fixturesFolder: 'cypress/fixtures'"));
+        assert!(
+            new_config.contains("specPattern: 'cypress/integration/**/*.cy")
+        );
+
+        let spec = "cy.fixture('Users/AdminUser').then((user) => {});
+cy.fixture(\"Users/AdminUser.json\").as('admin');
+cy.fixture('Users/Missing.json');
+";
+        let (new_spec, changes) = rewrite_fixtures(
+            &root.join("Cypress/Integration/Login.cy.ts"),
+            spec,
+            scanner::converted_path,
+        );
+        assert_eq!(
+            new_spec,
+            "cy.fixture('users/admin-user').then((user) => {});
+cy.fixture(\"users/admin-user.json\").as('admin');
+cy.fixture('users/Missing.json');
+"
+        );
+        assert_eq!(changes, 5);
+        Ok(())
+    }
+}
@@ -0,0 +1,210 @@
+use crate::plan::Plan;
+use crate::scanner::{self, rewrite_file_path, splice, ScanOptions, Scanner};
+use crate::NameStyle;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// The template engines Phoenix renders `Show.html` from
+const TEMPLATE_ENGINES: [&str; 3] = ["heex", "eex", "leex"];
+
+/// Elixir sources and Phoenix templates, named in snake_case. Module names
+/// don't follow from paths, so renaming a file never breaks one; only the
+/// paths Elixir code spells out need updating.
+pub struct ElixirScanner;
+
+impl Scanner for ElixirScanner {
+    fn matches(&self, path: &Path) -> bool {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|ext| ext == "ex" || ext == "exs" || is_template(ext))
+    }
+
+    fn name_style(&self) -> NameStyle {
+        NameStyle::Snake
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_paths(path, content, scanner::converted_path)
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_paths(path, content, |found| plan.target_of(found))
+    }
+
+    /// Files loaded by path that lead nowhere. Templates given to `render`
+    /// may belong to another view, so they aren't checked.
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> Vec<String> {
+        let dir = path.parent().unwrap_or(Path::new(""));
+        file_regex()
+            .captures_iter(content)
+            .map(|caps| caps[1].to_string())
+            .filter(|file| scanner::leads_nowhere(dir, file, &[]))
+            .collect()
+    }
+}
+
+fn is_template(ext: &str) -> bool {
+    TEMPLATE_ENGINES.contains(&ext)
+}
+
+/// The paths Elixir code loads files by, or embeds templates by
+fn file_regex() -> Regex {
+    Regex::new(concat!(
+        r#"\b(?:import_config|Code\.(?:require|eval|compile)_file|"#,
+        r#"embed_templates)\s*\(?\s*"([^"\n]+)""#,
+    ))
+    .unwrap()
+}
+
+/// Rewrites `import_config "Prod.secret.exs"`, `Code.require_file`,
+/// `Code.eval_file` and `Code.compile_file` paths and `embed_templates`
+/// globs, relative to the file, and the template names given to `render`
+fn rewrite_paths(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    if path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(is_template)
+    {
+        return (content.to_string(), 0);
+    }
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let render =
+        Regex::new(r#"\brender\s*\((?:[^()"]*,\s*)?"([^"\n]+)""#).unwrap();
+
+    let mut edits = Vec::new();
+    for caps in file_regex().captures_iter(content) {
+        let file = caps.get(1).unwrap();
+        // `"#{config_env()}.exs"` is only known at runtime
+        if file.as_str().contains("#{") {
+            continue;
+        }
+        edits.extend(rewrite_file_path(
+            dir,
+            file.as_str(),
+            file.start(),
+            &[],
+            &rename,
+        ));
+    }
+    for caps in render.captures_iter(content) {
+        let name = caps.get(1).unwrap();
+        let Some(template) = find_template(dir, name.as_str()) else {
+            continue;
+        };
+        let Some(new) = rename(&template) else {
+            continue;
+        };
+        let new = new.file_stem().and_then(|n| n.to_str());
+        if let Some(new) = new.filter(|new| *new != name.as_str()) {
+            edits.push((name.range(), new.to_string()));
+        }
+    }
+    splice(content, edits)
+}
+
+/// The template `render` finds for `name`, like `Show.html` for
+/// `Show.html.heex`, in the `templates` directory nearest `dir`
+fn find_template(dir: &Path, name: &str) -> Option<PathBuf> {
+    if name.contains(['/', '#']) {
+        return None;
+    }
+    let templates = dir
+        .ancestors()
+        .map(|ancestor| ancestor.join("templates"))
+        .find(|templates| templates.is_dir())?;
+    WalkDir::new(templates)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .find(|entry| {
+            let path = entry.path();
+            path.file_stem().is_some_and(|stem| stem == name)
+                && path
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .is_some_and(is_template)
+        })
+        .map(|entry| entry.into_path())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_elixir_paths() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("config"))?;
+        fs::create_dir_all(root.join("lib/app_web/templates/UserPage"))?;
+        fs::create_dir_all(root.join("lib/app_web/controllers"))?;
+        fs::create_dir_all(root.join("lib/app_web/components/Layouts"))?;
+        fs::write(root.join("config/Prod.secret.exs"), "")?;
+        fs::write(
+            root.join("lib/app_web/templates/UserPage/ShowProfile.html.heex"),
+            "",
+        )?;
+
+        let config = "\
+import Config
+import_config \"Prod.secret.exs\"
+import_config \"#{config_env()}.exs\"
+";
+        let path = root.join("config/config.exs");
+        assert!(ElixirScanner.matches(&path));
+        let (new_content, changes) =
+            ElixirScanner.convert(&path, config, &ScanOptions::default());
+        assert_eq!(
+            new_content,
+            config.replace("\"Prod.secret", "\"prod.secret")
+        );
+        assert_eq!(changes, 1);
+        let orphans = ElixirScanner.orphans(
+            &path,
+            &format!("{}import_config \"Gone.exs\"\n", config),
+            &ScanOptions::default(),
+        );
+        assert_eq!(orphans, ["Gone.exs"]);
+
+        let controller = "\
+def show(conn, _params), do: render(conn, \"ShowProfile.html\", user: nil)
+embed_templates \"../components/Layouts/*\"
+";
+        let (new_content, changes) = ElixirScanner.convert(
+            &root.join("lib/app_web/controllers/user_page_controller.ex"),
+            controller,
+            &ScanOptions::default(),
+        );
+        assert_eq!(
+            new_content,
+            controller
+                .replace("ShowProfile", "show_profile")
+                .replace("Layouts/*", "layouts/*")
+        );
+        assert_eq!(changes, 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Text encodings we can read source files in and write them back with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8 {
+        bom: bool,
+    },
+    Utf16Le,
+    Utf16Be,
+    /// Legacy single-byte files; every byte maps to the same code point
+    Latin1,
+}
+
+impl Encoding {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Encoding::Utf8 { bom: false } => "UTF-8",
+            Encoding::Utf8 { bom: true } => "UTF-8 with BOM",
+            Encoding::Utf16Le => "UTF-16LE",
+            Encoding::Utf16Be => "UTF-16BE",
+            Encoding::Latin1 => "Latin-1",
+        }
+    }
+}
+
+/// Detects the encoding of `bytes` and decodes them. Returns `None` for
+/// content that looks binary or can't be decoded with any supported encoding.
+pub fn decode(bytes: &[u8]) -> Option<(String, Encoding)> {
+    if let Some(rest) = bytes.strip_prefix(b"\xEF\xBB\xBF") {
+        let text = String::from_utf8(rest.to_vec()).ok()?;
+        return Some((text, Encoding::Utf8 { bom: true }));
+    }
+    if let Some(rest) = bytes.strip_prefix(b"\xFF\xFE") {
+        return decode_utf16(rest, u16::from_le_bytes)
+            .map(|text| (text, Encoding::Utf16Le));
+    }
+    if let Some(rest) = bytes.strip_prefix(b"\xFE\xFF") {
+        return decode_utf16(rest, u16::from_be_bytes)
+            .map(|text| (text, Encoding::Utf16Be));
+    }
+
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return Some((text.to_string(), Encoding::Utf8 { bom: false }));
+    }
+
+    // NUL bytes never appear in legacy text files, only in binaries
+    if bytes.contains(&0) {
+        return None;
+    }
+    Some((bytes.iter().map(|&b| b as char).collect(), Encoding::Latin1))
+}
+
+/// Whether the file at `path` looks binary: it has a NUL byte near the
+/// start, which text in any supported encoding but UTF-16 never does
+pub fn looks_binary(path: &Path) -> bool {
+    let mut head = [0; 8000];
+    let read = File::open(path).and_then(|mut file| file.read(&mut head));
+    match read {
+        Ok(len) => {
+            let head = &head[..len];
+            let utf16 =
+                head.starts_with(b"\xFF\xFE") || head.starts_with(b"\xFE\xFF");
+            !utf16 && head.contains(&0)
+        }
+        Err(_) => false,
+    }
+}
+
+/// Encodes `text` back into `encoding`, including any byte order mark.
+/// Returns `None` if the text can no longer be represented.
+pub fn encode(text: &str, encoding: Encoding) -> Option<Vec<u8>> {
+    match encoding {
+        Encoding::Utf8 { bom } => {
+            let mut out = Vec::with_capacity(text.len() + 3);
+            if bom {
+                out.extend_from_slice(b"\xEF\xBB\xBF");
+            }
+            out.extend_from_slice(text.as_bytes());
+            Some(out)
+        }
+        Encoding::Utf16Le => {
+            Some(encode_utf16(text, b"\xFF\xFE", u16::to_le_bytes))
+        }
+        Encoding::Utf16Be => {
+            Some(encode_utf16(text, b"\xFE\xFF", u16::to_be_bytes))
+        }
+        Encoding::Latin1 => text
+            .chars()
+            .map(|c| u8::try_from(u32::from(c)).ok())
+            .collect(),
+    }
+}
+
+fn decode_utf16(bytes: &[u8], to_unit: fn([u8; 2]) -> u16) -> Option<String> {
+    if !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+    let units = bytes
+        .chunks_exact(2)
+        .map(|pair| to_unit([pair[0], pair[1]]));
+    char::decode_utf16(units)
+        .collect::<Result<String, _>>()
+        .ok()
+}
+
+fn encode_utf16(
+    text: &str,
+    bom: &[u8],
+    to_bytes: fn(u16) -> [u8; 2],
+) -> Vec<u8> {
+    let mut out = bom.to_vec();
+    for unit in text.encode_utf16() {
+        out.extend_from_slice(&to_bytes(unit));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trips() {
+        let samples: &[&[u8]] = &[
+            b"import A from './A';",
+            b"\xEF\xBB\xBFimport A from './A';",
+            b"\xFF\xFEi\x00m\x00",
+            b"\xFE\xFF\x00i\x00m",
+            b"// caf\xE9\nimport A from './A';",
+        ];
+        for bytes in samples {
+            let (text, encoding) = decode(bytes).unwrap();
+            assert_eq!(encode(&text, encoding).as_deref(), Some(*bytes));
+        }
+    }
+
+    #[test]
+    fn test_detection() {
+        assert_eq!(decode(b"caf\xE9").unwrap().1, Encoding::Latin1);
+        assert_eq!(decode(b"\xFF\xFEi\x00").unwrap().1, Encoding::Utf16Le);
+        assert_eq!(decode(b"\x89PNG\x00\x00"), None);
+        assert_eq!(encode("\u{2603}", Encoding::Latin1), None);
+    }
+}
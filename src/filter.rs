@@ -0,0 +1,100 @@
+use crate::codeowners::Owners;
+use anyhow::{Context, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::path::Path;
+
+/// Include/exclude glob filters, matched against paths relative to the root.
+/// Patterns without a `/` match a file or directory name at any depth.
+#[derive(Debug, Default)]
+pub struct PathFilter {
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+    /// Only paths this team owns are renamed, from `--owned-by`
+    owners: Option<Owners>,
+}
+
+impl PathFilter {
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self> {
+        Ok(Self {
+            include: build_set(include)?,
+            exclude: build_set(exclude)?,
+            owners: None,
+        })
+    }
+
+    /// Only renames paths `owners` says the team owns
+    pub fn owned_by(self, owners: Owners) -> Self {
+        Self {
+            owners: Some(owners),
+            ..self
+        }
+    }
+
+    /// Whether a directory should be pruned from the walk entirely
+    pub fn is_excluded(&self, rel_path: &Path) -> bool {
+        self.exclude
+            .as_ref()
+            .is_some_and(|set| set.is_match(rel_path))
+    }
+
+    /// Whether a file should be processed
+    pub fn is_included(&self, rel_path: &Path) -> bool {
+        !self.is_excluded(rel_path)
+            && self
+                .include
+                .as_ref()
+                .is_none_or(|set| set.is_match(rel_path))
+    }
+
+    /// Whether a file or directory may be renamed. References to it are
+    /// rewritten wherever they are, in files owned by anyone, so they
+    /// follow the renames of those that are.
+    pub fn is_renamed(&self, rel_path: &Path) -> bool {
+        self.is_included(rel_path)
+            && self.owners.as_ref().is_none_or(|o| o.owns(rel_path))
+    }
+}
+
+fn build_set(patterns: &[String]) -> Result<Option<GlobSet>> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(compile_glob(pattern)?);
+    }
+    Ok(Some(builder.build()?))
+}
+
+/// Compiles a filter pattern, anchoring patterns without a `/` at any depth
+pub fn compile_glob(pattern: &str) -> Result<Glob> {
+    let pattern = pattern.trim_end_matches('/');
+    let full = if pattern.contains('/') {
+        pattern.to_string()
+    } else {
+        format!("**/{}", pattern)
+    };
+    Glob::new(&full)
+        .with_context(|| format!("Invalid glob pattern: {}", pattern))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_filter() -> Result<()> {
+        let filter = PathFilter::new(
+            &["src/**".to_string()],
+            &["node_modules".to_string(), "*.d.ts".to_string()],
+        )?;
+
+        assert!(filter.is_included(Path::new("src/App.ts")));
+        assert!(!filter.is_included(Path::new("test/App.ts")));
+        assert!(!filter.is_included(Path::new("src/Types.d.ts")));
+        assert!(filter.is_excluded(Path::new("web/node_modules")));
+        assert!(PathFilter::default().is_included(Path::new("any/File.ts")));
+        Ok(())
+    }
+}
@@ -0,0 +1,371 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+/// Delay before the first retry; doubled after each attempt
+const INITIAL_BACKOFF: Duration = Duration::from_millis(50);
+
+/// Runs a filesystem operation, retrying with exponential backoff while it
+/// fails because another process holds the file open
+pub fn with_retry<T>(
+    retries: u32,
+    op: impl FnMut() -> io::Result<T>,
+) -> io::Result<T> {
+    retry_when(retries, is_locked, op)
+}
+
+fn retry_when<T>(
+    retries: u32,
+    is_transient: impl Fn(&io::Error) -> bool,
+    mut op: impl FnMut() -> io::Result<T>,
+) -> io::Result<T> {
+    let mut delay = INITIAL_BACKOFF;
+    let mut attempt = 0;
+    loop {
+        match op() {
+            Err(err) if attempt < retries && is_transient(&err) => {
+                thread::sleep(delay);
+                delay *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Whether an error means the file is held by an editor, indexer or
+/// antivirus scanner. Only Windows refuses to rename or write open files.
+pub fn is_locked(err: &io::Error) -> bool {
+    // ERROR_ACCESS_DENIED, ERROR_SHARING_VIOLATION, ERROR_LOCK_VIOLATION
+    cfg!(windows) && matches!(err.raw_os_error(), Some(5 | 32 | 33))
+}
+
+/// Identifies a file independently of the path it was reached through, so
+/// hard links and files seen via symlinked directories compare equal
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FileId(
+    #[cfg(unix)] (u64, u64),
+    #[cfg(not(unix))] std::path::PathBuf,
+);
+
+/// Returns the identity of the file at `path`, following symlinks
+pub fn file_id(path: &Path) -> Option<FileId> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let meta = fs::metadata(path).ok()?;
+        Some(FileId((meta.dev(), meta.ino())))
+    }
+
+    #[cfg(not(unix))]
+    {
+        fs::canonicalize(path).ok().map(FileId)
+    }
+}
+
+/// Points an existing symlink at a new target
+pub fn replace_symlink(link: &Path, target: &Path) -> io::Result<()> {
+    fs::remove_file(link)?;
+
+    #[cfg(unix)]
+    return std::os::unix::fs::symlink(target, link);
+
+    #[cfg(windows)]
+    {
+        let resolved = link.parent().unwrap_or(Path::new(".")).join(target);
+        if resolved.is_dir() {
+            std::os::windows::fs::symlink_dir(target, link)
+        } else {
+            std::os::windows::fs::symlink_file(target, link)
+        }
+    }
+}
+
+/// Whether a rename failed because `from` and `to` are on different
+/// filesystems, like a bind mount or a separately mounted subdirectory
+pub fn is_cross_device(err: &io::Error) -> bool {
+    // EXDEV, ERROR_NOT_SAME_DEVICE
+    let code = if cfg!(windows) { 17 } else { 18 };
+    err.raw_os_error() == Some(code)
+}
+
+/// The filesystem the file at `path` is on, where that can be told
+pub fn device(path: &Path) -> Option<u64> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        fs::symlink_metadata(path).ok().map(|meta| meta.dev())
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        None
+    }
+}
+
+/// Room left for an unprivileged user on a filesystem
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FreeSpace {
+    pub bytes: u64,
+    pub inodes: u64,
+}
+
+/// The room left on the filesystem holding `path`, where that can be told
+pub fn free_space(path: &Path) -> Option<FreeSpace> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+        // SAFETY: `path` is NUL-terminated and `stat` is plain data that
+        // statvfs fills in
+        let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+        if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+            return None;
+        }
+        // The field types differ between platforms
+        #[allow(clippy::unnecessary_cast)]
+        let (blocks, block_size, files, inodes) = (
+            stat.f_bavail as u64,
+            stat.f_frsize as u64,
+            stat.f_files as u64,
+            stat.f_favail as u64,
+        );
+        Some(FreeSpace {
+            bytes: blocks.saturating_mul(block_size),
+            // Filesystems that make inodes as needed, like btrfs, have none
+            // to count
+            inodes: if files == 0 { u64::MAX } else { inodes },
+        })
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        None
+    }
+}
+
+/// The bytes and inodes a copy of the file or directory at `path` takes
+pub fn tree_size(path: &Path) -> (u64, u64) {
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .fold((0, 0), |(bytes, inodes), entry| {
+            let meta = entry.metadata().ok().filter(|m| m.is_file());
+            (bytes + meta.map_or(0, |m| m.len()), inodes + 1)
+        })
+}
+
+/// Moves `from` to `to` where a rename can't, across filesystems: copies
+/// it, keeping ownership, mode and extended attributes (POSIX ACLs live in
+/// those), then removes the original. Returns each path whose metadata
+/// couldn't all be kept, with what was lost.
+pub fn move_across(
+    from: &Path,
+    to: &Path,
+) -> io::Result<Vec<(PathBuf, String)>> {
+    let mut lost = Vec::new();
+    copy_tree(from, to, &mut lost)?;
+    match fs::symlink_metadata(from)?.is_dir() {
+        true => fs::remove_dir_all(from)?,
+        false => fs::remove_file(from)?,
+    }
+    Ok(lost)
+}
+
+fn copy_tree(
+    from: &Path,
+    to: &Path,
+    lost: &mut Vec<(PathBuf, String)>,
+) -> io::Result<()> {
+    let meta = fs::symlink_metadata(from)?;
+    if meta.is_symlink() {
+        let target = fs::read_link(from)?;
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(target, to)?;
+        #[cfg(windows)]
+        match fs::metadata(from).is_ok_and(|m| m.is_dir()) {
+            true => std::os::windows::fs::symlink_dir(target, to)?,
+            false => std::os::windows::fs::symlink_file(target, to)?,
+        }
+    } else if meta.is_dir() {
+        fs::create_dir(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_tree(&entry.path(), &to.join(entry.file_name()), lost)?;
+        }
+    } else {
+        fs::copy(from, to)?;
+    }
+    // After the contents, so a read-only directory can still be filled
+    keep_metadata(from, to, &meta, lost);
+    Ok(())
+}
+
+/// Gives `to` the ownership, mode and extended attributes of `from`, whose
+/// metadata is `meta`. Returns what couldn't be kept.
+pub fn copy_metadata(
+    from: &Path,
+    to: &Path,
+    meta: &fs::Metadata,
+) -> Vec<(PathBuf, String)> {
+    let mut lost = Vec::new();
+    keep_metadata(from, to, meta, &mut lost);
+    lost
+}
+
+#[cfg(unix)]
+fn keep_metadata(
+    from: &Path,
+    to: &Path,
+    meta: &fs::Metadata,
+    lost: &mut Vec<(PathBuf, String)>,
+) {
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+    let (uid, gid) = (meta.uid(), meta.gid());
+    if let Err(err) = std::os::unix::fs::lchown(to, Some(uid), Some(gid)) {
+        lost.push((to.into(), format!("owner {uid}:{gid} not kept: {err}")));
+    }
+    let attrs = xattr::list(from).into_iter().flatten();
+    for name in attrs {
+        let result = xattr::get(from, &name).and_then(|value| {
+            xattr::set(to, &name, &value.unwrap_or_default())
+        });
+        if let Err(err) = result {
+            let name = name.to_string_lossy();
+            lost.push((to.into(), format!("attribute {name} not kept: {err}")));
+        }
+    }
+    if meta.is_symlink() {
+        return;
+    }
+    // Last, since changing the owner clears the setuid and setgid bits
+    let mode = meta.mode() & 0o7777;
+    if let Err(err) = fs::set_permissions(to, fs::Permissions::from_mode(mode))
+    {
+        lost.push((to.into(), format!("mode {mode:o} not kept: {err}")));
+    }
+    // Without its setgid bit, files created in a directory get their
+    // creator's group rather than the directory's, which shared deployment
+    // trees rely on. chmod drops it silently when we're not in the group.
+    let setgid = 0o2000;
+    let kept = fs::metadata(to).is_ok_and(|m| m.mode() & setgid != 0);
+    if meta.is_dir() && mode & setgid != 0 && !kept {
+        lost.push((
+            to.into(),
+            "setgid bit not kept: new files won't inherit the directory's \
+             group"
+                .into(),
+        ));
+    }
+}
+
+#[cfg(not(unix))]
+fn keep_metadata(
+    _from: &Path,
+    to: &Path,
+    meta: &fs::Metadata,
+    lost: &mut Vec<(PathBuf, String)>,
+) {
+    if let Err(err) = fs::set_permissions(to, meta.permissions()) {
+        lost.push((to.into(), format!("permissions not kept: {err}")));
+    }
+}
+
+/// Whether any error in the chain is a lock failure
+pub fn is_locked_error(err: &anyhow::Error) -> bool {
+    err.chain()
+        .filter_map(|cause| cause.downcast_ref::<io::Error>())
+        .any(is_locked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_when() {
+        let mut calls = 0;
+        let result = retry_when(
+            3,
+            |_| true,
+            || {
+                calls += 1;
+                if calls < 3 {
+                    Err(io::Error::other("locked"))
+                } else {
+                    Ok(calls)
+                }
+            },
+        );
+        assert_eq!(result.unwrap(), 3);
+
+        let mut calls = 0;
+        let result: io::Result<()> = retry_when(
+            5,
+            |_| false,
+            || {
+                calls += 1;
+                Err(io::Error::other("denied"))
+            },
+        );
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_tree_size_and_free_space() -> io::Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let dir = temp_dir.path().join("Assets");
+        fs::create_dir_all(dir.join("Icons"))?;
+        fs::write(dir.join("Icons/Logo.svg"), "<svg/>")?;
+        fs::write(dir.join("Readme.md"), "# Assets\n")?;
+        assert_eq!(tree_size(&dir), (15, 4));
+        if cfg!(unix) {
+            assert!(free_space(&dir).is_some_and(|free| free.inodes > 0));
+        }
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_move_across_keeps_metadata() -> io::Result<()> {
+        use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+        let temp_dir = tempfile::TempDir::new()?;
+        let from = temp_dir.path().join("Releases");
+        fs::create_dir_all(from.join("Current"))?;
+        fs::write(from.join("Current/Deploy.sh"), "#!/bin/sh\n")?;
+        std::os::unix::fs::symlink("Current", from.join("Latest"))?;
+        let set_mode = |path: &Path, mode| {
+            fs::set_permissions(path, fs::Permissions::from_mode(mode))
+        };
+        set_mode(&from.join("Current/Deploy.sh"), 0o750)?;
+        set_mode(&from.join("Current"), 0o2750)?;
+        set_mode(&from, 0o700)?;
+        // Not every filesystem supports user attributes
+        let tagged = xattr::set(&from, "user.kebabify.test", b"kept").is_ok();
+
+        let to = temp_dir.path().join("releases");
+        let lost = move_across(&from, &to)?;
+        assert_eq!(lost, []);
+        assert!(!from.exists());
+        let mode = |path: &Path| Ok::<_, io::Error>(fs::metadata(path)?.mode());
+        assert_eq!(mode(&to)? & 0o7777, 0o700);
+        assert_eq!(mode(&to.join("Current"))? & 0o7777, 0o2750);
+        assert_eq!(mode(&to.join("Current/Deploy.sh"))? & 0o7777, 0o750);
+        assert_eq!(fs::read_link(to.join("Latest"))?, Path::new("Current"));
+        if tagged {
+            assert_eq!(
+                xattr::get(&to, "user.kebabify.test")?,
+                Some(b"kept".to_vec())
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,60 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Markers are looked for this far into a file. Generators put them in a
+/// header comment, and looking further would catch files that merely talk
+/// about generated code.
+const HEADER_LEN: usize = 4096;
+
+/// The first of `markers` in the header of `content`, if the file says it
+/// was generated
+pub fn marker<'a>(content: &str, markers: &'a [String]) -> Option<&'a str> {
+    let mut end = content.len().min(HEADER_LEN);
+    while !content.is_char_boundary(end) {
+        end -= 1;
+    }
+    let header = &content[..end];
+    markers
+        .iter()
+        .find(|marker| header.contains(marker.as_str()))
+        .map(String::as_str)
+}
+
+/// Like `marker`, for the file at `path`. Files that can't be read or
+/// aren't text have no marker.
+pub fn file_marker<'a>(path: &Path, markers: &'a [String]) -> Option<&'a str> {
+    if markers.is_empty() {
+        return None;
+    }
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    File::open(path)
+        .ok()?
+        .take(HEADER_LEN as u64)
+        .read_to_end(&mut header)
+        .ok()?;
+    marker(&String::from_utf8_lossy(&header), markers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_marker() {
+        let markers = ["@generated".to_string(), "DO NOT EDIT".to_string()];
+        assert_eq!(
+            marker("// Code generated by protoc. DO NOT EDIT.\n", &markers),
+            Some("DO NOT EDIT")
+        );
+        assert_eq!(
+            marker("/** @generated */\nexport {}", &markers),
+            Some("@generated")
+        );
+        assert_eq!(marker("export const x = 1;\n", &markers), None);
+
+        let late = format!("{}// DO NOT EDIT", " ".repeat(HEADER_LEN));
+        assert_eq!(marker(&late, &markers), None);
+        assert_eq!(marker("// DO NOT EDIT", &[]), None);
+    }
+}
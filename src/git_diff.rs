@@ -0,0 +1,231 @@
+use crate::{needs_conversion_as, scanner, NameStyle, Options};
+use anyhow::{Context, Result};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Old and new paths, relative to the directory compared
+type Renames = Vec<(PathBuf, PathBuf)>;
+
+/// How the names under a directory changed between a Git revision and the
+/// working tree, for `kebabify diff`
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct NamingDiff {
+    /// Renames that are the conversion of the old path
+    pub converted: Renames,
+    /// Renames to something else, made by hand
+    pub renamed: Renames,
+    /// Names still to convert at the revision and now
+    pub before: Tally,
+    pub after: Tally,
+}
+
+/// How many of the file and directory names in a tree need converting
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Tally {
+    pub names: usize,
+    pub unconverted: usize,
+}
+
+/// Compares the names under `root` in the working tree with those at
+/// `git_ref`, using Git's rename detection to pair up old and new paths
+pub fn diff(root: &Path, git_ref: &str) -> Result<NamingDiff> {
+    let git = |args: &[&str]| -> Result<Vec<u8>> {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(root)
+            .output()
+            .context("Failed to run git")?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(output.stdout)
+    };
+    let status = git(&[
+        "diff",
+        "--name-status",
+        "-M",
+        "-z",
+        "--relative",
+        git_ref,
+        "--",
+        ".",
+    ])?;
+    let (converted, renamed) = classify(&status);
+    let then =
+        git(&["ls-tree", "-r", "-z", "--name-only", git_ref, "--", "."])?;
+    let now = git(&[
+        "ls-files",
+        "-z",
+        "--cached",
+        "--others",
+        "--exclude-standard",
+    ])?;
+    Ok(NamingDiff {
+        converted,
+        renamed,
+        before: tally(&paths(&then)),
+        after: tally(&paths(&now)),
+    })
+}
+
+fn paths(output: &[u8]) -> Vec<PathBuf> {
+    output
+        .split(|&b| b == 0)
+        .filter(|p| !p.is_empty())
+        .map(|p| PathBuf::from(String::from_utf8_lossy(p).into_owned()))
+        .collect()
+}
+
+/// Sorts the renames in `git diff --name-status -z` output into
+/// conversions and other renames. A deleted file and an added one that's
+/// its conversion count too, for when Git didn't pair them up because the
+/// content changed as well.
+fn classify(status: &[u8]) -> (Renames, Renames) {
+    let fields = paths(status);
+    let mut renames = Vec::new();
+    let (mut deleted, mut added) = (Vec::new(), Vec::new());
+    let mut fields = fields.iter();
+    while let Some(code) = fields.next() {
+        match code.to_string_lossy().chars().next() {
+            Some('R' | 'C') => {
+                let (Some(from), Some(to)) = (fields.next(), fields.next())
+                else {
+                    break;
+                };
+                renames.push((from.clone(), to.clone()));
+            }
+            Some('D') => deleted.extend(fields.next().cloned()),
+            Some('A') => added.extend(fields.next().cloned()),
+            _ => {
+                fields.next();
+            }
+        }
+    }
+    for from in deleted {
+        if let Some(i) = added.iter().position(|to| is_conversion(&from, to)) {
+            renames.push((from, added.remove(i)));
+        }
+    }
+    renames.sort();
+    renames
+        .into_iter()
+        .partition(|(from, to)| is_conversion(from, to))
+}
+
+/// Whether `to` is `from` with its names converted, in either style
+fn is_conversion(from: &Path, to: &Path) -> bool {
+    let (from, to): (Vec<_>, Vec<_>) =
+        (from.iter().collect(), to.iter().collect());
+    from != to
+        && from.len() == to.len()
+        && from.iter().zip(&to).all(|(old, new)| {
+            let (Some(old), Some(new)) = (old.to_str(), new.to_str()) else {
+                return old == new;
+            };
+            old == new
+                || [NameStyle::Kebab, NameStyle::Snake]
+                    .iter()
+                    .any(|&style| crate::convert_name_as(old, style) == new)
+        })
+}
+
+/// Counts the files and directories in `files` whose names need converting
+fn tally(files: &[PathBuf]) -> Tally {
+    let mut dirs = BTreeSet::new();
+    let mut unconverted = 0;
+    for file in files {
+        let style = scanner::name_style(file, false);
+        let name = file.file_name().and_then(|n| n.to_str());
+        if name.is_some_and(|n| needs_conversion_as(n, style))
+            && scanner::pinned(file, false).is_none()
+        {
+            unconverted += 1;
+        }
+        dirs.extend(file.ancestors().skip(1).filter(|d| *d != Path::new("")));
+    }
+    // A directory's style depends on its files, so it only counts as
+    // unconverted if it'd be converted either way
+    unconverted += dirs
+        .iter()
+        .filter_map(|d| d.file_name()?.to_str())
+        .filter(|name| {
+            needs_conversion_as(name, NameStyle::Kebab)
+                && needs_conversion_as(name, NameStyle::Snake)
+        })
+        .count();
+    Tally {
+        names: files.len() + dirs.len(),
+        unconverted,
+    }
+}
+
+impl NamingDiff {
+    pub fn print(&self, git_ref: &str, opts: &Options) {
+        let list = |title: &str, renames: &[(PathBuf, PathBuf)]| {
+            if renames.is_empty() {
+                return;
+            }
+            opts.log(format_args!("{} ({}):", title, renames.len()));
+            for (from, to) in renames {
+                opts.log(format_args!(
+                    "  {} -> {}",
+                    from.display(),
+                    to.display()
+                ));
+            }
+        };
+        list(&format!("Converted since {}", git_ref), &self.converted);
+        list(
+            &format!("Renamed otherwise since {}", git_ref),
+            &self.renamed,
+        );
+        for (when, tally) in [(git_ref, &self.before), ("Now", &self.after)] {
+            opts.log(format_args!(
+                "{}: {} of {} names still to convert",
+                when, tally.unconverted, tally.names
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_renames() {
+        let status = b"R100\0src/UserCard.ts\0src/user-card.ts\0\
+                       M\0src/main.ts\0\
+                       R090\0Lib/Old.ts\0lib/new-name.ts\0\
+                       D\0Api/ApiClient.ts\0\
+                       A\0api/api-client.ts\0\
+                       A\0docs/notes.md\0";
+        let (converted, renamed) = classify(status);
+        let pair = |from: &str, to: &str| (PathBuf::from(from), to.into());
+        assert_eq!(
+            converted,
+            [
+                pair("Api/ApiClient.ts", "api/api-client.ts"),
+                pair("src/UserCard.ts", "src/user-card.ts")
+            ]
+        );
+        assert_eq!(renamed, [pair("Lib/Old.ts", "lib/new-name.ts")]);
+
+        let tally = tally(&[
+            PathBuf::from("Lib/UserCard.ts"),
+            PathBuf::from("lib/user-card.ts"),
+        ]);
+        assert_eq!(
+            tally,
+            Tally {
+                names: 4,
+                unconverted: 2
+            }
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use crate::plan::Plan;
+use crate::scanner::{self, rewrite_file_path, splice, ScanOptions, Scanner};
+use crate::NameStyle;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Go sources. Files are named in lowercase snake_case, which keeps
+/// `_test` and build constraint suffixes like `_linux` working.
+pub struct GoScanner;
+
+impl Scanner for GoScanner {
+    fn matches(&self, path: &Path) -> bool {
+        path.extension().is_some_and(|e| e == "go")
+    }
+
+    fn name_style(&self) -> NameStyle {
+        NameStyle::Snake
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_embeds(path, content, scanner::converted_path)
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_embeds(path, content, |found| plan.target_of(found))
+    }
+
+    /// `//go:embed` patterns that aren't globs and lead nowhere
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> Vec<String> {
+        let dir = path.parent().unwrap_or(Path::new(""));
+        embeds(content)
+            .into_iter()
+            .filter(|(file, _)| scanner::leads_nowhere(dir, file, &[]))
+            .map(|(file, _)| file.to_string())
+            .collect()
+    }
+
+    /// A package's import path is its directory's path in the module, so
+    /// renaming a directory with Go code in it breaks every import of it.
+    /// The module root is named by `go.mod` instead and can be renamed.
+    fn pins_name(&self, dir: &Path) -> Option<&'static str> {
+        if !dir.is_dir() {
+            return None;
+        }
+        let in_module = scanner::absolute(dir)
+            .ancestors()
+            .skip(1)
+            .any(|ancestor| ancestor.join("go.mod").is_file());
+        if !in_module || dir.join("go.mod").is_file() {
+            return None;
+        }
+        let has_go =
+            WalkDir::new(dir)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .any(|entry| {
+                    entry.file_type().is_file() && self.matches(entry.path())
+                });
+        has_go.then_some(
+            "renaming it would change the import path of its Go packages",
+        )
+    }
+}
+
+/// Rewrites the file patterns of `//go:embed` directives, which are
+/// relative to the package directory
+fn rewrite_embeds(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut edits = Vec::new();
+    for (file, start) in embeds(content) {
+        edits.extend(rewrite_file_path(dir, file, start, &[], &rename));
+    }
+    splice(content, edits)
+}
+
+/// Each `//go:embed` pattern and where it starts in the file
+fn embeds(content: &str) -> Vec<(&str, usize)> {
+    let directive = Regex::new(r"(?m)^[ \t]*//go:embed[ \t]+(.*)$").unwrap();
+    let pattern = Regex::new(r#""([^"]*)"|`([^`]*)`|(\S+)"#).unwrap();
+    let mut files = Vec::new();
+    for caps in directive.captures_iter(content) {
+        let patterns = caps.get(1).unwrap();
+        for token in pattern.captures_iter(patterns.as_str()) {
+            let token = (1..=3).find_map(|i| token.get(i)).unwrap();
+            // `all:` also embeds hidden files, it isn't part of the path
+            let file = token.as_str();
+            let (file, skipped) = match file.strip_prefix("all:") {
+                Some(rest) => (rest, 4),
+                None => (file, 0),
+            };
+            files.push((file, patterns.start() + token.start() + skipped));
+        }
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_embeds_and_pinned_packages() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::write(root.join("go.mod"), "module example.com/app\n")?;
+        fs::create_dir_all(root.join("Server/Templates"))?;
+        fs::write(root.join("Server/Handler.go"), "")?;
+        fs::write(root.join("Server/Templates/Index.html"), "")?;
+        fs::write(root.join("Server/Schema.sql"), "")?;
+
+        let content = "\
+package server
+
+//go:embed Templates/*.html \"Schema.sql\" all:Templates
+var files embed.FS
+";
+        let (new_content, changes) = GoScanner.convert(
+            &root.join("Server/Handler.go"),
+            content,
+            &ScanOptions::default(),
+        );
+        assert_eq!(
+            new_content,
+            "\
+package server
+
+//go:embed templates/*.html \"schema.sql\" all:templates
+var files embed.FS
+"
+        );
+        assert_eq!(changes, 3);
+        let orphans = GoScanner.orphans(
+            &root.join("Server/Handler.go"),
+            "//go:embed Templates/*.html Schema.sql Seed.sql\n",
+            &ScanOptions::default(),
+        );
+        assert_eq!(orphans, ["Seed.sql"]);
+
+        assert!(scanner::pinned(&root.join("Server"), true).is_some());
+        assert!(scanner::pinned(&root.join("Server/Templates"), true).is_none());
+        assert!(scanner::pinned(root, true).is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+use crate::plan::Plan;
+use crate::scanner::{self, rewrite_file_path, splice, ScanOptions, Scanner};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// GraphQL schemas and operations. SDL has no imports of its own; files
+/// pull in each other's types and fragments through import comments, which
+/// graphql-import and the webpack and Vite loaders read.
+pub struct GraphqlScanner;
+
+impl Scanner for GraphqlScanner {
+    fn matches(&self, path: &Path) -> bool {
+        path.extension()
+            .is_some_and(|e| e == "graphql" || e == "gql")
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_imports(path, content, scanner::converted_path)
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_imports(path, content, |found| plan.target_of(found))
+    }
+
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> Vec<String> {
+        let dir = path.parent().unwrap_or(Path::new(""));
+        import_regex()
+            .captures_iter(content)
+            .map(|caps| caps[1].to_string())
+            .filter(|file| scanner::leads_nowhere(dir, file, &[]))
+            .collect()
+    }
+}
+
+/// `# import User, Post from "Types/User.graphql"`, `# import * from ...`
+/// and `#import "./Fragments/UserFields.graphql"`
+fn import_regex() -> Regex {
+    Regex::new(
+        r#"(?m)^[ \t]*#[ \t]*import[ \t]+(?:[^"'\n]*?[ \t]from[ \t]+)?["']([^"'\n]+)["']"#,
+    )
+    .unwrap()
+}
+
+/// Rewrites import comments, relative to the file they're in
+fn rewrite_imports(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut edits = Vec::new();
+    for caps in import_regex().captures_iter(content) {
+        let file = caps.get(1).unwrap();
+        edits.extend(rewrite_file_path(
+            dir,
+            file.as_str(),
+            file.start(),
+            &[],
+            &rename,
+        ));
+    }
+    splice(content, edits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_graphql_imports() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("Schema/Types"))?;
+        fs::create_dir_all(root.join("Schema/Fragments"))?;
+        fs::write(root.join("Schema/Types/UserProfile.graphql"), "")?;
+        fs::write(root.join("Schema/Fragments/UserFields.gql"), "")?;
+
+        let content = r#"# import UserProfile, Avatar from "Types/UserProfile.graphql"
+#import './Fragments/UserFields.gql'
+# import * from "Types/Missing.graphql"
+
+type Query {
+  me: UserProfile
+}
+"#;
+        let path = root.join("Schema/Query.graphql");
+        assert!(GraphqlScanner.matches(&path));
+        let (new_content, changes) =
+            GraphqlScanner.convert(&path, content, &ScanOptions::default());
+        assert_eq!(
+            new_content,
+            r#"# import UserProfile, Avatar from "types/user-profile.graphql"
+#import './fragments/user-fields.gql'
+# import * from "types/Missing.graphql"
+
+type Query {
+  me: UserProfile
+}
+"#
+        );
+        assert_eq!(changes, 5);
+        let orphans =
+            GraphqlScanner.orphans(&path, content, &ScanOptions::default());
+        assert_eq!(orphans, ["Types/Missing.graphql"]);
+        Ok(())
+    }
+}
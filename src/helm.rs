@@ -0,0 +1,203 @@
+use crate::plan::Plan;
+use crate::scanner::{self, rewrite_file_path, splice, ScanOptions, Scanner};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// Helm charts: their `values.yaml` files and templates, for the chart's
+/// files they name
+pub struct HelmScanner;
+
+impl Scanner for HelmScanner {
+    fn matches(&self, path: &Path) -> bool {
+        is_chart_file(path) || is_values_file(path) || is_template(path)
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_paths(path, content, scanner::converted_path)
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_paths(path, content, |found| plan.target_of(found))
+    }
+
+    /// Files templates read that aren't in the chart. Values only count as
+    /// paths when they name one of its files, so they're never orphans.
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> Vec<String> {
+        let Some(root) = chart_root(path).filter(|_| !is_values_file(path))
+        else {
+            return Vec::new();
+        };
+        files_regex()
+            .captures_iter(content)
+            .map(|caps| caps[1].to_string())
+            .filter(|file| scanner::leads_nowhere(root, file, &[]))
+            .collect()
+    }
+
+    fn pins_name(&self, path: &Path) -> Option<&'static str> {
+        is_chart_file(path).then_some("Helm looks for it by this name")
+    }
+}
+
+fn is_chart_file(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name == "Chart.yaml")
+}
+
+/// The directory of the chart the file at `path` belongs to
+fn chart_root(path: &Path) -> Option<&Path> {
+    path.ancestors()
+        .skip(1)
+        .find(|dir| dir.join("Chart.yaml").is_file())
+}
+
+/// `values.yaml`, `values-prod.yaml` and the like, next to `Chart.yaml`
+fn is_values_file(path: &Path) -> bool {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    let yaml = name.ends_with(".yaml") || name.ends_with(".yml");
+    yaml && name.starts_with("values")
+        && path
+            .parent()
+            .is_some_and(|dir| dir.join("Chart.yaml").is_file())
+}
+
+/// A file under a chart's `templates` directory
+fn is_template(path: &Path) -> bool {
+    let Some(root) = chart_root(path) else {
+        return false;
+    };
+    path.starts_with(root.join("templates"))
+        && path.extension().is_some_and(|e| {
+            e == "yaml" || e == "yml" || e == "tpl" || e == "txt"
+        })
+}
+
+/// `.Files.Get "Config/App.conf"`, `.Files.Glob` and the like
+fn files_regex() -> Regex {
+    Regex::new(r#"\.Files\.(?:Get|GetBytes|Lines|Glob)\s+"([^"\n]+)""#).unwrap()
+}
+
+/// Rewrites the paths templates read with `.Files.Get "Config/App.conf"`,
+/// `.Files.Glob` and the like, and the values that name one of the chart's
+/// files, all relative to the chart's directory
+fn rewrite_paths(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let Some(root) = chart_root(path) else {
+        return (content.to_string(), 0);
+    };
+    // `configFile: config/App.conf` or `- config/App.conf`
+    let value = Regex::new(concat!(
+        r#"(?m)(?:^[ \t]*-|:)[ \t]+["']?([^"'#\s:{}\[\],]+)["']?"#,
+        r"[ \t]*(?:#.*)?$",
+    ))
+    .unwrap();
+
+    let mut edits = Vec::new();
+    if is_values_file(path) {
+        for caps in value.captures_iter(content) {
+            let file = caps.get(1).unwrap();
+            // Bare words like "nginx" are rarely the chart's files
+            let looks_like_path = file.as_str().contains(['/', '.']);
+            if !looks_like_path || !root.join(file.as_str()).exists() {
+                continue;
+            }
+            edits.extend(rewrite_file_path(
+                root,
+                file.as_str(),
+                file.start(),
+                &[],
+                &rename,
+            ));
+        }
+    } else {
+        for caps in files_regex().captures_iter(content) {
+            let file = caps.get(1).unwrap();
+            edits.extend(rewrite_file_path(
+                root,
+                file.as_str(),
+                file.start(),
+                &[],
+                &rename,
+            ));
+        }
+    }
+    splice(content, edits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_helm_values_and_templates() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("Config"))?;
+        fs::create_dir_all(root.join("templates"))?;
+        fs::write(root.join("Chart.yaml"), "name: web")?;
+        fs::write(root.join("Config/AppSettings.json"), "")?;
+
+        let values = "\
+image: nginx:1.25
+configFile: Config/AppSettings.json # mounted at /etc/app
+extraFiles:
+  - \"Config/AppSettings.json\"
+  - Config/Missing.json
+";
+        let path = root.join("values.yaml");
+        assert!(HelmScanner.matches(&path));
+        assert!(HelmScanner.pins_name(&root.join("Chart.yaml")).is_some());
+        let (new_content, changes) =
+            HelmScanner.convert(&path, values, &ScanOptions::default());
+        assert_eq!(
+            new_content,
+            values.replace("Config/AppSettings", "config/app-settings")
+        );
+        assert_eq!(changes, 4);
+
+        let template = "\
+data:
+  settings.json: {{ .Files.Get \"Config/AppSettings.json\" | quote }}
+  {{- (.Files.Glob \"Config/*.json\").AsConfig | nindent 2 }}
+";
+        let path = root.join("templates/configmap.yaml");
+        assert!(HelmScanner.matches(&path));
+        let (new_content, changes) =
+            HelmScanner.convert(&path, template, &ScanOptions::default());
+        assert_eq!(
+            new_content,
+            template
+                .replace("Config/", "config/")
+                .replace("AppSettings", "app-settings")
+        );
+        assert_eq!(changes, 3);
+        let orphans = HelmScanner.orphans(
+            &path,
+            &template.replace("AppSettings", "Gone"),
+            &ScanOptions::default(),
+        );
+        assert_eq!(orphans, ["Config/Gone.json"]);
+        Ok(())
+    }
+}
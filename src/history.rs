@@ -0,0 +1,177 @@
+use crate::pending::portable;
+use crate::plan::Plan;
+use crate::{relative_to, Options};
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Every rename of every run so far, oldest run first, so a rename can be
+/// traced back after later runs
+pub const HISTORY_FILE: &str = ".kebabify-history.json";
+
+/// One run's renames, with when it finished in seconds since the epoch
+#[derive(Debug)]
+pub struct Run {
+    pub time: u64,
+    pub renames: Plan,
+}
+
+/// Loads the history under `root`, which is empty before the first run
+pub fn load(root: &Path) -> Result<Vec<Run>> {
+    let path = root.join(HISTORY_FILE);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(Vec::new())
+        }
+        Err(err) => {
+            return Err(err)
+                .with_context(|| format!("Failed to read {}", path.display()))
+        }
+    };
+    let value: Value = serde_json::from_str(&content)
+        .with_context(|| format!("Malformed {}", path.display()))?;
+
+    let mut runs = Vec::new();
+    let entries = value.get("runs").and_then(Value::as_array);
+    for run in entries.into_iter().flatten() {
+        let mut renames = Plan::default();
+        let list = run.get("renames").and_then(Value::as_array);
+        for rename in list.into_iter().flatten() {
+            let from = rename.get("from").and_then(Value::as_str);
+            let to = rename.get("to").and_then(Value::as_str);
+            if let (Some(from), Some(to)) = (from, to) {
+                renames.push(root.join(from), root.join(to));
+            }
+        }
+        let time = run.get("time").and_then(Value::as_u64).unwrap_or(0);
+        runs.push(Run { time, renames });
+    }
+    Ok(runs)
+}
+
+/// Prints every run in the history under `root`, oldest first
+pub fn print(root: &Path, opts: &Options) -> Result<()> {
+    let runs = load(root)?;
+    if runs.is_empty() {
+        println!("No renames recorded yet");
+    }
+    for run in runs {
+        println!("{} UTC:", format_time(run.time));
+        for rename in &run.renames.renames {
+            println!(
+                "  {} -> {}",
+                opts.display.show(&rename.from),
+                opts.display.show(&rename.to)
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Formats seconds since the epoch as `YYYY-MM-DD HH:MM`
+pub fn format_time(time: u64) -> String {
+    // Howard Hinnant's days-to-civil algorithm
+    let days = (time / 86400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524
+        - day_of_era / 146_096)
+        / 365;
+    let day_of_year =
+        day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    let seconds = time % 86400;
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60
+    )
+}
+
+/// Adds the renames of a run that processed `root` to the history, which
+/// is kept in `final_root`, where `root` is once the run renamed it. Only
+/// renames that were made are recorded, relative to the root, and a run
+/// that made none leaves the history as it is, or missing.
+pub fn record(root: &Path, final_root: &Path, plan: &Plan) -> Result<()> {
+    let renames: Vec<Value> = plan
+        .renames
+        .iter()
+        .filter(|r| r.from != root && fs::symlink_metadata(&r.to).is_ok())
+        .map(|r| {
+            json!({
+                "from": portable(relative_to(&r.from, root)),
+                "to": portable(relative_to(&r.to, final_root)),
+            })
+        })
+        .collect();
+    if renames.is_empty() {
+        return Ok(());
+    }
+
+    let path = final_root.join(HISTORY_FILE);
+    let mut history: Value = match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content)
+            .with_context(|| format!("Malformed {}", path.display()))?,
+        Err(_) => json!({ "runs": [] }),
+    };
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let Some(runs) = history.get_mut("runs").and_then(Value::as_array_mut)
+    else {
+        anyhow::bail!("Malformed {}: no \"runs\" list", path.display());
+    };
+    runs.push(json!({ "time": time, "renames": renames }));
+
+    let content = serde_json::to_string_pretty(&history)?;
+    fs::write(&path, content + "\n")
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_history_accumulates() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::write(root.join("a.ts"), "")?;
+        fs::write(root.join("b.ts"), "")?;
+        assert!(load(root)?.is_empty());
+        record(root, root, &Plan::default())?;
+        assert!(!root.join(HISTORY_FILE).exists());
+
+        let mut first = Plan::default();
+        first.push(root.join("A.ts"), root.join("a.ts"));
+        first.push(root.join("Failed.ts"), root.join("failed.ts"));
+        record(root, root, &first)?;
+        let mut second = Plan::default();
+        second.push(root.join("B.ts"), root.join("b.ts"));
+        record(root, root, &second)?;
+        record(root, root, &Plan::default())?;
+
+        let runs = load(root)?;
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].renames.renames.len(), 1);
+        assert_eq!(runs[0].renames.renames[0].from, root.join("A.ts"));
+        assert_eq!(runs[1].renames.renames[0].to, root.join("b.ts"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!(format_time(0), "1970-01-01 00:00");
+        assert_eq!(format_time(951_827_696), "2000-02-29 12:34");
+    }
+}
@@ -0,0 +1,109 @@
+use crate::mapping;
+use crate::plan::Plan;
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+use std::process::Command;
+use tempfile::Builder;
+
+/// Environment variable holding the path of the rename map CSV
+pub const RENAMES_ENV: &str = "KEBABIFY_RENAMES";
+
+/// Runs each `--exec` command in `final_root`, the root as the run left
+/// it, after a successful apply, stopping at the first one that fails. The
+/// renames are written to a temporary CSV, relative to `root`, named by
+/// `KEBABIFY_RENAMES`, with the count in `KEBABIFY_RENAME_COUNT`.
+/// It's created fresh under a random name, so nothing already at a
+/// guessable path in the shared temporary directory is written through,
+/// and removed when the commands are done.
+pub fn run_exec(
+    commands: &[String],
+    root: &Path,
+    final_root: &Path,
+    plan: &Plan,
+) -> Result<()> {
+    if commands.is_empty() {
+        return Ok(());
+    }
+
+    let map = Builder::new()
+        .prefix("kebabify-renames-")
+        .suffix(".csv")
+        .tempfile()
+        .context("Failed to create rename map")?;
+    let map_path = map.path();
+    mapping::write_mapping(plan, root, map.as_file()).with_context(|| {
+        format!("Failed to write rename map: {}", map_path.display())
+    })?;
+
+    let result = commands.iter().try_for_each(|command| {
+        println!("Running: {}", command);
+        let status = shell(command)
+            .current_dir(final_root)
+            .env(RENAMES_ENV, map_path)
+            .env("KEBABIFY_RENAME_COUNT", plan.renames.len().to_string())
+            .status()
+            .with_context(|| format!("Failed to run: {}", command))?;
+        if !status.success() {
+            bail!("Command failed ({}): {}", status, command);
+        }
+        Ok(())
+    });
+
+    result
+}
+
+/// Runs the `--verify-cmd` check in `dir`, the root as the run left it,
+/// and returns whether it passed
+pub fn verify(command: &str, dir: &Path) -> Result<bool> {
+    println!("Verifying: {}", command);
+    let status = shell(command)
+        .current_dir(dir)
+        .status()
+        .with_context(|| format!("Failed to run: {}", command))?;
+    Ok(status.success())
+}
+
+/// A command that runs `command` through the platform shell
+pub fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_run_exec() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        let mut plan = Plan::default();
+        plan.push(root.join("A.ts"), root.join("a.ts"));
+
+        let commands = vec![format!(
+            "cp \"${}\" copy.csv && test $KEBABIFY_RENAME_COUNT = 1",
+            RENAMES_ENV
+        )];
+        run_exec(&commands, root, root, &plan)?;
+        assert_eq!(
+            fs::read_to_string(root.join("copy.csv"))?,
+            "old_path,new_path\nA.ts,a.ts\n"
+        );
+
+        let commands = vec!["true".to_string(), "exit 3".to_string()];
+        assert!(run_exec(&commands, root, root, &plan).is_err());
+
+        assert!(verify("test -f copy.csv", root)?);
+        assert!(!verify("false", root)?);
+        Ok(())
+    }
+}
@@ -0,0 +1,369 @@
+use crate::plan::Plan;
+use crate::scanner::{
+    self, rewrite_config_paths, rewrite_file_path, splice, JsScanner,
+    ScanOptions, Scanner,
+};
+use regex::Regex;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// i18next configs, for the resource paths they load translations from,
+/// like `loadPath: '/Locales/{{lng}}/{{ns}}.json'`, and the namespaces and
+/// languages that name those files. Renaming `Checkout.json` without
+/// updating `ns: ['Checkout']` leaves every key on the page untranslated.
+pub struct I18nScanner;
+
+impl Scanner for I18nScanner {
+    fn matches(&self, path: &Path) -> bool {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let Some((stem, extension)) = name.rsplit_once('.') else {
+            return false;
+        };
+        let script =
+            matches!(extension, "js" | "cjs" | "mjs" | "ts" | "cts" | "mts");
+        script
+            && matches!(
+                stem,
+                "i18n"
+                    | "i18n.config"
+                    | "i18next.config"
+                    | "next-i18next.config"
+                    | "i18next-parser.config"
+            )
+    }
+
+    fn converts_unresolved(&self) -> bool {
+        true
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        scan: &ScanOptions,
+    ) -> (String, usize) {
+        let (content, resources) =
+            rewrite_resources(path, content, scanner::converted_path);
+        let (content, paths) =
+            rewrite_config_paths(path, &content, scanner::converted_path);
+        let (content, imports) = JsScanner.convert(path, &content, scan);
+        (content, resources + paths + imports)
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        scan: &ScanOptions,
+    ) -> (String, usize) {
+        let rename = |found: &Path| plan.target_of(found);
+        let (content, resources) = rewrite_resources(path, content, rename);
+        let (content, paths) = rewrite_config_paths(path, &content, rename);
+        let (content, imports) =
+            JsScanner.follow_plan(path, &content, plan, scan);
+        (content, resources + paths + imports)
+    }
+
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        scan: &ScanOptions,
+    ) -> Vec<String> {
+        JsScanner.orphans(path, content, scan)
+    }
+}
+
+const LANGUAGE: &str = "{{lng}}";
+const NAMESPACE: &str = "{{ns}}";
+
+/// Settings whose values are namespaces
+const NAMESPACE_KEYS: &[&str] = &["ns", "defaultNS", "fallbackNS"];
+
+/// Settings whose values are languages, i18next's and Next.js's
+const LANGUAGE_KEYS: &[&str] = &[
+    "lng",
+    "fallbackLng",
+    "supportedLngs",
+    "preload",
+    "locales",
+    "defaultLocale",
+];
+
+/// Rewrites the resource path templates that start from the site's root,
+/// which `rewrite_config_paths` leaves alone, and the namespaces and
+/// languages whose resource files are renamed
+fn rewrite_resources(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let templates = templates(dir, content);
+    let string = Regex::new(r#""([^"\n]*)"|'([^'\n]*)'|`([^`\n]*)`"#).unwrap();
+
+    let mut edits = Vec::new();
+    for caps in string.captures_iter(content) {
+        let text = caps.get(1).or(caps.get(2)).or(caps.get(3)).unwrap();
+        let Some(rooted) = text.as_str().strip_prefix('/') else {
+            continue;
+        };
+        if !text.as_str().contains(LANGUAGE) {
+            continue;
+        }
+        // Served from `public/` by most setups
+        let first = rooted.split('/').next().unwrap_or("");
+        let base = [dir.join("public"), dir.to_path_buf()]
+            .into_iter()
+            .find(|base| base.join(first).exists());
+        if let Some(base) = base {
+            let start = text.start() + 1;
+            edits.extend(rewrite_file_path(&base, rooted, start, &[], &rename));
+        }
+    }
+
+    for (keys, token) in
+        [(NAMESPACE_KEYS, NAMESPACE), (LANGUAGE_KEYS, LANGUAGE)]
+    {
+        for (range, value) in setting_values(content, keys) {
+            let new = templates
+                .iter()
+                .find_map(|t| renamed_value(t, token, &value, &rename));
+            if let Some(new) = new.filter(|new| *new != value) {
+                edits.push((range, new));
+            }
+        }
+    }
+    splice(content, edits)
+}
+
+/// The config's resource path templates, resolved against `dir`:
+/// `loadPath`-style ones with `{{lng}}`, i18next-parser's `$LOCALE` ones,
+/// next-i18next's `localePath`, and the usual `locales` directories
+fn templates(dir: &Path, content: &str) -> Vec<String> {
+    let string = Regex::new(r#""([^"\n]*)"|'([^'\n]*)'|`([^`\n]*)`"#).unwrap();
+    let locale_path =
+        Regex::new(r#"localePath["']?\s*:[^,\n]*?["'`]([^"'`\n]+)["'`]"#)
+            .unwrap();
+    let resolve = |value: &str| {
+        let value = value.trim_start_matches("./");
+        match value.strip_prefix('/') {
+            Some(rooted) => vec![
+                dir.join("public")
+                    .join(rooted)
+                    .to_string_lossy()
+                    .into_owned(),
+                dir.join(rooted).to_string_lossy().into_owned(),
+            ],
+            None => vec![dir.join(value).to_string_lossy().into_owned()],
+        }
+    };
+
+    let mut templates = Vec::new();
+    for caps in string.captures_iter(content) {
+        let text = caps.get(1).or(caps.get(2)).or(caps.get(3)).unwrap();
+        let template = text
+            .as_str()
+            .replace("$LOCALE", LANGUAGE)
+            .replace("$NAMESPACE", NAMESPACE);
+        if template.contains(LANGUAGE) {
+            templates.extend(resolve(&template));
+        }
+    }
+    for caps in locale_path.captures_iter(content) {
+        let base = caps[1].trim_end_matches('/');
+        templates.extend(resolve(&format!(
+            "{}/{}/{}.json",
+            base, LANGUAGE, NAMESPACE
+        )));
+    }
+    for base in ["public/locales", "locales"] {
+        templates.extend(resolve(&format!(
+            "{}/{}/{}.json",
+            base, LANGUAGE, NAMESPACE
+        )));
+    }
+    templates
+}
+
+/// The strings set for any of `keys`, alone or in a list, and where
+fn setting_values(content: &str, keys: &[&str]) -> Vec<(Range<usize>, String)> {
+    let setting =
+        Regex::new(r#"["']?(\w+)["']?\s*:\s*(\[[^\]]*\]|"[^"\n]*"|'[^'\n]*')"#)
+            .unwrap();
+    let string = Regex::new(r#""([^"\n]*)"|'([^'\n]*)'"#).unwrap();
+    let mut values = Vec::new();
+    for caps in setting.captures_iter(content) {
+        if !keys.contains(&&caps[1]) {
+            continue;
+        }
+        let value = caps.get(2).unwrap();
+        for inner in string.captures_iter(value.as_str()) {
+            let text = inner.get(1).or(inner.get(2)).unwrap();
+            let range =
+                value.start() + text.start()..value.start() + text.end();
+            values.push((range, text.as_str().to_string()));
+        }
+    }
+    values
+}
+
+/// What `value` becomes when it fills `token` in `template`: the resource
+/// file or directory it names is found on disk, with any other token
+/// matching whatever is there, and the name it's renamed to is read back
+fn renamed_value(
+    template: &str,
+    token: &str,
+    value: &str,
+    rename: &impl Fn(&Path) -> Option<PathBuf>,
+) -> Option<String> {
+    let other = if token == LANGUAGE {
+        NAMESPACE
+    } else {
+        LANGUAGE
+    };
+    let components: Vec<&str> = template.split('/').collect();
+    let index = components.iter().position(|c| c.contains(token))?;
+    let (prefix, suffix) = components[index].split_once(token)?;
+
+    // Only one directory level can hold the other token's values
+    if components[..index].iter().any(|c| c.contains(other)) {
+        return named_through(template, index, other, token, value, rename);
+    }
+    let name = format!("{}{}{}", prefix, value, suffix);
+    let path = Path::new(&components[..index].join("/")).join(&name);
+    if !path.exists() || name.contains(other) {
+        return None;
+    }
+    read_back(&path, prefix, suffix, rename)
+}
+
+/// Like `renamed_value`, when the other token is in a directory above the
+/// one `value` names, as in `locales/{{lng}}/{{ns}}.json`
+fn named_through(
+    template: &str,
+    index: usize,
+    other: &str,
+    token: &str,
+    value: &str,
+    rename: &impl Fn(&Path) -> Option<PathBuf>,
+) -> Option<String> {
+    let components: Vec<&str> = template.split('/').collect();
+    let at = components.iter().position(|c| c.contains(other))?;
+    let (other_prefix, other_suffix) = components[at].split_once(other)?;
+    let parent = components[..at].join("/");
+    let (prefix, suffix) = components[index].split_once(token)?;
+    let name = format!("{}{}{}", prefix, value, suffix);
+    if name.contains(other) {
+        return None;
+    }
+
+    let mut entries: Vec<PathBuf> = fs::read_dir(&parent)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|entry| {
+            let entry = entry.file_name().and_then(|n| n.to_str());
+            entry.is_some_and(|n| {
+                n.len() > other_prefix.len() + other_suffix.len()
+                    && n.starts_with(other_prefix)
+                    && n.ends_with(other_suffix)
+            })
+        })
+        .collect();
+    entries.sort();
+    let between = components[at + 1..index].join("/");
+    entries.into_iter().find_map(|entry| {
+        let path = entry.join(&between).join(&name);
+        path.exists()
+            .then(|| read_back(&path, prefix, suffix, rename))?
+    })
+}
+
+/// The value in the new name of `path`, between `prefix` and `suffix`
+fn read_back(
+    path: &Path,
+    prefix: &str,
+    suffix: &str,
+    rename: &impl Fn(&Path) -> Option<PathBuf>,
+) -> Option<String> {
+    let new = rename(path)?;
+    let new = new.file_name()?.to_str()?;
+    let value = new.strip_prefix(prefix)?.strip_suffix(suffix)?;
+    Some(value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_i18n_config() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        for lng in ["En-US", "pt_BR"] {
+            let dir = root.join("public/Locales").join(lng);
+            fs::create_dir_all(&dir)?;
+            fs::write(dir.join("Common.json"), "{}")?;
+            fs::write(dir.join("CheckoutPage.json"), "{}")?;
+        }
+
+        let content = r#"import i18n from 'i18next';
+import Backend from 'i18next-http-backend';
+
+i18n.use(Backend).init({
+  lng: 'En-US',
+  supportedLngs: ['En-US', 'pt_BR'],
+  ns: ['Common', 'CheckoutPage', 'Missing'],
+  defaultNS: 'Common',
+  backend: { loadPath: '/Locales/{{lng}}/{{ns}}.json' },
+});
+"#;
+        let path = root.join("i18n.ts");
+        assert!(I18nScanner.matches(&path));
+        let (new_content, changes) =
+            I18nScanner.convert(&path, content, &ScanOptions::default());
+        assert_eq!(
+            new_content,
+            r#"import i18n from 'i18next';
+import Backend from 'i18next-http-backend';
+
+i18n.use(Backend).init({
+  lng: 'en-US',
+  supportedLngs: ['en-US', 'pt_BR'],
+  ns: ['common', 'checkout-page', 'Missing'],
+  defaultNS: 'common',
+  backend: { loadPath: '/locales/{{lng}}/{{ns}}.json' },
+});
+"#
+        );
+        assert_eq!(changes, 6);
+
+        // next-i18next names the directory rather than a template
+        let content = r#"module.exports = {
+  i18n: { defaultLocale: 'En-US', locales: ['En-US'] },
+  defaultNS: 'CheckoutPage',
+  localePath: path.resolve('./public/Locales'),
+};
+"#;
+        let (new_content, _) = I18nScanner.convert(
+            &root.join("next-i18next.config.js"),
+            content,
+            &ScanOptions::default(),
+        );
+        assert_eq!(
+            new_content,
+            r#"module.exports = {
+  i18n: { defaultLocale: 'en-US', locales: ['en-US'] },
+  defaultNS: 'checkout-page',
+  localePath: path.resolve('./public/locales'),
+};
+"#
+        );
+        Ok(())
+    }
+}
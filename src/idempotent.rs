@@ -0,0 +1,112 @@
+use crate::doctor::{print_findings, Finding};
+use crate::report::Report;
+use crate::{convert_name_as, encoding, scanner, Options};
+use anyhow::Result;
+use std::fs;
+use std::path::Path;
+
+/// Runs the conversion of `dir` twice in memory, without changing anything,
+/// and reports every name or file the second run would change again. A
+/// converted tree has to be a fixed point, or repeated runs would keep
+/// mutating it. Returns how many problems were found.
+pub fn check(dir: &Path, opts: &Options, report: &mut Report) -> Result<usize> {
+    let plan = crate::plan_directory(dir, opts, report)?;
+    let mut findings = Vec::new();
+
+    for rename in &plan.renames {
+        let Some(name) = rename.to.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let style = scanner::name_style(&rename.from, rename.from.is_dir());
+        let again = convert_name_as(name, style);
+        if again != name {
+            findings.push(Finding {
+                path: rename.from.clone(),
+                problem: format!(
+                    "renamed to '{}', which a second run would rename to '{}'",
+                    name, again
+                ),
+                fix: "report this as a kebabify bug".to_string(),
+            });
+        }
+    }
+
+    let files = crate::source_files(dir, opts, report)?;
+    let exceptions = plan.exceptions();
+    for entry in &files {
+        let path = entry.path();
+        let Ok(bytes) = fs::read(path) else {
+            continue;
+        };
+        let Some((content, _)) = encoding::decode(&bytes) else {
+            continue;
+        };
+        let convert = |content: &str| {
+            scanner::convert_file(path, content, &opts.scan, &plan, &exceptions)
+        };
+        let (once, _) = convert(&content);
+        let (_, changes) = convert(&once);
+        if changes > 0 {
+            findings.push(Finding {
+                path: path.to_path_buf(),
+                problem: format!(
+                    "a second run would change {} more references",
+                    changes
+                ),
+                fix: "report this as a kebabify bug".to_string(),
+            });
+        }
+    }
+
+    print_findings(&findings, opts);
+    opts.log(format_args!(
+        "Checked {} renames and {} files",
+        plan.renames.len(),
+        files.len()
+    ));
+    Ok(findings.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan::Plan;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_second_run_changes_nothing() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = &temp_dir.path().join("project");
+        fs::create_dir_all(root.join("ComponentLibrary"))?;
+        fs::create_dir_all(root.join("Models"))?;
+        fs::write(root.join("ComponentLibrary/XMLHttpButton.ts"), "")?;
+        fs::write(root.join("Models/UserRecord.py"), "")?;
+        fs::write(
+            root.join("App.ts"),
+            "import b from './ComponentLibrary/XMLHttpButton';",
+        )?;
+
+        let opts = Options::default();
+        assert_eq!(check(root, &opts, &mut Report::default())?, 0);
+
+        // Once converted for real, there's nothing left to do
+        crate::process_imports(
+            root,
+            &Plan::default(),
+            &opts,
+            &mut Report::default(),
+        )?;
+        crate::process_directory(root, &opts, &mut Report::default())?;
+        let plan = crate::plan_directory(root, &opts, &mut Report::default())?;
+        assert!(plan.renames.is_empty());
+        let content = fs::read_to_string(root.join("app.ts"))?;
+        crate::process_imports(
+            root,
+            &Plan::default(),
+            &opts,
+            &mut Report::default(),
+        )?;
+        assert_eq!(fs::read_to_string(root.join("app.ts"))?, content);
+        Ok(())
+    }
+}
@@ -0,0 +1,266 @@
+use crate::aliases::Alias;
+use crate::resolve::normalize;
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// The packages a project imports by name, as its package manager laid
+/// them out
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Installed {
+    /// Packages from a registry, whose imports name files kebabify doesn't
+    /// rename
+    pub external: BTreeSet<String>,
+    /// Packages that live in the repo, like workspace packages and `link:`
+    /// dependencies, and where
+    pub local: Vec<Alias>,
+}
+
+/// The packages installed for the project at `root`, from Yarn
+/// Plug'n'Play's `.pnp.cjs` or `.pnp.data.json`, or else from the
+/// `node_modules` of `root` and the directories above it. pnpm's virtual
+/// store is read through `node_modules/.pnpm/node_modules`, and links that
+/// lead out of `node_modules` are local packages.
+pub fn find(root: &Path) -> Result<Installed> {
+    let mut installed = Installed::default();
+    let depth = fs::canonicalize(root)
+        .map(|dir| dir.ancestors().count())
+        .unwrap_or(1);
+    let mut dir = root.to_path_buf();
+    for _ in 0..depth {
+        for name in [".pnp.data.json", ".pnp.cjs"] {
+            let path = dir.join(name);
+            let Ok(content) = fs::read_to_string(&path) else {
+                continue;
+            };
+            let state = match name {
+                ".pnp.cjs" => runtime_state(&content),
+                _ => Some(content),
+            };
+            let state: Value = state
+                .and_then(|state| serde_json::from_str(&state).ok())
+                .with_context(|| format!("Malformed {}", path.display()))?;
+            read_pnp(&dir, &state, &mut installed);
+            // Plug'n'Play projects don't look any further up
+            return Ok(installed);
+        }
+        read_node_modules(&dir.join("node_modules"), &mut installed);
+        dir = normalize(&dir.join(".."));
+    }
+    Ok(installed)
+}
+
+/// The JSON `.pnp.cjs` embeds as the `RAW_RUNTIME_STATE` string literal
+fn runtime_state(content: &str) -> Option<String> {
+    let start = content.find("RAW_RUNTIME_STATE")?;
+    let rest = &content[start..];
+    let quote = rest.find(['\'', '"'])?;
+    let delimiter = rest[quote..].chars().next()?;
+    let mut json = String::new();
+    let mut chars = rest[quote + 1..].chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next()? {
+                // A line continuation
+                '\n' => {}
+                'n' => json.push('\n'),
+                escaped => json.push(escaped),
+            },
+            c if c == delimiter => return Some(json),
+            c => json.push(c),
+        }
+    }
+    None
+}
+
+/// Reads the `packageRegistryData` of a Plug'n'Play state: a list of
+/// `[name, [[reference, { packageLocation, linkType }]]]`. Locations are
+/// relative to `dir`; those outside `.yarn` and the cache are in the repo.
+fn read_pnp(dir: &Path, state: &Value, installed: &mut Installed) {
+    let registry = state.get("packageRegistryData").and_then(Value::as_array);
+    for package in registry.into_iter().flatten() {
+        let Some(name) = package.get(0).and_then(Value::as_str) else {
+            continue;
+        };
+        let references = package.get(1).and_then(Value::as_array);
+        let location = references
+            .into_iter()
+            .flatten()
+            .filter_map(|r| r.get(1)?.get("packageLocation")?.as_str())
+            .find(|location| {
+                !location.contains("/node_modules/")
+                    && !location.starts_with("./.yarn/")
+            });
+        match location {
+            Some(location) => add_local(installed, name, dir.join(location)),
+            None => {
+                installed.external.insert(name.to_string());
+            }
+        }
+    }
+}
+
+/// Reads the packages in `node_modules`, `@scope/name` ones included
+fn read_node_modules(node_modules: &Path, installed: &mut Installed) {
+    let mut entries: Vec<(String, PathBuf)> = Vec::new();
+    let list = |dir: &Path, prefix: &str, out: &mut Vec<(String, PathBuf)>| {
+        let Ok(read) = fs::read_dir(dir) else {
+            return;
+        };
+        for entry in read.filter_map(|e| e.ok()) {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !name.starts_with('.') {
+                out.push((format!("{}{}", prefix, name), entry.path()));
+            }
+        }
+    };
+    list(node_modules, "", &mut entries);
+    // pnpm hoists every package it installed into its virtual store here
+    list(&node_modules.join(".pnpm/node_modules"), "", &mut entries);
+    let scopes: Vec<(String, PathBuf)> = entries
+        .iter()
+        .filter(|(name, _)| name.starts_with('@'))
+        .cloned()
+        .collect();
+    entries.retain(|(name, _)| !name.starts_with('@'));
+    for (scope, path) in scopes {
+        list(&path, &format!("{}/", scope), &mut entries);
+    }
+
+    for (name, path) in entries {
+        let target = fs::read_link(&path)
+            .map(|target| normalize(&path.parent().unwrap().join(target)));
+        match target {
+            Ok(target) if !in_node_modules(&target) => {
+                add_local(installed, &name, target)
+            }
+            _ => {
+                installed.external.insert(name);
+            }
+        }
+    }
+}
+
+fn add_local(installed: &mut Installed, name: &str, dir: PathBuf) {
+    let dir = normalize(&dir);
+    if !installed.local.iter().any(|p| p.prefix == name) {
+        installed.local.push(Alias {
+            prefix: name.to_string(),
+            dir,
+        });
+    }
+}
+
+fn in_node_modules(path: &Path) -> bool {
+    path.components()
+        .any(|c| c == Component::Normal("node_modules".as_ref()))
+}
+
+/// The package a bare specifier like `@scope/name/file` imports from
+pub fn package_name(spec: &str) -> &str {
+    let mut parts = spec.splitn(3, '/');
+    let first = parts.next().unwrap_or(spec);
+    match (first.starts_with('@'), parts.next()) {
+        (true, Some(second)) => &spec[..first.len() + 1 + second.len()],
+        _ => first,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_pnp_packages() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::write(
+            root.join(".pnp.cjs"),
+            r#"#!/usr/bin/env node
+/* eslint-disable */
+"use strict";
+
+const RAW_RUNTIME_STATE =
+'{\
+  "__info": ["This file is automatically generated. Don\'t touch it"],\
+  "packageRegistryData": [\
+    [null, [[null, { "packageLocation": "./", "linkType": "SOFT" }]]],\
+    ["@acme/ui", [\
+      ["workspace:packages/UI", {\
+        "packageLocation": "./packages/UI/",\
+        "linkType": "SOFT"\
+      }]\
+    ]],\
+    ["SomeLib", [\
+      ["npm:1.0.0", {\
+        "packageLocation": "./.yarn/cache/SomeLib-npm-1.0.0-abc.zip/node_modules/SomeLib/",\
+        "linkType": "HARD"\
+      }]\
+    ]]\
+  ]\
+}';
+
+function $$SETUP_STATE(hydrateRuntimeState, basePath) {}
+"#,
+        )?;
+
+        fs::create_dir_all(root.join("packages/UI"))?;
+        let installed = find(&root.join("packages/UI"))?;
+        assert_eq!(installed, find(root)?);
+        assert_eq!(installed.external, BTreeSet::from(["SomeLib".into()]));
+        assert_eq!(
+            installed.local,
+            [Alias {
+                prefix: "@acme/ui".into(),
+                dir: root.join("packages/UI"),
+            }]
+        );
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_pnpm_packages() -> Result<()> {
+        use std::os::unix::fs::symlink;
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        let store = root.join("node_modules/.pnpm");
+        fs::create_dir_all(store.join("ReactNative@0.74.0/node_modules"))?;
+        fs::create_dir_all(store.join("Left-Pad@1.0.0/node_modules"))?;
+        fs::create_dir_all(store.join("node_modules"))?;
+        fs::create_dir_all(root.join("node_modules/@acme"))?;
+        fs::create_dir_all(root.join("packages/UI"))?;
+        symlink(
+            ".pnpm/ReactNative@0.74.0/node_modules/ReactNative",
+            root.join("node_modules/ReactNative"),
+        )?;
+        symlink(
+            "../Left-Pad@1.0.0/node_modules/Left-Pad",
+            store.join("node_modules/Left-Pad"),
+        )?;
+        symlink("../../packages/UI", root.join("node_modules/@acme/ui"))?;
+
+        let installed = find(&root.join("packages/UI"))?;
+        assert_eq!(
+            installed.external,
+            BTreeSet::from(["Left-Pad".into(), "ReactNative".into()])
+        );
+        assert_eq!(installed.local[0].prefix, "@acme/ui");
+        assert_eq!(installed.local[0].dir, root.join("packages/UI"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_package_name() {
+        assert_eq!(
+            package_name("react-native/Libraries/Image"),
+            "react-native"
+        );
+        assert_eq!(package_name("@acme/ui/src/Button"), "@acme/ui");
+        assert_eq!(package_name("@acme/ui"), "@acme/ui");
+        assert_eq!(package_name("lodash"), "lodash");
+    }
+}
@@ -0,0 +1,515 @@
+use crate::aliases::Alias;
+use crate::default_imports::{DefaultImports, ImportCase};
+use crate::describe::DescribeBlocks;
+use crate::plan::Plan;
+use crate::report::Aborted;
+use crate::scanner::ScanOptions;
+use crate::{fs_util, Comments, ImportExtensions, Options, Separators};
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Steps of the current run, kept on disk until it finishes so an
+/// interrupted run can be resumed
+pub const JOURNAL_FILE: &str = ".kebabify-journal.jsonl";
+
+/// A change made to disk, with what's needed to undo it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Renamed {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    Wrote {
+        path: PathBuf,
+        original: Vec<u8>,
+    },
+    Relinked {
+        link: PathBuf,
+        target: PathBuf,
+    },
+    /// A file `--on-conflict overwrite` replaced, with what it held
+    Replaced {
+        path: PathBuf,
+        original: Vec<u8>,
+    },
+}
+
+/// How a phase of a run rewrites imports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+    None,
+    /// Convert every specifier's case, as `-i` does
+    Convert,
+    /// Follow the phase's rename plan, as `--mapping` does
+    Plan,
+}
+
+/// One planned batch of import edits and renames, as read back from the
+/// journal file, with the steps that already completed
+#[derive(Debug)]
+pub struct Phase {
+    pub imports: ImportMode,
+    pub scan: ScanOptions,
+    pub plan: Plan,
+    pub imports_done: bool,
+    pub written: HashSet<PathBuf>,
+}
+
+/// Every change a run has made, in order, so it can be rolled back. With a
+/// journal file open, each step is also appended to it as it happens.
+#[derive(Debug, Default)]
+pub struct Journal {
+    pub changes: Vec<Change>,
+    file: Option<(PathBuf, File)>,
+    /// Phases written to the file so far, and the one steps belong to
+    phases: usize,
+    current: usize,
+}
+
+impl Journal {
+    /// Starts a journal file in `root`. Refuses if an interrupted run left
+    /// one behind, unless `resuming` it.
+    pub fn open(&mut self, root: &Path, resuming: bool) -> Result<()> {
+        let path = root.join(JOURNAL_FILE);
+        if resuming && !path.exists() {
+            bail!("Nothing to resume: no journal in {}", root.display());
+        }
+        if !resuming && path.exists() {
+            return Err(Aborted(format!(
+                "An interrupted run left {} behind. Run `kebabify resume` \
+                 to finish it, or delete the file to start over",
+                path.display()
+            ))
+            .into());
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| {
+                format!("Failed to open journal: {}", path.display())
+            })?;
+        self.file = Some((path, file));
+        Ok(())
+    }
+
+    /// Removes the journal file once the run is over. `root` is where the
+    /// target directory is now, in case the run renamed it.
+    pub fn close(&mut self, root: &Path) -> Result<()> {
+        if self.file.take().is_none() {
+            return Ok(());
+        }
+        let path = root.join(JOURNAL_FILE);
+        fs::remove_file(&path).with_context(|| {
+            format!("Failed to remove journal: {}", path.display())
+        })
+    }
+
+    /// Writes down what a phase is about to do, before it starts
+    pub fn begin_phase(
+        &mut self,
+        imports: ImportMode,
+        scan: &ScanOptions,
+        plan: &Plan,
+    ) {
+        let imports = match imports {
+            ImportMode::None => "none",
+            ImportMode::Convert => "convert",
+            ImportMode::Plan => "plan",
+        };
+        let scan = json!({
+            "separators": scan
+                .separators
+                .to_possible_value()
+                .map(|v| v.get_name().to_string()),
+            "import_extensions": scan
+                .import_extensions
+                .to_possible_value()
+                .map(|v| v.get_name().to_string()),
+            "collapse_index": scan.collapse_index,
+            "prefer_dot_slash": scan.prefer_dot_slash,
+            "aliases": scan
+                .aliases
+                .iter()
+                .map(|a| json!({ "prefix": a.prefix, "dir": absolute(&a.dir) }))
+                .collect::<Vec<_>>(),
+            "packages": scan
+                .packages
+                .iter()
+                .map(|a| json!({ "prefix": a.prefix, "dir": absolute(&a.dir) }))
+                .collect::<Vec<_>>(),
+            "external": scan.external,
+            "quote": scan.quote.map(String::from),
+            "comments": scan
+                .comments
+                .to_possible_value()
+                .map(|v| v.get_name().to_string()),
+            "template_literals": scan.template_literals,
+            "string_refs": scan.string_refs,
+            "comment_paths": scan.comment_paths,
+            "html_templates": scan.html_templates,
+            "xml_attributes": scan.xml_attributes,
+            "describe": scan
+                .describe
+                .to_possible_value()
+                .map(|v| v.get_name().to_string()),
+            "default_imports": scan
+                .default_imports
+                .to_possible_value()
+                .map(|v| v.get_name().to_string()),
+            "import_case": scan
+                .import_case
+                .to_possible_value()
+                .map(|v| v.get_name().to_string()),
+            "roots": scan.roots.iter().map(|r| absolute(r)).collect::<Vec<_>>(),
+        });
+        let renames: Vec<Value> = plan
+            .renames
+            .iter()
+            .map(
+                |r| json!({ "from": absolute(&r.from), "to": absolute(&r.to) }),
+            )
+            .collect();
+        self.append(json!({ "phase": {
+                "imports": imports,
+                "scan": scan,
+                "renames": renames,
+            } }));
+        self.current = self.phases;
+        self.phases += 1;
+    }
+
+    /// Continues a phase already in the journal file, when resuming
+    pub fn continue_phase(&mut self, index: usize) {
+        self.current = index;
+    }
+
+    /// Marks the current phase's import edits as finished
+    pub fn imports_done(&mut self) {
+        self.append(json!({ "imports_done": true, "in": self.current }));
+    }
+
+    pub fn record(&mut self, change: Change) {
+        self.append(match &change {
+            Change::Renamed { from, to } => {
+                json!({ "renamed": { "from": absolute(from), "to": absolute(to) } })
+            }
+            Change::Wrote { path, .. } => {
+                json!({ "wrote": absolute(path), "in": self.current })
+            }
+            Change::Relinked { link, .. } => json!({ "relinked": absolute(link) }),
+            Change::Replaced { path, .. } => {
+                json!({ "replaced": absolute(path) })
+            }
+        });
+        self.changes.push(change);
+    }
+
+    fn append(&mut self, line: Value) {
+        let Some((path, file)) = &mut self.file else {
+            return;
+        };
+        // Losing the journal only costs the ability to resume, so it
+        // mustn't stop the run itself
+        if let Err(err) = writeln!(file, "{}", line) {
+            eprintln!("Warning: failed to write {}: {}", path.display(), err);
+        }
+    }
+
+    /// Undoes every recorded change, newest first. Keeps going past
+    /// failures so as much as possible is restored.
+    pub fn rollback(&mut self, opts: &Options) -> Result<()> {
+        let mut failed = 0;
+        while let Some(change) = self.changes.pop() {
+            if let Err(err) = undo(&change, opts) {
+                eprintln!("Error: {:#}", err);
+                failed += 1;
+            }
+        }
+        if failed > 0 {
+            bail!("{} change(s) couldn't be rolled back", failed);
+        }
+        Ok(())
+    }
+}
+
+/// Reads the phases an interrupted run wrote to the journal in `root`
+pub fn load(root: &Path) -> Result<Option<Vec<Phase>>> {
+    let path = root.join(JOURNAL_FILE);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(None)
+        }
+        Err(err) => {
+            return Err(err).with_context(|| {
+                format!("Failed to read journal: {}", path.display())
+            })
+        }
+    };
+
+    let mut phases: Vec<Phase> = Vec::new();
+    for line in content.lines() {
+        // A crash can leave the last line half-written
+        let Ok(entry) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        if let Some(phase) = entry.get("phase") {
+            phases.push(parse_phase(phase).with_context(|| {
+                format!("Malformed journal: {}", path.display())
+            })?);
+            continue;
+        }
+        let index = entry.get("in").and_then(Value::as_u64);
+        let Some(phase) = index.and_then(|i| phases.get_mut(i as usize)) else {
+            continue;
+        };
+        if entry.get("imports_done").is_some() {
+            phase.imports_done = true;
+        } else if let Some(path) = entry.get("wrote").and_then(Value::as_str) {
+            phase.written.insert(PathBuf::from(path));
+        }
+    }
+    Ok(Some(phases))
+}
+
+fn parse_phase(phase: &Value) -> Option<Phase> {
+    let imports = match phase.get("imports")?.as_str()? {
+        "none" => ImportMode::None,
+        "convert" => ImportMode::Convert,
+        "plan" => ImportMode::Plan,
+        _ => return None,
+    };
+    let mut scan = ScanOptions::default();
+    if let Some(options) = phase.get("scan") {
+        if let Some(name) = options.get("separators").and_then(Value::as_str) {
+            scan.separators = Separators::from_str(name, false).ok()?;
+        }
+        let extensions =
+            options.get("import_extensions").and_then(Value::as_str);
+        if let Some(name) = extensions {
+            scan.import_extensions =
+                ImportExtensions::from_str(name, false).ok()?;
+        }
+        let flag = |name: &str| {
+            options.get(name).and_then(Value::as_bool).unwrap_or(false)
+        };
+        scan.collapse_index = flag("collapse_index");
+        scan.prefer_dot_slash = flag("prefer_dot_slash");
+        let aliases = |name: &str| {
+            let list = options.get(name).and_then(Value::as_array);
+            list.into_iter()
+                .flatten()
+                .map(|alias| {
+                    Some(Alias {
+                        prefix: alias.get("prefix")?.as_str()?.to_string(),
+                        dir: PathBuf::from(alias.get("dir")?.as_str()?),
+                    })
+                })
+                .collect::<Option<Vec<_>>>()
+        };
+        scan.aliases = aliases("aliases")?;
+        scan.packages = aliases("packages")?;
+        let external = options.get("external").and_then(Value::as_array);
+        scan.external = external
+            .into_iter()
+            .flatten()
+            .filter_map(|p| p.as_str().map(String::from))
+            .collect();
+        if let Some(name) = options.get("comments").and_then(Value::as_str) {
+            scan.comments = Comments::from_str(name, false).ok()?;
+        }
+        let quote = options.get("quote").and_then(Value::as_str);
+        scan.quote = quote.and_then(|q| q.chars().next());
+        scan.template_literals = flag("template_literals");
+        scan.string_refs = flag("string_refs");
+        scan.comment_paths = flag("comment_paths");
+        scan.html_templates = flag("html_templates");
+        let attributes =
+            options.get("xml_attributes").and_then(Value::as_array);
+        scan.xml_attributes = attributes
+            .into_iter()
+            .flatten()
+            .filter_map(|a| a.as_str().map(String::from))
+            .collect();
+        if let Some(name) = options.get("describe").and_then(Value::as_str) {
+            scan.describe = DescribeBlocks::from_str(name, false).ok()?;
+        }
+        let imports = options.get("default_imports").and_then(Value::as_str);
+        if let Some(name) = imports {
+            scan.default_imports =
+                DefaultImports::from_str(name, false).ok()?;
+        }
+        if let Some(name) = options.get("import_case").and_then(Value::as_str) {
+            scan.import_case = ImportCase::from_str(name, false).ok()?;
+        }
+        let roots = options.get("roots").and_then(Value::as_array);
+        scan.roots = roots
+            .into_iter()
+            .flatten()
+            .filter_map(|r| r.as_str().map(PathBuf::from))
+            .collect();
+    }
+    let mut plan = Plan::default();
+    for rename in phase.get("renames")?.as_array()? {
+        plan.push(
+            PathBuf::from(rename.get("from")?.as_str()?),
+            PathBuf::from(rename.get("to")?.as_str()?),
+        );
+    }
+    Some(Phase {
+        imports,
+        scan,
+        plan,
+        imports_done: false,
+        written: HashSet::new(),
+    })
+}
+
+/// Journal paths are absolute so a resume doesn't depend on the working
+/// directory of the interrupted run
+fn absolute(path: &Path) -> String {
+    std::path::absolute(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn undo(change: &Change, opts: &Options) -> Result<()> {
+    let display = &opts.display;
+    match change {
+        Change::Renamed { from, to } => {
+            opts.log(format_args!(
+                "Restoring: {} -> {}",
+                display.show(to),
+                display.show(from)
+            ));
+            fs_util::with_retry(opts.retries, || fs::rename(to, from))
+                .with_context(|| {
+                    format!("Failed to restore: {}", display.show(from))
+                })
+        }
+        Change::Wrote { path, original } => {
+            opts.log(format_args!("Restoring: {}", display.show(path)));
+            fs_util::with_retry(opts.retries, || fs::write(path, original))
+                .with_context(|| {
+                    format!("Failed to restore: {}", display.show(path))
+                })
+        }
+        Change::Relinked { link, target } => {
+            opts.log(format_args!("Restoring link: {}", display.show(link)));
+            fs_util::replace_symlink(link, target).with_context(|| {
+                format!("Failed to restore symlink: {}", display.show(link))
+            })
+        }
+        // Its name was taken back when the file that replaced it moved
+        // back, so it goes where it was
+        Change::Replaced { path, original } => {
+            opts.log(format_args!("Restoring: {}", display.show(path)));
+            fs_util::with_retry(opts.retries, || fs::write(path, original))
+                .with_context(|| {
+                    format!("Failed to restore: {}", display.show(path))
+                })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_rollback() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::write(root.join("main.ts"), "new")?;
+        fs::create_dir(root.join("lib"))?;
+        fs::write(root.join("lib/button.ts"), "")?;
+
+        let mut journal = Journal::default();
+        journal.record(Change::Wrote {
+            path: root.join("main.ts"),
+            original: b"old".to_vec(),
+        });
+        journal.record(Change::Renamed {
+            from: root.join("Lib"),
+            to: root.join("lib"),
+        });
+        journal.record(Change::Renamed {
+            from: root.join("lib/Button.ts"),
+            to: root.join("lib/button.ts"),
+        });
+        journal.rollback(&Options::default())?;
+
+        assert!(journal.changes.is_empty());
+        assert!(root.join("Lib/Button.ts").exists());
+        assert_eq!(fs::read_to_string(root.join("main.ts"))?, "old");
+        Ok(())
+    }
+
+    #[test]
+    fn test_journal_file() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        let mut plan = Plan::default();
+        plan.push(root.join("Lib"), root.join("lib"));
+
+        let mut journal = Journal::default();
+        journal.open(root, false)?;
+        let scan = ScanOptions {
+            separators: Separators::Slash,
+            import_extensions: ImportExtensions::Never,
+            collapse_index: true,
+            prefer_dot_slash: false,
+            aliases: vec![Alias {
+                prefix: "@components".to_string(),
+                dir: root.join("src/components"),
+            }],
+            packages: vec![Alias {
+                prefix: "@acme/ui".to_string(),
+                dir: root.join("packages/ui"),
+            }],
+            external: ["react-native".to_string()].into(),
+            quote: Some('\''),
+            comments: Comments::Flag,
+            template_literals: true,
+            string_refs: true,
+            roots: vec![root.join("public")],
+            comment_paths: true,
+            html_templates: true,
+            xml_attributes: vec!["href".to_string()],
+            describe: DescribeBlocks::Rewrite,
+            default_imports: DefaultImports::Flag,
+            import_case: ImportCase::Camel,
+        };
+        journal.begin_phase(ImportMode::Convert, &scan, &plan);
+        journal.record(Change::Wrote {
+            path: root.join("main.ts"),
+            original: Vec::new(),
+        });
+        journal.begin_phase(ImportMode::Plan, &ScanOptions::default(), &plan);
+        journal.imports_done();
+        journal.continue_phase(0);
+        journal.imports_done();
+        assert!(Journal::default().open(root, false).is_err());
+
+        let phases = load(root)?.unwrap();
+        assert_eq!(phases.len(), 2);
+        assert_eq!(phases[0].imports, ImportMode::Convert);
+        assert_eq!(phases[0].scan, scan);
+        assert!(phases[0].written.contains(&root.join("main.ts")));
+        assert!(phases[0].imports_done);
+        assert_eq!(phases[1].plan.renames, plan.renames);
+        assert!(phases[1].imports_done);
+
+        journal.close(root)?;
+        assert!(load(root)?.is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,245 @@
+use crate::conventions;
+use crate::plan::Plan;
+use crate::scanner::{self, rewrite_file_path, splice, ScanOptions, Scanner};
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Where Maven and Gradle keep a module's classpath resources
+const RESOURCE_DIRS: [&str; 2] = ["src/main/resources", "src/test/resources"];
+
+/// Java and Kotlin sources. Their files are named after the class they
+/// declare and their directories after their package, so unless
+/// `--force-java` is given they keep their names. Strings naming a
+/// classpath resource, like `getResource("/Templates/Welcome.html")`, are
+/// updated when the resource is renamed.
+pub struct JvmScanner;
+
+impl Scanner for JvmScanner {
+    fn matches(&self, path: &Path) -> bool {
+        path.extension().is_some_and(|e| e == "java" || e == "kt")
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_resources(path, content, scanner::converted_path)
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_resources(path, content, |found| plan.target_of(found))
+    }
+
+    /// Resources loaded with `getResource` or named `classpath:` that
+    /// aren't in any of the module's resource directories. Other strings
+    /// only count as resources when they name one, so they're never orphans.
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> Vec<String> {
+        let roots = resource_roots(path);
+        if roots.is_empty() {
+            return Vec::new();
+        }
+        let resource = Regex::new(concat!(
+            r#"getResource(?:AsStream)?\s*\(\s*"([^"\n]*)"|"#,
+            r#""classpath:([^"\n]*)""#,
+        ))
+        .unwrap();
+        resource
+            .captures_iter(content)
+            .filter_map(|caps| caps.get(1).or(caps.get(2)))
+            .map(|file| file.as_str().trim_start_matches('/'))
+            .filter(|file| {
+                roots
+                    .iter()
+                    .all(|root| scanner::leads_nowhere(root, file, &[]))
+            })
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn pins_name(&self, path: &Path) -> Option<&'static str> {
+        if conventions::get().force_java {
+            return None;
+        }
+        if path.is_dir() {
+            return is_package(path).then_some(
+                "it's a Java or Kotlin package directory, renaming it breaks \
+                 the package (use --force-java to rename it anyway)",
+            );
+        }
+        if path.extension().is_some_and(|e| e == "java") {
+            return Some(
+                "Java needs the file named after its class (use --force-java \
+                 to rename it anyway)",
+            );
+        }
+        let content = fs::read_to_string(path).ok()?;
+        let stem = path.file_stem()?.to_str()?;
+        let defines_class = class_regex()
+            .captures_iter(&content)
+            .any(|caps| &caps[1] == stem);
+        defines_class.then_some(
+            "it's named after its Kotlin class (use --force-java to rename it \
+             anyway)",
+        )
+    }
+}
+
+/// `class Foo`, `data class Foo`, `interface Foo`, `object Foo`,
+/// `enum class Foo` and the like
+fn class_regex() -> Regex {
+    Regex::new(concat!(
+        r"(?m)^[ \t]*(?:(?:public|internal|private|abstract|open|sealed|",
+        r"data|enum|annotation|value|inline|fun)\s+)*",
+        r"(?:class|interface|object)\s+(\w+)",
+    ))
+    .unwrap()
+}
+
+/// Whether `dir` is a package: a directory with Java or Kotlin files of its
+/// own, or with some under it inside a `java` or `kotlin` source root
+fn is_package(dir: &Path) -> bool {
+    let in_source_root = scanner::absolute(dir)
+        .ancestors()
+        .skip(1)
+        .any(|a| a.file_name().is_some_and(|n| n == "java" || n == "kotlin"));
+    let depth = if in_source_root { usize::MAX } else { 1 };
+    WalkDir::new(dir)
+        .max_depth(depth)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .any(|e| e.file_type().is_file() && JvmScanner.matches(e.path()))
+}
+
+/// The resource directories of the module the file at `path` is in
+fn resource_roots(path: &Path) -> Vec<PathBuf> {
+    let Some(module) = path.ancestors().skip(1).find(|dir| {
+        RESOURCE_DIRS
+            .iter()
+            .any(|resources| dir.join(resources).is_dir())
+    }) else {
+        return Vec::new();
+    };
+    RESOURCE_DIRS
+        .iter()
+        .map(|resources| module.join(resources))
+        .filter(|resources| resources.is_dir())
+        .collect()
+}
+
+/// Rewrites string literals that name a file or directory in one of the
+/// module's resource directories, like `"/Templates/Welcome.html"` or
+/// `"classpath:Templates/Welcome.html"`
+fn rewrite_resources(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let roots = resource_roots(path);
+    if roots.is_empty() {
+        return (content.to_string(), 0);
+    }
+    let string = Regex::new(r#""([^"\n]*)""#).unwrap();
+
+    let mut edits = Vec::new();
+    for caps in string.captures_iter(content) {
+        let text = caps.get(1).unwrap();
+        let file = text.as_str();
+        let file = file.strip_prefix("classpath:").unwrap_or(file);
+        let file = file.trim_start_matches('/');
+        // Bare words like "Templates" are rarely resource paths
+        let looks_like_path = file.contains('/') || file.contains('.');
+        if !looks_like_path || file.contains(['$', '\\']) {
+            continue;
+        }
+        let Some(root) = roots.iter().find(|root| root.join(file).exists())
+        else {
+            continue;
+        };
+        let start = text.end() - file.len();
+        edits.extend(rewrite_file_path(root, file, start, &[], &rename));
+    }
+    splice(content, edits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_jvm_names_and_resources() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        let java = root.join("src/main/java/com/Acme");
+        let kotlin = root.join("src/main/kotlin/com/acme");
+        fs::create_dir_all(&java)?;
+        fs::create_dir_all(&kotlin)?;
+        fs::create_dir_all(root.join("src/main/resources/Templates"))?;
+        fs::write(
+            root.join("src/main/resources/Templates/WelcomeMail.html"),
+            "",
+        )?;
+        fs::write(java.join("UserService.java"), "")?;
+        fs::write(
+            kotlin.join("UserRepo.kt"),
+            "data class UserRepo(val id: Int)",
+        )?;
+        fs::write(
+            kotlin.join("StringUtils.kt"),
+            "fun String.shout() = uppercase()",
+        )?;
+
+        assert!(JvmScanner
+            .pins_name(&java.join("UserService.java"))
+            .is_some());
+        assert!(JvmScanner.pins_name(&java).is_some());
+        assert!(JvmScanner
+            .pins_name(&root.join("src/main/java/com"))
+            .is_some());
+        assert!(JvmScanner.pins_name(&kotlin.join("UserRepo.kt")).is_some());
+        assert!(JvmScanner
+            .pins_name(&kotlin.join("StringUtils.kt"))
+            .is_none());
+        let resources = root.join("src/main/resources/Templates");
+        assert!(JvmScanner.pins_name(&resources).is_none());
+
+        let content = r#"
+var mail = getClass().getResource("/Templates/WelcomeMail.html");
+@Value("classpath:Templates/WelcomeMail.html") Resource template;
+String name = "Templates";
+"#;
+        let (new_content, changes) = JvmScanner.convert(
+            &java.join("UserService.java"),
+            content,
+            &ScanOptions::default(),
+        );
+        assert_eq!(
+            new_content,
+            content.replace("Templates/WelcomeMail", "templates/welcome-mail")
+        );
+        assert_eq!(changes, 4);
+        let orphans = JvmScanner.orphans(
+            &java.join("UserService.java"),
+            &content.replace("WelcomeMail", "Gone"),
+            &ScanOptions::default(),
+        );
+        assert_eq!(orphans, ["Templates/Gone.html", "Templates/Gone.html"]);
+        Ok(())
+    }
+}
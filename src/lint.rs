@@ -0,0 +1,170 @@
+use crate::plan::Plan;
+use crate::scanner::{
+    self, rewrite_config_paths, rewrite_file_path, splice, JsScanner,
+    ScanOptions, Scanner,
+};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// ESLint and Prettier configs, for the globs their `overrides` and flat
+/// config `files` apply to. A glob like `src/Components/**` that no longer
+/// matches anything doesn't fail; its rules just stop applying.
+pub struct LintScanner;
+
+impl Scanner for LintScanner {
+    fn matches(&self, path: &Path) -> bool {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if matches!(name, ".eslintrc" | ".prettierrc") {
+            return true;
+        }
+        let Some((stem, extension)) = name.rsplit_once('.') else {
+            return false;
+        };
+        let script =
+            matches!(extension, "js" | "cjs" | "mjs" | "ts" | "cts" | "mts");
+        let data = matches!(extension, "json" | "json5" | "yaml" | "yml");
+        match stem {
+            "eslint.config" | "prettier.config" => script,
+            ".eslintrc" | ".prettierrc" => script || data,
+            _ => false,
+        }
+    }
+
+    fn converts_unresolved(&self) -> bool {
+        true
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        scan: &ScanOptions,
+    ) -> (String, usize) {
+        let (content, paths) =
+            rewrite_paths(path, content, scanner::converted_path);
+        let (content, imports) = JsScanner.convert(path, &content, scan);
+        (content, paths + imports)
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        scan: &ScanOptions,
+    ) -> (String, usize) {
+        let (content, paths) =
+            rewrite_paths(path, content, |found| plan.target_of(found));
+        let (content, imports) =
+            JsScanner.follow_plan(path, &content, plan, scan);
+        (content, paths + imports)
+    }
+
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        scan: &ScanOptions,
+    ) -> Vec<String> {
+        JsScanner.orphans(path, content, scan)
+    }
+}
+
+/// Rewrites quoted paths and globs, and in a YAML config, or a
+/// `.eslintrc`/`.prettierrc` that may be YAML, the unquoted ones in lists
+/// and after keys, like `- src/Components/**/*.tsx`
+fn rewrite_paths(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let (content, quoted) = rewrite_config_paths(path, content, &rename);
+    let yaml = path.extension().is_none_or(|e| e == "yaml" || e == "yml");
+    if !yaml {
+        return (content, quoted);
+    }
+
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let plain = Regex::new(
+        r#"(?m)^[ \t]*(?:-[ \t]+|[\w-]+:[ \t]+)([^\s#'"\[{][^\s#]*)"#,
+    )
+    .unwrap();
+    let mut edits = Vec::new();
+    for caps in plain.captures_iter(&content) {
+        let text = caps.get(1).unwrap();
+        let value = text.as_str().trim_start_matches('!');
+        if !value.contains(['/', '.']) || value.starts_with('/') {
+            continue;
+        }
+        let start = text.end() - value.len();
+        edits.extend(rewrite_file_path(dir, value, start, &[], &rename));
+    }
+    let (content, plain) = splice(&content, edits);
+    (content, quoted + plain)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_lint_config_globs() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("Src/Components"))?;
+        fs::create_dir_all(root.join("Src/Legacy"))?;
+
+        let flat = r#"export default [
+  { files: ['Src/Components/**/*.tsx'], rules: { 'react/prop-types': 'off' } },
+  { ignores: ["Src/Legacy/**"] },
+];
+"#;
+        let path = root.join("eslint.config.mjs");
+        assert!(LintScanner.matches(&path));
+        let (new_flat, changes) =
+            LintScanner.convert(&path, flat, &ScanOptions::default());
+        assert_eq!(
+            new_flat,
+            r#"export default [
+  { files: ['src/components/**/*.tsx'], rules: { 'react/prop-types': 'off' } },
+  { ignores: ["src/legacy/**"] },
+];
+"#
+        );
+        assert_eq!(changes, 4);
+
+        let yaml = "overrides:
+  - files:
+      - Src/Components/**/*.tsx
+    excludedFiles: '!Src/Legacy/**'
+    rules:
+      no-console: off
+";
+        let path = root.join(".eslintrc.yml");
+        assert!(LintScanner.matches(&path));
+        let (new_yaml, _) =
+            LintScanner.convert(&path, yaml, &ScanOptions::default());
+        assert_eq!(
+            new_yaml,
+            "overrides:
+  - files:
+      - src/components/**/*.tsx
+    excludedFiles: '!src/legacy/**'
+    rules:
+      no-console: off
+"
+        );
+
+        let prettier = r#"{ "overrides": [{ "files": "Src/Legacy/*.js", "options": { "semi": true } }] }"#;
+        let (new_prettier, _) = LintScanner.convert(
+            &root.join(".prettierrc"),
+            prettier,
+            &ScanOptions::default(),
+        );
+        assert!(new_prettier.contains(r#""files": "src/legacy/*.js""#));
+        Ok(())
+    }
+}
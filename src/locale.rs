@@ -0,0 +1,81 @@
+/// ISO 639-1 language codes
+const LANGUAGES: &[&str] = &[
+    "aa", "ab", "ae", "af", "ak", "am", "an", "ar", "as", "av", "ay", "az",
+    "ba", "be", "bg", "bh", "bi", "bm", "bn", "bo", "br", "bs", "ca", "ce",
+    "ch", "co", "cr", "cs", "cu", "cv", "cy", "da", "de", "dv", "dz", "ee",
+    "el", "en", "eo", "es", "et", "eu", "fa", "ff", "fi", "fj", "fo", "fr",
+    "fy", "ga", "gd", "gl", "gn", "gu", "gv", "ha", "he", "hi", "ho", "hr",
+    "ht", "hu", "hy", "hz", "ia", "id", "ie", "ig", "ii", "ik", "io", "is",
+    "it", "iu", "ja", "jv", "ka", "kg", "ki", "kj", "kk", "kl", "km", "kn",
+    "ko", "kr", "ks", "ku", "kv", "kw", "ky", "la", "lb", "lg", "li", "ln",
+    "lo", "lt", "lu", "lv", "mg", "mh", "mi", "mk", "ml", "mn", "mr", "ms",
+    "mt", "my", "na", "nb", "nd", "ne", "ng", "nl", "nn", "no", "nr", "nv",
+    "ny", "oc", "oj", "om", "or", "os", "pa", "pi", "pl", "ps", "pt", "qu",
+    "rm", "rn", "ro", "ru", "rw", "sa", "sc", "sd", "se", "sg", "si", "sk",
+    "sl", "sm", "sn", "so", "sq", "sr", "ss", "st", "su", "sv", "sw", "ta",
+    "te", "tg", "th", "ti", "tk", "tl", "tn", "to", "tr", "ts", "tt", "tw",
+    "ty", "ug", "uk", "ur", "uz", "ve", "vi", "vo", "wa", "wo", "xh", "yi",
+    "yo", "za", "zh", "zu",
+];
+
+/// ISO 15924 scripts that locale codes commonly name
+const SCRIPTS: &[&str] = &[
+    "Arab", "Armn", "Beng", "Cyrl", "Deva", "Ethi", "Geor", "Grek", "Guru",
+    "Hans", "Hant", "Hebr", "Jpan", "Knda", "Kore", "Latn", "Mlym", "Mong",
+    "Taml", "Telu", "Thai", "Tibt",
+];
+
+/// The canonical casing of a BCP-47 locale code like `En-US` or
+/// `zh_hant_TW`, with its separator kept: `en-US`, `zh_Hant_TW`. Only codes
+/// with a known script or an uppercase region count, since a bare `de` or
+/// a `to-do` is as likely words. Kebab-casing `en-US` would make it
+/// `en-us`, which i18n libraries and case-sensitive servers don't find.
+pub fn canonical(part: &str) -> Option<String> {
+    let separator = if part.contains('_') { '_' } else { '-' };
+    let subtags: Vec<&str> = part.split(separator).collect();
+    let (language, rest) = subtags.split_first()?;
+    let language = language.to_ascii_lowercase();
+    if !LANGUAGES.contains(&language.as_str()) {
+        return None;
+    }
+
+    let mut canonical = vec![language];
+    let mut rest = rest.iter().peekable();
+    let script = rest.peek().and_then(|s| {
+        SCRIPTS.iter().find(|script| script.eq_ignore_ascii_case(s))
+    });
+    if let Some(script) = script {
+        canonical.push(script.to_string());
+        rest.next();
+    }
+    if let Some(region) = rest.next_if(|s| {
+        (s.len() == 2 && s.chars().all(|c| c.is_ascii_uppercase()))
+            || (s.len() == 3 && s.chars().all(|c| c.is_ascii_digit()))
+    }) {
+        canonical.push(region.to_string());
+    }
+    if rest.next().is_some() || canonical.len() == 1 {
+        return None;
+    }
+    Some(canonical.join(&separator.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical() {
+        assert_eq!(canonical("En-US").as_deref(), Some("en-US"));
+        assert_eq!(canonical("pt_BR").as_deref(), Some("pt_BR"));
+        assert_eq!(canonical("ZH-hant-TW").as_deref(), Some("zh-Hant-TW"));
+        assert_eq!(canonical("sr-Latn").as_deref(), Some("sr-Latn"));
+        assert_eq!(canonical("es-419").as_deref(), Some("es-419"));
+        assert_eq!(canonical("De"), None);
+        assert_eq!(canonical("my-component"), None);
+        assert_eq!(canonical("to-do"), None);
+        assert_eq!(canonical("My-Page-UI"), None);
+        assert_eq!(canonical("Xx-US"), None);
+        assert_eq!(canonical("en-US-Extra"), None);
+    }
+}
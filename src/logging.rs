@@ -0,0 +1,167 @@
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+use std::fmt::Debug;
+use std::fs::OpenOptions;
+use std::io::IsTerminal;
+use std::path::Path;
+use std::sync::Mutex;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::{filter_fn, Targets};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::fmt::{self, format::DefaultFields, format::Format};
+use tracing_subscriber::layer::Context as LayerContext;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{EnvFilter, Layer, Registry};
+
+/// The target of the per-file progress lines a run prints, like "Renamed:
+/// ..."
+pub const PROGRESS: &str = "kebabify::progress";
+
+/// How log events are written to stderr
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    /// One readable line per event
+    #[default]
+    Text,
+    /// One JSON object per event, with its fields and spans
+    Json,
+}
+
+/// The filter to log with: `--log-level` wins over `-v`, which wins over
+/// `RUST_LOG`. `None` means nothing asked for logging, so none is set up
+/// and the output is exactly what it's always been.
+fn directives(
+    level: Option<&str>,
+    verbose: u8,
+    env: Option<&str>,
+) -> Option<String> {
+    let from_verbose = match verbose {
+        0 => None,
+        1 => Some("info"),
+        2 => Some("debug"),
+        _ => Some("trace"),
+    };
+    match (level, from_verbose, env) {
+        (Some(level), _, _) => Some(format!("kebabify={level}")),
+        (None, Some(level), _) => Some(format!("kebabify={level}")),
+        (None, None, Some(env)) if !env.is_empty() => Some(env.to_string()),
+        _ => None,
+    }
+}
+
+/// Prints progress lines on stdout, as they've always been printed, unless
+/// `--log-level` is above `info`. Sends other `tracing` events to stderr at
+/// the level asked for, if any, and every event down to `debug`, progress
+/// included, to `log_file`, if given, whatever the terminal shows.
+pub fn init(
+    level: Option<&str>,
+    verbose: u8,
+    format: LogFormat,
+    log_file: Option<&Path>,
+) -> Result<()> {
+    let env = std::env::var(EnvFilter::DEFAULT_ENV).ok();
+    let directives = directives(level, verbose, env.as_deref());
+
+    let mut layers = Vec::new();
+    let progress = match level {
+        Some(level) => EnvFilter::try_new(format!("kebabify={level}"))
+            .map_err(|err| anyhow!("Invalid log level {:?}: {}", level, err))?,
+        None => EnvFilter::new("kebabify=info"),
+    };
+    layers.push(
+        Progress
+            .with_filter(progress)
+            .with_filter(filter_fn(|meta| meta.target() == PROGRESS))
+            .boxed(),
+    );
+    if let Some(directives) = directives {
+        let filter = EnvFilter::try_new(&directives).map_err(|err| {
+            anyhow!("Invalid log level {:?}: {}", directives, err)
+        })?;
+        let layer = fmt::layer()
+            .with_writer(std::io::stderr)
+            .with_ansi(std::io::stderr().is_terminal())
+            .with_target(false);
+        layers.push(
+            formatted(layer, format)
+                .with_filter(filter)
+                // Already on stdout
+                .with_filter(filter_fn(|meta| meta.target() != PROGRESS))
+                .boxed(),
+        );
+    }
+    if let Some(path) = log_file {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| {
+                format!("Failed to open log file: {}", path.display())
+            })?;
+        let layer = fmt::layer()
+            .with_writer(Mutex::new(file))
+            .with_ansi(false)
+            .with_target(false);
+        let filter = Targets::new().with_target("kebabify", Level::DEBUG);
+        layers.push(formatted(layer, format).with_filter(filter).boxed());
+    }
+    tracing_subscriber::registry().with(layers).init();
+    Ok(())
+}
+
+/// Prints each progress event's message on a line of its own
+struct Progress;
+
+impl<S: Subscriber> Layer<S> for Progress {
+    fn on_event(&self, event: &Event<'_>, _ctx: LayerContext<'_, S>) {
+        let mut message = Message(String::new());
+        event.record(&mut message);
+        println!("{}", message.0);
+    }
+}
+
+/// The `message` field of an event
+struct Message(String);
+
+impl Visit for Message {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == "message" {
+            self.0 = format!("{:?}", value);
+        }
+    }
+}
+
+/// `layer` writing in `format`
+fn formatted<W>(
+    layer: fmt::Layer<Registry, DefaultFields, Format, W>,
+    format: LogFormat,
+) -> Box<dyn Layer<Registry> + Send + Sync>
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    match format {
+        LogFormat::Text => layer.boxed(),
+        LogFormat::Json => layer.json().with_current_span(true).boxed(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_directives() {
+        assert_eq!(directives(None, 0, None), None);
+        assert_eq!(directives(None, 0, Some("")), None);
+        assert_eq!(directives(None, 2, None), Some("kebabify=debug".into()));
+        assert_eq!(
+            directives(Some("warn"), 3, Some("trace")),
+            Some("kebabify=warn".into())
+        );
+        assert_eq!(
+            directives(None, 0, Some("kebabify::plan=trace")),
+            Some("kebabify::plan=trace".into())
+        );
+    }
+}
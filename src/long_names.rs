@@ -0,0 +1,137 @@
+use anyhow::{bail, Result};
+
+/// Past these, a converted name is warned about: long kebab names run into
+/// path limits and are hard to read
+pub const DEFAULT_MAX_LENGTH: usize = 60;
+pub const DEFAULT_MAX_WORDS: usize = 6;
+
+/// Limits on converted names, and the abbreviations that shorten the names
+/// that break them
+#[derive(Debug, Clone)]
+pub struct LongNames {
+    pub max_length: usize,
+    pub max_words: usize,
+    /// `(word, abbreviation)` pairs from `--abbreviate WORD=ABBR`
+    abbreviations: Vec<(String, String)>,
+}
+
+impl Default for LongNames {
+    fn default() -> Self {
+        Self {
+            max_length: DEFAULT_MAX_LENGTH,
+            max_words: DEFAULT_MAX_WORDS,
+            abbreviations: Vec::new(),
+        }
+    }
+}
+
+impl LongNames {
+    pub fn new(
+        max_length: usize,
+        max_words: usize,
+        abbreviations: &[String],
+    ) -> Result<Self> {
+        let mut pairs = Vec::new();
+        for spec in abbreviations {
+            let Some((word, short)) = spec.split_once('=') else {
+                bail!("Expected WORD=ABBR, got: {}", spec);
+            };
+            let is_word = |s: &str| {
+                !s.is_empty()
+                    && s.chars().all(|c| c.is_lowercase() || c.is_ascii_digit())
+            };
+            if !is_word(word) || !is_word(short) {
+                bail!(
+                    "Invalid --abbreviate {:?}: both sides must be one \
+                     lowercase word",
+                    spec
+                );
+            }
+            pairs.push((word.to_string(), short.to_string()));
+        }
+        Ok(Self {
+            max_length,
+            max_words,
+            abbreviations: pairs,
+        })
+    }
+
+    /// What makes the converted `name` too long, if anything. Words are
+    /// counted in the part before the first dot, so `.test.ts` is free.
+    pub fn problem(&self, name: &str) -> Option<String> {
+        let length = name.chars().count();
+        if length > self.max_length {
+            return Some(format!(
+                "'{}' is {} characters long (over {})",
+                name, length, self.max_length
+            ));
+        }
+        let stem = name.split('.').next().unwrap_or(name);
+        let words = stem
+            .split(['-', '_', ' '])
+            .filter(|w| !w.is_empty())
+            .count();
+        (words > self.max_words).then(|| {
+            format!("'{}' has {} words (over {})", name, words, self.max_words)
+        })
+    }
+
+    /// `name` with its words abbreviated, if it's too long and any of them
+    /// have an abbreviation
+    pub fn abbreviate(&self, name: &str) -> Option<String> {
+        if self.abbreviations.is_empty() || self.problem(name).is_none() {
+            return None;
+        }
+        let mut short = String::with_capacity(name.len());
+        let mut word = String::new();
+        let flush = |word: &mut String, short: &mut String| {
+            let found = self.abbreviations.iter().find(|(w, _)| w == word);
+            short.push_str(found.map_or(word.as_str(), |(_, a)| a.as_str()));
+            word.clear();
+        };
+        for c in name.chars() {
+            if matches!(c, '-' | '_' | '.') {
+                flush(&mut word, &mut short);
+                short.push(c);
+            } else {
+                word.push(c);
+            }
+        }
+        flush(&mut word, &mut short);
+        (short != name).then_some(short)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_long_names() -> Result<()> {
+        let limits = LongNames::new(
+            40,
+            6,
+            &["configuration=config".into(), "management=mgmt".into()],
+        )?;
+        assert_eq!(limits.problem("user-card.test.ts"), None);
+        assert_eq!(
+            limits.problem("abstract-user-account-configuration-manager.ts"),
+            Some(
+                "'abstract-user-account-configuration-manager.ts' is 46 \
+                 characters long (over 40)"
+                    .into()
+            )
+        );
+        assert_eq!(
+            limits.problem("a-b-c-d-e-f-g.ts"),
+            Some("'a-b-c-d-e-f-g.ts' has 7 words (over 6)".into())
+        );
+        assert_eq!(
+            limits.abbreviate("abstract-user-account-configuration-manager.ts"),
+            Some("abstract-user-account-config-manager.ts".into())
+        );
+        assert_eq!(limits.abbreviate("configuration.ts"), None);
+        assert!(LongNames::new(60, 6, &["Config".into()]).is_err());
+        Ok(())
+    }
+}
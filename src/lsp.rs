@@ -0,0 +1,189 @@
+use crate::plan::Plan;
+use crate::report::Report;
+use crate::{encoding, import_regex, resolve, Options};
+use anyhow::{Context, Result};
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// What `initialize` advertises: we want to hear about every file rename
+pub fn capabilities() -> Value {
+    json!({
+        "capabilities": {
+            "workspace": {
+                "fileOperations": {
+                    "willRename": {
+                        "filters": [{ "pattern": { "glob": "**/*" } }]
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Reads the `files: [{ oldUri, newUri }]` of a `workspace/willRenameFiles`
+/// request into a plan. Returns `None` if the params are malformed.
+pub fn rename_plan(params: &Value) -> Option<Plan> {
+    let mut plan = Plan::default();
+    for file in params.get("files")?.as_array()? {
+        let from = uri_to_path(file.get("oldUri")?.as_str()?)?;
+        let to = uri_to_path(file.get("newUri")?.as_str()?)?;
+        plan.push(from, to);
+    }
+    Some(plan)
+}
+
+/// Builds the `WorkspaceEdit` that keeps every relative import under `dir`
+/// pointing at the right file once `plan` is applied. Edits are against the
+/// files as they are now, since the editor applies them before renaming.
+pub fn workspace_edit(
+    dir: &Path,
+    plan: &Plan,
+    opts: &Options,
+    report: &mut Report,
+) -> Result<Value> {
+    // URIs are absolute, so walk from an absolute root to compare paths
+    let dir = std::path::absolute(dir)
+        .with_context(|| format!("Invalid directory: {}", dir.display()))?;
+    let mut changes = Map::new();
+
+    for entry in crate::source_files(&dir, opts, report)? {
+        let path = entry.path();
+        let Some((content, _)) =
+            fs::read(path).ok().and_then(|b| encoding::decode(&b))
+        else {
+            continue;
+        };
+        let edits = import_edits(path, &content, plan);
+        if !edits.is_empty() {
+            changes.insert(path_to_uri(path), Value::Array(edits));
+        }
+    }
+    Ok(json!({ "changes": changes }))
+}
+
+/// One `TextEdit` per import in `content` that needs to change
+fn import_edits(path: &Path, content: &str, plan: &Plan) -> Vec<Value> {
+    let old_dir = path.parent().unwrap_or(Path::new("."));
+    let new_path = plan.target_of(path).unwrap_or(path.to_path_buf());
+    let new_dir = new_path.parent().unwrap_or(Path::new("."));
+
+    import_regex()
+        .captures_iter(content)
+        .filter_map(|caps| {
+            let spec = caps.get(2)?;
+            let new_spec = resolve::rewrite_specifier(
+                spec.as_str(),
+                old_dir,
+                new_dir,
+                plan,
+            )?;
+            Some(json!({
+                "range": {
+                    "start": position(content, spec.start()),
+                    "end": position(content, spec.end()),
+                },
+                "newText": new_spec,
+            }))
+        })
+        .collect()
+}
+
+/// LSP position of a byte offset. Characters are counted in UTF-16 code
+/// units, as the protocol requires.
+fn position(content: &str, offset: usize) -> Value {
+    let before = &content[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    json!({
+        "line": before.matches('\n').count(),
+        "character": before[line_start..].encode_utf16().count(),
+    })
+}
+
+/// Decodes a `file://` URI. Other schemes aren't files we can rename.
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file://")?;
+    let mut bytes = Vec::with_capacity(rest.len());
+    let mut input = rest.bytes();
+    while let Some(b) = input.next() {
+        if b == b'%' {
+            let hex = [input.next()?, input.next()?];
+            let hex = std::str::from_utf8(&hex).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+        } else {
+            bytes.push(b);
+        }
+    }
+    let path = String::from_utf8(bytes).ok()?;
+
+    // `file:///C:/src` on Windows
+    if cfg!(windows) {
+        if let Some(drive_path) = path.strip_prefix('/') {
+            if drive_path.as_bytes().get(1) == Some(&b':') {
+                return Some(PathBuf::from(drive_path));
+            }
+        }
+    }
+    Some(PathBuf::from(path))
+}
+
+fn path_to_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut uri = String::from("file://");
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+    for b in path.bytes() {
+        match b {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'_'
+            | b'.'
+            | b'~'
+            | b'/'
+            | b':' => uri.push(b as char),
+            _ => uri.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_import_edits() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::write(root.join("UserCard.ts"), "")?;
+
+        let mut plan = Plan::default();
+        plan.push(root.join("UserCard.ts"), root.join("user-card.ts"));
+        let content = "// é\n/* 😀 */ import c from './UserCard';";
+        let edits = import_edits(&root.join("main.ts"), content, &plan);
+
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0]["newText"], "./user-card");
+        assert_eq!(
+            edits[0]["range"]["start"],
+            json!({ "line": 1, "character": 24 })
+        );
+        assert_eq!(
+            edits[0]["range"]["end"],
+            json!({ "line": 1, "character": 34 })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_uris_round_trip() {
+        let path = Path::new("/src/My App/Button.ts");
+        assert_eq!(path_to_uri(path), "file:///src/My%20App/Button.ts");
+        assert_eq!(uri_to_path(&path_to_uri(path)).as_deref(), Some(path));
+        assert_eq!(uri_to_path("untitled:Untitled-1"), None);
+    }
+}
@@ -1,14 +1,117 @@
-use anyhow::{Context, Result};
-use clap::Parser;
+use anyhow::{bail, Context, Result};
+use clap::{ArgGroup, CommandFactory, Parser, Subcommand, ValueEnum};
 use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::ffi::{OsStr, OsString};
 use std::fs;
 use std::path::{Path, PathBuf};
-use walkdir::WalkDir;
+use walkdir::{DirEntry, WalkDir};
+
+mod acronyms;
+mod aliases;
+mod ambiguity;
+mod analyze;
+mod brands;
+mod cancel;
+mod codeowners;
+mod conventions;
+mod cpp;
+mod csharp;
+mod css_classes;
+mod default_imports;
+mod describe;
+mod directives;
+mod display;
+mod docker;
+mod docs;
+mod doctor;
+mod e2e;
+mod elixir;
+mod encoding;
+mod filter;
+mod fs_util;
+mod generated;
+mod git_diff;
+mod go;
+mod graphql;
+mod helm;
+mod history;
+mod hooks;
+mod i18n;
+mod idempotent;
+mod installed;
+mod journal;
+mod jvm;
+mod lint;
+mod locale;
+mod logging;
+mod long_names;
+mod lsp;
+mod make;
+mod mapping;
+mod messages;
+mod migration;
+mod monorepo;
+mod orphans;
+mod package;
+mod path_cache;
+mod pending;
+mod php;
+mod plan;
+mod prettier;
+mod preview;
+mod probe;
+mod proto;
+mod python;
+mod redirects;
+mod report;
+mod resolve;
+mod response_file;
+mod resume;
+mod ruby;
+mod rules;
+mod rust;
+mod scanner;
+mod schema;
+mod serve;
+mod sfc;
+mod shell;
+mod stats;
+mod string_refs;
+mod tailwind;
+mod temp_names;
+mod terraform;
+mod tsconfig;
+mod tui;
+mod validators;
+mod vendored;
+mod workspace;
+mod write_back;
+mod xml;
+
+use default_imports::{DefaultImports, ImportCase};
+use describe::DescribeBlocks;
+use display::PathDisplay;
+use filter::PathFilter;
+use journal::ImportMode;
+use logging::LogFormat;
+use long_names::LongNames;
+use messages::{text, Lang, Msg};
+use plan::Plan;
+use report::{Aborted, Report, WarningKind};
+use scanner::ScanOptions;
+use temp_names::{Cleaned, TempNames};
+use vendored::Vendored;
+use write_back::Fsync;
 
 /// CLI tool to convert PascalCase filenames to kebab-case
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Clone)]
 #[command(author, version, about)]
+#[command(group(ArgGroup::new("verified_refs").multiple(true)))]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// The directory path to process
     #[arg(default_value = ".")]
     path: PathBuf,
@@ -20,70 +123,1938 @@ struct Args {
     /// Process both filenames and imports
     #[arg(long, short = 'a', conflicts_with = "imports")]
     all: bool,
+
+    /// Apply renames from an `old_path,new_path` CSV/TSV file and fix imports
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["imports", "all"])]
+    mapping: Option<PathBuf>,
+
+    /// Only process files matching this glob (repeatable)
+    #[arg(long, value_name = "GLOB", global = true)]
+    include: Vec<String>,
+
+    /// Skip files and directories matching this glob (repeatable)
+    #[arg(long, value_name = "GLOB", global = true)]
+    exclude: Vec<String>,
+
+    /// Only process paths CODEOWNERS gives to this owner, like org/frontend
+    /// (the leading @ is optional)
+    #[arg(long, value_name = "OWNER", global = true)]
+    owned_by: Option<String>,
+
+    /// Process vendored directories matching this glob, which are skipped
+    /// by default: `vendor`, `third_party`, `bower_components`, Git
+    /// submodules and nested repositories (repeatable)
+    #[arg(long, value_name = "GLOB", global = true)]
+    include_vendored: Vec<String>,
+
+    /// Show paths in output relative to this directory [default: repo root]
+    #[arg(long, value_name = "DIR", global = true)]
+    relative_to: Option<PathBuf>,
+
+    /// Stop at the first failed rename or edit instead of reporting at the end
+    #[arg(long)]
+    fail_fast: bool,
+
+    /// Convert names that aren't valid UTF-8, keeping undecodable bytes as-is
+    #[arg(long)]
+    convert_non_utf8: bool,
+
+    /// Treat unreadable files and directories as errors instead of warnings
+    #[arg(long, global = true)]
+    strict: bool,
+
+    /// Exit with code 4 if the run had any warnings, e.g. imports left
+    /// pointing at missing files with --report-orphans
+    #[arg(long, global = true)]
+    error_on_warnings: bool,
+
+    /// Refuse to apply a plan with more than N renames unless it's
+    /// confirmed on the terminal [default: 500 without --yes]
+    #[arg(long, value_name = "N")]
+    max_changes: Option<usize>,
+
+    /// Apply plans of any size, and names converted by a guess, without
+    /// asking
+    #[arg(long, short = 'y')]
+    yes: bool,
+
+    /// Log what's being done to stderr: once for each step, twice for
+    /// each decision, three times for everything
+    #[arg(long, short = 'v', action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
+
+    /// Log at this level, overriding -v and RUST_LOG
+    #[arg(
+        long,
+        value_name = "LEVEL",
+        value_parser = ["off", "error", "warn", "info", "debug", "trace"],
+        global = true
+    )]
+    log_level: Option<String>,
+
+    /// Append a detailed log of the run to FILE, down to each skipped file
+    /// and why, however little the terminal shows
+    #[arg(long, value_name = "FILE", global = true)]
+    log_file: Option<PathBuf>,
+
+    /// The language of prompts, progress and the summary. JSON output,
+    /// the journal and logs are always in English.
+    #[arg(long, value_enum, default_value_t = Lang::En, global = true)]
+    lang: Lang,
+
+    /// How log lines are written
+    #[arg(long, value_enum, default_value_t = LogFormat::Text, global = true)]
+    log_format: LogFormat,
+
+    /// How runs of capitals like `XMLHTTPRequest` split into words
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = AcronymStyle::Dictionary,
+        global = true
+    )]
+    acronym_style: AcronymStyle,
+
+    /// Where numbers with a one-letter unit, like `Vector3D`, go
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = NumberStyle::Attach,
+        global = true
+    )]
+    number_style: NumberStyle,
+
+    /// The case C and C++ files are named in
+    #[arg(long, value_enum, default_value_t = NameStyle::Snake, global = true)]
+    c_style: NameStyle,
+
+    /// Rename Java and Kotlin files and package directories, which keep
+    /// their names by default because classes and packages are named
+    /// after them
+    #[arg(long, global = true)]
+    force_java: bool,
+
+    /// A brand or compound name to convert as one word, like `ChatGPT`,
+    /// or to a form of its own, like `NextJS=nextjs` (repeatable). Takes
+    /// the place of a built-in one with the same name.
+    #[arg(long, value_name = "NAME[=FORM]", global = true)]
+    brand: Vec<String>,
+
+    /// Don't use the built-in brand names (`GitHub`, `iOS`, `TypeScript`...)
+    #[arg(long, global = true)]
+    no_builtin_brands: bool,
+
+    /// Warn about converted names longer than this, in characters
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = long_names::DEFAULT_MAX_LENGTH,
+        global = true
+    )]
+    warn_length: usize,
+
+    /// Warn about converted names with more words than this
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = long_names::DEFAULT_MAX_WORDS,
+        global = true
+    )]
+    warn_words: usize,
+
+    /// Abbreviate WORD in names over the --warn-length or --warn-words
+    /// limits, e.g. `configuration=config` (repeatable)
+    #[arg(long, value_name = "WORD=ABBR", global = true)]
+    abbreviate: Vec<String>,
+
+    /// Seed for the temporary names used by two-step renames and file
+    /// writes, to reproduce a run exactly; random by default
+    #[arg(long, value_name = "N")]
+    temp_seed: Option<u64>,
+
+    /// How many times to retry a rename or write blocked by a locked file
+    #[arg(long, value_name = "N", default_value_t = 5)]
+    retries: u32,
+
+    /// How far rewritten files are flushed to disk
+    #[arg(long, value_enum, default_value_t = Fsync::None)]
+    fsync: Fsync,
+
+    /// Don't rename symlinks themselves, only update what they point at
+    #[arg(long)]
+    keep_link_names: bool,
+
+    /// Walk into symlinked directories. Links leading outside the directory
+    /// being converted are skipped, so nothing above it is ever renamed.
+    #[arg(long, global = true)]
+    follow: bool,
+
+    /// How to write path separators in rewritten imports
+    #[arg(long, value_enum, default_value_t = Separators::Keep)]
+    separators: Separators,
+
+    /// Whether relative imports name their file's extension
+    #[arg(long, value_enum, default_value_t = ImportExtensions::Keep)]
+    import_extensions: ImportExtensions,
+
+    /// Add the extensions native ESM and NodeNext resolution need to
+    /// relative imports, same as --import-extensions esm
+    #[arg(long, conflicts_with = "import_extensions")]
+    add_extensions: bool,
+
+    /// Write relative imports of a directory's index file, like
+    /// "./lib/index", as the directory, "./lib"
+    #[arg(long)]
+    collapse_index: bool,
+
+    /// Write relative imports by their shortest path, starting with "./"
+    /// or "../", so "../lib/button" from inside lib/ becomes "./button"
+    #[arg(long)]
+    prefer_dot_slash: bool,
+
+    /// Rewrite relative imports that climb out of their directory, like
+    /// "../../components/UserCard", through the tsconfig.json or
+    /// jsconfig.json path alias for where they lead
+    #[arg(long)]
+    prefer_aliases: bool,
+
+    /// An alias for --prefer-aliases to use besides the tsconfig.json
+    /// ones, like "@components=src/components" (repeatable, relative to
+    /// the target directory)
+    #[arg(long, value_name = "PREFIX=DIR", requires = "prefer_aliases")]
+    alias: Vec<String>,
+
+    /// Process the workspace's packages, from pnpm-workspace.yaml or the
+    /// "workspaces" of package.json, as one run with one rename plan
+    #[arg(long)]
+    workspace: bool,
+
+    /// Like --workspace, with the packages listed in FILE, one directory or
+    /// glob per line, relative to FILE
+    #[arg(long, value_name = "FILE")]
+    workspace_file: Option<PathBuf>,
+
+    /// Like --workspace, limited to the Nx projects and workspace packages
+    /// with files changed since the git revision REF
+    #[arg(long, value_name = "REF", conflicts_with = "workspace_file")]
+    affected: Option<String>,
+
+    /// What to do with imports in comments and commented-out code
+    #[arg(long, value_enum, default_value_t = Comments::Rewrite)]
+    comments: Comments,
+
+    /// What to do with describe('UserCard', ...) blocks named after a
+    /// renamed test file
+    #[arg(long, value_enum, default_value_t = DescribeBlocks::Keep)]
+    describe_blocks: DescribeBlocks,
+
+    /// What to do with the names files that are renamed are default
+    /// imported under, when the file's default export is named after it
+    #[arg(long, value_enum, default_value_t = DefaultImports::Keep)]
+    default_imports: DefaultImports,
+
+    /// The case --default-imports names them in
+    #[arg(long, value_enum, default_value_t = ImportCase::Pascal)]
+    import_case: ImportCase,
+
+    /// Write the imports kebabify rewrites with the quotes the project's
+    /// Prettier config asks for, instead of the quotes they had
+    #[arg(long)]
+    respect_prettier: bool,
+
+    /// Also rewrite verified relative paths inside template literals
+    /// (styled-components, emotion)
+    #[arg(long)]
+    template_literals: bool,
+
+    /// Also rewrite quoted strings in any text file that resolve to a
+    /// renamed file or directory
+    #[arg(long, group = "verified_refs")]
+    string_refs: bool,
+
+    /// Also rewrite paths mentioned in comments, like "see
+    /// ./Utils/DateHelpers.ts", that resolve to a renamed file or directory
+    #[arg(long, group = "verified_refs")]
+    comment_paths: bool,
+
+    /// Also rewrite src, href, poster and action attributes in lit-html
+    /// and hyperHTML templates that resolve to a renamed file or directory
+    #[arg(long, group = "verified_refs")]
+    html_templates: bool,
+
+    /// An attribute whose relative paths are updated in .xml, .mjml and
+    /// XSLT files (repeatable; replaces the defaults)
+    #[arg(
+        long,
+        value_name = "NAME",
+        default_values_t = xml::DEFAULT_ATTRIBUTES.map(String::from)
+    )]
+    xml_attribute: Vec<String>,
+
+    /// A directory that string, comment and template references like
+    /// "/images/logo.png" are relative to, such as a web app's public/
+    /// (repeatable, relative to the target directory)
+    #[arg(long, value_name = "DIR", requires = "verified_refs")]
+    ref_root: Vec<PathBuf>,
+
+    /// Run this shell command after a successful run (repeatable). The
+    /// rename map is in the CSV file named by $KEBABIFY_RENAMES.
+    #[arg(long, value_name = "COMMAND")]
+    exec: Vec<String>,
+
+    /// Before renaming, run this shell command with the plan as CSV on its
+    /// stdin; each path it prints keeps its name (repeatable)
+    #[arg(long, value_name = "COMMAND")]
+    veto_cmd: Vec<String>,
+
+    /// Before renaming, keep the names of files another process has open,
+    /// according to lsof
+    #[arg(long)]
+    veto_open_files: bool,
+
+    /// Check the result with this shell command (e.g. "tsc --noEmit") and
+    /// offer to roll everything back if it fails
+    #[arg(long, value_name = "COMMAND")]
+    verify_cmd: Option<String>,
+
+    /// Roll back without asking when --verify-cmd fails
+    #[arg(long, requires = "verify_cmd")]
+    rollback_on_fail: bool,
+
+    /// Run the conversion twice in memory and report anything the second
+    /// run would change, without touching the disk
+    #[arg(long, conflicts_with = "mapping")]
+    check_idempotent: bool,
+
+    /// Show the renames and reference changes a run would make, with every
+    /// path as it will be once renamed, without touching the disk
+    #[arg(long, conflicts_with_all = ["mapping", "check_idempotent"])]
+    dry_run: bool,
+
+    /// Also convert CSS class names to kebab-case, in stylesheets and
+    /// everywhere they're used, keeping any that can't all be renamed
+    #[arg(long)]
+    css_classes: bool,
+
+    /// Count the files in each case, the directories with the most names
+    /// to convert and the references to update, without planning renames
+    #[arg(
+        long,
+        conflicts_with_all = ["mapping", "check_idempotent", "dry_run"]
+    )]
+    analyze: bool,
+
+    /// After the run, write redirects from the old to the new URL of every
+    /// renamed SvelteKit, Next.js or Nuxt page
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    redirects: Option<redirects::RedirectFormat>,
+
+    /// File to write redirects to, relative to the target directory
+    /// [default: _redirects, vercel.json or redirects.conf]
+    #[arg(long, value_name = "FILE", requires = "redirects")]
+    redirects_file: Option<PathBuf>,
+
+    /// Text that marks a generated file, whose references and name are left
+    /// alone (repeatable; replaces the defaults)
+    #[arg(
+        long,
+        value_name = "TEXT",
+        default_values_t = ["@generated".to_string(), "DO NOT EDIT".to_string()]
+    )]
+    generated_marker: Vec<String>,
+
+    /// Process generated files like any other
+    #[arg(long)]
+    include_generated: bool,
+
+    /// After the run, report references anywhere in the tree that point at
+    /// files that don't exist
+    #[arg(long)]
+    report_orphans: bool,
+
+    /// What to do when a file's new name is taken by a file that keeps its
+    /// name
+    #[arg(long, value_enum, default_value_t = OnConflict::Number)]
+    on_conflict: OnConflict,
+
+    /// Delete the files --on-conflict overwrite replaces, instead of moving
+    /// them to the trash
+    #[arg(long)]
+    no_trash: bool,
+}
+
+/// What to do when a new name is taken
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum OnConflict {
+    /// Use the first free numbered name, like `user-card-2.ts`
+    #[default]
+    Number,
+    /// Replace the file that has the name, moving it to the trash
+    Overwrite,
+}
+
+/// How a run of capitals like `HTTPSProxy` splits into words
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum AcronymStyle {
+    /// Into the known acronyms, longest first (`https-proxy`); runs that
+    /// can't be split that way stay one word
+    #[default]
+    Dictionary,
+    /// Into the known acronyms, shortest first, with any capitals left over
+    /// as words of their own (`http-s-proxy`)
+    First,
+    /// Only at its last capital, which starts the next word
+    /// (`XMLHTTPRequest` becomes `xmlhttp-request`)
+    Last,
 }
 
-fn main() -> Result<()> {
-    let args = Args::parse();
+/// Where a number with a one-letter unit, like the `3D` of `Vector3D`,
+/// goes. Numbers without one stay with the word before them either way
+/// (`S3Client` becomes `s3-client`).
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum NumberStyle {
+    /// With the word before it (`vector3d`)
+    #[default]
+    Attach,
+    /// As a word of its own (`vector-3d`)
+    Separate,
+}
+
+/// Separator style for rewritten import paths
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Separators {
+    /// Keep whatever each import already uses
+    #[default]
+    Keep,
+    /// Always use `/`
+    Slash,
+    /// Always use escaped backslashes
+    Backslash,
+}
+
+/// Whether rewritten imports name their file's extension
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum ImportExtensions {
+    /// Leave each import as it's written
+    #[default]
+    Keep,
+    /// Add the extension to imports that leave it out
+    Always,
+    /// Leave the extension out of script imports that resolve without it
+    Never,
+    /// Name the file native ESM loads: the extension a TypeScript file
+    /// compiles to (`./util.js` for `util.ts`) and a directory's index file
+    Esm,
+}
 
-    // Process imports first to ensure paths are still valid
-    if args.all || args.imports {
-        process_imports(&args.path)?;
+/// What to do with imports inside comments and commented-out code
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Comments {
+    /// Rewrite them like any other import
+    #[default]
+    Rewrite,
+    /// Leave them as they are
+    Skip,
+    /// Leave them as they are and warn about each one that's out of date
+    Flag,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum Command {
+    /// Report case collisions and mis-cased imports without renaming anything
+    Doctor {
+        /// The directory path to check
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+    /// Report imports and other references that point at files that don't
+    /// exist, without renaming anything
+    Orphans {
+        /// The directory path to check
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+    /// Report names that aren't kebab-case or break the naming rules, and
+    /// references that aren't converted or don't resolve, without changing
+    /// anything
+    Check {
+        /// The directory path to check
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Longest allowed file or directory name, in characters
+        #[arg(long, value_name = "N")]
+        max_name_length: Option<usize>,
+
+        /// Characters no name may contain
+        #[arg(long, value_name = "CHARS", default_value = "")]
+        forbid_chars: String,
+
+        /// Files in directories matching DIR must start with TEXT
+        /// (repeatable)
+        #[arg(long, value_name = "DIR=TEXT")]
+        require_prefix: Vec<String>,
+
+        /// Files in directories matching DIR must end with TEXT before
+        /// their extension (repeatable)
+        #[arg(long, value_name = "DIR=TEXT")]
+        require_suffix: Vec<String>,
+
+        /// Only check names, not the references in source files
+        #[arg(long, conflicts_with = "imports_only")]
+        names_only: bool,
+
+        /// Only check that references are converted and point at a file,
+        /// not the names themselves
+        #[arg(long)]
+        imports_only: bool,
+    },
+    /// Review the rename plan interactively and apply the renames you keep
+    Tui {
+        /// The directory path to process
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+    /// Queue a rename for the next run, which also fixes its imports
+    Queue {
+        /// Current path, relative to the project directory
+        from: PathBuf,
+        /// New path, relative to the project directory
+        to: PathBuf,
+        /// The project directory
+        #[arg(long, default_value = ".")]
+        dir: PathBuf,
+    },
+    /// Report the files converted or otherwise renamed since a Git
+    /// revision, and how many names are still to convert then and now
+    Diff {
+        /// The revision to compare with, e.g. `main` or `HEAD~10`
+        #[arg(value_name = "REF")]
+        git_ref: String,
+        /// The directory to compare, inside a Git repository
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+    /// List the renames of every run so far, from the history file
+    History {
+        /// The directory the runs processed
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+    /// Show the stats counted over every run, or turn counting them on or
+    /// off. They're kept in .kebabify-stats.json and never sent anywhere.
+    Stats {
+        /// The directory the runs processed
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Count each run from now on
+        #[arg(long, conflicts_with = "disable")]
+        enable: bool,
+
+        /// Stop counting runs, keeping the stats so far
+        #[arg(long)]
+        disable: bool,
+    },
+    /// Summarize the last run's renames by package, with what's left to fix
+    /// by hand
+    Report {
+        /// The directory the runs processed
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Format the summary as Markdown, to paste into a pull request
+        #[arg(long)]
+        markdown: bool,
+
+        /// Summarize every run in the history, not just the last one
+        #[arg(long)]
+        all: bool,
+    },
+    /// Finish a run that was interrupted, using the journal it left behind
+    Resume {
+        /// The directory the interrupted run was processing
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+    /// Print a shell completion script
+    Completions {
+        /// The shell to generate completions for
+        shell: clap_complete::Shell,
+    },
+    /// Print the man page
+    Man,
+    /// Report how the filesystem under a directory treats names: case,
+    /// length limits, Unicode normalization and renames onto existing files
+    Probe {
+        /// A directory on the filesystem to probe
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+    /// Restore or delete the temporary files a crashed run left behind.
+    /// Don't run it while kebabify is working on the same directory.
+    Cleanup {
+        /// The directory the crashed run was processing
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+    /// Answer plan/apply/check requests as JSON-RPC over stdin and stdout
+    Serve {
+        /// The project directory to serve
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+}
+
+impl Args {
+    /// Whether the command renames files, and so needs the filesystem
+    /// probed first
+    fn renames_files(&self) -> bool {
+        !self.dry_run
+            && !self.check_idempotent
+            && !self.analyze
+            && matches!(
+                self.command,
+                None | Some(
+                    Command::Tui { .. }
+                        | Command::Resume { .. }
+                        | Command::Serve { .. }
+                )
+            )
     }
 
-    // Then rename files and directories
-    if args.all || !args.imports {
-        process_directory(&args.path)?;
+    fn is_resume(&self) -> bool {
+        matches!(self.command, Some(Command::Resume { .. }))
     }
 
-    Ok(())
+    /// The directory the run operates on
+    fn root(&self) -> &Path {
+        match &self.command {
+            Some(
+                Command::Doctor { path }
+                | Command::Orphans { path }
+                | Command::History { path }
+                | Command::Diff { path, .. }
+                | Command::Stats { path, .. }
+                | Command::Report { path, .. }
+                | Command::Check { path, .. }
+                | Command::Resume { path }
+                | Command::Serve { path }
+                | Command::Tui { path },
+            ) => path,
+            _ => &self.path,
+        }
+    }
 }
 
-fn process_directory(dir: &Path) -> Result<()> {
-    // Collect paths first to avoid renaming issues during iteration
-    let entries: Vec<_> = WalkDir::new(dir)
-        .follow_links(true)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .collect();
+/// Settings shared by every pass, derived from the command line
+#[derive(Debug, Default)]
+struct Options {
+    filter: PathFilter,
+    vendored: Vendored,
+    display: PathDisplay,
+    convert_non_utf8: bool,
+    retries: u32,
+    fsync: Fsync,
+    keep_link_names: bool,
+    /// Whether symlinked directories are walked into
+    follow_links: bool,
+    /// The most renames a plan may have without confirmation
+    max_changes: Option<usize>,
+    /// Whether to go ahead without asking, from `--yes`
+    yes: bool,
+    /// What the filesystem being renamed on can do
+    fs: probe::Capabilities,
+    temp: TempNames,
+    on_conflict: OnConflict,
+    /// Whether files replaced by `OnConflict::Overwrite` go to the trash
+    /// rather than being deleted
+    trash: bool,
+    /// Markers of generated files to leave alone; empty to process them
+    generated_markers: Vec<String>,
+    long_names: LongNames,
+    /// The packages to process as one workspace, or none
+    members: Vec<workspace::Member>,
+    /// Checks that can veto renames before the plan is applied
+    validators: validators::Validators,
+    scan: ScanOptions,
+    /// Suppress progress output, e.g. while stdout carries a protocol
+    quiet: bool,
+}
+
+impl Options {
+    /// Reports progress as an `info` event, which `logging` prints on
+    /// stdout. With output suppressed it's a `debug` event, which only
+    /// reaches `--log-file`.
+    fn log(&self, message: std::fmt::Arguments) {
+        if self.quiet {
+            tracing::debug!(target: logging::PROGRESS, "{}", message);
+        } else {
+            tracing::info!(target: logging::PROGRESS, "{}", message);
+        }
+    }
+}
 
-    // First, process files (top-down)
-    for entry in entries.iter() {
-        if entry.file_type().is_file() {
-            if let Some(filename) = entry.file_name().to_str() {
-                if needs_conversion(filename) {
-                    rename_file(entry.path())?;
+fn main() {
+    let code = match try_main() {
+        Ok(()) => 0,
+        Err(err) => {
+            eprintln!("Error: {:?}", err);
+            report::exit_code(&err)
+        }
+    };
+    std::process::exit(code);
+}
+
+fn try_main() -> Result<()> {
+    let started = std::time::Instant::now();
+    // `@file` arguments are expanded before clap sees them so CI can pass
+    // more filters than the OS allows on a command line
+    let argv = response_file::expand_args(std::env::args_os(), takes_value)?;
+    let args = Args::try_parse_from(argv).unwrap_or_else(|err| {
+        let _ = err.print();
+        std::process::exit(match err.use_stderr() {
+            true => report::EXIT_USAGE,
+            false => 0,
+        })
+    });
+    logging::init(
+        args.log_level.as_deref(),
+        args.verbose,
+        args.log_format,
+        args.log_file.as_deref(),
+    )?;
+    tracing::info!(
+        args = ?std::env::args_os().skip(1).collect::<Vec<_>>(),
+        "started"
+    );
+    messages::set(args.lang);
+    conventions::set(conventions::Conventions {
+        acronyms: args.acronym_style,
+        numbers: args.number_style,
+        brands: brands::load(&args.brand, !args.no_builtin_brands)?,
+        c_style: args.c_style,
+        force_java: args.force_java,
+    });
+
+    match &args.command {
+        Some(Command::Completions { shell }) => {
+            let mut command = Args::command();
+            let name = command.get_name().to_string();
+            clap_complete::generate(
+                *shell,
+                &mut command,
+                name,
+                &mut std::io::stdout(),
+            );
+            return Ok(());
+        }
+        Some(Command::Queue { from, to, dir }) => {
+            return pending::queue(dir, from, to);
+        }
+        Some(Command::Probe { path }) => {
+            probe::probe(path, &TempNames::default())?.print();
+            return Ok(());
+        }
+        Some(Command::Cleanup { path }) => {
+            for (path, cleaned) in temp_names::cleanup(path)? {
+                match cleaned {
+                    Cleaned::Restored(to) => println!(
+                        "Restored: {} -> {}",
+                        path.display(),
+                        to.display()
+                    ),
+                    Cleaned::Deleted => println!("Deleted: {}", path.display()),
+                    Cleaned::Kept(why) => {
+                        println!("Kept: {} ({})", path.display(), why)
+                    }
                 }
             }
+            return Ok(());
+        }
+        Some(Command::Man) => {
+            return clap_mangen::Man::new(Args::command())
+                .render(&mut std::io::stdout())
+                .context("Failed to write man page");
         }
+        _ => {}
     }
+    let opts = options(&args)?;
 
-    // Then process directories (bottom-up)
-    for entry in entries.iter().rev() {
-        if entry.file_type().is_dir() {
-            if let Some(dirname) = entry.file_name().to_str() {
-                if needs_conversion(dirname) {
-                    rename_file(entry.path())?;
-                }
+    let mut report = Report::new(args.fail_fast, args.strict);
+
+    if let Some(Command::Doctor { path }) = &args.command {
+        let problems = doctor::run(path, &opts, &mut report)?;
+        report.print_summary(&opts.display);
+        if problems > 0 {
+            std::process::exit(report::EXIT_VIOLATIONS);
+        }
+        exit_on_warnings(&args, &report);
+        return Ok(());
+    }
+
+    if let Some(Command::History { path }) = &args.command {
+        return history::print(path, &opts);
+    }
+
+    if let Some(Command::Diff { git_ref, path }) = &args.command {
+        git_diff::diff(path, git_ref)?.print(git_ref, &opts);
+        return Ok(());
+    }
+
+    if let Some(Command::Stats {
+        path,
+        enable,
+        disable,
+    }) = &args.command
+    {
+        return match (enable, disable) {
+            (false, false) => stats::print(path),
+            _ => stats::set_enabled(path, *enable),
+        };
+    }
+
+    if let Some(Command::Report {
+        path,
+        markdown,
+        all,
+    }) = &args.command
+    {
+        return migration::print(path, *all, *markdown, &opts, &mut report);
+    }
+
+    if let Some(Command::Orphans { path }) = &args.command {
+        let orphans = orphans::run(path, &opts, &mut report)?;
+        report.print_summary(&opts.display);
+        if orphans > 0 {
+            std::process::exit(report::EXIT_VIOLATIONS);
+        }
+        exit_on_warnings(&args, &report);
+        return Ok(());
+    }
+
+    if args.check_idempotent {
+        let problems = idempotent::check(args.root(), &opts, &mut report)?;
+        report.print_summary(&opts.display);
+        if problems > 0 {
+            std::process::exit(report::EXIT_VIOLATIONS);
+        }
+        exit_on_warnings(&args, &report);
+        return Ok(());
+    }
+
+    if args.analyze {
+        analyze::analyze(args.root(), &opts, &mut report)?.print();
+        report.print_summary(&opts.display);
+        exit_on_warnings(&args, &report);
+        return Ok(());
+    }
+
+    if args.dry_run {
+        preview::run(
+            args.root(),
+            args.all || args.imports,
+            args.all || !args.imports,
+            &opts,
+            &mut report,
+        )?;
+        if args.css_classes {
+            css_classes::preview(args.root(), &opts, &mut report)?;
+        }
+        report.print_summary(&opts.display);
+        return Ok(());
+    }
+
+    if let Some(Command::Check {
+        path,
+        max_name_length,
+        forbid_chars,
+        require_prefix,
+        require_suffix,
+        names_only,
+        imports_only,
+    }) = &args.command
+    {
+        let rules = rules::NamingRules::new(
+            *max_name_length,
+            forbid_chars,
+            require_prefix,
+            require_suffix,
+            opts.long_names.clone(),
+        )?;
+        let mut findings = match imports_only {
+            true => Vec::new(),
+            false => rules::check(path, &rules, &opts, &mut report)?,
+        };
+        let names = findings.len();
+        if !names_only {
+            findings.extend(rules::check_imports(path, &opts, &mut report)?);
+        }
+        doctor::print_findings(&findings, &opts);
+        report.print_summary(&opts.display);
+        let mut code = 0;
+        if names > 0 {
+            code |= report::EXIT_VIOLATIONS;
+        }
+        if findings.len() > names {
+            code |= report::EXIT_IMPORT_VIOLATIONS;
+        }
+        if code != 0 {
+            std::process::exit(code);
+        }
+        exit_on_warnings(&args, &report);
+        return Ok(());
+    }
+
+    cancel::install()?;
+    if let Some(Command::Serve { .. }) = &args.command {
+        let stdin = std::io::stdin().lock();
+        return serve::run(&args, &opts, stdin, std::io::stdout().lock());
+    }
+
+    let plan = match apply(&args, &opts, &mut report, started) {
+        Err(err) if cancel::is_cancelled(&err) => {
+            report.print_summary(&opts.display);
+            eprintln!(
+                "\n{}",
+                text(Msg::Interrupted, &[&args.root().display()])
+            );
+            std::process::exit(cancel::EXIT_CANCELLED);
+        }
+        result => result?,
+    };
+    let root = args.root();
+    let final_root = plan.target_of(root).unwrap_or(root.into());
+
+    report.print_summary(&opts.display);
+    if report.has_failures() {
+        if !args.exec.is_empty() {
+            eprintln!("Skipped --exec commands because the run had failures");
+        }
+        std::process::exit(report::EXIT_PARTIAL_FAILURE);
+    }
+
+    if let Some(command) = &args.verify_cmd {
+        if !hooks::verify(command, &final_root)? {
+            if args.rollback_on_fail || confirm(&text(Msg::RollBack, &[]))? {
+                report.journal.rollback(&opts)?;
+                return Err(Aborted(
+                    "Verification failed, all changes were rolled back"
+                        .to_string(),
+                )
+                .into());
             }
+            bail!("Verification failed, changes were kept");
         }
     }
+    hooks::run_exec(&args.exec, root, &final_root, &plan)?;
+    exit_on_warnings(&args, &report);
     Ok(())
 }
 
-fn process_imports(dir: &Path) -> Result<()> {
-    let entries: Vec<_> = WalkDir::new(dir)
-        .follow_links(true)
+/// The options a run with `args` works with
+fn options(args: &Args) -> Result<Options> {
+    let members = match (&args.affected, &args.workspace_file) {
+        (Some(since), _) => monorepo::affected(args.root(), since)?,
+        (None, Some(manifest)) => {
+            workspace::members(args.root(), Some(manifest))?
+        }
+        (None, None) if args.workspace => {
+            workspace::members(args.root(), None)?
+        }
+        (None, None) => Vec::new(),
+    };
+    let installed = installed::find(args.root())?;
+    let mut packages = workspace::packages(&members);
+    for package in installed.local {
+        if !packages.iter().any(|p| p.prefix == package.prefix) {
+            packages.push(package);
+        }
+    }
+    let temp = match args.temp_seed {
+        Some(seed) => TempNames::new(seed),
+        None => TempNames::default(),
+    };
+    Ok(Options {
+        filter: match &args.owned_by {
+            Some(team) => PathFilter::new(&args.include, &args.exclude)?
+                .owned_by(codeowners::Owners::load(args.root(), team)?),
+            None => PathFilter::new(&args.include, &args.exclude)?,
+        },
+        vendored: Vendored::new(&args.include_vendored)?,
+        display: match &args.relative_to {
+            Some(dir) => PathDisplay::new(dir),
+            None => PathDisplay::for_root(args.root()),
+        },
+        convert_non_utf8: args.convert_non_utf8,
+        retries: args.retries,
+        fsync: args.fsync,
+        keep_link_names: args.keep_link_names,
+        follow_links: args.follow,
+        max_changes: match (args.max_changes, args.yes) {
+            (Some(max), _) => Some(max),
+            (None, true) => None,
+            (None, false) => Some(DEFAULT_MAX_CHANGES),
+        },
+        yes: args.yes,
+        fs: if args.renames_files() {
+            probe::probe(args.root(), &temp)?
+        } else {
+            probe::Capabilities::default()
+        },
+        temp,
+        on_conflict: args.on_conflict,
+        trash: !args.no_trash,
+        generated_markers: if args.include_generated {
+            Vec::new()
+        } else {
+            args.generated_marker.clone()
+        },
+        long_names: LongNames::new(
+            args.warn_length,
+            args.warn_words,
+            &args.abbreviate,
+        )?,
+        scan: ScanOptions {
+            packages,
+            external: installed.external,
+            separators: args.separators,
+            import_extensions: match args.add_extensions {
+                true => ImportExtensions::Esm,
+                false => args.import_extensions,
+            },
+            collapse_index: args.collapse_index,
+            prefer_dot_slash: args.prefer_dot_slash,
+            aliases: if args.prefer_aliases {
+                aliases::load(args.root(), &args.alias)?
+            } else {
+                Vec::new()
+            },
+            quote: if args.respect_prettier {
+                prettier::quote(args.root())?
+            } else {
+                None
+            },
+            comments: args.comments,
+            template_literals: args.template_literals,
+            string_refs: args.string_refs,
+            comment_paths: args.comment_paths,
+            html_templates: args.html_templates,
+            xml_attributes: args.xml_attribute.clone(),
+            describe: args.describe_blocks,
+            default_imports: args.default_imports,
+            import_case: args.import_case,
+            roots: args.ref_root.iter().map(|r| args.root().join(r)).collect(),
+        },
+        members,
+        validators: validators::Validators {
+            commands: args.veto_cmd.clone(),
+            open_files: args.veto_open_files,
+        },
+        quiet: matches!(args.command, Some(Command::Serve { .. })),
+    })
+}
+
+/// Makes the changes the run asks for, the same way whether they come from
+/// the command line, the TUI or `serve`: recorded in the journal as they're
+/// made, so an interrupted run can be resumed or rolled back, then in the
+/// history and stats, with redirects and orphans after. Returns the renames
+/// made; a cancelled run's error is left to the caller.
+fn apply(
+    args: &Args,
+    opts: &Options,
+    report: &mut Report,
+    started: std::time::Instant,
+) -> Result<Plan> {
+    let root = args.root();
+    report.journal.open(root, args.is_resume())?;
+    let result = match &args.command {
+        Some(Command::Tui { path }) => tui::run(path, opts, report),
+        Some(Command::Resume { path }) => resume::run(path, opts, report),
+        _ => run(args, opts, report),
+    };
+    let plan = match result {
+        // Nothing was changed, e.g. a plan over --max-changes, so there's
+        // nothing to resume either
+        Err(err)
+            if !cancel::is_cancelled(&err)
+                && !args.is_resume()
+                && report.journal.changes.is_empty() =>
+        {
+            report.journal.close(root)?;
+            return Err(err);
+        }
+        result => result?,
+    };
+    let final_root = plan.target_of(root).unwrap_or(root.into());
+    report.journal.close(&final_root)?;
+    history::record(root, &final_root, &plan)?;
+    stats::record(&final_root, started.elapsed(), report)?;
+    if let Some(format) = args.redirects {
+        redirects::write(
+            root,
+            &final_root,
+            &plan,
+            format,
+            args.redirects_file.as_deref(),
+            opts,
+            report,
+        )?;
+    }
+    if args.report_orphans {
+        for orphan in orphans::find(&final_root, opts, report)? {
+            report.warn(
+                &orphan.path,
+                WarningKind::UnresolvedImport,
+                orphan.problem,
+            );
+        }
+    }
+    Ok(plan)
+}
+
+/// Whether `arg` is an option that takes a value, like `--owned-by`, of
+/// kebabify or any of its subcommands. Every such option is long.
+fn takes_value(arg: &str) -> bool {
+    fn any_takes(command: &clap::Command, long: &str) -> bool {
+        command.get_arguments().any(|opt| {
+            opt.get_long() == Some(long) && opt.get_action().takes_values()
+        }) || command.get_subcommands().any(|sub| any_takes(sub, long))
+    }
+    arg.strip_prefix("--")
+        .is_some_and(|long| any_takes(&Args::command(), long))
+}
+
+/// Exits with `EXIT_WARNINGS` if the run had warnings and
+/// `--error-on-warnings` was given
+fn exit_on_warnings(args: &Args, report: &Report) {
+    if args.error_on_warnings && !report.warnings.is_empty() {
+        std::process::exit(report::EXIT_WARNINGS);
+    }
+}
+
+/// The most renames a plan may have without confirmation, unless `--yes`
+/// or `--max-changes` says otherwise
+const DEFAULT_MAX_CHANGES: usize = 500;
+
+/// Stops before anything is changed when `plan` has more renames than
+/// `--max-changes` and that isn't confirmed on the terminal, so a mistyped
+/// path can't rename a whole home directory, or when it has more to copy
+/// across filesystems than there's room for
+fn check_plan_size(plan: &Plan, opts: &Options) -> Result<()> {
+    plan.check_space(opts)?;
+    let count = plan.renames.len();
+    let Some(max) = opts.max_changes.filter(|&max| count > max) else {
+        return Ok(());
+    };
+    if confirm(&text(Msg::ApplyLargePlan, &[&count, &max]))? {
+        return Ok(());
+    }
+    Err(Aborted(text(Msg::PlanTooLarge, &[&count, &max])).into())
+}
+
+/// Asks a yes/no question on the terminal. Answers no when stdin isn't
+/// interactive.
+fn confirm(question: &str) -> Result<bool> {
+    use std::io::{IsTerminal, Write};
+
+    if !std::io::stdin().is_terminal() {
+        return Ok(false);
+    }
+    eprint!("{} {} ", question, text(Msg::YesNo, &[]));
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(messages::is_yes(answer.trim()))
+}
+
+/// Runs the requested passes and returns the renames that were made
+fn run(args: &Args, opts: &Options, report: &mut Report) -> Result<Plan> {
+    let root = args.root();
+    let mut plan = process_pending(root, opts, report)?;
+    if args.css_classes {
+        css_classes::run(root, opts, report)?;
+    }
+
+    if let Some(mapping) = &args.mapping {
+        let mapped = process_mapping(root, mapping, opts, report)?;
+        plan.extend(mapped);
+        return Ok(plan);
+    }
+
+    if !opts.members.is_empty() {
+        let renamed = workspace::convert(
+            root,
+            &opts.members,
+            args.all || args.imports,
+            args.all || !args.imports,
+            opts,
+            report,
+        )?;
+        plan.extend(renamed);
+        return Ok(plan);
+    }
+    let renamed = convert_directory(
+        root,
+        args.all || args.imports,
+        args.all || !args.imports,
+        opts,
+        report,
+    )?;
+    plan.extend(renamed);
+    Ok(plan)
+}
+
+/// Applies renames other tools queued in the pending file, keeping any
+/// that couldn't be done for the next run
+fn process_pending(
+    dir: &Path,
+    opts: &Options,
+    report: &mut Report,
+) -> Result<Plan> {
+    let Some(plan) = pending::load(dir)? else {
+        return Ok(Plan::default());
+    };
+    plan.validate(opts).with_context(|| {
+        format!(
+            "Queued renames in {} can't be applied",
+            pending::PENDING_FILE
+        )
+    })?;
+    apply_plan(dir, &plan, opts, report)?;
+
+    let mut remaining = Plan::default();
+    for rename in &plan.renames {
+        if rename.from.exists() && !rename.to.exists() {
+            remaining.push(rename.from.clone(), rename.to.clone());
+        }
+    }
+    pending::save(dir, &remaining)?;
+    Ok(plan)
+}
+
+/// Walks `dir`, pruning excluded directories and dropping files that don't
+/// pass the include/exclude filters. Entries that can't be read are
+/// reported rather than silently skipped. Siblings come in name order,
+/// whatever order the filesystem lists them in, so plans, reports and
+/// journals are the same on every machine. Symlinked directories are only
+/// walked into with `--follow`, and never when they lead outside `dir`.
+/// `.git` and `node_modules` are never walked into at all.
+fn walk(
+    dir: &Path,
+    opts: &Options,
+    report: &mut Report,
+) -> Result<Vec<DirEntry>> {
+    let span = tracing::info_span!(
+        "walk",
+        root = %dir.display(),
+        entries = tracing::field::Empty
+    )
+    .entered();
+    let root = fs::canonicalize(dir)
+        .with_context(|| format!("Failed to resolve {}", dir.display()))?;
+    let mut entries = Vec::new();
+    let mut walker = WalkDir::new(dir)
+        .follow_links(opts.follow_links)
+        .sort_by_file_name()
         .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file() && matches_source_file(e.path()))
+        .filter_entry(|e| {
+            if temp_names::is_temporary(e.file_name()) {
+                return false;
+            }
+            let excluded = e.file_type().is_dir()
+                && opts.filter.is_excluded(relative_to(e.path(), dir));
+            if excluded {
+                tracing::debug!(path = %e.path().display(), "excluded, skipped");
+            }
+            !excluded
+        });
+
+    while let Some(entry) = walker.next() {
+        let never_walked = match &entry {
+            Ok(e) if e.depth() > 0 => vendored::never_walked(e.path()),
+            _ => None,
+        };
+        if let (Ok(entry), Some(reason)) = (&entry, never_walked) {
+            if entry.file_type().is_dir() {
+                report.skip(entry.path(), reason);
+                walker.skip_current_dir();
+            }
+            continue;
+        }
+        match entry {
+            Ok(entry) if entry.path_is_symlink() && entry.depth() > 0 => {
+                match escapes(entry.path(), &root) {
+                    Some(target) if opts.follow_links => {
+                        report.warn(
+                            entry.path(),
+                            WarningKind::OutsideRoot,
+                            format!(
+                                "leads to {}, outside {}, skipped",
+                                target.display(),
+                                root.display()
+                            ),
+                        );
+                        if entry.file_type().is_dir() {
+                            walker.skip_current_dir();
+                        }
+                    }
+                    _ if entry.file_type().is_dir()
+                        || opts
+                            .filter
+                            .is_included(relative_to(entry.path(), dir)) =>
+                    {
+                        entries.push(entry)
+                    }
+                    _ => {}
+                }
+            }
+            Ok(entry) if entry.depth() > 0 && entry.file_type().is_dir() => {
+                let rel_path = relative_to(entry.path(), dir);
+                match opts.vendored.reason(entry.path(), rel_path) {
+                    Some(reason) => {
+                        report.warn_once(
+                            entry.path(),
+                            WarningKind::Vendored,
+                            format!(
+                                "{}, skipped (use --include-vendored to \
+                                 process it)",
+                                reason
+                            ),
+                        );
+                        walker.skip_current_dir();
+                    }
+                    None => entries.push(entry),
+                }
+            }
+            Ok(entry) => {
+                if entry.file_type().is_dir()
+                    || opts.filter.is_included(relative_to(entry.path(), dir))
+                {
+                    entries.push(entry);
+                } else {
+                    tracing::debug!(
+                        path = %entry.path().display(),
+                        "not included, skipped"
+                    );
+                }
+            }
+            Err(err) => {
+                let path = err.path().unwrap_or(dir).to_path_buf();
+                report.unreadable(&path, err)?;
+            }
+        }
+    }
+    let entries = drop_aliases(entries);
+    span.record("entries", entries.len());
+    Ok(entries)
+}
+
+/// Where the symlink at `path` really leads, if that's outside `root`
+fn escapes(path: &Path, root: &Path) -> Option<PathBuf> {
+    let target = fs::canonicalize(path).ok()?;
+    (!target.starts_with(root)).then_some(target)
+}
+
+/// Drops entries reached through a symlinked directory when the same file
+/// or directory is also reachable through its real path, so nothing gets
+/// renamed twice under different names
+fn drop_aliases(entries: Vec<DirEntry>) -> Vec<DirEntry> {
+    let linked_dirs: HashSet<&Path> = entries
+        .iter()
+        .filter(|e| e.path_is_symlink() && e.file_type().is_dir())
+        .map(|e| e.path())
+        .collect();
+    let is_aliased = |entry: &DirEntry| {
+        entry
+            .path()
+            .ancestors()
+            .skip(1)
+            .any(|dir| linked_dirs.contains(dir))
+    };
+
+    let real_ids: HashSet<_> = entries
+        .iter()
+        .filter(|e| !e.path_is_symlink() && !is_aliased(e))
+        .filter_map(|e| fs_util::file_id(e.path()))
+        .collect();
+
+    let mut seen = HashSet::new();
+    let keep: Vec<bool> = entries
+        .iter()
+        .map(|entry| {
+            !is_aliased(entry)
+                || fs_util::file_id(entry.path())
+                    .is_none_or(|id| !real_ids.contains(&id) && seen.insert(id))
+        })
         .collect();
 
+    entries
+        .into_iter()
+        .zip(keep)
+        .filter_map(|(entry, keep)| keep.then_some(entry))
+        .collect()
+}
+
+/// Source files whose imports should be processed. Each file is returned
+/// once even when hard links or symlinks make it reachable by several paths.
+fn source_files(
+    dir: &Path,
+    opts: &Options,
+    report: &mut Report,
+) -> Result<Vec<DirEntry>> {
+    let mut seen = HashSet::new();
+    Ok(walk(dir, opts, report)?
+        .into_iter()
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| {
+            scanner::scanner_for(e.path()).is_some()
+                || opts.scan.string_refs
+                    && !is_own_file(e.path())
+                    && !encoding::looks_binary(e.path())
+        })
+        .filter(|e| fs_util::file_id(e.path()).is_none_or(|id| seen.insert(id)))
+        .collect())
+}
+
+/// Files kebabify keeps its own state in, which hold paths as data
+fn is_own_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.starts_with(".kebabify-"))
+}
+
+fn relative_to<'a>(path: &'a Path, root: &Path) -> &'a Path {
+    path.strip_prefix(root).unwrap_or(path)
+}
+
+/// Renames every file and directory under `dir` that needs converting and
+/// returns what was renamed, mapped to final paths. Runs go through
+/// `convert_directory`; this is the renaming on its own, for tests.
+#[cfg(test)]
+fn process_directory(
+    dir: &Path,
+    opts: &Options,
+    report: &mut Report,
+) -> Result<Plan> {
+    // Collect paths first to avoid renaming issues during iteration
+    let entries = walk(dir, opts, report)?;
+    let links = collect_symlinks(&entries);
+
+    let plan = plan_entries(dir, &entries, opts, report);
+    plan.check_space(opts)?;
+    rename_with_plan(&links, &plan, opts, report)?;
+    Ok(plan)
+}
+
+/// Converts the tree under `dir` as a journaled phase: imports first, while
+/// the paths they name still exist, then file and directory names
+fn convert_directory(
+    dir: &Path,
+    imports: bool,
+    renames: bool,
+    opts: &Options,
+    report: &mut Report,
+) -> Result<Plan> {
+    let entries = walk(dir, opts, report)?;
+    let links = collect_symlinks(&entries);
+    let mut plan = if renames {
+        plan_entries(dir, &entries, opts, report)
+    } else {
+        Plan::default()
+    };
+    let vetoed = opts.validators.review(dir, &mut plan, report)?;
+
+    check_plan_size(&plan, opts)?;
+    ambiguity::review(&plan, opts)?;
+
+    let mode = import_mode(imports, vetoed);
+    report.journal.begin_phase(mode, &opts.scan, &plan);
+    if imports {
+        let files = source_files(dir, opts, report)?;
+        update_references(&files, mode, &plan, opts, report)?;
+    }
+    report.journal.imports_done();
+
+    rename_with_plan(&links, &plan, opts, report)?;
+    Ok(plan)
+}
+
+/// Applies the renames in `plan`, then points `links` at the new names
+fn rename_with_plan(
+    links: &[(PathBuf, PathBuf)],
+    plan: &Plan,
+    opts: &Options,
+    report: &mut Report,
+) -> Result<()> {
+    plan.apply(opts, report)?;
+    retarget_symlinks(links, plan, opts, report)
+}
+
+/// Works out the final path of every file and directory under `dir` whose
+/// name needs converting, without touching the disk
+fn plan_directory(
+    dir: &Path,
+    opts: &Options,
+    report: &mut Report,
+) -> Result<Plan> {
+    let entries = walk(dir, opts, report)?;
+    Ok(plan_entries(dir, &entries, opts, report))
+}
+
+fn plan_entries(
+    dir: &Path,
+    entries: &[DirEntry],
+    opts: &Options,
+    report: &mut Report,
+) -> Plan {
+    let span = tracing::info_span!(
+        "plan",
+        root = %dir.display(),
+        renames = tracing::field::Empty
+    )
+    .entered();
+    let mut plan = Plan::default();
+    let mut siblings: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+    let mut taken = HashSet::new();
+
+    // Entries come parent-first, so a parent's final path is always known
+    // by the time its children are planned
+    for entry in entries {
+        if entry.path_is_symlink() && opts.keep_link_names {
+            tracing::debug!(
+                path = %entry.path().display(),
+                "symlink, name kept (--keep-link-names)"
+            );
+            continue;
+        }
+        if !opts.filter.is_renamed(relative_to(entry.path(), dir)) {
+            continue;
+        }
+
+        let path = entry.path();
+        let (Some(parent), Some(mut new_name)) =
+            (path.parent(), converted_name(path, opts, report))
+        else {
+            continue;
+        };
+        if let Some(name) = new_name.to_str() {
+            if let Some(short) = opts.long_names.abbreviate(name) {
+                new_name = short.into();
+            }
+        }
+        if let Some(problem) =
+            new_name.to_str().and_then(|n| opts.long_names.problem(n))
+        {
+            report.warn_once(path, WarningKind::LongName, problem);
+        }
+        let final_parent = plan.target_of(parent).unwrap_or(parent.into());
+        let siblings =
+            siblings.entry(parent.to_path_buf()).or_insert_with(|| {
+                fs::read_dir(parent)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|e| e.ok().map(|e| e.path()))
+                    .collect()
+            });
+        let overwrite = opts.on_conflict == OnConflict::Overwrite;
+        let target = free_target(
+            path,
+            &final_parent,
+            &new_name,
+            siblings,
+            &taken,
+            overwrite,
+        );
+        if target.file_name() != Some(&new_name) {
+            report.warn(
+                path,
+                WarningKind::NumberedName,
+                format!(
+                    "'{}' is taken, renamed to '{}' instead",
+                    new_name.to_string_lossy(),
+                    target.file_name().unwrap_or_default().to_string_lossy()
+                ),
+            );
+        }
+        if let Some(problem) = opts.fs.too_long(&target) {
+            report.warn(path, WarningKind::TooLong, problem);
+            continue;
+        }
+        if let Some(caveat) = scanner::rename_caveat(path, &target) {
+            report.warn(path, WarningKind::RenameCaveat, caveat);
+        }
+        taken.insert(target.to_string_lossy().to_lowercase());
+        tracing::debug!(
+            from = %path.display(),
+            to = %target.display(),
+            "planned rename"
+        );
+        plan.push(path.to_path_buf(), target);
+    }
+    span.record("renames", plan.renames.len());
+
+    // References converted from here on name what was planned
+    let planned: HashMap<&Path, &Path> = plan
+        .renames
+        .iter()
+        .map(|r| (r.from.as_path(), r.to.as_path()))
+        .collect();
     for entry in entries {
-        process_file_imports(entry.path())?;
+        let path = entry.path();
+        let to = planned
+            .get(resolve::normalize(path).as_path())
+            .and_then(|to| Some(path.with_file_name(to.file_name()?)));
+        path_cache::record(path, to);
+    }
+    plan
+}
+
+/// Where `path` goes when renamed to `new_name`. If the name is taken, case
+/// insensitively, by a sibling that keeps its name or by another rename
+/// into the same directory (`taken`, lowercased), the first free numbered
+/// name is used instead: `user-card-2.ts`, `user-card-3.ts`, and so on.
+/// Plans are built in path order, so the numbers are the same every run.
+/// With `overwrite`, a file may take the name of a sibling file, which is
+/// replaced when the plan is applied.
+fn free_target(
+    path: &Path,
+    final_parent: &Path,
+    new_name: &OsStr,
+    siblings: &[PathBuf],
+    taken: &HashSet<String>,
+    overwrite: bool,
+) -> PathBuf {
+    let is_taken = |name: &str| {
+        let name = name.to_lowercase();
+        let key = final_parent.join(&name).to_string_lossy().to_lowercase();
+        taken.contains(&key)
+            || siblings.iter().any(|sibling| {
+                sibling != path
+                    && sibling.file_name().is_some_and(|n| {
+                        n.to_string_lossy().to_lowercase() == name
+                    })
+                    && scanner::converted_path(sibling).is_none()
+                    && !(overwrite && path.is_file() && sibling.is_file())
+            })
+    };
+    let name = new_name.to_string_lossy();
+    if !is_taken(&name) {
+        return final_parent.join(new_name);
+    }
+
+    let separator = match scanner::name_style(path, path.is_dir()) {
+        NameStyle::Kebab => '-',
+        NameStyle::Snake => '_',
+    };
+    // The number goes before the extensions, after any leading dots
+    let dots = name.len() - name.trim_start_matches('.').len();
+    let split = name[dots..].find('.').map_or(name.len(), |i| dots + i);
+    let (stem, extensions) = name.split_at(split);
+    (2..)
+        .map(|n| format!("{}{}{}{}", stem, separator, n, extensions))
+        .find(|numbered| !is_taken(numbered))
+        .map(|numbered| final_parent.join(numbered))
+        .unwrap()
+}
+
+/// Symlinks in the tree along with the raw target each one points at
+fn collect_symlinks(entries: &[DirEntry]) -> Vec<(PathBuf, PathBuf)> {
+    let mut links: Vec<PathBuf> = entries
+        .iter()
+        .filter(|e| e.path_is_symlink())
+        .map(|e| e.path().to_path_buf())
+        .collect();
+    // The walk leaves node_modules out, but packages a workspace links in
+    // there, as `node_modules/@acme/ui`, still point into the tree
+    for dir in entries.iter().filter(|e| e.file_type().is_dir()) {
+        let installed = dir.path().join("node_modules");
+        if !installed.is_dir() {
+            continue;
+        }
+        links.extend(
+            WalkDir::new(installed)
+                .min_depth(1)
+                .max_depth(2)
+                .sort_by_file_name()
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| e.path_is_symlink())
+                .map(DirEntry::into_path),
+        );
+    }
+    links
+        .into_iter()
+        .filter_map(|link| {
+            let target = fs::read_link(&link).ok()?;
+            Some((link, target))
+        })
+        .collect()
+}
+
+/// Points symlinks at the new names of renamed targets, keeping relative
+/// links relative, and warns about links the run would leave dangling
+fn retarget_symlinks(
+    links: &[(PathBuf, PathBuf)],
+    plan: &Plan,
+    opts: &Options,
+    report: &mut Report,
+) -> Result<()> {
+    for (link, target) in links {
+        cancel::checkpoint()?;
+        let link_dir = link.parent().unwrap_or(Path::new("."));
+        let old_target = resolve::normalize(&link_dir.join(target));
+        let new_link = plan.target_of(link).unwrap_or(link.clone());
+        let new_target = plan.target_of(&old_target).unwrap_or(old_target);
+
+        let raw_target = if target.is_absolute() {
+            new_target.clone()
+        } else {
+            let new_dir = new_link.parent().unwrap_or(Path::new("."));
+            let relative = resolve::relative_specifier(new_dir, &new_target);
+            // Only keep a leading `./` if the link had one
+            match relative.strip_prefix("./") {
+                Some(rest) if !target.starts_with(".") => PathBuf::from(rest),
+                _ => PathBuf::from(relative),
+            }
+        };
+
+        if &raw_target != target {
+            opts.log(format_args!(
+                "{}",
+                text(
+                    Msg::RetargetingLink,
+                    &[&opts.display.show(&new_link), &raw_target.display()]
+                )
+            ));
+            let result = fs_util::replace_symlink(&new_link, &raw_target)
+                .with_context(|| {
+                    format!(
+                        "Failed to update symlink: {}",
+                        opts.display.show(&new_link)
+                    )
+                });
+            if report.check(&new_link, result)?.is_some() {
+                report.journal.record(journal::Change::Relinked {
+                    link: new_link.clone(),
+                    target: target.clone(),
+                });
+            }
+        }
+
+        if !new_target.exists() {
+            report.warn(
+                &new_link,
+                WarningKind::DanglingSymlink,
+                format!("points at missing {}", new_target.display()),
+            );
+        }
     }
     Ok(())
 }
 
+/// Returns the converted name of `path` if it needs converting
+fn converted_name(
+    path: &Path,
+    opts: &Options,
+    report: &mut Report,
+) -> Option<OsString> {
+    let name = path.file_name()?;
+    let is_dir = path.is_dir();
+    let style = scanner::name_style(path, is_dir);
+
+    match name.to_str() {
+        Some(name) if !needs_conversion_as(name, style) => {
+            tracing::debug!(path = %path.display(), "already converted");
+            return None;
+        }
+        Some(_) => {}
+        None if !needs_conversion_as(&name.to_string_lossy(), style) => {
+            return None
+        }
+        None if opts.convert_non_utf8 => {}
+        None => {
+            report.warn(
+                path,
+                WarningKind::NonUtf8Name,
+                "name is not valid UTF-8, skipped \
+                 (use --convert-non-utf8 to convert it)",
+            );
+            return None;
+        }
+    }
+    if let Some(reason) = scanner::pinned(path, is_dir) {
+        report.warn(path, WarningKind::PinnedName, reason);
+        return None;
+    }
+    let marker = (!is_dir)
+        .then(|| generated::file_marker(path, &opts.generated_markers))
+        .flatten();
+    if let Some(marker) = marker {
+        report.warn(
+            path,
+            WarningKind::Generated,
+            format!(
+                "marked {:?}, name kept (use --include-generated to rename it)",
+                marker
+            ),
+        );
+        return None;
+    }
+
+    // Use the same tokenizer as the import rewriter so names on disk and
+    // rewritten imports always agree
+    let converted = convert_os_str(name, style);
+    if converted.is_none() {
+        report.warn(
+            path,
+            WarningKind::NonUtf8Name,
+            "name can't be converted safely on this platform",
+        );
+    }
+    converted
+}
+
+fn process_mapping(
+    dir: &Path,
+    mapping: &Path,
+    opts: &Options,
+    report: &mut Report,
+) -> Result<Plan> {
+    let plan = mapping::load_mapping(mapping, dir)?;
+    plan.validate(opts)?;
+    apply_plan(dir, &plan, opts, report)?;
+    Ok(plan)
+}
+
+/// Applies a plan to the tree under `dir`: fixes imports while every file
+/// is still at its original location, then renames, then points symlinks
+/// at the new names
+fn apply_plan(
+    dir: &Path,
+    plan: &Plan,
+    opts: &Options,
+    report: &mut Report,
+) -> Result<()> {
+    check_plan_size(plan, opts)?;
+    let links = collect_symlinks(&walk(dir, opts, report)?);
+    report
+        .journal
+        .begin_phase(ImportMode::Plan, &opts.scan, plan);
+    let files = source_files(dir, opts, report)?;
+    update_references(&files, ImportMode::Plan, plan, opts, report)?;
+    report.journal.imports_done();
+
+    rename_with_plan(&links, plan, opts, report)
+}
+
+/// How a run rewrites references. Once a rename has been vetoed, they
+/// follow the plan, as they do for `--mapping`, because converting them
+/// would point references to the vetoed file at a name it won't have.
+fn import_mode(imports: bool, vetoed: bool) -> ImportMode {
+    match (imports, vetoed) {
+        (false, _) => ImportMode::None,
+        (true, false) => ImportMode::Convert,
+        (true, true) => ImportMode::Plan,
+    }
+}
+
+/// Rewrites the references in each of `files` as `mode` says
+fn update_references(
+    files: &[DirEntry],
+    mode: ImportMode,
+    plan: &Plan,
+    opts: &Options,
+    report: &mut Report,
+) -> Result<()> {
+    if mode == ImportMode::Convert {
+        return process_imports_in(files, plan, opts, report);
+    }
+    if mode == ImportMode::None {
+        return Ok(());
+    }
+    for entry in files {
+        cancel::checkpoint()?;
+        let path = entry.path();
+        let result = process_file_imports(
+            path,
+            opts,
+            &opts.scan,
+            plan,
+            report,
+            |content, scan| {
+                scanner::follow_plan_in_file(path, content, plan, scan)
+            },
+        );
+        report.check(path, result)?;
+    }
+    write_back::flush(opts, report)
+}
+
+/// Rewrites relative imports in the file at `path` so they still resolve
+/// once `plan` has been applied, including when the file itself moves.
+fn update_imports_with_plan(
+    path: &Path,
+    content: &str,
+    plan: &Plan,
+) -> (String, usize) {
+    let old_dir = path.parent().unwrap_or(Path::new("."));
+    let new_path = plan.target_of(path).unwrap_or(path.to_path_buf());
+    let new_dir = new_path.parent().unwrap_or(Path::new("."));
+    let mut changes = 0;
+
+    let result = replace_specifiers(content, |spec| {
+        let new_spec = resolve::rewrite_specifier(spec, old_dir, new_dir, plan);
+        changes += new_spec.is_some() as usize;
+        new_spec
+    });
+
+    (result, changes)
+}
+
+/// Converts the references in every source file under `dir`. `plan` is
+/// the run's rename plan, for renames that aren't plain conversions. Runs
+/// go through `convert_directory`; this is the conversion on its own, for
+/// tests.
+#[cfg(test)]
+fn process_imports(
+    dir: &Path,
+    plan: &Plan,
+    opts: &Options,
+    report: &mut Report,
+) -> Result<()> {
+    let entries = source_files(dir, opts, report)?;
+    process_imports_in(&entries, plan, opts, report)
+}
+
+/// Converts the references in each of `files`, as `process_imports` does
+fn process_imports_in(
+    files: &[DirEntry],
+    plan: &Plan,
+    opts: &Options,
+    report: &mut Report,
+) -> Result<()> {
+    let _span = tracing::info_span!("imports", files = files.len()).entered();
+    let exceptions = plan.exceptions();
+
+    for entry in files {
+        cancel::checkpoint()?;
+        let path = entry.path();
+        let result = process_file_imports(
+            path,
+            opts,
+            &opts.scan,
+            plan,
+            report,
+            |content, scan| {
+                scanner::convert_file(path, content, scan, plan, &exceptions)
+            },
+        );
+        report.check(path, result)?;
+    }
+    write_back::flush(opts, report)
+}
+
 fn matches_source_file(path: &Path) -> bool {
     matches!(
         path.extension().and_then(|e| e.to_str()),
@@ -91,37 +2062,203 @@ fn matches_source_file(path: &Path) -> bool {
     )
 }
 
-fn process_file_imports(path: &Path) -> Result<()> {
-    let content = fs::read_to_string(path)?;
-    let (new_content, changes) = update_imports(&content);
+/// Reads a source file, applies `update` to its content and writes it back
+/// in its original encoding if any imports changed. `plan` is the renames
+/// `update` follows.
+fn process_file_imports(
+    path: &Path,
+    opts: &Options,
+    scan: &ScanOptions,
+    plan: &Plan,
+    report: &mut Report,
+    update: impl Fn(&str, &ScanOptions) -> (String, usize),
+) -> Result<()> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == std::io::ErrorKind::PermissionDenied => {
+            return report.unreadable(path, err);
+        }
+        Err(err) => {
+            return Err(err).with_context(|| {
+                format!("Failed to read file: {}", opts.display.show(path))
+            })
+        }
+    };
+    let Some((content, encoding)) = encoding::decode(&bytes) else {
+        report.warn(
+            path,
+            WarningKind::UnknownEncoding,
+            "unrecognized text encoding, skipped",
+        );
+        return Ok(());
+    };
+    let (new_content, changes) = update(&content, scan);
+    tracing::trace!(path = %path.display(), changes, "scanned");
+    if scan.comments == Comments::Flag {
+        let everything = ScanOptions {
+            comments: Comments::Rewrite,
+            ..scan.clone()
+        };
+        let (rewritten, _) = update(&content, &everything);
+        for (line, old, new) in scanner::held_back(&new_content, &rewritten) {
+            report.warn(
+                path,
+                WarningKind::CommentedImport,
+                format!(
+                    "line {}: '{}' left as is in a comment (would be '{}')",
+                    line, old, new
+                ),
+            );
+        }
+    }
+    if scan.describe == DescribeBlocks::Flag {
+        let renamed = ScanOptions {
+            describe: DescribeBlocks::Rewrite,
+            ..scan.clone()
+        };
+        let (rewritten, _) = update(&content, &renamed);
+        let lines = new_content.lines().zip(rewritten.lines()).enumerate();
+        for (i, (old, new)) in lines.filter(|(_, (old, new))| old != new) {
+            report.warn(
+                path,
+                WarningKind::DescribeBlock,
+                format!(
+                    "line {}: '{}' is named after the old file (would be \
+                     '{}')",
+                    i + 1,
+                    old.trim(),
+                    new.trim()
+                ),
+            );
+        }
+    }
+    if scan.default_imports != DefaultImports::Keep {
+        let case = scan.import_case;
+        for found in default_imports::mismatches(path, &content, plan, case) {
+            let kept = match (scan.default_imports, found.blocked) {
+                (DefaultImports::Rewrite, None) => continue,
+                (DefaultImports::Rewrite, Some(reason)) => {
+                    format!(", kept because {}", reason)
+                }
+                _ => String::new(),
+            };
+            report.warn(
+                path,
+                WarningKind::DefaultImport,
+                format!(
+                    "line {}: imported as '{}'{} (would be '{}')",
+                    found.line, found.local, kept, found.wanted
+                ),
+            );
+        }
+    }
+    if changes > 0 && is_left_generated(path, &content, changes, opts, report) {
+        return Ok(());
+    }
 
     if changes > 0 {
-        println!("Updated {} imports in: {}", changes, path.display());
-        fs::write(path, new_content)?;
+        opts.log(format_args!(
+            "{}",
+            text(Msg::UpdatedImports, &[&changes, &opts.display.show(path)])
+        ));
+        tracing::info!(path = %path.display(), changes, "updated imports");
+        write_content(path, bytes, &new_content, encoding, opts, report)?;
     }
 
     Ok(())
 }
 
-fn update_imports(content: &str) -> (String, usize) {
-    let mut changes = 0;
+/// Whether the file at `path` is marked generated, so its `changes`
+/// references are left alone, warning about it if so
+fn is_left_generated(
+    path: &Path,
+    content: &str,
+    changes: usize,
+    opts: &Options,
+    report: &mut Report,
+) -> bool {
+    let Some(marker) = generated::marker(content, &opts.generated_markers)
+    else {
+        return false;
+    };
+    report.warn(
+        path,
+        WarningKind::Generated,
+        format!(
+            "marked {:?}, {} references left alone \
+             (use --include-generated to update them)",
+            marker, changes
+        ),
+    );
+    true
+}
 
-    let import_regex = Regex::new(
+/// Queues `content` to be written over the file at `path`, whose
+/// `original` bytes were in `encoding`. It's recorded in the journal once
+/// it's written, when the queue is flushed.
+fn write_content(
+    path: &Path,
+    original: Vec<u8>,
+    content: &str,
+    encoding: encoding::Encoding,
+    opts: &Options,
+    report: &mut Report,
+) -> Result<()> {
+    let new_bytes = encoding::encode(content, encoding).with_context(|| {
+        format!(
+            "Updated content can't be written as {}: {}",
+            encoding.name(),
+            opts.display.show(path)
+        )
+    })?;
+    write_back::queue(path, original, new_bytes, opts, report)
+}
+
+fn import_regex() -> Regex {
+    Regex::new(
         r#"(?x)
         ((?:import|export)\s+(?:type\s+)?[^"']*?from\s*["']|require\(["'])  # import/export/require start with optional type
         ([^"']+)                                                  # path capture
         (["'][\);]?)                                             # closing quote/paren
     "#,
     )
-    .unwrap();
+    .unwrap()
+}
+
+/// Calls `rewrite` with every import specifier in `content` and splices in
+/// the replacements it returns. Everything outside the specifiers, including
+/// line endings, quotes and a leading BOM, is copied through byte-for-byte.
+fn replace_specifiers(
+    content: &str,
+    mut rewrite: impl FnMut(&str) -> Option<String>,
+) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut last = 0;
+
+    for caps in import_regex().captures_iter(content) {
+        let spec = caps.get(2).unwrap();
+        if let Some(new_spec) = rewrite(spec.as_str()) {
+            result.push_str(&content[last..spec.start()]);
+            result.push_str(&new_spec);
+            last = spec.end();
+        }
+    }
+    result.push_str(&content[last..]);
+    result
+}
 
-    let result = import_regex.replace_all(content, |caps: &regex::Captures| {
-        let prefix = &caps[1];
-        let path = &caps[2];
-        let suffix = &caps[3];
+/// Converts the case of every import in `content` except those of
+/// `scan.external` packages
+fn update_imports(content: &str, scan: &ScanOptions) -> (String, usize) {
+    let separators = scan.separators;
+    let mut changes = 0;
 
-        // Split the path into segments
-        let segments: Vec<&str> = path.split('/').collect();
+    let result = replace_specifiers(content, |path| {
+        if scan.external.contains(installed::package_name(path)) {
+            return None;
+        }
+        // Split the path into segments, remembering the separator after each
+        let (segments, seps) = split_specifier(path);
         let new_segments: Vec<String> = segments
             .iter()
             .map(|segment| {
@@ -129,41 +2266,118 @@ fn update_imports(content: &str) -> (String, usize) {
                 if *segment == "." || *segment == ".." {
                     segment.to_string()
                 } else {
-                    // Split segment into filename and extension if it has one
-                    let parts: Vec<&str> = segment.split('.').collect();
-                    let result = if parts.len() > 1 {
-                        // Has extension
-                        let name = parts[0];
-                        let ext = parts[1..].join(".");
-                        if needs_conversion(name) {
-                            changes += 1;
-                            format!("{}.{}", pascal_to_kebab_smart(name), ext)
-                        } else {
-                            segment.to_string()
-                        }
-                    } else {
-                        // No extension - convert if needed
-                        if needs_conversion(segment) {
-                            changes += 1;
-                            pascal_to_kebab_smart(segment)
-                        } else {
-                            segment.to_string()
-                        }
-                    };
-                    result
+                    let converted = convert_name(segment);
+                    if converted != *segment {
+                        changes += 1;
+                    }
+                    converted
                 }
             })
             .collect();
 
-        format!("{}{}{}", prefix, new_segments.join("/"), suffix)
+        let backslash = seps
+            .iter()
+            .copied()
+            .find(|sep| sep.starts_with('\\'))
+            .unwrap_or("\\\\");
+        let new_seps: Vec<&str> = seps
+            .iter()
+            .map(|sep| match separators {
+                Separators::Keep => sep,
+                Separators::Slash => "/",
+                Separators::Backslash => backslash,
+            })
+            .collect();
+        if new_seps != seps {
+            changes += 1;
+        }
+
+        let mut joined = new_segments[0].clone();
+        for (sep, segment) in new_seps.iter().zip(&new_segments[1..]) {
+            joined.push_str(sep);
+            joined.push_str(segment);
+        }
+        Some(joined)
     });
 
-    (result.to_string(), changes)
+    (result, changes)
+}
+
+/// Splits an import path on `/`, `\` and escaped `\\` separators. Returns
+/// the segments and the separators between them, as written.
+fn split_specifier(path: &str) -> (Vec<&str>, Vec<&str>) {
+    let mut segments = Vec::new();
+    let mut seps = Vec::new();
+    let mut start = 0;
+    let bytes = path.as_bytes();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let sep_len = match (bytes[i], bytes.get(i + 1)) {
+            (b'\\', Some(b'\\')) => 2,
+            (b'/' | b'\\', _) => 1,
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+        segments.push(&path[start..i]);
+        seps.push(&path[i..i + sep_len]);
+        i += sep_len;
+        start = i;
+    }
+    segments.push(&path[start..]);
+    (segments, seps)
+}
+
+/// Converts a file or directory name, or a single import path segment.
+/// Every dot-separated part is converted on its own, so `MyComponent.test.tsx`
+/// becomes `my-component.test.tsx` whether it's found on disk or in an
+/// import with or without its extension.
+fn convert_name(name: &str) -> String {
+    convert_name_as(name, NameStyle::Kebab)
+}
+
+/// The case a name is converted to. Most files are kebab-case, but some
+/// languages need names that are valid identifiers.
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum NameStyle {
+    /// `string-helpers.hpp`: words are joined with `-`
+    #[default]
+    Kebab,
+    /// `string_helpers.hpp`: words are joined with `_`, including words
+    /// that were joined with `-`
+    Snake,
+}
+
+fn convert_name_as(name: &str, style: NameStyle) -> String {
+    name.split('.')
+        .map(|part| match (style, locale::canonical(part)) {
+            // Locale codes keep their own casing, `En-US` becoming `en-US`
+            (_, Some(code)) => code,
+            (NameStyle::Kebab, None) => pascal_to_kebab_smart(part),
+            // Convert each word on its own so `__init__` and `My_Model`
+            // keep their underscores
+            (NameStyle::Snake, None) => part
+                .split('_')
+                .map(|word| pascal_to_kebab_smart(word).replace('-', "_"))
+                .collect::<Vec<_>>()
+                .join("_"),
+        })
+        .collect::<Vec<_>>()
+        .join(".")
 }
 
 fn needs_conversion(filename: &str) -> bool {
+    needs_conversion_as(filename, NameStyle::Kebab)
+}
+
+fn needs_conversion_as(filename: &str, style: NameStyle) -> bool {
     // Check if the filename contains uppercase letters
-    filename.chars().any(|c| c.is_uppercase())
+    (filename.chars().any(|c| c.is_uppercase())
+        || (style == NameStyle::Snake && filename.contains('-')))
+        // Locale codes like `en-US` are already as they should be
+        && convert_name_as(filename, style) != filename
 }
 
 #[derive(Debug, PartialEq)]
@@ -235,41 +2449,9 @@ fn pascal_to_kebab(s: &str) -> String {
     result
 }
 
-fn acronym_to_kebab(s: &str) -> String {
-    let mut result = String::new();
-    let mut acronym = String::new();
-    let mut prev_lower = false;
-
-    for c in s.chars() {
-        if c.is_uppercase() {
-            if !acronym.is_empty() && prev_lower {
-                result.push('-');
-            }
-            acronym.push(c);
-            prev_lower = false;
-        } else {
-            if !acronym.is_empty() {
-                result.push_str(&acronym.to_lowercase());
-                acronym.clear();
-            }
-            result.push(c);
-            prev_lower = true;
-        }
-    }
-
-    if !acronym.is_empty() {
-        if prev_lower {
-            result.push('-');
-        }
-        result.push_str(&acronym.to_lowercase());
-    }
-
-    result
-}
-
 fn camel_to_kebab(s: &str) -> String {
     let mut result = String::with_capacity(s.len() + 5);
-    let mut chars = s.chars().peekable();
+    let mut chars = s.chars();
 
     // Handle first character
     if let Some(c) = chars.next() {
@@ -277,7 +2459,7 @@ fn camel_to_kebab(s: &str) -> String {
     }
 
     // Handle rest of the string
-    while let Some(c) = chars.next() {
+    for c in chars {
         if c.is_uppercase() {
             result.push('-');
             result.push(c.to_lowercase().next().unwrap());
@@ -290,47 +2472,92 @@ fn camel_to_kebab(s: &str) -> String {
 }
 
 fn pascal_to_kebab_smart(filename: &str) -> String {
-    let case = detect_case(filename);
-    match case {
-        Case::Kebab => filename.to_string(),
-        Case::Pascal => pascal_to_kebab(filename),
-        Case::Camel => camel_to_kebab(filename),
-        Case::Acronym => acronym_to_kebab(filename),
+    let Some(segments) = brands::split(filename, &conventions::get().brands)
+    else {
+        return words_to_kebab(filename);
+    };
+    let mut words: Vec<String> = Vec::new();
+    for segment in segments {
+        let text = match segment {
+            brands::Segment::Brand(form) => {
+                words.push(form.to_string());
+                continue;
+            }
+            brands::Segment::Text(text) => text.trim_matches('-'),
+        };
+        // A number right after a brand is its version: `OAuth2`
+        let rest = text.trim_start_matches(|c: char| c.is_ascii_digit());
+        let version = &text[..text.len() - rest.len()];
+        let text = match words.last_mut() {
+            Some(last) if !version.is_empty() => {
+                last.push_str(version);
+                rest
+            }
+            _ => text,
+        };
+        if !text.is_empty() {
+            words.push(words_to_kebab(text));
+        }
     }
+    words.join("-")
 }
 
-fn rename_file(path: &Path) -> Result<()> {
-    let parent = path.parent().context("Failed to get parent directory")?;
-
-    // Get just the stem (filename without extension)
-    let stem = path
-        .file_stem()
-        .context("Failed to get file stem")?
-        .to_string_lossy();
-
-    // Convert only the stem to kebab case using our new smart function
-    let new_stem = pascal_to_kebab_smart(&stem);
-
-    // Create new filename with original extension
-    let new_filename = if let Some(ext) = path.extension() {
-        format!("{}.{}", new_stem, ext.to_string_lossy())
-    } else {
-        new_stem
+/// Splits a name into words at its case changes
+fn words_to_kebab(filename: &str) -> String {
+    let case = detect_case(filename);
+    let kebab = match case {
+        Case::Kebab => return filename.to_string(),
+        Case::Pascal => pascal_to_kebab(filename),
+        Case::Camel => camel_to_kebab(filename),
+        Case::Acronym => acronyms::acronym_to_kebab(filename),
     };
+    place_number_units(&kebab, conventions::get().numbers)
+}
 
-    let new_path = parent.join(new_filename);
+/// Keeps a number and its one-letter unit together: splitting at each
+/// capital turns `Vector3D` into `vector3-d`, which becomes `vector3d` or
+/// `vector-3d`
+fn place_number_units(kebab: &str, style: NumberStyle) -> String {
+    let unit = Regex::new(r"(^|-)([^-]*?)(\d+)-([a-z])(-|$)").unwrap();
+    let mut result = kebab.to_string();
+    // Matches can't overlap, so go again until nothing's left
+    while let Some(caps) = unit.captures(&result) {
+        let (start, word, digits) = (&caps[1], &caps[2], &caps[3]);
+        let gap = match style {
+            NumberStyle::Separate if !word.is_empty() => "-",
+            _ => "",
+        };
+        let fixed = format!(
+            "{}{}{}{}{}{}",
+            start, word, gap, digits, &caps[4], &caps[5]
+        );
+        result.replace_range(caps.get(0).unwrap().range(), &fixed);
+    }
+    result
+}
 
-    println!(
-        "Renaming: {} -> {}",
-        path.display(),
-        new_path.file_name().unwrap().to_string_lossy()
-    );
+/// Converts a name that may not be valid UTF-8. Decodable runs are converted
+/// and any other bytes are kept exactly as they were.
+#[cfg(unix)]
+fn convert_os_str(name: &OsStr, style: NameStyle) -> Option<OsString> {
+    use std::os::unix::ffi::{OsStrExt, OsStringExt};
 
-    std::fs::rename(path, new_path).with_context(|| {
-        format!("Failed to rename file: {}", path.display())
-    })?;
+    let mut converted = Vec::with_capacity(name.len());
+    for chunk in name.as_bytes().utf8_chunks() {
+        converted.extend_from_slice(
+            convert_name_as(chunk.valid(), style).as_bytes(),
+        );
+        converted.extend_from_slice(chunk.invalid());
+    }
+    Some(OsString::from_vec(converted))
+}
 
-    Ok(())
+/// Other platforms can't split an invalid name into bytes, so only valid
+/// Unicode names are converted
+#[cfg(not(unix))]
+fn convert_os_str(name: &OsStr, style: NameStyle) -> Option<OsString> {
+    name.to_str()
+        .map(|name| convert_name_as(name, style).into())
 }
 
 #[cfg(test)]
@@ -355,6 +2582,7 @@ mod tests {
     /// - Is "ID" an acronym for "Identifier" or just the word "Id"?
     /// - Is "UNESCO" one acronym or "UN-ESCO"?
     /// - Is "LASER" still an acronym even though it's now commonly written as "laser"?
+    ///
     /// The best we can do is make educated guesses based on common patterns.
     fn test_pascal_to_kebab_smart() {
         // Pascal case
@@ -387,12 +2615,97 @@ mod tests {
         assert_eq!(pascal_to_kebab_smart("already-kebab"), "already-kebab");
     }
 
+    #[test]
+    fn test_brand_names() {
+        assert_eq!(pascal_to_kebab_smart("GitHubClient"), "github-client");
+        assert_eq!(pascal_to_kebab_smart("jQueryPlugin"), "jquery-plugin");
+        assert_eq!(pascal_to_kebab_smart("iOSApp"), "ios-app");
+        assert_eq!(pascal_to_kebab_smart("MyWebSocket"), "my-websocket");
+        assert_eq!(pascal_to_kebab_smart("OAuth2Provider"), "oauth2-provider");
+        assert_eq!(
+            convert_name("UseTypeScriptAPI.test.ts"),
+            "use-typescript-api.test.ts"
+        );
+    }
+
+    #[test]
+    fn test_number_units() {
+        assert_eq!(pascal_to_kebab_smart("S3Client"), "s3-client");
+        assert_eq!(pascal_to_kebab_smart("Base64Encoder"), "base64-encoder");
+        assert_eq!(pascal_to_kebab_smart("Vector3D"), "vector3d");
+        assert_eq!(pascal_to_kebab_smart("Vector3DMath"), "vector3d-math");
+        assert_eq!(pascal_to_kebab_smart("Render2Dom"), "render2-dom");
+        assert_eq!(
+            place_number_units("vector3-d-math", NumberStyle::Separate),
+            "vector-3d-math"
+        );
+        assert_eq!(
+            place_number_units("scene-2-d-4-k", NumberStyle::Separate),
+            "scene-2d-4k"
+        );
+    }
+
+    #[test]
+    fn test_disk_names_and_imports_agree() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let names = [
+            "MyComponent.test.tsx",
+            "Button.Styles.ts",
+            "UserCard.stories.svelte",
+            "APIClient.d.ts",
+            ".EnvConfig.ts",
+        ];
+
+        for name in names {
+            let path = temp_dir.path().join(name);
+            fs::write(&path, "")?;
+            let renamed = converted_name(
+                &path,
+                &Options::default(),
+                &mut Report::default(),
+            )
+            .unwrap();
+            let on_disk = renamed.to_str().unwrap();
+
+            let (import, _) = update_imports(
+                &format!("import X from './{}';", name),
+                &ScanOptions::default(),
+            );
+            assert_eq!(import, format!("import X from './{}';", on_disk));
+
+            // Extensionless imports must match the on-disk stem as well
+            let stem = name.rsplit_once('.').unwrap().0;
+            let (import, _) = update_imports(
+                &format!("import X from './{}';", stem),
+                &ScanOptions::default(),
+            );
+            let disk_stem = on_disk.rsplit_once('.').unwrap().0;
+            assert_eq!(import, format!("import X from './{}';", disk_stem));
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_needs_conversion() {
         assert!(needs_conversion("MyComponent"));
         assert!(needs_conversion("ButtonComponent"));
         assert!(!needs_conversion("my-component"));
         assert!(!needs_conversion("regular-file"));
+        assert!(needs_conversion_as("my-module.py", NameStyle::Snake));
+        assert!(!needs_conversion("en-US"));
+        assert!(!needs_conversion_as("pt_BR.json", NameStyle::Snake));
+        assert!(needs_conversion("En-US"));
+        assert_eq!(convert_name("En-US"), "en-US");
+    }
+
+    #[test]
+    fn test_snake_case() {
+        let snake = |name| convert_name_as(name, NameStyle::Snake);
+        assert_eq!(snake("UserModel.py"), "user_model.py");
+        assert_eq!(snake("XMLParser.py"), "xml_parser.py");
+        assert_eq!(snake("my-module.py"), "my_module.py");
+        assert_eq!(snake("__init__.py"), "__init__.py");
+        assert_eq!(snake("My_Model.py"), "my_model.py");
     }
 
     #[test]
@@ -409,7 +2722,8 @@ mod tests {
             export type { ExportedType } from './TypeDefinitions';
         "#;
 
-        let (new_content, changes) = update_imports(content);
+        let (new_content, changes) =
+            update_imports(content, &ScanOptions::default());
 
         println!("New content:\n{}", new_content);
 
@@ -425,6 +2739,74 @@ mod tests {
         assert!(new_content.contains("./type-definitions"));
     }
 
+    #[test]
+    fn test_update_imports_preserves_surrounding_bytes() {
+        let content = "\u{FEFF}import A from './MyA';\r\n\
+                       import {\r\n  B,\r\n} from \"./MyB\";\n\
+                       const c = require('./MyC');\r\n";
+
+        let (new_content, changes) =
+            update_imports(content, &ScanOptions::default());
+
+        assert_eq!(changes, 3);
+        assert_eq!(
+            new_content,
+            "\u{FEFF}import A from './my-a';\r\n\
+             import {\r\n  B,\r\n} from \"./my-b\";\n\
+             const c = require('./my-c');\r\n"
+        );
+    }
+
+    #[test]
+    fn test_update_imports_with_backslashes() {
+        let content = r#"import A from '.\\Lib\\MyButton';
+import B from '..\Shared\UserCard.ts';"#;
+
+        let (kept, changes) = update_imports(content, &ScanOptions::default());
+        assert_eq!(changes, 4);
+        assert_eq!(
+            kept,
+            r#"import A from '.\\lib\\my-button';
+import B from '..\shared\user-card.ts';"#
+        );
+
+        let slash = ScanOptions {
+            separators: Separators::Slash,
+            ..Default::default()
+        };
+        let (slashed, _) = update_imports(content, &slash);
+        assert!(slashed.contains("'./lib/my-button'"));
+        assert!(slashed.contains("'../shared/user-card.ts'"));
+
+        let backslash = ScanOptions {
+            separators: Separators::Backslash,
+            ..Default::default()
+        };
+        let (back, changes) =
+            update_imports("import A from './Lib/A';", &backslash);
+        assert_eq!(back, r#"import A from '.\\lib\\a';"#);
+        assert_eq!(changes, 3);
+    }
+
+    #[test]
+    fn test_update_imports_skips_installed_packages() {
+        let content = "import { Image } from 'react-native/Libraries/Image';
+import Logo from '@acme/ui/src/UserCard';
+import X from 'components/UserCard';";
+        let scan = ScanOptions {
+            external: ["react-native".to_string()].into(),
+            ..Default::default()
+        };
+        let (new_content, changes) = update_imports(content, &scan);
+        assert_eq!(changes, 2);
+        assert_eq!(
+            new_content,
+            "import { Image } from 'react-native/Libraries/Image';
+import Logo from '@acme/ui/src/user-card';
+import X from 'components/user-card';"
+        );
+    }
+
     #[test]
     fn test_matches_source_file() {
         assert!(matches_source_file(Path::new("test.ts")));
@@ -434,6 +2816,37 @@ mod tests {
         assert!(!matches_source_file(Path::new("test")));
     }
 
+    #[test]
+    fn test_cli_definition() {
+        // Completions and the man page are generated from this
+        Args::command().debug_assert();
+    }
+
+    #[test]
+    fn test_option_values_are_not_response_files() -> Result<()> {
+        let argv = [
+            "kebabify",
+            "--owned-by",
+            "@org/team",
+            "--exclude",
+            "@x",
+            ".",
+        ];
+        let argv =
+            response_file::expand_args(argv.map(OsString::from), takes_value)?;
+        let args = Args::try_parse_from(argv)?;
+        assert_eq!(args.owned_by.as_deref(), Some("@org/team"));
+        assert_eq!(args.exclude, ["@x"]);
+
+        let argv = ["kebabify", "--owned-by=@org/team", "."];
+        let argv =
+            response_file::expand_args(argv.map(OsString::from), takes_value)?;
+        let args = Args::try_parse_from(argv)?;
+        assert_eq!(args.owned_by.as_deref(), Some("@org/team"));
+        assert!(takes_value("--exec") && !takes_value("--apply"));
+        Ok(())
+    }
+
     mod integration {
         use super::*;
         use std::path::PathBuf;
@@ -464,7 +2877,11 @@ mod tests {
         fn test_rename_files() -> Result<()> {
             let (_temp_dir, test_dir) = setup_test_directory()?;
 
-            process_directory(&test_dir)?;
+            process_directory(
+                &test_dir,
+                &Options::default(),
+                &mut Report::default(),
+            )?;
 
             assert!(test_dir.join("my-component.svelte").exists());
             assert!(test_dir.join("component-library").exists());
@@ -479,7 +2896,12 @@ mod tests {
         fn test_process_imports() -> Result<()> {
             let (_temp_dir, test_dir) = setup_test_directory()?;
 
-            process_imports(&test_dir)?;
+            process_imports(
+                &test_dir,
+                &Plan::default(),
+                &Options::default(),
+                &mut Report::default(),
+            )?;
 
             let content =
                 fs::read_to_string(test_dir.join("MyComponent.svelte"))?;
@@ -490,13 +2912,589 @@ mod tests {
             Ok(())
         }
 
+        #[test]
+        fn test_plan_is_in_path_order() -> Result<()> {
+            let temp_dir = TempDir::new()?;
+            let root = temp_dir.path().join("project");
+            for name in ["Zeta", "Alpha", "Mid"] {
+                fs::create_dir_all(root.join(name))?;
+                fs::write(root.join(name).join("FileB.ts"), "")?;
+                fs::write(root.join(name).join("FileA.ts"), "")?;
+            }
+
+            let plan = plan_directory(
+                &root,
+                &Options::default(),
+                &mut Report::default(),
+            )?;
+            let from: Vec<_> = plan
+                .renames
+                .iter()
+                .map(|r| relative_to(&r.from, &root).to_path_buf())
+                .collect();
+            let mut sorted = from.clone();
+            sorted.sort();
+            assert_eq!(from, sorted);
+            assert_eq!(from.len(), 9);
+            Ok(())
+        }
+
+        #[test]
+        fn test_taken_names_are_numbered() -> Result<()> {
+            let temp_dir = TempDir::new()?;
+            let root = temp_dir.path().join("project");
+            fs::create_dir_all(root.join("Shared"))?;
+            fs::create_dir_all(root.join("shared"))?;
+            fs::write(root.join("Shared/UserCard.ts"), "")?;
+            fs::write(root.join("Shared/user-card.ts"), "")?;
+            fs::write(root.join("shared/Other.ts"), "")?;
+            fs::write(
+                root.join("main.ts"),
+                "import a from './Shared/UserCard';
+import b from './Shared/user-card';
+import c from './shared/Other';
+",
+            )?;
+
+            let mut report = Report::default();
+            convert_directory(
+                &root,
+                true,
+                true,
+                &Options::default(),
+                &mut report,
+            )?;
+
+            assert!(root.join("shared-2/user-card-2.ts").exists());
+            assert!(root.join("shared-2/user-card.ts").exists());
+            assert!(root.join("shared/other.ts").exists());
+            assert_eq!(
+                fs::read_to_string(root.join("main.ts"))?,
+                "import a from './shared-2/user-card-2';
+import b from './shared-2/user-card';
+import c from './shared/other';
+"
+            );
+            let numbered = report
+                .warnings
+                .iter()
+                .filter(|w| w.kind == WarningKind::NumberedName)
+                .count();
+            assert_eq!(numbered, 2);
+            Ok(())
+        }
+
+        #[test]
+        fn test_taken_names_are_overwritten() -> Result<()> {
+            let temp_dir = TempDir::new()?;
+            let root = &temp_dir.path().join("app");
+            fs::create_dir(root)?;
+            fs::write(root.join("UserCard.ts"), "new")?;
+            fs::write(root.join("user-card.ts"), "old")?;
+            let opts = Options {
+                on_conflict: OnConflict::Overwrite,
+                ..Default::default()
+            };
+
+            let mut report = Report::default();
+            convert_directory(root, false, true, &opts, &mut report)?;
+
+            assert_eq!(fs::read_to_string(root.join("user-card.ts"))?, "new");
+            assert!(!root.join("UserCard.ts").exists());
+            assert_eq!(report.warnings[0].kind, WarningKind::Replaced);
+
+            report.journal.rollback(&opts)?;
+            assert_eq!(fs::read_to_string(root.join("UserCard.ts"))?, "new");
+            assert_eq!(fs::read_to_string(root.join("user-card.ts"))?, "old");
+            Ok(())
+        }
+
+        #[test]
+        fn test_generated_files_are_left_alone() -> Result<()> {
+            let (_temp_dir, test_dir) = setup_test_directory()?;
+            let generated = "// Code generated by gen. DO NOT EDIT.\n\
+                             import a from './ComponentLibrary/ButtonComponent.svelte';";
+            fs::write(test_dir.join("ApiClient.ts"), generated)?;
+            let opts = Options {
+                generated_markers: vec!["DO NOT EDIT".into()],
+                ..Default::default()
+            };
+
+            let mut report = Report::default();
+            convert_directory(&test_dir, true, true, &opts, &mut report)?;
+            assert_eq!(
+                fs::read_to_string(test_dir.join("ApiClient.ts"))?,
+                generated
+            );
+            assert!(test_dir.join("my-component.svelte").exists());
+            let kinds: Vec<_> =
+                report.warnings.iter().map(|w| &w.kind).collect();
+            assert_eq!(
+                kinds,
+                [&WarningKind::Generated, &WarningKind::Generated]
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn test_excluded_paths_are_left_alone() -> Result<()> {
+            let (_temp_dir, test_dir) = setup_test_directory()?;
+            let opts = Options {
+                filter: PathFilter::new(&[], &["ComponentLibrary".into()])?,
+                ..Default::default()
+            };
+
+            process_directory(&test_dir, &opts, &mut Report::default())?;
+
+            assert!(test_dir.join("my-component.svelte").exists());
+            assert!(test_dir
+                .join("ComponentLibrary/ButtonComponent.svelte")
+                .exists());
+
+            Ok(())
+        }
+
+        #[test]
+        fn test_git_and_node_modules_are_never_walked() -> Result<()> {
+            let (_temp_dir, test_dir) = setup_test_directory()?;
+            fs::create_dir_all(test_dir.join(".git/refs"))?;
+            fs::write(test_dir.join(".git/HEAD"), "ref: refs/heads/main")?;
+            fs::create_dir_all(test_dir.join("node_modules/SomePkg"))?;
+            fs::write(test_dir.join("node_modules/SomePkg/Index.js"), "")?;
+            let opts = Options {
+                vendored: Vendored::new(&["**".into()])?,
+                ..Default::default()
+            };
+
+            let mut report = Report::default();
+            process_directory(&test_dir, &opts, &mut report)?;
+
+            assert!(test_dir.join("my-component.svelte").exists());
+            assert!(test_dir.join(".git/HEAD").exists());
+            assert!(test_dir.join("node_modules/SomePkg/Index.js").exists());
+            assert_eq!(report.skipped.len(), 2);
+            assert!(report.warnings.is_empty());
+
+            Ok(())
+        }
+
+        #[test]
+        fn test_owned_by_only_limits_renames() -> Result<()> {
+            let temp_dir = TempDir::new()?;
+            let root = &temp_dir.path().join("app");
+            fs::create_dir_all(root.join("src/Assets"))?;
+            fs::write(
+                root.join("CODEOWNERS"),
+                "* @org/platform\n/src/Assets/ @org/frontend\n",
+            )?;
+            fs::write(root.join("src/Assets/Logo.png"), "")?;
+            fs::write(
+                root.join("src/App.vue"),
+                "<template><img src=\"./Assets/Logo.png\"></template>",
+            )?;
+            let opts = Options {
+                filter: PathFilter::default()
+                    .owned_by(codeowners::Owners::load(root, "org/frontend")?),
+                ..Default::default()
+            };
+
+            convert_directory(root, true, true, &opts, &mut Report::default())?;
+
+            assert!(root.join("src/assets/logo.png").exists());
+            assert!(root.join("CODEOWNERS").exists());
+            assert_eq!(
+                fs::read_to_string(root.join("src/App.vue"))?,
+                "<template><img src=\"./assets/logo.png\"></template>"
+            );
+
+            Ok(())
+        }
+
+        #[test]
+        fn test_owned_by_team_from_the_command_line() -> Result<()> {
+            let temp_dir = TempDir::new()?;
+            let root = &temp_dir.path().join("app");
+            fs::create_dir_all(root.join("src/Assets"))?;
+            fs::create_dir_all(root.join("src/Server"))?;
+            fs::write(
+                root.join("CODEOWNERS"),
+                "* @platform\n/src/Assets/ @frontend-team\n",
+            )?;
+            fs::write(root.join("src/Assets/SiteLogo.png"), "")?;
+            fs::write(root.join("src/Server/ApiClient.ts"), "")?;
+
+            let argv = [
+                "kebabify".as_ref(),
+                "--owned-by".as_ref(),
+                "@frontend-team".as_ref(),
+                "--yes".as_ref(),
+                root.as_os_str(),
+            ];
+            let argv = response_file::expand_args(
+                argv.map(OsString::from),
+                takes_value,
+            )?;
+            let args = Args::try_parse_from(argv)?;
+            let opts = options(&args)?;
+            let mut report = Report::default();
+            apply(&args, &opts, &mut report, std::time::Instant::now())?;
+
+            assert!(root.join("src/assets/site-logo.png").exists());
+            assert!(root.join("src/Server/ApiClient.ts").exists());
+            Ok(())
+        }
+
+        #[test]
+        fn test_failed_rename_does_not_stop_the_run() -> Result<()> {
+            let (_temp_dir, test_dir) = setup_test_directory()?;
+            // Converting adds a dash per word, so this name gets too long
+            // for the filesystem
+            let broken = format!("{}.ts", "Ab".repeat(120));
+            fs::write(test_dir.join(&broken), "")?;
+
+            let mut report = Report::new(false, false);
+            process_directory(&test_dir, &Options::default(), &mut report)?;
+
+            assert_eq!(report.failures.len(), 1);
+            assert!(test_dir.join("my-component.svelte").exists());
+
+            let (_temp_dir, test_dir) = setup_test_directory()?;
+            fs::write(test_dir.join(&broken), "")?;
+            let mut report = Report::new(true, false);
+            assert!(process_directory(
+                &test_dir,
+                &Options::default(),
+                &mut report
+            )
+            .is_err());
+
+            Ok(())
+        }
+
+        #[cfg(target_os = "linux")]
+        #[test]
+        fn test_non_utf8_names() -> Result<()> {
+            use std::os::unix::ffi::OsStrExt;
+
+            let (_temp_dir, test_dir) = setup_test_directory()?;
+            let name = OsStr::from_bytes(b"My\xffFile.ts");
+            fs::write(test_dir.join(name), "")?;
+
+            let mut report = Report::default();
+            process_directory(&test_dir, &Options::default(), &mut report)?;
+            assert_eq!(report.warnings.len(), 1);
+            assert!(test_dir.join(name).exists());
+
+            let opts = Options {
+                convert_non_utf8: true,
+                ..Default::default()
+            };
+            process_directory(&test_dir, &opts, &mut Report::default())?;
+            assert!(test_dir
+                .join(OsStr::from_bytes(b"my\xfffile.ts"))
+                .exists());
+
+            Ok(())
+        }
+
+        #[test]
+        fn test_imports_keep_file_encoding() -> Result<()> {
+            let (_temp_dir, test_dir) = setup_test_directory()?;
+            let utf16 = test_dir.join("Wide.ts");
+            let mut bytes = vec![0xFF, 0xFE];
+            for unit in "import A from './ComponentLibrary/A';".encode_utf16() {
+                bytes.extend_from_slice(&unit.to_le_bytes());
+            }
+            fs::write(&utf16, bytes)?;
+            let latin1 = test_dir.join("Legacy.ts");
+            fs::write(&latin1, b"// caf\xE9\nimport A from './MyThing';")?;
+            fs::write(test_dir.join("Binary.ts"), b"\x00\xFFImport")?;
+
+            let mut report = Report::default();
+            process_imports(
+                &test_dir,
+                &Plan::default(),
+                &Options::default(),
+                &mut report,
+            )?;
+
+            let (text, encoding) =
+                encoding::decode(&fs::read(&utf16)?).unwrap();
+            assert_eq!(encoding, encoding::Encoding::Utf16Le);
+            assert!(text.contains("./component-library/a"));
+            assert_eq!(
+                fs::read(&latin1)?,
+                b"// caf\xE9\nimport A from './my-thing';"
+            );
+            assert_eq!(report.warnings.len(), 1);
+
+            Ok(())
+        }
+
+        #[cfg(unix)]
+        #[test]
+        fn test_unreadable_entries_are_reported() -> Result<()> {
+            let (_temp_dir, test_dir) = setup_test_directory()?;
+            // Following a dangling link fails the same way an unreadable
+            // entry does, and works even when tests run as root
+            std::os::unix::fs::symlink(
+                test_dir.join("Missing.ts"),
+                test_dir.join("Dangling.ts"),
+            )?;
+            let opts = Options {
+                follow_links: true,
+                ..Default::default()
+            };
+
+            let mut report = Report::new(false, false);
+            process_directory(&test_dir, &opts, &mut report)?;
+            assert_eq!(report.warnings[0].kind, WarningKind::Unreadable);
+            assert!(test_dir.join("my-component.svelte").exists());
+
+            let mut report = Report::new(false, true);
+            assert!(process_imports(
+                &test_dir,
+                &Plan::default(),
+                &opts,
+                &mut report
+            )
+            .is_err());
+
+            Ok(())
+        }
+
+        #[cfg(unix)]
+        #[test]
+        fn test_symlinks_follow_renamed_targets() -> Result<()> {
+            let (_temp_dir, test_dir) = setup_test_directory()?;
+            fs::create_dir(test_dir.join("Links"))?;
+            std::os::unix::fs::symlink(
+                "../ComponentLibrary/ButtonComponent.svelte",
+                test_dir.join("Links/Button.svelte"),
+            )?;
+
+            let plan = process_directory(
+                &test_dir,
+                &Options::default(),
+                &mut Report::default(),
+            )?;
+
+            let link = test_dir.join("links/button.svelte");
+            assert_eq!(
+                fs::read_link(&link)?,
+                Path::new("../component-library/button-component.svelte")
+            );
+            assert!(link.exists());
+            assert_eq!(
+                plan.target_of(
+                    &test_dir.join("ComponentLibrary/ButtonComponent.svelte")
+                ),
+                Some(
+                    test_dir.join("component-library/button-component.svelte")
+                )
+            );
+
+            Ok(())
+        }
+
+        #[cfg(unix)]
+        #[test]
+        fn test_files_reachable_twice_are_processed_once() -> Result<()> {
+            let (_temp_dir, test_dir) = setup_test_directory()?;
+            std::os::unix::fs::symlink(
+                "ComponentLibrary",
+                test_dir.join("Alias"),
+            )?;
+            fs::hard_link(
+                test_dir.join("MyComponent.svelte"),
+                test_dir.join("Linked.svelte"),
+            )?;
+
+            let opts = Options {
+                follow_links: true,
+                ..Default::default()
+            };
+            let mut report = Report::default();
+            let sources = source_files(&test_dir, &opts, &mut report)?;
+            let names: Vec<_> =
+                sources.iter().map(|e| e.file_name().to_owned()).collect();
+            assert_eq!(names.len(), 2);
+            assert!(!sources
+                .iter()
+                .any(|e| e.path().starts_with(test_dir.join("Alias"))));
+
+            let plan = process_directory(&test_dir, &opts, &mut report)?;
+            assert!(report.failures.is_empty());
+            assert!(test_dir
+                .join("component-library/button-component.svelte")
+                .exists());
+            assert!(test_dir.join("linked.svelte").exists());
+            assert_eq!(plan.renames.len(), 5);
+
+            Ok(())
+        }
+
+        #[test]
+        fn test_large_plans_need_confirmation() -> Result<()> {
+            let (_temp_dir, test_dir) = setup_test_directory()?;
+            let opts = Options {
+                max_changes: Some(1),
+                ..Default::default()
+            };
+
+            let mut report = Report::default();
+            let result =
+                convert_directory(&test_dir, true, true, &opts, &mut report);
+            assert!(result.is_err());
+            assert!(test_dir.join("ComponentLibrary").exists());
+            assert!(fs::read_to_string(test_dir.join("MyComponent.svelte"))?
+                .contains("./ComponentLibrary/ButtonComponent.svelte"));
+            Ok(())
+        }
+
+        #[cfg(unix)]
+        #[test]
+        fn test_symlinks_never_escape_the_root() -> Result<()> {
+            let (temp_dir, test_dir) = setup_test_directory()?;
+            let outside = temp_dir.path().join("Outside");
+            fs::create_dir(&outside)?;
+            fs::write(outside.join("SecretFile.ts"), "")?;
+            std::os::unix::fs::symlink(&outside, test_dir.join("Escape"))?;
+            std::os::unix::fs::symlink("..", test_dir.join("Parent"))?;
+
+            // Not followed by default: only the links themselves are renamed
+            let mut report = Report::default();
+            process_directory(&test_dir, &Options::default(), &mut report)?;
+            assert!(test_dir.join("escape").is_symlink());
+            assert!(outside.join("SecretFile.ts").exists());
+
+            let opts = Options {
+                follow_links: true,
+                ..Default::default()
+            };
+            let mut report = Report::default();
+            let plan = process_directory(&test_dir, &opts, &mut report)?;
+            assert!(outside.join("SecretFile.ts").exists());
+            assert!(temp_dir.path().join("Outside").exists());
+            assert!(plan.renames.iter().all(|r| r.from.starts_with(&test_dir)
+                && !r.from.starts_with(test_dir.join("escape"))
+                && !r.from.starts_with(test_dir.join("parent"))));
+            assert_eq!(
+                report
+                    .warnings
+                    .iter()
+                    .filter(|w| w.kind == WarningKind::OutsideRoot)
+                    .count(),
+                2
+            );
+
+            Ok(())
+        }
+
+        #[test]
+        fn test_process_mapping() -> Result<()> {
+            let (temp_dir, test_dir) = setup_test_directory()?;
+            let mapping = temp_dir.path().join("renames.csv");
+            fs::write(
+                &mapping,
+                "old_path,new_path\n\
+                 ComponentLibrary,ui\n\
+                 MyComponent.svelte,pages/home.svelte\n",
+            )?;
+
+            process_mapping(
+                &test_dir,
+                &mapping,
+                &Options::default(),
+                &mut Report::default(),
+            )?;
+
+            assert!(test_dir.join("ui/ButtonComponent.svelte").exists());
+            let content =
+                fs::read_to_string(test_dir.join("pages/home.svelte"))?;
+            assert!(content.contains("'../ui/ButtonComponent.svelte'"));
+
+            Ok(())
+        }
+
+        #[test]
+        fn test_process_pending() -> Result<()> {
+            let (_temp_dir, test_dir) = setup_test_directory()?;
+            pending::queue(
+                &test_dir,
+                Path::new("ComponentLibrary"),
+                Path::new("ui"),
+            )?;
+
+            let plan = process_pending(
+                &test_dir,
+                &Options::default(),
+                &mut Report::default(),
+            )?;
+
+            assert_eq!(plan.renames.len(), 1);
+            assert!(test_dir.join("ui/ButtonComponent.svelte").exists());
+            let content =
+                fs::read_to_string(test_dir.join("MyComponent.svelte"))?;
+            assert!(content.contains("'./ui/ButtonComponent.svelte'"));
+            assert!(!test_dir.join(pending::PENDING_FILE).exists());
+
+            Ok(())
+        }
+
+        #[test]
+        fn test_aliases_follow_their_renamed_directories() -> Result<()> {
+            let temp_dir = TempDir::new()?;
+            let root = &temp_dir.path().join("app");
+            fs::create_dir_all(root.join("src/Components"))?;
+            fs::create_dir_all(root.join("src/pages/Deep"))?;
+            fs::write(
+                root.join("tsconfig.json"),
+                r#"{ "compilerOptions": { "paths": {
+                    "@components/*": ["src/Components/*"] } } }"#,
+            )?;
+            fs::write(root.join("src/Components/UserCard.ts"), "")?;
+            fs::write(
+                root.join("src/pages/Deep/Page.ts"),
+                "import card from '../../Components/UserCard';",
+            )?;
+            let opts = Options {
+                scan: ScanOptions {
+                    aliases: aliases::load(root, &[])?,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+
+            convert_directory(root, true, true, &opts, &mut Report::default())?;
+
+            assert_eq!(
+                fs::read_to_string(root.join("src/pages/deep/page.ts"))?,
+                "import card from '@components/user-card';"
+            );
+            let tsconfig = fs::read_to_string(root.join("tsconfig.json"))?;
+            assert!(tsconfig.contains(r#"["src/components/*"]"#));
+            assert!(root.join("src/components/user-card.ts").exists());
+
+            Ok(())
+        }
+
         #[test]
         fn test_full_process() -> Result<()> {
             let (_temp_dir, test_dir) = setup_test_directory()?;
 
             // Process both imports and filenames
-            process_imports(&test_dir)?;
-            process_directory(&test_dir)?;
+            process_imports(
+                &test_dir,
+                &Plan::default(),
+                &Options::default(),
+                &mut Report::default(),
+            )?;
+            process_directory(
+                &test_dir,
+                &Options::default(),
+                &mut Report::default(),
+            )?;
 
             // Check if files were renamed
             assert!(test_dir.join("my-component.svelte").exists());
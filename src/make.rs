@@ -0,0 +1,255 @@
+use crate::doctor::Finding;
+use crate::plan::Plan;
+use crate::scanner::{self, rewrite_file_path, splice, ScanOptions, Scanner};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// The names make, just and Task look for a file by
+const TOOL_FILES: [&str; 10] = [
+    "Makefile",
+    "makefile",
+    "GNUmakefile",
+    "justfile",
+    "Justfile",
+    ".justfile",
+    "Taskfile.yml",
+    "Taskfile.yaml",
+    "taskfile.yml",
+    "taskfile.yaml",
+];
+
+/// Makefiles, justfiles and Taskfiles, for the paths in their targets,
+/// prerequisites and recipes
+pub struct MakeScanner;
+
+impl Scanner for MakeScanner {
+    fn matches(&self, path: &Path) -> bool {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        is_tool_file(name) || name.ends_with(".mk")
+    }
+
+    /// The files the tools find by name. Included `.mk` files can be named
+    /// anything.
+    fn pins_name(&self, path: &Path) -> Option<&'static str> {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        is_tool_file(name)
+            .then_some("make, just or Task looks for it by this name")
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_paths(path, content, scanner::converted_path)
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_paths(path, content, |found| plan.target_of(found))
+    }
+
+    /// Prerequisites and included makefiles that aren't files and that no
+    /// rule makes, the files a justfile imports and the Taskfiles a
+    /// Taskfile includes, when they lead nowhere. Other words may be files
+    /// a recipe creates.
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> Vec<String> {
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let pattern = match name.to_lowercase().as_str() {
+            "justfile" | ".justfile" => {
+                r#"(?m)^[ \t]*(?:import|mod[ \t]+\w+)\??[ \t]+['"]([^'"\n]+)['"]"#
+            }
+            name if name.starts_with("taskfile") => {
+                r#"(?m)^[ \t]*taskfile:[ \t]*["']?([^"'#\s]+)"#
+            }
+            _ => return makefile_orphans(dir, content),
+        };
+        Regex::new(pattern)
+            .unwrap()
+            .captures_iter(content)
+            .map(|caps| caps[1].to_string())
+            .filter(|file| scanner::leads_nowhere(dir, file, &[]))
+            .collect()
+    }
+
+    /// Paths built from a variable can't be checked against the disk
+    fn findings(&self, path: &Path, content: &str) -> Vec<Finding> {
+        words(content)
+            .into_iter()
+            .filter(|(start, word)| {
+                after_variable(content, *start)
+                    && word
+                        .split('/')
+                        .any(|s| s.chars().any(char::is_uppercase))
+            })
+            .map(|(start, word)| {
+                let variable = content[..start]
+                    .rfind('$')
+                    .map_or("", |i| &content[i..start]);
+                Finding {
+                    path: path.to_path_buf(),
+                    problem: format!(
+                        "'{}{}' is built from a variable, so it isn't updated",
+                        variable, word
+                    ),
+                    fix: "update it by hand if the renames affect it"
+                        .to_string(),
+                }
+            })
+            .collect()
+    }
+}
+
+fn is_tool_file(name: &str) -> bool {
+    TOOL_FILES.contains(&name)
+}
+
+/// The prerequisites in a makefile, and the files it `include`s, that
+/// aren't files and aren't targets of its own rules
+fn makefile_orphans(dir: &Path, content: &str) -> Vec<String> {
+    let rule =
+        Regex::new(r"(?m)^([^\t#=:\n][^#=:\n]*)::?[ \t]*([^=;#\n]*)$").unwrap();
+    let include = Regex::new(r"(?m)^include[ \t]+([^#\n]*)").unwrap();
+    let mut targets = Vec::new();
+    let mut needed = Vec::new();
+    for caps in rule.captures_iter(content) {
+        targets.extend(caps.get(1).unwrap().as_str().split_whitespace());
+        let prerequisites = caps.get(2).unwrap();
+        needed.extend(
+            words(prerequisites.as_str())
+                .into_iter()
+                .filter(|&(start, _)| {
+                    !after_variable(content, prerequisites.start() + start)
+                })
+                .map(|(_, word)| word),
+        );
+    }
+    for caps in include.captures_iter(content) {
+        let files = caps.get(1).unwrap().as_str();
+        needed.extend(files.split_whitespace().filter(|f| !f.contains('$')));
+    }
+    needed
+        .into_iter()
+        .filter(|file| {
+            !file.starts_with('/')
+                && !targets.contains(file)
+                && scanner::leads_nowhere(dir, file, &[])
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+/// Words that look like paths, with where they start: they have a `/` or an
+/// extension, and aren't flags or URLs
+fn words(content: &str) -> Vec<(usize, &str)> {
+    let word = Regex::new(r"[\w./@+-]+").unwrap();
+    word.find_iter(content)
+        .filter(|m| {
+            let text = m.as_str();
+            (text.contains('/') || text.trim_start_matches('.').contains('.'))
+                && !text.starts_with('-')
+                && !content[m.end()..].starts_with("://")
+        })
+        .map(|m| (m.start(), m.as_str()))
+        .collect()
+}
+
+/// Whether the word at `start` follows a variable, like `$(SRC)/App.ts`
+fn after_variable(content: &str, start: usize) -> bool {
+    let before = &content[..start];
+    before.ends_with(')') || before.ends_with('}')
+}
+
+/// Rewrites path words that resolve against the file's directory, where
+/// make, just and task run their recipes
+fn rewrite_paths(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut edits = Vec::new();
+    for (start, word) in words(content) {
+        if word.starts_with('/') || after_variable(content, start) {
+            continue;
+        }
+        edits.extend(rewrite_file_path(dir, word, start, &[], &rename));
+    }
+    splice(content, edits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_makefile_paths() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("Src"))?;
+        fs::write(root.join("Src/AppServer.ts"), "")?;
+
+        let content = "\
+SRC = Src
+build/AppServer.js: Src/AppServer.ts
+\ttsc Src/AppServer.ts --outDir build
+\tcp $(SRC)/AppServer.ts dist/ && curl https://Example.com/X.js
+";
+        let path = root.join("Makefile");
+        assert!(scanner::pinned(&path, false).is_some());
+        assert!(scanner::pinned(&root.join("Taskfile.yml"), false).is_some());
+        assert!(scanner::pinned(&root.join("Rules.mk"), false).is_none());
+        let (new_content, changes) =
+            MakeScanner.convert(&path, content, &ScanOptions::default());
+        assert_eq!(
+            new_content,
+            "\
+SRC = Src
+build/AppServer.js: src/app-server.ts
+\ttsc src/app-server.ts --outDir build
+\tcp $(SRC)/AppServer.ts dist/ && curl https://Example.com/X.js
+"
+        );
+        assert_eq!(changes, 4);
+
+        let findings = MakeScanner.findings(&path, content);
+        assert_eq!(findings.len(), 1);
+        assert!(findings[0].problem.contains("$(SRC)/AppServer.ts"));
+
+        let content = "\
+include Rules/Gone.mk
+.PHONY: all
+all: build/AppServer.js build/Other.js
+build/AppServer.js: Src/AppServer.ts Src/Removed.ts | $(DIST)/x.js
+\ttsc Src/AppServer.ts --outDir build
+";
+        let orphans =
+            MakeScanner.orphans(&path, content, &ScanOptions::default());
+        assert_eq!(
+            orphans,
+            ["build/Other.js", "Src/Removed.ts", "Rules/Gone.mk"]
+        );
+        let orphans = MakeScanner.orphans(
+            &root.join("justfile"),
+            "import 'Src/AppServer.ts'\nimport? 'Tasks/Gone.just'\n",
+            &ScanOptions::default(),
+        );
+        assert_eq!(orphans, ["Tasks/Gone.just"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+use crate::plan::Plan;
+use anyhow::{bail, Context, Result};
+use std::io::Write;
+use std::path::Path;
+
+/// Loads an `old_path,new_path` mapping file into a plan. Paths are taken
+/// relative to `root`; a tab-separated file is read as TSV.
+pub fn load_mapping(file: &Path, root: &Path) -> Result<Plan> {
+    let is_tsv = file
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("tsv"));
+
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .delimiter(if is_tsv { b'\t' } else { b',' })
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_path(file)
+        .with_context(|| {
+            format!("Failed to read mapping file: {}", file.display())
+        })?;
+
+    let mut plan = Plan::default();
+    for (index, record) in reader.records().enumerate() {
+        let record = record.with_context(|| {
+            format!("Malformed row {} in {}", index + 1, file.display())
+        })?;
+        let fields: Vec<&str> = record.iter().collect();
+
+        match fields.as_slice() {
+            [] | [""] => continue,
+            [old, new, ..] if index == 0 && is_header(old, new) => continue,
+            [old, new, ..] if !old.is_empty() && !new.is_empty() => {
+                plan.push(root.join(old), root.join(new));
+            }
+            _ => bail!(
+                "Row {} in {} must have an old and a new path",
+                index + 1,
+                file.display()
+            ),
+        }
+    }
+    plan.compose();
+    Ok(plan)
+}
+
+/// Writes `plan` as an `old_path,new_path` CSV with paths relative to
+/// `root`, in the format `load_mapping` reads
+pub fn write_mapping(plan: &Plan, root: &Path, out: impl Write) -> Result<()> {
+    let mut writer = csv::Writer::from_writer(out);
+    writer.write_record(["old_path", "new_path"])?;
+    for rename in &plan.renames {
+        writer.write_record([
+            crate::relative_to(&rename.from, root)
+                .to_string_lossy()
+                .as_ref(),
+            crate::relative_to(&rename.to, root)
+                .to_string_lossy()
+                .as_ref(),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn is_header(old: &str, new: &str) -> bool {
+    matches!(
+        (old.to_lowercase().as_str(), new.to_lowercase().as_str()),
+        ("old_path", "new_path") | ("old", "new") | ("from", "to")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_mapping() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let csv = temp_dir.path().join("renames.csv");
+        fs::write(
+            &csv,
+            "old_path,new_path\nLib/Button.ts,lib/button.ts\n\"A, B.ts\",a-b.ts\n",
+        )?;
+        let tsv = temp_dir.path().join("renames.tsv");
+        fs::write(&tsv, "Lib/Button.ts\tlib/button.ts\n")?;
+
+        let root = Path::new("/root");
+        let plan = load_mapping(&csv, root)?;
+        assert_eq!(plan.renames.len(), 2);
+        assert_eq!(plan.renames[1].from, root.join("A, B.ts"));
+        assert_eq!(load_mapping(&tsv, root)?.renames.len(), 1);
+
+        let mut written = Vec::new();
+        write_mapping(&plan, root, &mut written)?;
+        fs::write(&csv, written)?;
+        assert_eq!(load_mapping(&csv, root)?.renames, plan.renames);
+
+        fs::write(&csv, "Lib/Button.ts\n")?;
+        assert!(load_mapping(&csv, root).is_err());
+        Ok(())
+    }
+}
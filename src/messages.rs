@@ -0,0 +1,278 @@
+use crate::report::WarningKind;
+use clap::ValueEnum;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+/// The language of messages meant for people: prompts, progress and the
+/// summary. Machine formats (JSON output, the journal, logs) stay English.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Lang {
+    #[default]
+    En,
+    Es,
+}
+
+static LANG: OnceLock<Lang> = OnceLock::new();
+
+/// Sets the language for the rest of the run. Only the first call counts.
+pub fn set(lang: Lang) {
+    let _ = LANG.set(lang);
+}
+
+fn lang() -> Lang {
+    LANG.get().copied().unwrap_or_default()
+}
+
+/// A message in the catalog. `{0}`, `{1}`... in its text stand for the
+/// arguments it's given.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Msg {
+    Renaming,
+    Trashing,
+    Deleting,
+    RetargetingLink,
+    UpdatedImports,
+    RenamingClass,
+    UpdatedClasses,
+    WouldRename,
+    WouldUpdate,
+    WouldRenameClass,
+    DryRun,
+    GuessedNames,
+    GuessedName,
+    ApplyGuesses,
+    GuessesDeclined,
+    ApplyLargePlan,
+    PlanTooLarge,
+    RollBack,
+    Interrupted,
+    Skipped,
+    Warnings,
+    Failures,
+    Locked,
+    YesNo,
+}
+
+impl Msg {
+    #[cfg(test)]
+    const ALL: [Msg; 24] = [
+        Msg::Renaming,
+        Msg::Trashing,
+        Msg::Deleting,
+        Msg::RetargetingLink,
+        Msg::UpdatedImports,
+        Msg::RenamingClass,
+        Msg::UpdatedClasses,
+        Msg::WouldRename,
+        Msg::WouldUpdate,
+        Msg::WouldRenameClass,
+        Msg::DryRun,
+        Msg::GuessedNames,
+        Msg::GuessedName,
+        Msg::ApplyGuesses,
+        Msg::GuessesDeclined,
+        Msg::ApplyLargePlan,
+        Msg::PlanTooLarge,
+        Msg::RollBack,
+        Msg::Interrupted,
+        Msg::Skipped,
+        Msg::Warnings,
+        Msg::Failures,
+        Msg::Locked,
+        Msg::YesNo,
+    ];
+
+    fn english(self) -> &'static str {
+        match self {
+            Msg::Renaming => "Renaming: {0} -> {1}",
+            Msg::Trashing => "Trashing: {0}",
+            Msg::Deleting => "Deleting: {0}",
+            Msg::RetargetingLink => "Retargeting link: {0} -> {1}",
+            Msg::UpdatedImports => "Updated {0} imports in: {1}",
+            Msg::RenamingClass => "Renaming class: {0} -> {1}",
+            Msg::UpdatedClasses => "Updated {0} class names in: {1}",
+            Msg::WouldRename => "Would rename: {0} -> {1}",
+            Msg::WouldUpdate => "Would update {0} references in: {1}",
+            Msg::WouldRenameClass => "Would rename class: {0} -> {1}",
+            Msg::DryRun => {
+                "Dry run: {0} renames and {1} files to update, nothing was \
+                 changed"
+            }
+            Msg::GuessedNames => "Names converted by a guess:",
+            Msg::GuessedName => "  {0} -> {1} ({2}; could also be {3})",
+            Msg::ApplyGuesses => "Apply these conversions?",
+            Msg::GuessesDeclined => {
+                "Stopped before changing anything. Rename these by hand, or \
+                 with --mapping, then run again"
+            }
+            Msg::ApplyLargePlan => {
+                "The plan renames {0} files and directories, more than the \
+                 {1} --max-changes allows. Apply it?"
+            }
+            Msg::PlanTooLarge => {
+                "The plan renames {0} files and directories, more than the \
+                 {1} --max-changes allows. Check the path, then raise \
+                 --max-changes or pass --yes to apply it."
+            }
+            Msg::RollBack => "Verification failed. Roll back all changes?",
+            Msg::Interrupted => {
+                "Interrupted. Run `kebabify resume {0}` to finish."
+            }
+            Msg::Skipped => "{0} directories left out:",
+            Msg::Warnings => "{0} warning(s) ({1}):",
+            Msg::Failures => "{0} operation(s) failed:",
+            Msg::Locked => {
+                "These files were locked by another process. Close any \
+                 editors or indexers using them and run again:"
+            }
+            Msg::YesNo => "[y/N]",
+        }
+    }
+
+    fn spanish(self) -> &'static str {
+        match self {
+            Msg::Renaming => "Renombrando: {0} -> {1}",
+            Msg::Trashing => "Moviendo a la papelera: {0}",
+            Msg::Deleting => "Eliminando: {0}",
+            Msg::RetargetingLink => "Redirigiendo enlace: {0} -> {1}",
+            Msg::UpdatedImports => "{0} importaciones actualizadas en: {1}",
+            Msg::RenamingClass => "Renombrando clase: {0} -> {1}",
+            Msg::UpdatedClasses => "{0} nombres de clase actualizados en: {1}",
+            Msg::WouldRename => "Se renombraría: {0} -> {1}",
+            Msg::WouldUpdate => "Se actualizarían {0} referencias en: {1}",
+            Msg::WouldRenameClass => "Se renombraría la clase: {0} -> {1}",
+            Msg::DryRun => {
+                "Simulación: {0} renombrados y {1} archivos por actualizar, \
+                 no se cambió nada"
+            }
+            Msg::GuessedNames => "Nombres convertidos por suposición:",
+            Msg::GuessedName => "  {0} -> {1} ({2}; también podría ser {3})",
+            Msg::ApplyGuesses => "¿Aplicar estas conversiones?",
+            Msg::GuessesDeclined => {
+                "Detenido antes de cambiar nada. Renómbrelos a mano, o con \
+                 --mapping, y vuelva a ejecutar"
+            }
+            Msg::ApplyLargePlan => {
+                "El plan renombra {0} archivos y directorios, más de los {1} \
+                 que permite --max-changes. ¿Aplicarlo?"
+            }
+            Msg::PlanTooLarge => {
+                "El plan renombra {0} archivos y directorios, más de los {1} \
+                 que permite --max-changes. Revise la ruta y luego aumente \
+                 --max-changes o pase --yes para aplicarlo."
+            }
+            Msg::RollBack => {
+                "La verificación falló. ¿Revertir todos los cambios?"
+            }
+            Msg::Interrupted => {
+                "Interrumpido. Ejecute `kebabify resume {0}` para terminar."
+            }
+            Msg::Skipped => "{0} directorios omitidos:",
+            Msg::Warnings => "{0} advertencia(s) ({1}):",
+            Msg::Failures => "{0} operación(es) fallaron:",
+            Msg::Locked => {
+                "Otro proceso tenía bloqueados estos archivos. Cierre los \
+                 editores o indexadores que los usen y vuelva a ejecutar:"
+            }
+            Msg::YesNo => "[s/N]",
+        }
+    }
+}
+
+/// `msg` in the run's language, with its placeholders filled in from `args`
+pub fn text(msg: Msg, args: &[&dyn Display]) -> String {
+    let template = match lang() {
+        Lang::En => msg.english(),
+        Lang::Es => msg.spanish(),
+    };
+    fill(template, args)
+}
+
+/// Replaces each `{N}` in `template` with `args[N]`, in one pass so an
+/// argument that happens to contain `{1}` is left as it is
+fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut text = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        text.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let arg = after.find('}').and_then(|end| {
+            Some((args.get(after[..end].parse::<usize>().ok()?)?, end))
+        });
+        match arg {
+            Some((arg, end)) => {
+                text.push_str(&arg.to_string());
+                rest = &after[end + 1..];
+            }
+            None => {
+                text.push('{');
+                rest = after;
+            }
+        }
+    }
+    text.push_str(rest);
+    text
+}
+
+/// Whether `answer` to a `Msg::YesNo` question means yes
+pub fn is_yes(answer: &str) -> bool {
+    match lang() {
+        Lang::En => matches!(answer, "y" | "Y" | "yes"),
+        Lang::Es => matches!(answer, "s" | "S" | "si" | "sí" | "y" | "Y"),
+    }
+}
+
+/// What the summary calls a kind of warning
+pub fn warning_kind(kind: WarningKind) -> String {
+    let spanish = match kind {
+        _ if lang() == Lang::En => return kind.to_string(),
+        WarningKind::Unreadable => "ilegible",
+        WarningKind::NonUtf8Name => "nombre no UTF-8",
+        WarningKind::UnknownEncoding => "codificación desconocida",
+        WarningKind::DanglingSymlink => "enlace simbólico roto",
+        WarningKind::PinnedName => "nombre conservado",
+        WarningKind::NumberedName => "nombre numerado",
+        WarningKind::Replaced => "archivo reemplazado",
+        WarningKind::OutsideRoot => "fuera de la raíz",
+        WarningKind::MetadataLost => "metadatos no conservados",
+        WarningKind::TooLong => "nombre demasiado largo",
+        WarningKind::LongName => "nombre largo",
+        WarningKind::Vendored => "directorio de terceros",
+        WarningKind::Generated => "archivo generado",
+        WarningKind::CommentedImport => "importación comentada",
+        WarningKind::UnresolvedImport => "importación sin resolver",
+        WarningKind::Vetoed => "renombrado vetado",
+        WarningKind::DescribeBlock => "nombre de prueba obsoleto",
+        WarningKind::ClassKept => "clase conservada",
+        WarningKind::DefaultImport => "nombre de importación por defecto",
+        WarningKind::RenameCaveat => "pendiente a mano",
+    };
+    spanish.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalogs_agree() {
+        let placeholders = |text: &str| -> Vec<usize> {
+            (0..5)
+                .filter(|i| text.contains(&format!("{{{}}}", i)))
+                .collect()
+        };
+        for msg in Msg::ALL {
+            assert_eq!(
+                placeholders(msg.english()),
+                placeholders(msg.spanish()),
+                "{:?}",
+                msg
+            );
+        }
+        assert_eq!(
+            fill(Msg::Renaming.spanish(), &[&"A.ts", &"a.ts"]),
+            "Renombrando: A.ts -> a.ts"
+        );
+        assert_eq!(fill("{1} {0}", &[&"{1}", &"b"]), "b {1}");
+    }
+}
@@ -0,0 +1,206 @@
+use crate::doctor::Finding;
+use crate::history::{self, format_time};
+use crate::pending::portable;
+use crate::plan::Rename;
+use crate::report::Report;
+use crate::{orphans, relative_to, Options};
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Files that make the directory they're in a package of its own
+const MANIFESTS: &[&str] = &[
+    "package.json",
+    "Cargo.toml",
+    "go.mod",
+    "pyproject.toml",
+    "composer.json",
+    "Gemfile",
+];
+
+/// Prints a summary of the last run under `root`, or of every run with
+/// `all`: the renames grouped by package, with counts, and the references
+/// still left pointing at missing files. With `markdown`, it's formatted to
+/// paste into a pull request.
+pub fn print(
+    root: &Path,
+    all: bool,
+    markdown: bool,
+    opts: &Options,
+    report: &mut Report,
+) -> Result<()> {
+    let runs = history::load(root)?;
+    let runs = if all {
+        &runs[..]
+    } else {
+        &runs[runs.len().saturating_sub(1)..]
+    };
+    let Some(last) = runs.last() else {
+        println!("No renames recorded yet");
+        return Ok(());
+    };
+    let renames: Vec<&Rename> =
+        runs.iter().flat_map(|run| &run.renames.renames).collect();
+    let follow_ups = orphans::find(root, opts, report)?;
+
+    let when = if runs.len() == 1 {
+        format!("on {} UTC", format_time(last.time))
+    } else {
+        format!("in {} runs", runs.len())
+    };
+    print!("{}", summary(root, &renames, &follow_ups, &when, markdown));
+    Ok(())
+}
+
+fn summary(
+    root: &Path,
+    renames: &[&Rename],
+    follow_ups: &[Finding],
+    when: &str,
+    markdown: bool,
+) -> String {
+    let mut packages: BTreeMap<PathBuf, Vec<&Rename>> = BTreeMap::new();
+    for rename in renames {
+        packages
+            .entry(package(root, &rename.to))
+            .or_default()
+            .push(rename);
+    }
+    let directories = renames.iter().filter(|r| r.to.is_dir()).count();
+    let totals = format!(
+        "Renamed {} and {} across {} {}",
+        count(renames.len() - directories, "file", "files"),
+        count(directories, "directory", "directories"),
+        count(packages.len(), "package", "packages"),
+        when
+    );
+    let name = |dir: &Path| {
+        let dir = portable(relative_to(dir, root));
+        if dir.is_empty() {
+            ".".to_string()
+        } else {
+            dir
+        }
+    };
+
+    let mut out = String::new();
+    if !markdown {
+        out += &format!("{}\n", totals);
+        for (dir, renames) in &packages {
+            let renamed = count(renames.len(), "rename", "renames");
+            out += &format!("  {}: {}\n", name(dir), renamed);
+        }
+        if !follow_ups.is_empty() {
+            out += "Manual follow-ups:\n";
+        }
+        for finding in follow_ups {
+            let path = portable(relative_to(&finding.path, root));
+            out += &format!("  {}: {}\n", path, finding.problem);
+        }
+        return out;
+    }
+
+    let code =
+        |path: &Path| format!("`{}`", portable(path).replace('|', "\\|"));
+    out += &format!("## Kebab-case migration\n\n{}.\n", totals);
+    for (dir, renames) in &packages {
+        out += &format!(
+            "\n### `{}` ({})\n\n| From | To |\n| --- | --- |\n",
+            name(dir),
+            count(renames.len(), "rename", "renames")
+        );
+        for rename in renames {
+            out += &format!(
+                "| {} | {} |\n",
+                code(relative_to(&rename.from, dir)),
+                code(relative_to(&rename.to, dir))
+            );
+        }
+    }
+    out += "\n### Manual follow-ups\n\n";
+    if follow_ups.is_empty() {
+        out += "None: every reference points at an existing file.\n";
+    }
+    for finding in follow_ups {
+        out += &format!(
+            "- [ ] {}: {}\n",
+            code(relative_to(&finding.path, root)),
+            finding.problem
+        );
+    }
+    out
+}
+
+fn count(n: usize, one: &str, many: &str) -> String {
+    format!("{} {}", n, if n == 1 { one } else { many })
+}
+
+/// The closest directory above `path`, within `root`, with a package
+/// manifest, or `root` itself
+fn package(root: &Path, path: &Path) -> PathBuf {
+    path.ancestors()
+        .skip(1)
+        .take_while(|dir| dir.starts_with(root))
+        .find(|dir| MANIFESTS.iter().any(|m| dir.join(m).is_file()))
+        .unwrap_or(root)
+        .to_path_buf()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan::Plan;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_markdown_summary() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = &temp_dir.path().join("project");
+        fs::create_dir_all(root.join("packages/ui/src/user-card"))?;
+        fs::write(root.join("packages/ui/package.json"), "{}")?;
+        fs::write(root.join("packages/ui/src/user-card/index.ts"), "")?;
+        fs::write(root.join("main-app.ts"), "")?;
+
+        let mut plan = Plan::default();
+        plan.push(
+            root.join("packages/ui/src/UserCard"),
+            root.join("packages/ui/src/user-card"),
+        );
+        plan.push(root.join("MainApp.ts"), root.join("main-app.ts"));
+        let renames: Vec<&Rename> = plan.renames.iter().collect();
+        let follow_ups = [Finding {
+            path: root.join("main-app.ts"),
+            problem: "'./Gone' doesn't point at any file".to_string(),
+            fix: String::new(),
+        }];
+
+        assert_eq!(
+            summary(root, &renames, &follow_ups, "in 2 runs", true),
+            "\
+## Kebab-case migration
+
+Renamed 1 file and 1 directory across 2 packages in 2 runs.
+
+### `.` (1 rename)
+
+| From | To |
+| --- | --- |
+| `MainApp.ts` | `main-app.ts` |
+
+### `packages/ui` (1 rename)
+
+| From | To |
+| --- | --- |
+| `src/UserCard` | `src/user-card` |
+
+### Manual follow-ups
+
+- [ ] `main-app.ts`: './Gone' doesn't point at any file
+"
+        );
+        assert!(summary(root, &renames, &[], "", false)
+            .contains("  packages/ui: 1 rename\n"));
+        Ok(())
+    }
+}
@@ -0,0 +1,299 @@
+use crate::plan::Plan;
+use crate::scanner::{self, rewrite_file_path, splice, ScanOptions, Scanner};
+use crate::workspace::{self, Member};
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use walkdir::WalkDir;
+
+/// Nx `project.json` and `nx.json` and Turborepo `turbo.json`, for the
+/// paths in their targets, inputs and outputs. Task runners cache against
+/// these, so stale ones mean stale builds.
+pub struct MonorepoScanner;
+
+impl Scanner for MonorepoScanner {
+    fn matches(&self, path: &Path) -> bool {
+        matches!(
+            path.file_name().and_then(|n| n.to_str()),
+            Some("project.json" | "nx.json" | "turbo.json")
+        )
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_paths(path, content, scanner::converted_path)
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_paths(path, content, |found| plan.target_of(found))
+    }
+
+    /// Paths whose first part is in the tree but that lead nowhere from
+    /// there. Outputs like `dist` may not have been built yet, so paths
+    /// that don't start in the tree aren't taken to be missing.
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> Vec<String> {
+        paths(path, content)
+            .into_iter()
+            .filter(|(file, _, _)| !file.contains(char::is_whitespace))
+            .filter(|(file, _, base)| scanner::leads_nowhere(base, file, &[]))
+            .map(|(file, _, _)| file.to_string())
+            .collect()
+    }
+
+    fn pins_name(&self, path: &Path) -> Option<&'static str> {
+        self.matches(path)
+            .then_some("Nx and Turborepo look for their config by this name")
+    }
+}
+
+/// The closest directory at or above `dir` with `nx.json` or `turbo.json`
+fn workspace_root(dir: &Path) -> &Path {
+    dir.ancestors()
+        .find(|d| d.join("nx.json").is_file() || d.join("turbo.json").is_file())
+        .unwrap_or(dir)
+}
+
+/// Rewrites the string values that are paths, including globs like
+/// `{projectRoot}/src/**/*.ts` and negations like `!Docs/**`
+fn rewrite_paths(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let mut edits = Vec::new();
+    for (file, start, base) in paths(path, content) {
+        edits.extend(rewrite_file_path(base, file, start, &[], &rename));
+    }
+    splice(content, edits)
+}
+
+/// The string values that are paths, with where each starts and the
+/// directory it's relative to: the first one its first part exists in.
+/// Nx paths are relative to the workspace root unless they start with
+/// `{projectRoot}`; Turborepo's are relative to the package, and
+/// `$TURBO_ROOT$` is the root.
+fn paths<'a>(
+    path: &'a Path,
+    content: &'a str,
+) -> Vec<(&'a str, usize, &'a Path)> {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let root = workspace_root(dir);
+    let is_turbo = path.file_name().is_some_and(|n| n == "turbo.json");
+    let string = Regex::new(r#""((?:[^"\\\n]|\\.)*)"(\s*:)?"#).unwrap();
+
+    let mut paths = Vec::new();
+    for caps in string.captures_iter(content) {
+        // Keys aren't paths
+        if caps.get(2).is_some() {
+            continue;
+        }
+        let text = caps.get(1).unwrap();
+        let value = text.as_str().trim_start_matches('!');
+        let mut start = text.start() + text.len() - value.len();
+        let tokens = [
+            ("{projectRoot}/", dir),
+            ("{workspaceRoot}/", root),
+            ("$TURBO_ROOT$/", root),
+        ];
+        let (file, bases) = match tokens
+            .iter()
+            .find_map(|(token, base)| Some((value.strip_prefix(token)?, base)))
+        {
+            Some((file, base)) => {
+                start += value.len() - file.len();
+                (file, vec![*base])
+            }
+            None if value.contains(['{', '$', '\\'])
+                || value.contains("://") =>
+            {
+                continue
+            }
+            None if is_turbo => (value, vec![dir, root]),
+            None => (value, vec![root, dir]),
+        };
+        if !file.contains(['/', '.']) {
+            continue;
+        }
+        let first = file.split('/').next().unwrap_or(file);
+        let Some(base) =
+            bases.into_iter().find(|base| base.join(first).exists())
+        else {
+            continue;
+        };
+        paths.push((file, start, base));
+    }
+    paths
+}
+
+/// The projects of the workspace at `root` with files changed since the
+/// git revision `since`, committed or not, including untracked files.
+/// Projects are Nx projects (directories with a `project.json`) and the
+/// workspace's packages.
+pub fn affected(root: &Path, since: &str) -> Result<Vec<Member>> {
+    let mut projects: Vec<Member> = WalkDir::new(root)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| {
+            let name = e.file_name().to_string_lossy();
+            e.depth() == 0 || !(name == "node_modules" || name.starts_with('.'))
+        })
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name() == "project.json")
+        .filter_map(|e| {
+            Some(Member {
+                name: None,
+                dir: e.path().parent()?.to_path_buf(),
+            })
+        })
+        .collect();
+    if let Ok(members) = workspace::members(root, None) {
+        for member in members {
+            if !projects.iter().any(|p| p.dir == member.dir) {
+                projects.push(member);
+            }
+        }
+    }
+
+    let git = |args: &[&str]| -> Result<Vec<PathBuf>> {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(root)
+            .output()
+            .context("Failed to run git")?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|line| root.join(line))
+            .collect())
+    };
+    let mut changed = git(&["diff", "--name-only", "--relative", since])?;
+    changed.extend(git(&["ls-files", "--others", "--exclude-standard"])?);
+
+    let affected = affected_projects(projects, &changed);
+    if affected.is_empty() {
+        anyhow::bail!("No projects changed since {}", since);
+    }
+    Ok(affected)
+}
+
+/// The outermost of `projects` that contain one of `changed`
+fn affected_projects(
+    projects: Vec<Member>,
+    changed: &[PathBuf],
+) -> Vec<Member> {
+    let touched: Vec<Member> = projects
+        .into_iter()
+        .filter(|p| changed.iter().any(|file| file.starts_with(&p.dir)))
+        .collect();
+    touched
+        .iter()
+        .filter(|p| {
+            !touched
+                .iter()
+                .any(|o| o.dir != p.dir && p.dir.starts_with(&o.dir))
+        })
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_project_config_paths() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::write(root.join("nx.json"), "{}")?;
+        fs::create_dir_all(root.join("Apps/WebShop/Src"))?;
+        fs::create_dir_all(root.join("Apps/WebShop/Docs"))?;
+        fs::write(root.join("Apps/WebShop/Src/Main.ts"), "")?;
+
+        let content = r#"{
+  "name": "web-shop",
+  "sourceRoot": "Apps/WebShop/Src",
+  "targets": {
+    "build": {
+      "outputs": ["{workspaceRoot}/dist/Apps/WebShop"],
+      "inputs": ["{projectRoot}/Src/**/*.ts", "!{projectRoot}/Docs/**"],
+      "options": { "main": "Apps/WebShop/Src/Main.ts" }
+    }
+  },
+  "implicitDependencies": ["Shared"]
+}"#;
+        let (new_content, changes) = MonorepoScanner.convert(
+            &root.join("Apps/WebShop/project.json"),
+            content,
+            &ScanOptions::default(),
+        );
+        assert_eq!(
+            new_content,
+            r#"{
+  "name": "web-shop",
+  "sourceRoot": "apps/web-shop/src",
+  "targets": {
+    "build": {
+      "outputs": ["{workspaceRoot}/dist/Apps/WebShop"],
+      "inputs": ["{projectRoot}/src/**/*.ts", "!{projectRoot}/docs/**"],
+      "options": { "main": "apps/web-shop/src/main.ts" }
+    }
+  },
+  "implicitDependencies": ["Shared"]
+}"#
+        );
+        assert_eq!(changes, 9);
+
+        let orphans = MonorepoScanner.orphans(
+            &root.join("Apps/WebShop/project.json"),
+            &content.replace("Src/Main.ts", "Src/Gone.ts"),
+            &ScanOptions::default(),
+        );
+        assert_eq!(orphans, ["Apps/WebShop/Src/Gone.ts"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_affected_projects() {
+        let project = |dir: &str| Member {
+            name: None,
+            dir: PathBuf::from(dir),
+        };
+        let projects = vec![
+            project("/r/apps/web"),
+            project("/r/apps/web/e2e"),
+            project("/r/libs/ui"),
+        ];
+        let changed = [
+            PathBuf::from("/r/apps/web/e2e/App.spec.ts"),
+            PathBuf::from("/r/README.md"),
+        ];
+        assert_eq!(
+            affected_projects(projects, &changed),
+            [project("/r/apps/web")]
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use crate::doctor::{print_findings, Finding};
+use crate::report::Report;
+use crate::scanner::scanner_for;
+use crate::{aliases, encoding, Options};
+use anyhow::Result;
+use std::fs;
+use std::path::Path;
+
+/// Finds references anywhere under `dir` that point at files that don't
+/// exist, whether or not kebabify touched them, and prints them. Returns
+/// how many were found.
+pub fn run(dir: &Path, opts: &Options, report: &mut Report) -> Result<usize> {
+    let findings = find(dir, opts, report)?;
+    print_findings(&findings, opts);
+    Ok(findings.len())
+}
+
+pub fn find(
+    dir: &Path,
+    opts: &Options,
+    report: &mut Report,
+) -> Result<Vec<Finding>> {
+    // Imports through an alias are checked whether or not they're being
+    // preferred
+    let mut scan = opts.scan.clone();
+    if scan.aliases.is_empty() {
+        scan.aliases = aliases::load(dir, &[]).unwrap_or_default();
+    }
+    let mut findings = Vec::new();
+    for entry in crate::source_files(dir, opts, report)? {
+        let path = entry.path();
+        let Some(scanner) = scanner_for(path) else {
+            continue;
+        };
+        let Ok(bytes) = fs::read(path) else {
+            continue;
+        };
+        let Some((content, _)) = encoding::decode(&bytes) else {
+            continue;
+        };
+        for reference in scanner.orphans(path, &content, &scan) {
+            findings.push(Finding {
+                path: path.to_path_buf(),
+                problem: format!("'{}' doesn't point at any file", reference),
+                fix: "point it at the file's current path, or remove it"
+                    .to_string(),
+            });
+        }
+    }
+    Ok(findings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_orphaned_imports() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir(root.join("lib"))?;
+        fs::write(root.join("lib/button.ts"), "")?;
+        fs::write(root.join("lib/icon.svg"), "")?;
+        fs::write(
+            root.join("main.ts"),
+            "import b from './lib/button';
+import i from './lib/icon.svg?raw';
+import x from './lib/Removed';
+import y from 'some-package';
+import a from '@lib/button';
+import z from '@lib/Gone';
+",
+        )?;
+        let tsconfig =
+            r#"{"compilerOptions": {"paths": {"@lib/*": ["lib/*"]}}}"#;
+        fs::write(root.join("tsconfig.json"), tsconfig)?;
+
+        let findings = find(root, &Options::default(), &mut Report::default())?;
+        assert_eq!(findings.len(), 2);
+        assert!(findings[0].problem.contains("./lib/Removed"));
+        assert!(findings[1].problem.contains("@lib/Gone"));
+        Ok(())
+    }
+}
@@ -0,0 +1,233 @@
+use crate::plan::Plan;
+use crate::scanner::{self, rewrite_file_path, splice, ScanOptions, Scanner};
+use regex::Regex;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// `package.json` and `.lintstagedrc`, for the paths in `scripts`, the
+/// `files` array and lint-staged's globs and commands. They're plain
+/// strings rather than imports, so nothing else notices when one goes
+/// stale until a script fails or a file is left out of a published package.
+pub struct PackageScanner;
+
+impl Scanner for PackageScanner {
+    fn matches(&self, path: &Path) -> bool {
+        matches!(
+            path.file_name().and_then(|n| n.to_str()),
+            Some("package.json" | ".lintstagedrc" | ".lintstagedrc.json")
+        )
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_paths(path, content, scanner::converted_path)
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_paths(path, content, |found| plan.target_of(found))
+    }
+
+    /// Paths whose first part is in the package but that lead nowhere from
+    /// there. Build outputs like `dist` may not exist yet, so paths that
+    /// don't start in the package aren't taken to be missing.
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> Vec<String> {
+        let dir = path.parent().unwrap_or(Path::new(""));
+        paths(path, content)
+            .into_iter()
+            .filter(|(file, _)| {
+                let first = file.split('/').next().unwrap_or(file);
+                file.contains('/')
+                    && dir.join(first).exists()
+                    && scanner::leads_nowhere(dir, file, &[])
+            })
+            .map(|(file, _)| file.to_string())
+            .collect()
+    }
+}
+
+/// A JSON string, and whether it's a key
+fn string_regex() -> Regex {
+    Regex::new(r#""((?:[^"\\\n]|\\.)*)"(\s*:)?"#).unwrap()
+}
+
+/// Rewrites the paths in a `package.json` or `.lintstagedrc`
+fn rewrite_paths(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut edits = Vec::new();
+    for (file, start) in paths(path, content) {
+        edits.extend(rewrite_file_path(dir, file, start, &[], &rename));
+    }
+    splice(content, edits)
+}
+
+/// The paths in the `scripts`, `files` and `lint-staged` fields of a
+/// `package.json`, or in the whole of a `.lintstagedrc`, with where each
+/// starts. All of them are relative to the file's directory.
+fn paths<'a>(path: &Path, content: &'a str) -> Vec<(&'a str, usize)> {
+    let mut found = Vec::new();
+    let mut paths = |range: Range<usize>| {
+        let value = &content[range.clone()];
+        let file = value.trim_start_matches('!').trim_start_matches("./");
+        if file.starts_with('/') || file.contains("://") {
+            return;
+        }
+        found.push((file, range.end - file.len()));
+    };
+
+    let is_package = path.file_name().is_some_and(|n| n == "package.json");
+    let fields = if is_package {
+        let field =
+            Regex::new(r#""(scripts|files|lint-staged)"\s*:\s*[\[{]"#).unwrap();
+        field
+            .captures_iter(content)
+            .filter_map(|caps| {
+                let start = caps.get(0).unwrap().end() - 1;
+                Some((caps[1].to_string(), start..value_end(content, start)?))
+            })
+            .collect()
+    } else {
+        vec![("lint-staged".to_string(), 0..content.len())]
+    };
+
+    for (field, body) in fields {
+        for caps in string_regex().captures_iter(&content[body.clone()]) {
+            let text = caps.get(1).unwrap();
+            let range = body.start + text.start()..body.start + text.end();
+            let is_key = caps.get(2).is_some();
+            match (field.as_str(), is_key) {
+                ("files", _) | ("lint-staged", true) => paths(range),
+                ("scripts" | "lint-staged", false) => {
+                    for word in command_words(&content[range.clone()]) {
+                        paths(range.start + word.start..range.start + word.end)
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    found
+}
+
+/// Where the JSON object or array starting at `start` ends, skipping over
+/// brackets in strings like the `{ts,tsx}` of a glob
+fn value_end(content: &str, start: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in content[start..].char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '{' | '[' if !in_string => depth += 1,
+            '}' | ']' if !in_string => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(start + i + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The words of a shell command that could be paths, like
+/// `./Scripts/BuildIcons.mjs` in `node ./Scripts/BuildIcons.mjs && tsc`,
+/// or `Config/Jest.js` in `--config=Config/Jest.js`
+fn command_words(command: &str) -> Vec<Range<usize>> {
+    let word = Regex::new(r#"[^\s"'\\=;&|()<>`$]+"#).unwrap();
+    word.find_iter(command)
+        .filter(|word| {
+            let word = word.as_str();
+            word.contains(['/', '.']) && !word.starts_with('-')
+        })
+        .map(|word| word.range())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_package_json_paths() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("Scripts"))?;
+        fs::create_dir_all(root.join("Src/Legacy"))?;
+        fs::create_dir_all(root.join("Bin"))?;
+        fs::write(root.join("Scripts/BuildIcons.mjs"), "")?;
+        fs::write(root.join("Bin/Cli.js"), "")?;
+        fs::write(root.join("JestConfig.js"), "")?;
+
+        let content = r#"{
+  "name": "@acme/Icons",
+  "main": "./Bin/Cli.js",
+  "scripts": {
+    "build:icons": "node ./Scripts/BuildIcons.mjs && tsc -p tsconfig.json",
+    "test": "jest --config=JestConfig.js \"Src/**/*.test.ts\"",
+    "lint": "eslint Src --ignore-pattern lodash.debounce"
+  },
+  "files": ["Bin", "Src/**/*.js", "!Src/Legacy/**"],
+  "lint-staged": {
+    "Src/**/*.{ts,tsx}": ["eslint --fix", "node Scripts/BuildIcons.mjs"]
+  }
+}
+"#;
+        let path = root.join("package.json");
+        assert!(PackageScanner.matches(&path));
+        let (new_content, changes) =
+            PackageScanner.convert(&path, content, &ScanOptions::default());
+        assert_eq!(
+            new_content,
+            r#"{
+  "name": "@acme/Icons",
+  "main": "./Bin/Cli.js",
+  "scripts": {
+    "build:icons": "node ./scripts/build-icons.mjs && tsc -p tsconfig.json",
+    "test": "jest --config=jest-config.js \"src/**/*.test.ts\"",
+    "lint": "eslint Src --ignore-pattern lodash.debounce"
+  },
+  "files": ["bin", "src/**/*.js", "!src/legacy/**"],
+  "lint-staged": {
+    "src/**/*.{ts,tsx}": ["eslint --fix", "node scripts/build-icons.mjs"]
+  }
+}
+"#
+        );
+        assert_eq!(changes, 11);
+
+        let content = r#"{
+  "scripts": { "build": "node Scripts/Gone.mjs && node dist/cli.js" },
+  "files": ["Bin/Cli.js", "Bin/Missing.js"]
+}
+"#;
+        let orphans =
+            PackageScanner.orphans(&path, content, &ScanOptions::default());
+        assert_eq!(orphans, ["Scripts/Gone.mjs", "Bin/Missing.js"]);
+        Ok(())
+    }
+}
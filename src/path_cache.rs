@@ -0,0 +1,93 @@
+use crate::resolve::normalize;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// What's been worked out about paths in the tree as it is now: why a name
+/// is pinned, and what each path is renamed to. The rename pass, the import
+/// pass and every scanner ask about the same paths over and over, and
+/// pinning can mean reading files or walking whole directories, so each
+/// answer is worked out once and shared. Keys are normalized, so `./Lib`
+/// and `Lib` are the same path. A run works on one thread, so each thread
+/// has its own.
+#[derive(Debug, Default)]
+struct Cache {
+    pinned: HashMap<PathBuf, Option<&'static str>>,
+    renamed: HashMap<PathBuf, Option<PathBuf>>,
+}
+
+thread_local! {
+    static CACHE: RefCell<Cache> = RefCell::default();
+}
+
+/// Why the name of `path` is pinned, working it out with `compute` the
+/// first time it's asked
+pub fn pinned(
+    path: &Path,
+    compute: impl FnOnce() -> Option<&'static str>,
+) -> Option<&'static str> {
+    let key = normalize(path);
+    if let Some(known) = CACHE.with_borrow(|c| c.pinned.get(&key).copied()) {
+        return known;
+    }
+    // Not borrowing the cache, since working it out can ask about other
+    // paths
+    let pinned = compute();
+    CACHE.with_borrow_mut(|c| c.pinned.insert(key, pinned));
+    pinned
+}
+
+/// The path `path` is renamed to, working it out with `compute` the first
+/// time it's asked, unless the rename pass has already recorded it
+pub fn renamed(
+    path: &Path,
+    compute: impl FnOnce() -> Option<PathBuf>,
+) -> Option<PathBuf> {
+    let key = normalize(path);
+    if let Some(known) = CACHE.with_borrow(|c| c.renamed.get(&key).cloned()) {
+        return known;
+    }
+    let renamed = compute();
+    CACHE.with_borrow_mut(|c| c.renamed.insert(key, renamed.clone()));
+    renamed
+}
+
+/// Records what the rename pass planned for `path`, so references are
+/// converted to the name it'll really have, numbered or abbreviated, and
+/// not to one it won't, like the name of a file it leaves alone
+pub fn record(path: &Path, to: Option<PathBuf>) {
+    CACHE.with_borrow_mut(|c| c.renamed.insert(normalize(path), to));
+}
+
+/// Forgets everything, once the tree has changed under it
+pub fn clear() {
+    CACHE.take();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_answers_are_shared() {
+        let path = Path::new("/path-cache-test/Lib/UserCard.ts");
+        let mut computed = 0;
+        let mut compute = || {
+            computed += 1;
+            Some(PathBuf::from("/path-cache-test/Lib/user-card.ts"))
+        };
+        let first = renamed(path, &mut compute);
+        let again =
+            renamed(Path::new("/path-cache-test/./Lib/UserCard.ts"), compute);
+        assert_eq!(first, again);
+        assert_eq!(computed, 1);
+
+        record(path, Some("/path-cache-test/Lib/user-card-2.ts".into()));
+        assert_eq!(
+            renamed(path, || None),
+            Some(PathBuf::from("/path-cache-test/Lib/user-card-2.ts"))
+        );
+        assert_eq!(pinned(path, || Some("kept")), Some("kept"));
+        assert_eq!(pinned(path, || None), Some("kept"));
+    }
+}
@@ -0,0 +1,117 @@
+use crate::plan::Plan;
+use crate::relative_to;
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Renames queued by other tools, applied at the start of the next run
+pub const PENDING_FILE: &str = ".kebabify-pending.json";
+
+fn pending_path(root: &Path) -> PathBuf {
+    root.join(PENDING_FILE)
+}
+
+/// Loads the queued renames under `root`, if there are any
+pub fn load(root: &Path) -> Result<Option<Plan>> {
+    let path = pending_path(root);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(None)
+        }
+        Err(err) => {
+            return Err(err)
+                .with_context(|| format!("Failed to read {}", path.display()))
+        }
+    };
+    let value: Value = serde_json::from_str(&content)
+        .with_context(|| format!("Malformed {}", path.display()))?;
+
+    let mut plan = Plan::default();
+    let renames = value.get("renames").and_then(Value::as_array);
+    for (index, rename) in renames.into_iter().flatten().enumerate() {
+        let from = rename.get("from").and_then(Value::as_str);
+        let to = rename.get("to").and_then(Value::as_str);
+        let (Some(from), Some(to)) = (from, to) else {
+            bail!(
+                "Rename {} in {} must have a \"from\" and a \"to\" path",
+                index + 1,
+                path.display()
+            );
+        };
+        plan.push(root.join(from), root.join(to));
+    }
+    plan.compose();
+    Ok(Some(plan))
+}
+
+/// Writes the queue back, or removes the file once nothing is left
+pub fn save(root: &Path, plan: &Plan) -> Result<()> {
+    let path = pending_path(root);
+    if plan.renames.is_empty() {
+        return match fs::remove_file(&path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err)
+                .with_context(|| {
+                    format!("Failed to remove {}", path.display())
+                }),
+            _ => Ok(()),
+        };
+    }
+
+    let renames: Vec<Value> = plan
+        .renames
+        .iter()
+        .map(|r| {
+            json!({
+                "from": portable(relative_to(&r.from, root)),
+                "to": portable(relative_to(&r.to, root)),
+            })
+        })
+        .collect();
+    let content = serde_json::to_string_pretty(&json!({ "renames": renames }))?;
+    fs::write(&path, content + "\n")
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Adds a rename to the queue under `root`
+pub fn queue(root: &Path, from: &Path, to: &Path) -> Result<()> {
+    let mut plan = load(root)?.unwrap_or_default();
+    plan.push(
+        root.join(relative_to(from, root)),
+        root.join(relative_to(to, root)),
+    );
+    save(root, &plan)
+}
+
+/// Keeps the file usable across platforms
+pub fn portable(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_queue_round_trip() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        assert!(load(root)?.is_none());
+
+        queue(root, Path::new("Lib/Old.ts"), Path::new("lib/new.ts"))?;
+        queue(root, &root.join("A.ts"), &root.join("a.ts"))?;
+        let plan = load(root)?.unwrap();
+        assert_eq!(plan.renames.len(), 2);
+        assert_eq!(plan.renames[0].from, root.join("Lib/Old.ts"));
+        assert_eq!(plan.renames[1].to, root.join("a.ts"));
+
+        save(root, &Plan::default())?;
+        assert!(!root.join(PENDING_FILE).exists());
+
+        fs::write(root.join(PENDING_FILE), r#"{"renames":[{"from":"a"}]}"#)?;
+        assert!(load(root).is_err());
+        Ok(())
+    }
+}
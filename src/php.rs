@@ -0,0 +1,234 @@
+use crate::plan::Plan;
+use crate::scanner::{self, rewrite_file_path, splice, ScanOptions, Scanner};
+use regex::Regex;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// PHP sources. Class files under a PSR-4 autoload directory are named
+/// after their class and keep their names.
+pub struct PhpScanner;
+
+impl Scanner for PhpScanner {
+    fn matches(&self, path: &Path) -> bool {
+        path.extension().is_some_and(|e| e == "php")
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_includes(path, content, scanner::converted_path)
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_includes(path, content, |found| plan.target_of(found))
+    }
+
+    /// Includes relative to the file, after `__DIR__ .` or
+    /// `dirname(__FILE__) .`, that lead nowhere. Other paths are looked up
+    /// on the include path too, so they can't be told apart from here.
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> Vec<String> {
+        let dir = path.parent().unwrap_or(Path::new(""));
+        include_regex()
+            .captures_iter(content)
+            .filter(|caps| caps.get(1).is_some())
+            .map(|caps| caps[2].to_string())
+            .filter(|file| {
+                let relative = file.trim_start_matches('/');
+                scanner::leads_nowhere(dir, relative, &[])
+            })
+            .collect()
+    }
+
+    /// PSR-4 maps namespaces to directories and class names to file names,
+    /// so renaming either stops the class from autoloading
+    fn pins_name(&self, path: &Path) -> Option<&'static str> {
+        let path = &scanner::absolute(path);
+        if !psr4_dirs(path).iter().any(|base| path.starts_with(base)) {
+            return None;
+        }
+        if path.is_dir() {
+            let has_php = WalkDir::new(path)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .any(|e| e.file_type().is_file() && self.matches(e.path()));
+            return has_php.then_some(
+                "it's a PSR-4 namespace directory, renaming it breaks \
+                 autoloading",
+            );
+        }
+        let content = fs::read_to_string(path).ok()?;
+        let stem = path.file_stem()?.to_str()?;
+        let defines_class = class_regex()
+            .captures_iter(&content)
+            .any(|caps| &caps[1] == stem);
+        defines_class
+            .then_some("PSR-4 autoloading needs the file named after its class")
+    }
+}
+
+/// `class Foo`, `final class Foo`, `interface Foo`, `trait Foo`, `enum Foo`
+fn class_regex() -> Regex {
+    Regex::new(
+        r"(?m)^[ \t]*(?:(?:abstract|final|readonly)\s+)*(?:class|interface|trait|enum)\s+(\w+)",
+    )
+    .unwrap()
+}
+
+/// The PSR-4 base directories of the nearest `composer.json` above `path`
+fn psr4_dirs(path: &Path) -> Vec<PathBuf> {
+    let Some((root, composer)) = path.ancestors().find_map(|dir| {
+        let content = fs::read_to_string(dir.join("composer.json")).ok()?;
+        Some((dir, serde_json::from_str::<Value>(&content).ok()?))
+    }) else {
+        return Vec::new();
+    };
+
+    let mut dirs = Vec::new();
+    for section in ["autoload", "autoload-dev"] {
+        let Some(map) = composer[section]["psr-4"].as_object() else {
+            continue;
+        };
+        for value in map.values() {
+            let paths = match value {
+                Value::Array(paths) => paths.iter().collect(),
+                path => vec![path],
+            };
+            dirs.extend(paths.into_iter().filter_map(Value::as_str).map(|p| {
+                crate::resolve::normalize(&root.join(p.trim_end_matches('/')))
+            }));
+        }
+    }
+    dirs
+}
+
+/// `require`, `require_once`, `include` and `include_once` with a literal
+/// path, and the `__DIR__ .` or `dirname(__FILE__) .` before it
+fn include_regex() -> Regex {
+    Regex::new(
+        r#"\b(?:require|include)(?:_once)?\s*\(?\s*((?:__DIR__|dirname\(\s*__FILE__\s*\))\s*\.\s*)?['"]([^'"]*)['"]"#,
+    )
+    .unwrap()
+}
+
+/// Rewrites the paths of `require`, `require_once`, `include` and
+/// `include_once`. Paths after `__DIR__ .` or `dirname(__FILE__) .` are
+/// relative to the file; other relative paths are tried against it too.
+fn rewrite_includes(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut edits = Vec::new();
+    for caps in include_regex().captures_iter(content) {
+        let file = caps.get(2).unwrap();
+        edits.extend(rewrite_file_path(
+            dir,
+            file.as_str(),
+            file.start(),
+            &[],
+            &rename,
+        ));
+    }
+    splice(content, edits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_includes_and_psr4() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::write(
+            root.join("composer.json"),
+            r#"{ "autoload": { "psr-4": { "App\\": "src/" } } }"#,
+        )?;
+        fs::create_dir_all(root.join("src/Models"))?;
+        fs::create_dir_all(root.join("Helpers"))?;
+        fs::write(
+            root.join("src/Models/User.php"),
+            "<?php\nfinal class User {}",
+        )?;
+        fs::write(
+            root.join("src/Models/Helpers.php"),
+            "<?php\nfunction f() {}",
+        )?;
+        fs::write(
+            root.join("Helpers/StringUtils.php"),
+            "<?php\nclass StringUtils {}",
+        )?;
+
+        let content = "<?php
+require __DIR__ . '/Helpers/StringUtils.php';
+include_once(dirname(__FILE__) . \"/src/Models/User.php\");
+require 'vendor/autoload.php';
+";
+        let (new_content, changes) = PhpScanner.convert(
+            &root.join("index.php"),
+            content,
+            &ScanOptions::default(),
+        );
+        assert_eq!(
+            new_content,
+            "<?php
+require __DIR__ . '/helpers/string-utils.php';
+include_once(dirname(__FILE__) . \"/src/Models/User.php\");
+require 'vendor/autoload.php';
+"
+        );
+        assert_eq!(changes, 2);
+        let orphans = PhpScanner.orphans(
+            &root.join("index.php"),
+            &format!("{}require __DIR__ . '/Helpers/Gone.php';\n", content),
+            &ScanOptions::default(),
+        );
+        assert_eq!(orphans, ["/Helpers/Gone.php"]);
+
+        let pinned = |path: &str, is_dir| {
+            scanner::pinned(&root.join(path), is_dir).is_some()
+        };
+        assert!(pinned("src/Models/User.php", false));
+        assert!(pinned("src/Models", true));
+        assert!(!pinned("src/Models/Helpers.php", false));
+        assert!(!pinned("Helpers/StringUtils.php", false));
+        Ok(())
+    }
+
+    #[test]
+    fn test_psr4_with_relative_root() -> Result<()> {
+        // Under the working directory, so it can be reached as `./.tmpXXXX`
+        let temp_dir = TempDir::new_in(".")?;
+        let root = &Path::new(".").join(temp_dir.path().file_name().unwrap());
+        fs::write(
+            root.join("composer.json"),
+            r#"{ "autoload": { "psr-4": { "App\\": "src/" } } }"#,
+        )?;
+        fs::create_dir_all(root.join("src/Models"))?;
+        fs::write(root.join("src/Models/User.php"), "<?php\nclass User {}")?;
+
+        let user = root.join("src/Models/User.php");
+        assert!(PhpScanner.pins_name(&user).is_some());
+        assert!(PhpScanner.pins_name(&root.join("src/Models")).is_some());
+        Ok(())
+    }
+}
@@ -0,0 +1,729 @@
+use crate::journal::Change;
+use crate::messages::{text, Msg};
+use crate::report::{Aborted, Report, WarningKind};
+use crate::resolve::normalize;
+use crate::temp_names::{self, Kind as TempKind};
+use crate::{
+    convert_name_as, fs_util, path_cache, NameStyle, OnConflict, Options,
+};
+use anyhow::{anyhow, Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A single move from `from` to `to`, both expressed as they exist
+/// before and after the whole plan has been applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rename {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+/// An ordered set of renames that is verified before anything touches disk
+#[derive(Debug, Default, Clone)]
+pub struct Plan {
+    /// Add and change these through `push`, `extend`, `compose` and
+    /// `remove`, which keep `index` in step
+    pub renames: Vec<Rename>,
+    index: Trie,
+}
+
+/// The targets of a plan's renames keyed by the segments of their sources,
+/// so finding where a path ends up takes as many steps as the path has
+/// segments, however many renames there are
+#[derive(Debug, Default, Clone)]
+struct Trie {
+    to: Option<PathBuf>,
+    children: HashMap<OsString, Trie>,
+}
+
+impl Trie {
+    fn build(renames: &[Rename]) -> Trie {
+        let mut trie = Trie::default();
+        for rename in renames {
+            trie.insert(rename);
+        }
+        trie
+    }
+
+    /// A later rename of the same source replaces an earlier one
+    fn insert(&mut self, rename: &Rename) {
+        let node = rename.from.components().fold(self, |node, segment| {
+            node.children
+                .entry(segment.as_os_str().to_os_string())
+                .or_default()
+        });
+        node.to = Some(rename.to.clone());
+    }
+
+    /// The target of the deepest source `path` is at or under, and the
+    /// number of segments of `path` that source takes up
+    fn deepest(&self, path: &Path) -> Option<(&Path, usize)> {
+        let mut node = self;
+        let mut found = node.to.as_deref().map(|to| (to, 0));
+        for (depth, segment) in path.components().enumerate() {
+            let Some(child) = node.children.get(segment.as_os_str()) else {
+                break;
+            };
+            node = child;
+            if let Some(to) = &node.to {
+                found = Some((to, depth + 1));
+            }
+        }
+        found
+    }
+}
+
+impl Plan {
+    /// Adds a rename. Paths are normalized so `./Lib` and `Lib` match.
+    pub fn push(&mut self, from: PathBuf, to: PathBuf) {
+        let rename = Rename {
+            from: normalize(&from),
+            to: normalize(&to),
+        };
+        self.index.insert(&rename);
+        self.renames.push(rename);
+    }
+
+    /// Adds the renames of `other` after this plan's own
+    pub fn extend(&mut self, other: Plan) {
+        for rename in other.renames {
+            self.index.insert(&rename);
+            self.renames.push(rename);
+        }
+    }
+
+    /// Makes every target a final path. A target written under a directory
+    /// the plan itself renames, like `Lib/button.ts` next to `Lib -> lib`,
+    /// is moved under that directory's new path. Parents are composed before
+    /// their children, so this holds through nested renamed directories.
+    /// Targets under another rename's target are already final and kept.
+    pub fn compose(&mut self) {
+        let mut order: Vec<usize> = (0..self.renames.len()).collect();
+        order.sort_by_key(|&i| self.renames[i].from.components().count());
+
+        let mut composed = Plan::default();
+        for i in order {
+            let rename = &self.renames[i];
+            let to = match rename.to.parent() {
+                Some(parent)
+                    if !self
+                        .renames
+                        .iter()
+                        .any(|r| parent.starts_with(&r.to)) =>
+                {
+                    match (composed.target_of(parent), rename.to.file_name()) {
+                        (Some(parent), Some(name)) => parent.join(name),
+                        _ => rename.to.clone(),
+                    }
+                }
+                _ => rename.to.clone(),
+            };
+            composed.push(rename.from.clone(), to.clone());
+            self.renames[i].to = to;
+        }
+        self.index = Trie::build(&self.renames);
+    }
+
+    /// Drops the rename of `from`, if there is one, moving the targets of
+    /// anything planned under it back under its old path. Returns whether
+    /// there was.
+    pub fn remove(&mut self, from: &Path) -> bool {
+        let from = normalize(from);
+        let Some(i) = self.renames.iter().position(|r| r.from == from) else {
+            return false;
+        };
+        let removed = self.renames.remove(i);
+        for rename in &mut self.renames {
+            if let Ok(rest) = rename.to.strip_prefix(&removed.to) {
+                rename.to = removed.from.join(rest);
+            }
+        }
+        self.index = Trie::build(&self.renames);
+        true
+    }
+
+    /// Renames whose new name isn't simply the old one converted, like
+    /// numbered duplicates. References can't be converted to them on their
+    /// own, so they have to follow the plan.
+    pub fn exceptions(&self) -> Plan {
+        let is_plain = |r: &Rename| {
+            let (Some(from), Some(to)) = (r.from.file_name(), r.to.file_name())
+            else {
+                return true;
+            };
+            let from = from.to_string_lossy();
+            [NameStyle::Kebab, NameStyle::Snake]
+                .iter()
+                .any(|style| *to == *convert_name_as(&from, *style))
+        };
+        let renames: Vec<Rename> = self
+            .renames
+            .iter()
+            .filter(|r| !is_plain(r))
+            .cloned()
+            .collect();
+        Plan {
+            index: Trie::build(&renames),
+            renames,
+        }
+    }
+
+    /// Returns where `path` ends up once the plan is applied, taking renamed
+    /// ancestor directories into account. `None` if nothing above it moves.
+    pub fn target_of(&self, path: &Path) -> Option<PathBuf> {
+        let path = &normalize(path);
+        // The deepest matching source wins so nested renames compose
+        let (to, depth) = self.index.deepest(path)?;
+        let rest: PathBuf = path.components().skip(depth).collect();
+        if rest.as_os_str().is_empty() {
+            Some(to.to_path_buf())
+        } else {
+            Some(to.join(rest))
+        }
+    }
+
+    /// Checks the plan against itself and the filesystem, reporting every
+    /// problem at once rather than stopping at the first.
+    pub fn validate(&self, opts: &Options) -> Result<()> {
+        let display = &opts.display;
+        let mut problems = Vec::new();
+        let mut sources = HashSet::new();
+        let mut targets: HashMap<String, &Path> = HashMap::new();
+
+        for rename in &self.renames {
+            if fs::symlink_metadata(&rename.from).is_err() {
+                problems.push(format!(
+                    "source does not exist: {}",
+                    display.show(&rename.from)
+                ));
+            }
+            if !sources.insert(rename.from.as_path()) {
+                problems.push(format!(
+                    "source listed more than once: {}",
+                    display.show(&rename.from)
+                ));
+            }
+
+            // Compare case-insensitively so the plan is safe on macOS/Windows
+            let key = rename.to.to_string_lossy().to_lowercase();
+            if let Some(other) = targets.insert(key, &rename.from) {
+                problems.push(format!(
+                    "{} and {} both map to {}",
+                    display.show(other),
+                    display.show(&rename.from),
+                    display.show(&rename.to)
+                ));
+            }
+        }
+
+        for rename in &self.renames {
+            let is_case_only = rename.from.to_string_lossy().to_lowercase()
+                == rename.to.to_string_lossy().to_lowercase();
+            // An existing target is only fine if it's being moved out of the way
+            if !is_case_only
+                && fs::symlink_metadata(&rename.to).is_ok()
+                && !sources.contains(rename.to.as_path())
+            {
+                problems.push(format!(
+                    "target already exists: {}",
+                    display.show(&rename.to)
+                ));
+            }
+        }
+
+        if !problems.is_empty() {
+            return Err(Aborted(format!(
+                "Plan failed verification:\n  {}",
+                problems.join("\n  ")
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Checks that each filesystem renames copy onto has room for the
+    /// copies. A rename across filesystems copies the file or directory
+    /// before removing the original, so running out of space or inodes
+    /// halfway would leave it split between the two.
+    pub fn check_space(&self, opts: &Options) -> Result<()> {
+        let display = &opts.display;
+        let mut ordered: Vec<&Rename> = self.renames.iter().collect();
+        ordered.sort_by_key(|r| r.from.components().count());
+
+        // For each filesystem copied onto: a directory on it, and the bytes
+        // and inodes the copies take
+        let mut needed: HashMap<u64, (&Path, u64, u64)> = HashMap::new();
+        let mut copied: Vec<&Path> = Vec::new();
+        for rename in ordered {
+            // Copied along with its directory
+            if copied.iter().any(|dir| rename.from.starts_with(dir)) {
+                continue;
+            }
+            let Some(target) =
+                rename.to.ancestors().skip(1).find(|dir| dir.is_dir())
+            else {
+                continue;
+            };
+            let (Some(from), Some(to)) =
+                (fs_util::device(&rename.from), fs_util::device(target))
+            else {
+                continue;
+            };
+            if from == to {
+                continue;
+            }
+            copied.push(&rename.from);
+            let (bytes, inodes) = fs_util::tree_size(&rename.from);
+            let total = needed.entry(to).or_insert((target, 0, 0));
+            total.1 += bytes;
+            total.2 += inodes;
+        }
+
+        let mut problems = Vec::new();
+        for (target, bytes, inodes) in needed.into_values() {
+            let Some(free) = fs_util::free_space(target) else {
+                continue;
+            };
+            if bytes > free.bytes {
+                problems.push(format!(
+                    "{} to copy onto the filesystem of {}, {} free",
+                    show_size(bytes),
+                    display.show(target),
+                    show_size(free.bytes)
+                ));
+            }
+            if inodes > free.inodes {
+                problems.push(format!(
+                    "{} files and directories to copy onto the filesystem \
+                     of {}, room for {}",
+                    inodes,
+                    display.show(target),
+                    free.inodes
+                ));
+            }
+        }
+
+        if !problems.is_empty() {
+            return Err(Aborted(format!(
+                "Not enough room to move across filesystems:\n  {}",
+                problems.join("\n  ")
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Applies the renames, shallowest first, following each source through
+    /// the directory moves that have already happened. Failed renames are
+    /// recorded in `report`; renames inside a directory that failed to move
+    /// happen in place where possible.
+    pub fn apply(&self, opts: &Options, report: &mut Report) -> Result<()> {
+        let _span = tracing::info_span!("rename", renames = self.renames.len())
+            .entered();
+        let mut ordered: Vec<&Rename> = self.renames.iter().collect();
+        ordered.sort_by_key(|r| r.from.components().count());
+
+        let mut done = Plan::default();
+        let mut failed: Vec<&Path> = Vec::new();
+        for rename in ordered {
+            crate::cancel::checkpoint()?;
+            let current =
+                done.target_of(&rename.from).unwrap_or(rename.from.clone());
+
+            let mut to = rename.to.clone();
+            if failed.iter().any(|dir| rename.from.starts_with(dir)) {
+                match self.in_place_target(rename, &current) {
+                    Some(in_place) => {
+                        tracing::debug!(
+                            from = %rename.from.display(),
+                            to = %in_place.display(),
+                            "renaming in place, its directory didn't move"
+                        );
+                        to = in_place
+                    }
+                    None => {
+                        report.check::<()>(
+                            &rename.from,
+                            Err(anyhow!(
+                                "Skipped {}: its directory couldn't be moved",
+                                opts.display.show(&rename.from)
+                            )),
+                        )?;
+                        continue;
+                    }
+                }
+            }
+
+            // Already done, e.g. by an interrupted run being resumed
+            if fs::symlink_metadata(&current).is_err()
+                && fs::symlink_metadata(&to).is_ok()
+            {
+                done.push(rename.from.clone(), to);
+                continue;
+            }
+
+            let result = displace(&current, &to, opts).and_then(|displaced| {
+                match move_path(&current, &to, opts) {
+                    Ok(lost) => Ok((displaced, lost)),
+                    Err(err) => {
+                        if let Some(displaced) = &displaced {
+                            displaced.restore(&to);
+                        }
+                        Err(err)
+                    }
+                }
+            });
+            let Some((displaced, lost)) = report.check(&current, result)?
+            else {
+                failed.push(&rename.from);
+                continue;
+            };
+            if let Some(displaced) = displaced {
+                report.warn(&to, WarningKind::Replaced, displaced.how);
+                report.journal.record(Change::Replaced {
+                    path: to.clone(),
+                    original: displaced.original,
+                });
+            }
+            for (path, what) in lost {
+                report.warn(&path, WarningKind::MetadataLost, what);
+            }
+            tracing::info!(
+                from = %current.display(),
+                to = %to.display(),
+                "renamed"
+            );
+            report.journal.record(Change::Renamed {
+                from: current,
+                to: to.clone(),
+            });
+            done.push(rename.from.clone(), to);
+        }
+        path_cache::clear();
+        Ok(())
+    }
+
+    /// For a rename that only changes the last path component, returns the
+    /// equivalent rename next to `current`
+    fn in_place_target(
+        &self,
+        rename: &Rename,
+        current: &Path,
+    ) -> Option<PathBuf> {
+        let parent = rename.from.parent()?;
+        let final_parent = self.target_of(parent).unwrap_or(parent.into());
+        if rename.to.parent()? != final_parent {
+            return None;
+        }
+        Some(current.parent()?.join(rename.to.file_name()?))
+    }
+}
+
+/// `bytes` in the largest unit that keeps it at least 1, like `3.2 GiB`
+fn show_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["bytes", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{} {}", bytes, UNITS[0]),
+        _ => format!("{:.1} {}", size, UNITS[unit]),
+    }
+}
+
+/// A file `--on-conflict overwrite` replaces, with its contents, so a move
+/// that fails or a rollback can put it back
+struct Displaced {
+    original: Vec<u8>,
+    how: &'static str,
+}
+
+impl Displaced {
+    /// Puts the file back at `to` after the move onto it failed, unless
+    /// it's still there as it was
+    fn restore(&self, to: &Path) {
+        if fs::read(to).is_ok_and(|now| now == self.original) {
+            return;
+        }
+        if let Err(err) = fs::write(to, &self.original) {
+            eprintln!("Error: failed to put back {}: {}", to.display(), err);
+        }
+    }
+}
+
+/// Clears the way for `from` to move to `to` with `--on-conflict
+/// overwrite`, keeping a copy of the file at `to` first. With the trash,
+/// the file is moved there; with `--no-trash`, the move itself replaces
+/// it, so a move that fails leaves it in place. Returns the copy, if a
+/// file is replaced. A `to` that's `from` itself, on a case-insensitive
+/// filesystem, is left alone.
+fn displace(
+    from: &Path,
+    to: &Path,
+    opts: &Options,
+) -> Result<Option<Displaced>> {
+    if opts.on_conflict != OnConflict::Overwrite
+        || !to.is_file()
+        || fs_util::file_id(from) == fs_util::file_id(to)
+    {
+        return Ok(None);
+    }
+    let display = &opts.display;
+    let original = fs::read(to).with_context(|| {
+        format!("Failed to read the file to replace: {}", display.show(to))
+    })?;
+    if opts.trash {
+        opts.log(format_args!(
+            "{}",
+            text(Msg::Trashing, &[&display.show(to)])
+        ));
+        trash::delete(to).with_context(|| {
+            format!("Failed to move to the trash: {}", display.show(to))
+        })?;
+        Ok(Some(Displaced {
+            original,
+            how: "replaced, the old file is in the trash",
+        }))
+    } else {
+        opts.log(format_args!(
+            "{}",
+            text(Msg::Deleting, &[&display.show(to)])
+        ));
+        Ok(Some(Displaced {
+            original,
+            how: "replaced, the old file was deleted",
+        }))
+    }
+}
+
+/// Moves `from` to `to`, copying it over when they're on different
+/// filesystems. Returns whatever metadata the copy couldn't keep.
+fn move_path(
+    from: &Path,
+    to: &Path,
+    opts: &Options,
+) -> Result<Vec<(PathBuf, String)>> {
+    let display = &opts.display;
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent).with_context(|| {
+            format!("Failed to create directory: {}", display.show(parent))
+        })?;
+    }
+
+    opts.log(format_args!(
+        "{}",
+        text(Msg::Renaming, &[&display.show(from), &display.show(to)])
+    ));
+    let rename = || match opts.fs.case_sensitive {
+        true => fs::rename(from, to),
+        false => rename_via_temporary(from, to, opts),
+    };
+    match fs_util::with_retry(opts.retries, rename) {
+        Err(err) if fs_util::is_cross_device(&err) => {
+            fs_util::move_across(from, to).with_context(|| {
+                format!(
+                    "Failed to copy across filesystems: {}",
+                    display.show(from)
+                )
+            })
+        }
+        result => result.map(|()| Vec::new()).with_context(|| {
+            format!("Failed to rename file: {}", display.show(from))
+        }),
+    }
+}
+
+/// Renames in two steps through a temporary name, for case-insensitive
+/// filesystems where renaming `UserCard.ts` to `usercard.ts` in one step
+/// can fail or leave the name as it was
+fn rename_via_temporary(
+    from: &Path,
+    to: &Path,
+    opts: &Options,
+) -> std::io::Result<()> {
+    let same_name = |a: &Path, b: &Path| {
+        a.to_string_lossy().to_lowercase() == b.to_string_lossy().to_lowercase()
+    };
+    let Some(name) = to.file_name().filter(|_| same_name(from, to)) else {
+        return fs::rename(from, to);
+    };
+    let temporary = to.with_file_name(opts.temp.next(TempKind::Rename, name));
+    // The temporary name doesn't say what it stands in for, so a cleanup
+    // after a crash reads it from a note next to it
+    temp_names::note_name(&temporary, name)?;
+    let renamed = fs::rename(from, &temporary).and_then(|()| {
+        fs::rename(&temporary, to).inspect_err(|_| {
+            let _ = fs::rename(&temporary, from);
+        })
+    });
+    temp_names::forget_name(&temporary);
+    renamed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_target_of_composes_directory_moves() {
+        let mut plan = Plan::default();
+        plan.push("/r/Lib".into(), "/r/lib".into());
+        plan.push("/r/Lib/Button.ts".into(), "/r/lib/button.ts".into());
+
+        assert_eq!(
+            plan.target_of(Path::new("/r/Lib/Input.ts")),
+            Some(PathBuf::from("/r/lib/Input.ts"))
+        );
+        assert_eq!(
+            plan.target_of(Path::new("/r/Lib/Button.ts")),
+            Some(PathBuf::from("/r/lib/button.ts"))
+        );
+        assert_eq!(plan.target_of(Path::new("/r/Other.ts")), None);
+    }
+
+    #[test]
+    fn test_target_of_follows_later_renames_and_removals() {
+        let mut plan = Plan::default();
+        plan.push("Src/Lib".into(), "src/lib".into());
+        plan.push("./Src/Lib".into(), "src/library".into());
+        plan.push("Src/LibTools".into(), "src/lib-tools".into());
+        assert_eq!(
+            plan.target_of(Path::new("Src/Lib/Forms/Input.ts")),
+            Some(PathBuf::from("src/library/Forms/Input.ts"))
+        );
+        assert_eq!(
+            plan.target_of(Path::new("Src/LibTools")),
+            Some(PathBuf::from("src/lib-tools"))
+        );
+        assert_eq!(plan.target_of(Path::new("Src/Li")), None);
+
+        let mut other = Plan::default();
+        other.push("Docs".into(), "docs".into());
+        plan.extend(other);
+        assert!(plan.remove(Path::new("Src/LibTools")));
+        assert_eq!(plan.target_of(Path::new("Src/LibTools/Cli.ts")), None);
+        assert_eq!(
+            plan.target_of(Path::new("Docs/Intro.md")),
+            Some(PathBuf::from("docs/Intro.md"))
+        );
+    }
+
+    #[test]
+    fn test_compose_moves_stale_targets() {
+        let mut plan = Plan::default();
+        plan.push(
+            "/r/Lib/Forms/Input.ts".into(),
+            "/r/Lib/Forms/input.ts".into(),
+        );
+        plan.push("/r/Lib".into(), "/r/lib".into());
+        plan.push("/r/Lib/Forms".into(), "/r/Lib/forms".into());
+        plan.push("/r/Lib/Button.ts".into(), "/r/lib/button.ts".into());
+        plan.compose();
+
+        let targets: Vec<_> =
+            plan.renames.iter().map(|r| r.to.clone()).collect();
+        assert_eq!(
+            targets,
+            [
+                PathBuf::from("/r/lib/forms/input.ts"),
+                PathBuf::from("/r/lib"),
+                PathBuf::from("/r/lib/forms"),
+                PathBuf::from("/r/lib/button.ts"),
+            ]
+        );
+        assert_eq!(
+            plan.target_of(Path::new("/r/Lib/Forms/Other.ts")),
+            Some(PathBuf::from("/r/lib/forms/Other.ts"))
+        );
+    }
+
+    #[test]
+    fn test_validate_reports_collisions() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::write(root.join("A.ts"), "")?;
+        fs::write(root.join("B.ts"), "")?;
+        fs::write(root.join("taken.ts"), "")?;
+
+        let mut plan = Plan::default();
+        plan.push(root.join("A.ts"), root.join("same.ts"));
+        plan.push(root.join("B.ts"), root.join("Same.ts"));
+        assert!(plan.validate(&Options::default()).is_err());
+
+        let mut plan = Plan::default();
+        plan.push(root.join("A.ts"), root.join("taken.ts"));
+        assert!(plan.validate(&Options::default()).is_err());
+
+        let mut plan = Plan::default();
+        plan.push(root.join("Missing.ts"), root.join("missing.ts"));
+        assert!(plan.validate(&Options::default()).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_moves_into_renamed_directory() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir(root.join("Lib"))?;
+        fs::write(root.join("Lib/Button.ts"), "")?;
+
+        let mut plan = Plan::default();
+        plan.push(root.join("Lib/Button.ts"), root.join("lib/button.ts"));
+        plan.push(root.join("Lib"), root.join("lib"));
+        plan.validate(&Options::default())?;
+        plan.apply(&Options::default(), &mut Report::default())?;
+
+        assert!(root.join("lib/button.ts").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_renames_in_place_when_directory_fails() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir(root.join("Lib"))?;
+        fs::write(root.join("Lib/Button.ts"), "")?;
+        // A non-empty directory in the way makes the directory rename fail
+        fs::create_dir_all(root.join("lib/child"))?;
+
+        let mut plan = Plan::default();
+        plan.push(root.join("Lib"), root.join("lib"));
+        plan.push(root.join("Lib/Button.ts"), root.join("lib/button.ts"));
+        let mut report = Report::default();
+        plan.apply(&Options::default(), &mut report)?;
+
+        assert_eq!(report.failures.len(), 1);
+        assert!(root.join("Lib/button.ts").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_failed_overwrite_keeps_the_file() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        // A directory can't replace a file, so the move fails
+        fs::create_dir(root.join("Widgets"))?;
+        fs::write(root.join("widgets"), "kept")?;
+
+        let mut plan = Plan::default();
+        plan.push(root.join("Widgets"), root.join("widgets"));
+        let opts = Options {
+            on_conflict: OnConflict::Overwrite,
+            ..Options::default()
+        };
+        let mut report = Report::default();
+        plan.apply(&opts, &mut report)?;
+
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(fs::read_to_string(root.join("widgets"))?, "kept");
+        assert!(report.journal.changes.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,77 @@
+use crate::aliases::strip_jsonc;
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+/// Config files Prettier reads, in the order it looks for them. Overrides
+/// for parts of the tree aren't followed.
+const CONFIG_FILES: &[&str] = &[
+    ".prettierrc",
+    ".prettierrc.json",
+    ".prettierrc.json5",
+    ".prettierrc.yaml",
+    ".prettierrc.yml",
+];
+
+/// The quote Prettier writes strings with in the project at `root`: `'`
+/// with `singleQuote`, otherwise `"`. `None` if the project has no
+/// Prettier config.
+pub fn quote(root: &Path) -> Result<Option<char>> {
+    let quote = |single: bool| Some(if single { '\'' } else { '"' });
+    for name in CONFIG_FILES {
+        let path = root.join(name);
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        if let Ok(config) =
+            serde_json::from_str::<Value>(&strip_jsonc(&content))
+        {
+            let single = config.get("singleQuote").and_then(Value::as_bool);
+            return Ok(quote(single.unwrap_or(false)));
+        }
+        // A YAML config; only the top-level `singleQuote: true` matters
+        let single = content.lines().any(|line| {
+            line.split_once(':').is_some_and(|(key, value)| {
+                key == "singleQuote" && value.trim() == "true"
+            })
+        });
+        return Ok(quote(single));
+    }
+
+    let path = root.join("package.json");
+    let Ok(content) = fs::read_to_string(&path) else {
+        return Ok(None);
+    };
+    let package: Value = serde_json::from_str(&content)
+        .with_context(|| format!("Malformed {}", path.display()))?;
+    let Some(config) = package.get("prettier").filter(|c| c.is_object()) else {
+        return Ok(None);
+    };
+    let single = config.get("singleQuote").and_then(Value::as_bool);
+    Ok(quote(single.unwrap_or(false)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_prettier_quote() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        assert_eq!(quote(root)?, None);
+
+        fs::write(root.join("package.json"), r#"{ "prettier": {} }"#)?;
+        assert_eq!(quote(root)?, Some('"'));
+        fs::write(
+            root.join(".prettierrc.yml"),
+            "semi: false\nsingleQuote: true\n",
+        )?;
+        assert_eq!(quote(root)?, Some('\''));
+        fs::write(root.join(".prettierrc"), "{ \"singleQuote\": false, }")?;
+        assert_eq!(quote(root)?, Some('"'));
+        Ok(())
+    }
+}
@@ -0,0 +1,185 @@
+use crate::messages::{text, Msg};
+use crate::plan::Plan;
+use crate::report::Report;
+use crate::{ambiguity, encoding, scanner, Options};
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A file whose references a run would rewrite
+#[derive(Debug, PartialEq, Eq)]
+pub struct FileChange {
+    /// Where the file is once the plan is applied
+    pub path: PathBuf,
+    pub changes: usize,
+    /// Line numbers, counted from 1, with the old and new line
+    pub lines: Vec<(usize, String, String)>,
+}
+
+/// Works out everything a run over `dir` would do, without touching the
+/// disk. References are rewritten exactly as a real run rewrites them,
+/// before anything moves, and every path is shown where it ends up once
+/// the whole plan is applied, so a file in a directory that's renamed too
+/// is shown under the directory's new name.
+pub fn preview(
+    dir: &Path,
+    imports: bool,
+    renames: bool,
+    opts: &Options,
+    report: &mut Report,
+) -> Result<(Plan, Vec<FileChange>)> {
+    let plan = if renames {
+        crate::plan_directory(dir, opts, report)?
+    } else {
+        Plan::default()
+    };
+    if !imports {
+        return Ok((plan, Vec::new()));
+    }
+
+    let exceptions = plan.exceptions();
+    let mut files = Vec::new();
+    for entry in crate::source_files(dir, opts, report)? {
+        let path = entry.path();
+        let Some((content, _)) =
+            fs::read(path).ok().and_then(|b| encoding::decode(&b))
+        else {
+            continue;
+        };
+        let (new_content, changes) = scanner::convert_file(
+            path,
+            &content,
+            &opts.scan,
+            &plan,
+            &exceptions,
+        );
+        // Left alone just as a real run leaves them; inline directives are
+        // already honoured by `convert_file`
+        if changes == 0
+            || crate::is_left_generated(path, &content, changes, opts, report)
+        {
+            continue;
+        }
+        let lines = content
+            .lines()
+            .zip(new_content.lines())
+            .enumerate()
+            .filter(|(_, (old, new))| old != new)
+            .map(|(i, (old, new))| (i + 1, old.to_string(), new.to_string()))
+            .collect();
+        files.push(FileChange {
+            path: plan.target_of(path).unwrap_or(path.to_path_buf()),
+            changes,
+            lines,
+        });
+    }
+    Ok((plan, files))
+}
+
+/// Prints what a run over `dir` would do, for `--dry-run`
+pub fn run(
+    dir: &Path,
+    imports: bool,
+    renames: bool,
+    opts: &Options,
+    report: &mut Report,
+) -> Result<()> {
+    let (plan, files) = preview(dir, imports, renames, opts, report)?;
+    let ambiguous = ambiguity::find(&plan);
+    if !ambiguous.is_empty() {
+        ambiguity::print(&ambiguous, opts);
+    }
+    let display = &opts.display;
+    for rename in &plan.renames {
+        opts.log(format_args!(
+            "{}",
+            text(
+                Msg::WouldRename,
+                &[&display.show(&rename.from), &display.show(&rename.to)]
+            )
+        ));
+    }
+    for file in &files {
+        opts.log(format_args!(
+            "{}",
+            text(
+                Msg::WouldUpdate,
+                &[&file.changes, &display.show(&file.path)]
+            )
+        ));
+        for (line, old, new) in &file.lines {
+            opts.log(format_args!("  {}: - {}", line, old.trim()));
+            opts.log(format_args!("  {}: + {}", line, new.trim()));
+        }
+    }
+    opts.log(format_args!(
+        "{}",
+        text(Msg::DryRun, &[&plan.renames.len(), &files.len()])
+    ));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_preview_shows_final_paths() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = &temp_dir.path().join("project");
+        fs::create_dir_all(root.join("Components/Forms"))?;
+        fs::write(root.join("Components/Forms/TextInput.ts"), "")?;
+        let form = "import input from './Forms/TextInput';\n";
+        fs::write(root.join("Components/SignupForm.ts"), form)?;
+
+        let mut report = Report::default();
+        let (plan, files) =
+            preview(root, true, true, &Options::default(), &mut report)?;
+        assert_eq!(
+            plan.target_of(&root.join("Components/Forms/TextInput.ts")),
+            Some(root.join("components/forms/text-input.ts"))
+        );
+        assert_eq!(
+            files,
+            [FileChange {
+                path: root.join("components/signup-form.ts"),
+                changes: 2,
+                lines: vec![(
+                    1,
+                    "import input from './Forms/TextInput';".into(),
+                    "import input from './forms/text-input';".into(),
+                )],
+            }]
+        );
+        // Nothing moved or changed
+        assert!(root.join("Components/Forms/TextInput.ts").exists());
+        assert_eq!(
+            fs::read_to_string(root.join("Components/SignupForm.ts"))?,
+            form
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_preview_leaves_out_what_a_run_leaves_alone() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = &temp_dir.path().join("project");
+        fs::create_dir_all(root.join("Forms"))?;
+        fs::write(root.join("Forms/TextInput.ts"), "")?;
+        let import = "import input from './Forms/TextInput';\n";
+        fs::write(root.join("client.ts"), format!("// @generated\n{import}"))?;
+        let ignored = format!("// kebabify-ignore-file\n{import}");
+        fs::write(root.join("ignored.ts"), ignored)?;
+
+        let opts = Options {
+            generated_markers: vec!["@generated".into()],
+            ..Options::default()
+        };
+        let mut report = Report::default();
+        let (_, files) = preview(root, true, true, &opts, &mut report)?;
+        assert_eq!(files, []);
+        assert_eq!(report.warnings.len(), 1);
+        Ok(())
+    }
+}
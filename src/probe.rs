@@ -0,0 +1,221 @@
+use crate::temp_names::{Kind, TempNames};
+use anyhow::{Context, Result};
+use std::ffi::OsStr;
+use std::fs;
+use std::path::Path;
+
+/// How a filesystem treats names, worked out by trying things in a scratch
+/// directory rather than guessed from the OS: SMB shares and WSL's drvfs
+/// mounts behave like Windows even on Linux.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Whether `Probe` and `probe` are different files
+    pub case_sensitive: bool,
+    /// The longest file name that can be created, in bytes
+    pub max_name_length: Option<usize>,
+    /// The longest path that can be created, in bytes, if it's below
+    /// `PATH_PROBE_LIMIT`
+    pub max_path_length: Option<usize>,
+    pub normalization: Normalization,
+    /// Whether a rename onto an existing file replaces it in one step, as
+    /// POSIX requires, rather than failing
+    pub replacing_rename: bool,
+}
+
+impl Default for Capabilities {
+    /// What's assumed when the filesystem can't be probed
+    fn default() -> Self {
+        Self {
+            case_sensitive: true,
+            max_name_length: None,
+            max_path_length: None,
+            normalization: Normalization::Preserved,
+            replacing_rename: true,
+        }
+    }
+}
+
+/// What happens to a name with an accented letter like `é`, which Unicode
+/// can write as one code point (NFC) or as `e` and a combining accent (NFD)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Normalization {
+    /// Kept as written; the two forms are different names (ext4, NTFS)
+    Preserved,
+    /// Kept as written, but either form finds the file (APFS)
+    Insensitive,
+    /// Stored decomposed, whatever was written (HFS+)
+    Decomposed,
+}
+
+/// Name lengths tried, in bytes: eCryptfs, most filesystems, and beyond
+const NAME_LENGTHS: [usize; 4] = [143, 255, 510, 1024];
+
+/// Paths are only probed up to this length; longer limits aren't reported
+const PATH_PROBE_LIMIT: usize = 4096;
+
+/// Probes the filesystem `dir` is on, in a hidden scratch directory that's
+/// removed afterwards
+pub fn probe(dir: &Path, temp: &TempNames) -> Result<Capabilities> {
+    let scratch = dir.join(temp.next(Kind::Probe, OsStr::new("scratch")));
+    fs::create_dir(&scratch).with_context(|| {
+        format!("Failed to create a probe directory in {}", dir.display())
+    })?;
+    let caps = probe_in(&scratch);
+    fs::remove_dir_all(&scratch)
+        .with_context(|| format!("Failed to remove {}", scratch.display()))?;
+    caps
+}
+
+fn probe_in(scratch: &Path) -> Result<Capabilities> {
+    let write = |name: &str| {
+        let path = scratch.join(name);
+        fs::write(&path, name).map(|()| path)
+    };
+
+    let case = write("Probe")?;
+    let case_sensitive = !scratch.join("probe").exists();
+    fs::remove_file(case)?;
+
+    let max_name_length = NAME_LENGTHS
+        .iter()
+        .take_while(|&&len| {
+            write(&"n".repeat(len)).and_then(fs::remove_file).is_ok()
+        })
+        .last()
+        .copied();
+
+    let composed = write("caf\u{e9}")?;
+    let listed = fs::read_dir(scratch)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.file_name())
+        .collect::<Vec<_>>();
+    let normalization = if listed.iter().any(|n| n == "cafe\u{301}") {
+        Normalization::Decomposed
+    } else if scratch.join("cafe\u{301}").exists() {
+        Normalization::Insensitive
+    } else {
+        Normalization::Preserved
+    };
+    fs::remove_file(composed)?;
+
+    let (from, onto) = (write("from")?, write("onto")?);
+    let replacing_rename = fs::rename(&from, &onto).is_ok()
+        && fs::read_to_string(&onto).is_ok_and(|c| c == "from");
+
+    Ok(Capabilities {
+        case_sensitive,
+        max_name_length,
+        max_path_length: max_path_length(scratch),
+        normalization,
+        replacing_rename,
+    })
+}
+
+/// Nests directories until one can't be created, and returns the length of
+/// the longest path that could be, if that's below `PATH_PROBE_LIMIT`
+fn max_path_length(scratch: &Path) -> Option<usize> {
+    const STEP: usize = 100;
+    let mut path = scratch.join("deep");
+    while path.as_os_str().len() < PATH_PROBE_LIMIT {
+        if fs::create_dir(&path).is_err() {
+            // Narrow it down to the byte with a shorter last name
+            let parent = path.parent()?;
+            let longest = (1..STEP)
+                .rev()
+                .map(|len| parent.join("p".repeat(len)))
+                .find(|path| fs::create_dir(path).is_ok())
+                .unwrap_or(parent.to_path_buf());
+            return Some(longest.as_os_str().len());
+        }
+        path.push("p".repeat(STEP));
+    }
+    None
+}
+
+impl Capabilities {
+    /// Whether `to` is a name the filesystem can't store. Only the name is
+    /// checked when the path limit isn't known.
+    pub fn too_long(&self, to: &Path) -> Option<String> {
+        let name = to.file_name()?.len();
+        match (self.max_name_length, self.max_path_length) {
+            (Some(max), _) if name > max => Some(format!(
+                "the new name is {name} bytes long, the filesystem allows {max}"
+            )),
+            (_, Some(max)) if to.as_os_str().len() > max => Some(format!(
+                "the new path is {} bytes long, the filesystem allows {max}",
+                to.as_os_str().len()
+            )),
+            _ => None,
+        }
+    }
+
+    /// Prints the capabilities and what each means for a run, for
+    /// `kebabify probe`
+    pub fn print(&self) {
+        let yes_no = |yes| if yes { "yes" } else { "no" };
+        println!("Case-sensitive: {}", yes_no(self.case_sensitive));
+        match self.max_name_length {
+            Some(max) if max == NAME_LENGTHS[NAME_LENGTHS.len() - 1] => {
+                println!("Longest name: {max} bytes or more")
+            }
+            Some(max) => println!("Longest name: {max} bytes"),
+            None => println!("Longest name: under {} bytes", NAME_LENGTHS[0]),
+        }
+        match self.max_path_length {
+            Some(max) => println!("Longest path: {max} bytes"),
+            None => println!("Longest path: {PATH_PROBE_LIMIT} bytes or more"),
+        }
+        println!(
+            "Unicode names: {}",
+            match self.normalization {
+                Normalization::Preserved => "kept as written",
+                Normalization::Insensitive =>
+                    "kept as written, NFC and NFD find the same file",
+                Normalization::Decomposed => "stored decomposed (NFD)",
+            }
+        );
+        println!("Rename replaces files: {}", yes_no(self.replacing_rename));
+
+        if !self.case_sensitive {
+            println!(
+                "Case-only renames will go through a temporary name, since \
+                 the filesystem can't tell the old and new names apart"
+            );
+        }
+        if self.max_name_length.is_some() || self.max_path_length.is_some() {
+            println!("Renames to names too long to store will be refused");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_probe_cleans_up() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let caps = probe(temp_dir.path(), &TempNames::default())?;
+        assert_eq!(fs::read_dir(temp_dir.path())?.count(), 0);
+        if cfg!(target_os = "linux") {
+            assert!(caps.case_sensitive);
+            assert_eq!(caps.max_name_length, Some(255));
+            assert!(caps.replacing_rename);
+        }
+
+        let long = temp_dir.path().join(format!("{}.ts", "a".repeat(300)));
+        assert_eq!(
+            Capabilities {
+                max_name_length: Some(255),
+                ..Default::default()
+            }
+            .too_long(&long),
+            Some(
+                "the new name is 303 bytes long, the filesystem allows 255"
+                    .into()
+            )
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+use crate::plan::Plan;
+use crate::scanner::{self, rewrite_file_path, splice, ScanOptions, Scanner};
+use crate::NameStyle;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// Protocol Buffers definitions. The style guide and `buf lint` want their
+/// files in snake_case, so `UserService.proto` becomes `user_service.proto`.
+pub struct ProtoScanner;
+
+impl Scanner for ProtoScanner {
+    fn matches(&self, path: &Path) -> bool {
+        path.extension().is_some_and(|e| e == "proto")
+    }
+
+    fn name_style(&self) -> NameStyle {
+        NameStyle::Snake
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_imports(path, content, scanner::converted_path)
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_imports(path, content, |found| plan.target_of(found))
+    }
+
+    /// Imports found in no directory above the file, when the directory
+    /// they start in is there. Ones like `google/protobuf/any.proto` come
+    /// with `protoc`.
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> Vec<String> {
+        let dir = path.parent().unwrap_or(Path::new(""));
+        import_regex()
+            .captures_iter(content)
+            .map(|caps| caps[1].to_string())
+            .filter(|file| {
+                let local = match file.split_once('/') {
+                    Some((first, _)) => {
+                        dir.ancestors().any(|base| base.join(first).is_dir())
+                    }
+                    None => true,
+                };
+                local
+                    && dir
+                        .ancestors()
+                        .all(|base| scanner::leads_nowhere(base, file, &[]))
+            })
+            .collect()
+    }
+}
+
+/// `import "Protos/UserService.proto";`, `import public` and `import weak`
+fn import_regex() -> Regex {
+    Regex::new(
+        r#"(?m)^[ \t]*import[ \t]+(?:(?:public|weak)[ \t]+)?["']([^"'\n]+)["']"#,
+    )
+    .unwrap()
+}
+
+/// Rewrites `import "Protos/UserService.proto";`, `import public` and
+/// `import weak` included. Imports are relative to one of `protoc`'s
+/// include paths, which aren't known here, so each is tried against the
+/// file's directory and then each directory above it.
+fn rewrite_imports(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut edits = Vec::new();
+    for caps in import_regex().captures_iter(content) {
+        let file = caps.get(1).unwrap();
+        // The first directory the import exists in
+        let Some(base) = dir
+            .ancestors()
+            .find(|base| base.join(file.as_str()).exists())
+        else {
+            continue;
+        };
+        edits.extend(rewrite_file_path(
+            base,
+            file.as_str(),
+            file.start(),
+            &[],
+            &rename,
+        ));
+    }
+    splice(content, edits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_proto_imports() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("Protos/Common"))?;
+        fs::write(root.join("Protos/UserService.proto"), "")?;
+        fs::write(root.join("Protos/Common/PageInfo.proto"), "")?;
+
+        let content = r#"syntax = "proto3";
+
+import "Protos/UserService.proto";
+import public "Protos/Common/PageInfo.proto";
+import "google/protobuf/Timestamp.proto";
+"#;
+        let path = root.join("Protos/Api.proto");
+        assert!(ProtoScanner.matches(&path));
+        assert_eq!(ProtoScanner.name_style(), NameStyle::Snake);
+        let (new_content, changes) =
+            ProtoScanner.convert(&path, content, &ScanOptions::default());
+        assert_eq!(
+            new_content,
+            r#"syntax = "proto3";
+
+import "protos/user_service.proto";
+import public "protos/common/page_info.proto";
+import "google/protobuf/Timestamp.proto";
+"#
+        );
+        assert_eq!(changes, 5);
+        let orphans = ProtoScanner.orphans(
+            &path,
+            &content.replace("Common/PageInfo", "Common/Gone"),
+            &ScanOptions::default(),
+        );
+        assert_eq!(orphans, ["Protos/Common/Gone.proto"]);
+        Ok(())
+    }
+}
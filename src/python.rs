@@ -0,0 +1,445 @@
+use crate::plan::Plan;
+use crate::scanner::{self, splice, ScanOptions, Scanner};
+use crate::NameStyle;
+use regex::Regex;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Python modules and stubs. Module names have to be identifiers, so their
+/// files and packages are named in snake_case.
+pub struct PythonScanner;
+
+impl Scanner for PythonScanner {
+    fn matches(&self, path: &Path) -> bool {
+        matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("py" | "pyi")
+        )
+    }
+
+    fn name_style(&self) -> NameStyle {
+        NameStyle::Snake
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_imports(path, content, scanner::converted_path)
+    }
+
+    /// Follows renames in place. Modules moved to another package would
+    /// need their import rewritten from scratch, so they keep their path.
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_imports(path, content, |module| plan.target_of(module))
+    }
+
+    /// Imports of local modules, relative or under a local package, that
+    /// stop being found partway along their path
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> Vec<String> {
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let roots = import_roots(dir);
+        let find_root = |top: &str| {
+            roots
+                .iter()
+                .copied()
+                .find(|root| find_module(root, top).is_some())
+        };
+        let keep = &|_: &Path| None;
+
+        let mut orphans = Vec::new();
+        for caps in statement_regex().captures_iter(content) {
+            if let Some(names) = caps.get(4) {
+                for item in item_regex().captures_iter(names.as_str()) {
+                    let module = &item[1];
+                    let segments: Vec<&str> = module.split('.').collect();
+                    let found = find_root(segments[0]).is_none_or(|root| {
+                        resolve(root, &segments, keep).path.is_some()
+                    });
+                    if !found {
+                        orphans.push(module.to_string());
+                    }
+                }
+                continue;
+            }
+            let dots = caps.get(1).map_or(0, |m| m.len());
+            let module = &caps[2];
+            let segments: Vec<&str> =
+                module.split('.').filter(|s| !s.is_empty()).collect();
+            let base = if dots > 0 {
+                dir.ancestors().nth(dots - 1)
+            } else {
+                segments.first().and_then(|top| find_root(top))
+            };
+            let found = base.is_none_or(|base| {
+                resolve(base, &segments, keep).path.is_some()
+            });
+            if !found {
+                orphans.push(format!("{}{}", &caps[1], module));
+            }
+        }
+        orphans
+    }
+}
+
+/// `import A.B as c, D` and `from .A import (b, C as c)`
+fn statement_regex() -> Regex {
+    Regex::new(
+        r"(?mx)
+        ^[\ \t]*(?:
+            from[\ \t]+(\.*)([\w.]*)[\ \t]+import[\ \t]+(\([^)]*\)|[^\n\#;]*)
+            | import[\ \t]+([^\n\#;]*)
+        )",
+    )
+    .unwrap()
+}
+
+/// One imported name and its alias, if it has one
+fn item_regex() -> Regex {
+    Regex::new(r"([^\W\d]\w*(?:\.[^\W\d]\w*)*)(\s+as\s+\w+)?").unwrap()
+}
+
+/// Rewrites the module paths of imports that name local modules. `rename`
+/// gives the new path of a module's file or package, or `None` if it keeps
+/// its name. Modules that can't be found on disk, like the standard library
+/// and installed packages, are left alone.
+fn rewrite_imports(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let roots = import_roots(dir);
+    let find_root = |top: &str| {
+        roots
+            .iter()
+            .copied()
+            .find(|root| find_module(root, top).is_some())
+    };
+
+    let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+    let mut statements = Vec::new();
+    // Old and new prefixes of dotted modules bound by `import A.B`, which
+    // the rest of the file refers to in full
+    let mut bound: HashMap<String, String> = HashMap::new();
+
+    for caps in statement_regex().captures_iter(content) {
+        statements.push(caps.get(0).unwrap().range());
+
+        if let Some(names) = caps.get(4) {
+            for item in item_regex().captures_iter(names.as_str()) {
+                let module = item.get(1).unwrap();
+                let segments: Vec<&str> = module.as_str().split('.').collect();
+                let Some(root) = find_root(segments[0]) else {
+                    continue;
+                };
+                let new = resolve(root, &segments, &rename).dotted(&segments);
+                if new == module.as_str() {
+                    continue;
+                }
+
+                let replacement = if item.get(2).is_some() {
+                    new
+                } else if segments.len() == 1 {
+                    // Keep the name the rest of the file uses
+                    format!("{} as {}", new, module.as_str())
+                } else {
+                    for (i, new_prefix) in prefixes(&new).enumerate() {
+                        bound.insert(segments[..=i].join("."), new_prefix);
+                    }
+                    new
+                };
+                let start = names.start() + module.start();
+                edits.push((start..start + module.len(), replacement));
+            }
+            continue;
+        }
+
+        let dots = caps.get(1).map_or(0, |m| m.len());
+        let module = caps.get(2).unwrap();
+        let segments: Vec<&str> = module
+            .as_str()
+            .split('.')
+            .filter(|s| !s.is_empty())
+            .collect();
+        let base = if dots > 0 {
+            dir.ancestors().nth(dots - 1)
+        } else {
+            segments.first().and_then(|top| find_root(top))
+        };
+        let Some(base) = base else {
+            continue;
+        };
+
+        let resolved = resolve(base, &segments, &rename);
+        let new = resolved.dotted(&segments);
+        if new != module.as_str() {
+            edits.push((module.range(), new));
+        }
+
+        // Names imported from a package may be its submodules
+        let Some(package) = resolved.path.filter(|p| p.is_dir()) else {
+            continue;
+        };
+        let names = caps.get(3).unwrap();
+        for item in item_regex().captures_iter(names.as_str()) {
+            let name = item.get(1).unwrap();
+            let new = resolve(&package, &[name.as_str()], &rename)
+                .dotted(&[name.as_str()]);
+            if new == name.as_str() {
+                continue;
+            }
+            let replacement = if item.get(2).is_some() {
+                new
+            } else {
+                format!("{} as {}", new, name.as_str())
+            };
+            let start = names.start() + name.start();
+            edits.push((start..start + name.len(), replacement));
+        }
+    }
+
+    if !bound.is_empty() {
+        edits.extend(rewrite_usages(content, &statements, &bound));
+    }
+    splice(content, edits)
+}
+
+/// Rewrites references like `A.B.func()` to modules that were imported with
+/// `import A.B` and renamed, outside the import statements themselves
+fn rewrite_usages(
+    content: &str,
+    statements: &[Range<usize>],
+    bound: &HashMap<String, String>,
+) -> Vec<(Range<usize>, String)> {
+    let chain_regex = Regex::new(r"[^\W\d]\w*(?:\.[^\W\d]\w*)*").unwrap();
+    let mut edits = Vec::new();
+
+    for chain in chain_regex.find_iter(content) {
+        if statements.iter().any(|s| s.contains(&chain.start())) {
+            continue;
+        }
+        // An attribute of something else, like `self.A`
+        let before = content[..chain.start()].chars().next_back();
+        if before.is_some_and(|c| c == '.' || c.is_alphanumeric()) {
+            continue;
+        }
+
+        // The longest prefix of the chain that names a bound module
+        let text = chain.as_str();
+        let longest = text
+            .match_indices('.')
+            .map(|(i, _)| i)
+            .chain([text.len()])
+            .rev()
+            .find_map(|end| Some((end, bound.get(&text[..end])?)));
+        if let Some((end, new)) = longest {
+            if new != &text[..end] {
+                edits.push((chain.start()..chain.start() + end, new.clone()));
+            }
+        }
+    }
+    edits
+}
+
+/// `a`, `a.b`, `a.b.c` for `a.b.c`
+fn prefixes(dotted: &str) -> impl Iterator<Item = String> + '_ {
+    dotted
+        .match_indices('.')
+        .map(|(i, _)| &dotted[..i])
+        .chain([dotted])
+        .map(str::to_string)
+}
+
+/// Directories an absolute import in `dir` may be relative to: `dir`
+/// itself, each package enclosing it, and the directory holding the
+/// outermost package
+fn import_roots(dir: &Path) -> Vec<&Path> {
+    let mut roots = Vec::new();
+    for ancestor in dir.ancestors() {
+        roots.push(ancestor);
+        if !ancestor.join("__init__.py").is_file() {
+            break;
+        }
+    }
+    roots
+}
+
+/// The package or module file named `name` in `dir`
+fn find_module(dir: &Path, name: &str) -> Option<PathBuf> {
+    let package = dir.join(name);
+    if package.is_dir() {
+        return Some(package);
+    }
+    ["py", "pyi"]
+        .iter()
+        .map(|ext| dir.join(format!("{}.{}", name, ext)))
+        .find(|file| file.is_file())
+}
+
+/// A module path looked up on disk, segment by segment
+struct Module {
+    /// New names of the segments that were found
+    names: Vec<String>,
+    /// The file or package the whole path names, if every segment was found
+    path: Option<PathBuf>,
+}
+
+impl Module {
+    /// The new module path, with segments that weren't found kept as is
+    fn dotted(&self, segments: &[&str]) -> String {
+        let kept = segments[self.names.len()..].iter().map(|s| s.to_string());
+        self.names
+            .iter()
+            .cloned()
+            .chain(kept)
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+}
+
+fn resolve(
+    base: &Path,
+    segments: &[&str],
+    rename: &impl Fn(&Path) -> Option<PathBuf>,
+) -> Module {
+    let mut names = Vec::new();
+    let mut current = base.to_path_buf();
+    for segment in segments {
+        let Some(found) = find_module(&current, segment) else {
+            return Module { names, path: None };
+        };
+        let new_name = rename(&found).and_then(|new| {
+            let name = if found.is_dir() {
+                new.file_name()
+            } else {
+                new.file_stem()
+            };
+            Some(name?.to_str()?.to_string())
+        });
+        names.push(new_name.unwrap_or(segment.to_string()));
+        current = found;
+    }
+    Module {
+        names,
+        path: Some(current),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn setup() -> Result<TempDir> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("App/Models"))?;
+        fs::write(root.join("App/__init__.py"), "")?;
+        fs::write(root.join("App/Models/__init__.py"), "")?;
+        fs::write(root.join("App/Models/UserModel.py"), "")?;
+        fs::write(root.join("App/Models/Helpers.py"), "")?;
+        fs::write(root.join("Settings.py"), "")?;
+        Ok(temp_dir)
+    }
+
+    #[test]
+    fn test_convert_imports() -> Result<()> {
+        let temp_dir = setup()?;
+        let root = temp_dir.path();
+
+        let content = "\
+import os, Settings
+import App.Models.Helpers
+from App.Models import (
+    UserModel,
+    Helpers as h,
+)
+from requests import Session
+
+App.Models.Helpers.run(os.path)
+";
+        let (new_content, changes) = PythonScanner.convert(
+            &root.join("main.py"),
+            content,
+            &ScanOptions::default(),
+        );
+        assert_eq!(
+            new_content,
+            "\
+import os, settings as Settings
+import app.models.helpers
+from app.models import (
+    user_model as UserModel,
+    helpers as h,
+)
+from requests import Session
+
+app.models.helpers.run(os.path)
+"
+        );
+        assert_eq!(changes, 6);
+        Ok(())
+    }
+
+    #[test]
+    fn test_relative_imports() -> Result<()> {
+        let temp_dir = setup()?;
+        let root = temp_dir.path();
+        let path = root.join("App/Models/Helpers.py");
+
+        let content =
+            "from .UserModel import UserModel\nfrom .. import Models\n";
+        let (new_content, _) =
+            PythonScanner.convert(&path, content, &ScanOptions::default());
+        assert_eq!(
+            new_content,
+            "from .user_model import UserModel\nfrom .. import models as Models\n"
+        );
+
+        let mut plan = Plan::default();
+        plan.push(
+            root.join("App/Models/UserModel.py"),
+            root.join("App/Models/user.py"),
+        );
+        let (new_content, changes) = PythonScanner.follow_plan(
+            &path,
+            content,
+            &plan,
+            &ScanOptions::default(),
+        );
+        assert_eq!(
+            new_content,
+            "from .user import UserModel\nfrom .. import Models\n"
+        );
+        assert_eq!(changes, 1);
+
+        let content = "\
+import os.path
+import App.Models.Gone, Settings
+from .UserModel import UserModel
+from .Removed import thing
+from App.Models import Helpers
+";
+        let orphans =
+            PythonScanner.orphans(&path, content, &ScanOptions::default());
+        assert_eq!(orphans, ["App.Models.Gone", ".Removed"]);
+        Ok(())
+    }
+}
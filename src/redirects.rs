@@ -0,0 +1,386 @@
+use crate::plan::Plan;
+use crate::report::Report;
+use crate::{relative_to, Options};
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde_json::{json, Value};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Component, Path};
+
+/// Where redirects for renamed routes are written
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedirectFormat {
+    /// A Netlify `_redirects` file
+    Netlify,
+    /// The `redirects` list of a `vercel.json`
+    Vercel,
+    /// nginx `rewrite` directives
+    Nginx,
+}
+
+impl RedirectFormat {
+    fn default_file(self) -> &'static str {
+        match self {
+            RedirectFormat::Netlify => "_redirects",
+            RedirectFormat::Vercel => "vercel.json",
+            RedirectFormat::Nginx => "redirects.conf",
+        }
+    }
+}
+
+/// One part of a route's URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Static(String),
+    /// `[id]`, matching one part
+    Param,
+    /// `[...slug]`, matching the rest of the URL
+    CatchAll,
+}
+
+/// Why a page file or directory named after a route parameter, like
+/// `[postId].tsx` or `[...slug]`, keeps its name: the page reads the
+/// parameter by it, as in `params.postId`, so converting it breaks the page
+pub fn pins_name(path: &Path, is_dir: bool) -> Option<&'static str> {
+    let name = path.file_name()?.to_str()?;
+    let stem = match is_dir {
+        true => name,
+        false => name.rsplit_once('.').map_or(name, |(stem, _)| stem),
+    };
+    param(stem)
+        .is_some()
+        .then_some("it's a route parameter, which the page reads by this name")
+}
+
+/// The name inside a route parameter segment like `[id]`, `[...slug]` or
+/// `[[...slug]]`
+fn param(segment: &str) -> Option<&str> {
+    let name = segment.strip_prefix('[')?.strip_suffix(']')?;
+    (!name.is_empty()).then_some(name)
+}
+
+/// The URL a page file is served at, for SvelteKit (`src/routes/**/
+/// +page.svelte`), the Next.js app router (`app/**/page.tsx`) and
+/// file-based routers like the Next.js pages router and Nuxt
+/// (`pages/**/*.vue`). `path` is relative to the project root.
+fn route(path: &Path) -> Option<Vec<Segment>> {
+    let parts: Vec<&str> = path
+        .components()
+        .map(|c| match c {
+            Component::Normal(part) => part.to_str(),
+            _ => None,
+        })
+        .collect::<Option<_>>()?;
+    let (file, dirs) = parts.split_last()?;
+    let (stem, extension) = file.rsplit_once('.')?;
+    let start = |root: &[&str]| {
+        let at = dirs.windows(root.len()).position(|w| w == root)?;
+        Some(&dirs[at + root.len()..])
+    };
+
+    let segments: Vec<&str> = if let Some(dirs) = start(&["src", "routes"]) {
+        (stem == "+page").then_some(dirs.to_vec())?
+    } else if let Some(dirs) = start(&["app"]) {
+        let is_page = stem == "page"
+            && matches!(extension, "js" | "jsx" | "ts" | "tsx" | "mdx");
+        is_page.then_some(dirs.to_vec())?
+    } else if let Some(dirs) = start(&["pages"]) {
+        let is_page = !stem.starts_with('_')
+            && matches!(extension, "js" | "jsx" | "ts" | "tsx" | "mdx" | "vue");
+        if !is_page {
+            return None;
+        }
+        let mut segments = dirs.to_vec();
+        if stem != "index" {
+            segments.push(stem);
+        }
+        segments
+    } else {
+        return None;
+    };
+
+    Some(
+        segments
+            .into_iter()
+            // `(group)` directories and `@slot`s don't show up in the URL
+            .filter(|s| !(s.starts_with('(') && s.ends_with(')')))
+            .filter(|s| !s.starts_with('@'))
+            .map(|s| match param(s) {
+                Some(name)
+                    if name.trim_start_matches('[').starts_with("...") =>
+                {
+                    Segment::CatchAll
+                }
+                Some(_) => Segment::Param,
+                None => Segment::Static(s.to_string()),
+            })
+            .collect(),
+    )
+}
+
+/// Writes the route as a pattern, naming parameters `:p1`, `:p2`, ... in
+/// order, so the old and new URL of a route use the same names
+fn pattern(route: &[Segment], format: RedirectFormat) -> String {
+    let mut params = 0;
+    let mut url = String::new();
+    for segment in route {
+        url.push('/');
+        match (segment, format) {
+            (Segment::Static(name), RedirectFormat::Nginx) => {
+                url.push_str(&regex::escape(name))
+            }
+            (Segment::Static(name), _) => url.push_str(name),
+            (Segment::Param, RedirectFormat::Nginx) => url.push_str("([^/]+)"),
+            (Segment::CatchAll, RedirectFormat::Nginx) => url.push_str("(.*)"),
+            (Segment::Param, _) => {
+                params += 1;
+                url.push_str(&format!(":p{}", params));
+            }
+            (Segment::CatchAll, RedirectFormat::Netlify) => url.push('*'),
+            (Segment::CatchAll, RedirectFormat::Vercel) => {
+                params += 1;
+                url.push_str(&format!(":p{}*", params));
+            }
+        }
+    }
+    if url.is_empty() {
+        url.push('/');
+    }
+    url
+}
+
+/// Where a redirect goes, with the parameters the source captured
+fn destination(route: &[Segment], format: RedirectFormat) -> String {
+    match format {
+        RedirectFormat::Netlify => {
+            pattern(route, format).replace('*', ":splat")
+        }
+        RedirectFormat::Vercel => pattern(route, format),
+        RedirectFormat::Nginx => {
+            let mut captures = 0;
+            let mut url = String::new();
+            for segment in route {
+                url.push('/');
+                match segment {
+                    Segment::Static(name) => url.push_str(name),
+                    Segment::Param | Segment::CatchAll => {
+                        captures += 1;
+                        url.push_str(&format!("${}", captures));
+                    }
+                }
+            }
+            if url.is_empty() {
+                url.push('/');
+            }
+            url
+        }
+    }
+}
+
+/// Old and new routes of every page whose URL the renames in `plan`
+/// changed. Pages are found in `final_root`, the tree after the run, and
+/// traced back to where they were under `root`.
+fn changed_routes(
+    root: &Path,
+    final_root: &Path,
+    plan: &Plan,
+    opts: &Options,
+    report: &mut Report,
+) -> Result<Vec<(Vec<Segment>, Vec<Segment>)>> {
+    let mut undo = Plan::default();
+    for rename in &plan.renames {
+        undo.push(rename.to.clone(), rename.from.clone());
+    }
+
+    let mut routes = Vec::new();
+    for entry in crate::walk(final_root, opts, report)? {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let new_path = entry.path();
+        let old_path = undo.target_of(new_path).unwrap_or(new_path.into());
+        let new = route(relative_to(new_path, final_root));
+        let old = route(relative_to(&old_path, root));
+        if let (Some(old), Some(new)) = (old, new) {
+            if old != new {
+                routes.push((old, new));
+            }
+        }
+    }
+    Ok(routes)
+}
+
+/// Writes redirects from the old to the new URL of every page the run
+/// renamed, to `file` (relative to the final root) or the format's usual
+/// file. Existing redirects in the file are kept.
+pub fn write(
+    root: &Path,
+    final_root: &Path,
+    plan: &Plan,
+    format: RedirectFormat,
+    file: Option<&Path>,
+    opts: &Options,
+    report: &mut Report,
+) -> Result<()> {
+    let routes = changed_routes(root, final_root, plan, opts, report)?;
+    if routes.is_empty() {
+        return Ok(());
+    }
+    let path =
+        final_root.join(file.unwrap_or(Path::new(format.default_file())));
+    write_routes(&path, &routes, format)?;
+    opts.log(format_args!(
+        "Wrote {} redirects to: {}",
+        routes.len(),
+        opts.display.show(&path)
+    ));
+    Ok(())
+}
+
+fn write_routes(
+    path: &Path,
+    routes: &[(Vec<Segment>, Vec<Segment>)],
+    format: RedirectFormat,
+) -> Result<()> {
+    let context = || format!("Failed to write redirects: {}", path.display());
+    let lines: Vec<String> = routes
+        .iter()
+        .map(|(old, new)| {
+            let (from, to) = (pattern(old, format), destination(new, format));
+            match format {
+                RedirectFormat::Netlify => format!("{}  {}  301\n", from, to),
+                RedirectFormat::Nginx => {
+                    format!("rewrite ^{}$ {} permanent;\n", from, to)
+                }
+                RedirectFormat::Vercel => String::new(),
+            }
+        })
+        .collect();
+
+    if format != RedirectFormat::Vercel {
+        let mut out = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(context)?;
+        return out
+            .write_all(lines.concat().as_bytes())
+            .with_context(context);
+    }
+
+    let mut config: Value = match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content)
+            .with_context(|| format!("Malformed {}", path.display()))?,
+        Err(_) => json!({}),
+    };
+    let Some(object) = config.as_object_mut() else {
+        anyhow::bail!("Malformed {}: not a JSON object", path.display());
+    };
+    let redirects = object.entry("redirects").or_insert_with(|| json!([]));
+    let Some(redirects) = redirects.as_array_mut() else {
+        anyhow::bail!(
+            "Malformed {}: \"redirects\" isn't a list",
+            path.display()
+        );
+    };
+    for (old, new) in routes {
+        redirects.push(json!({
+            "source": pattern(old, format),
+            "destination": destination(new, format),
+            "permanent": true,
+        }));
+    }
+    let content = serde_json::to_string_pretty(&config)?;
+    fs::write(path, content + "\n").with_context(context)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_routes() {
+        let url = |path: &str| {
+            route(Path::new(path)).map(|r| pattern(&r, RedirectFormat::Netlify))
+        };
+        assert_eq!(
+            url("src/routes/AboutUs/+page.svelte"),
+            Some("/AboutUs".into())
+        );
+        assert_eq!(url("src/routes/+page.svelte"), Some("/".into()));
+        assert_eq!(url("src/routes/Blog/+layout.svelte"), None);
+        assert_eq!(
+            url("app/(marketing)/Blog/[postId]/page.tsx"),
+            Some("/Blog/:p1".into())
+        );
+        assert_eq!(url("pages/Docs/[...slug].vue"), Some("/Docs/*".into()));
+        assert_eq!(url("pages/Docs/index.tsx"), Some("/Docs".into()));
+        assert_eq!(url("pages/_app.tsx"), None);
+        assert_eq!(url("src/components/Button.svelte"), None);
+
+        let pinned = |path: &str, is_dir| pins_name(Path::new(path), is_dir);
+        assert!(pinned("pages/Blog/[PostId].tsx", false).is_some());
+        assert!(pinned("pages/Docs/[...SlugParts].vue", false).is_some());
+        assert!(pinned("app/Blog/[[...Parts]]", true).is_some());
+        assert!(pinned("pages/Blog/PostList.tsx", false).is_none());
+        assert!(pinned("app/Blog", true).is_none());
+    }
+
+    #[test]
+    fn test_write_redirects() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = &temp_dir.path().join("project");
+        fs::create_dir_all(root.join("src/routes/about-us"))?;
+        fs::create_dir_all(root.join("pages/user-profile"))?;
+        fs::write(root.join("src/routes/about-us/+page.svelte"), "")?;
+        fs::write(root.join("pages/user-profile/[userId].tsx"), "")?;
+        fs::write(root.join("pages/other.tsx"), "")?;
+
+        let mut plan = Plan::default();
+        plan.push(
+            root.join("src/routes/AboutUs"),
+            root.join("src/routes/about-us"),
+        );
+        plan.push(
+            root.join("pages/UserProfile"),
+            root.join("pages/user-profile"),
+        );
+        plan.push(
+            root.join("pages/UserProfile/[userId].tsx"),
+            root.join("pages/user-profile/[userId].tsx"),
+        );
+        let opts = Options::default();
+        let mut report = Report::default();
+        for format in [RedirectFormat::Netlify, RedirectFormat::Nginx] {
+            write(root, root, &plan, format, None, &opts, &mut report)?;
+        }
+        write(
+            root,
+            root,
+            &plan,
+            RedirectFormat::Vercel,
+            None,
+            &opts,
+            &mut report,
+        )?;
+
+        assert_eq!(
+            fs::read_to_string(root.join("_redirects"))?,
+            "/UserProfile/:p1  /user-profile/:p1  301\n\
+             /AboutUs  /about-us  301\n"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("redirects.conf"))?,
+            "rewrite ^/UserProfile/([^/]+)$ /user-profile/$1 permanent;\n\
+             rewrite ^/AboutUs$ /about-us permanent;\n"
+        );
+        let vercel: Value = serde_json::from_str(&fs::read_to_string(
+            root.join("vercel.json"),
+        )?)?;
+        assert_eq!(vercel["redirects"][1]["source"], "/AboutUs");
+        assert_eq!(vercel["redirects"][1]["permanent"], true);
+        Ok(())
+    }
+}
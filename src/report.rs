@@ -0,0 +1,337 @@
+use crate::display::PathDisplay;
+use crate::fs_util;
+use crate::journal::Journal;
+use crate::messages::{self, text, Msg};
+use crate::write_back;
+use anyhow::{bail, Result};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+// Exit codes, listed in the README. Scripts tell outcomes apart by them,
+// so they mustn't change. An interrupted run exits with
+// `cancel::EXIT_CANCELLED`.
+
+/// Exit code when a check finds problems. For `check`, it's the bit for
+/// names, combined with `EXIT_IMPORT_VIOLATIONS` when both are checked.
+pub const EXIT_VIOLATIONS: i32 = 1;
+
+/// Exit bit for `check` finding references that aren't converted or don't
+/// resolve
+pub const EXIT_IMPORT_VIOLATIONS: i32 = 8;
+
+/// Exit code for a run that finished but couldn't complete every operation
+pub const EXIT_PARTIAL_FAILURE: i32 = 2;
+
+/// Exit code when a safety check stopped the run before it changed anything
+pub const EXIT_ABORTED: i32 = 3;
+
+/// Exit code for a run that had warnings, with `--error-on-warnings`
+pub const EXIT_WARNINGS: i32 = 4;
+
+/// Exit code for an error that ended the run
+pub const EXIT_ERROR: i32 = 5;
+
+/// Exit code for a command line that couldn't be parsed, as in sysexits.h
+pub const EXIT_USAGE: i32 = 64;
+
+/// Returned when a safety check, like `--max-changes`, stops a run before
+/// it changes anything
+#[derive(Debug)]
+pub struct Aborted(pub String);
+
+impl fmt::Display for Aborted {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Aborted {}
+
+/// The exit code for a run that ended with `err`
+pub fn exit_code(err: &anyhow::Error) -> i32 {
+    if crate::cancel::is_cancelled(err) {
+        crate::cancel::EXIT_CANCELLED
+    } else if err.is::<Aborted>() {
+        EXIT_ABORTED
+    } else {
+        EXIT_ERROR
+    }
+}
+
+/// An operation that failed without aborting the run
+#[derive(Debug)]
+pub struct Failure {
+    pub path: PathBuf,
+    pub error: anyhow::Error,
+}
+
+/// Categories of warnings, counted separately in the summary
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WarningKind {
+    Unreadable,
+    NonUtf8Name,
+    UnknownEncoding,
+    DanglingSymlink,
+    PinnedName,
+    NumberedName,
+    Replaced,
+    OutsideRoot,
+    MetadataLost,
+    TooLong,
+    LongName,
+    Vendored,
+    Generated,
+    CommentedImport,
+    UnresolvedImport,
+    Vetoed,
+    DescribeBlock,
+    ClassKept,
+    DefaultImport,
+    RenameCaveat,
+}
+
+impl fmt::Display for WarningKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            WarningKind::Unreadable => "unreadable",
+            WarningKind::NonUtf8Name => "non-UTF-8 name",
+            WarningKind::UnknownEncoding => "unknown encoding",
+            WarningKind::DanglingSymlink => "dangling symlink",
+            WarningKind::PinnedName => "name kept",
+            WarningKind::NumberedName => "name numbered",
+            WarningKind::Replaced => "file replaced",
+            WarningKind::OutsideRoot => "outside the root",
+            WarningKind::MetadataLost => "metadata not kept",
+            WarningKind::TooLong => "name too long",
+            WarningKind::LongName => "long name",
+            WarningKind::Vendored => "vendored directory",
+            WarningKind::Generated => "generated file",
+            WarningKind::CommentedImport => "commented import",
+            WarningKind::UnresolvedImport => "unresolved import",
+            WarningKind::Vetoed => "rename vetoed",
+            WarningKind::DescribeBlock => "stale test name",
+            WarningKind::ClassKept => "class name kept",
+            WarningKind::DefaultImport => "default import name",
+            WarningKind::RenameCaveat => "left to do by hand",
+        })
+    }
+}
+
+/// Something that was skipped or looked wrong, but isn't an error
+#[derive(Debug)]
+pub struct Warning {
+    pub path: PathBuf,
+    pub kind: WarningKind,
+    pub message: String,
+}
+
+/// Collects per-file failures so one bad file doesn't stop a migration
+#[derive(Debug, Default)]
+pub struct Report {
+    fail_fast: bool,
+    strict: bool,
+    pub failures: Vec<Failure>,
+    pub warnings: Vec<Warning>,
+    /// Changes made so far, for rolling back
+    pub journal: Journal,
+    /// Rewritten files not written yet
+    pub writes: Vec<write_back::Queued>,
+    /// Directories left out of every walk, and why
+    pub skipped: Vec<(PathBuf, &'static str)>,
+}
+
+impl Report {
+    pub fn new(fail_fast: bool, strict: bool) -> Self {
+        Self {
+            fail_fast,
+            strict,
+            ..Default::default()
+        }
+    }
+
+    /// Records a failed operation on `path` and carries on, or hands the
+    /// error back in fail-fast mode
+    pub fn check<T>(
+        &mut self,
+        path: &Path,
+        result: Result<T>,
+    ) -> Result<Option<T>> {
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(error) if self.fail_fast => Err(error),
+            Err(error) => {
+                tracing::error!(path = %path.display(), "{:#}", error);
+                eprintln!("Error: {:#}", error);
+                self.failures.push(Failure {
+                    path: path.to_path_buf(),
+                    error,
+                });
+                Ok(None)
+            }
+        }
+    }
+
+    pub fn warn(
+        &mut self,
+        path: &Path,
+        kind: WarningKind,
+        message: impl Into<String>,
+    ) {
+        let message = message.into();
+        tracing::warn!(path = %path.display(), %kind, "{}", message);
+        self.warnings.push(Warning {
+            path: path.to_path_buf(),
+            kind,
+            message,
+        });
+    }
+
+    /// Warns about `path` unless it's already been warned about as `kind`
+    pub fn warn_once(
+        &mut self,
+        path: &Path,
+        kind: WarningKind,
+        message: impl Into<String>,
+    ) {
+        if !self
+            .warnings
+            .iter()
+            .any(|w| w.kind == kind && w.path == path)
+        {
+            self.warn(path, kind, message);
+        }
+    }
+
+    /// Records a directory no walk goes into, once however many times the
+    /// tree is walked
+    pub fn skip(&mut self, path: &Path, reason: &'static str) {
+        if !self.skipped.iter().any(|(skipped, _)| skipped == path) {
+            tracing::debug!(path = %path.display(), reason, "skipped");
+            self.skipped.push((path.to_path_buf(), reason));
+        }
+    }
+
+    /// Records a file or directory that couldn't be read. In strict mode
+    /// this is an error, since the run would otherwise be incomplete.
+    pub fn unreadable(
+        &mut self,
+        path: &Path,
+        error: impl fmt::Display,
+    ) -> Result<()> {
+        if self.strict {
+            bail!("Can't read {}: {}", path.display(), error);
+        }
+        self.warn(path, WarningKind::Unreadable, error.to_string());
+        Ok(())
+    }
+
+    pub fn has_failures(&self) -> bool {
+        !self.failures.is_empty()
+    }
+
+    pub fn print_summary(&self, display: &PathDisplay) {
+        tracing::info!(
+            changes = self.journal.changes.len(),
+            warnings = self.warnings.len(),
+            failures = self.failures.len(),
+            "finished"
+        );
+        if !self.skipped.is_empty() {
+            eprintln!("\n{}", text(Msg::Skipped, &[&self.skipped.len()]));
+            for (path, reason) in &self.skipped {
+                eprintln!("  {}: {}", display.show(path), reason);
+            }
+        }
+        if !self.warnings.is_empty() {
+            let mut counts: BTreeMap<WarningKind, usize> = BTreeMap::new();
+            for warning in &self.warnings {
+                *counts.entry(warning.kind).or_default() += 1;
+            }
+            let counts: Vec<String> = counts
+                .iter()
+                .map(|(kind, count)| {
+                    format!("{} {}", count, messages::warning_kind(*kind))
+                })
+                .collect();
+
+            eprintln!(
+                "\n{}",
+                text(
+                    Msg::Warnings,
+                    &[&self.warnings.len(), &counts.join(", ")]
+                )
+            );
+            for warning in &self.warnings {
+                eprintln!(
+                    "  {}: {}",
+                    display.show(&warning.path),
+                    warning.message
+                );
+            }
+        }
+        if self.failures.is_empty() {
+            return;
+        }
+        eprintln!("\n{}", text(Msg::Failures, &[&self.failures.len()]));
+        for failure in &self.failures {
+            eprintln!("  {}: {:#}", display.show(&failure.path), failure.error);
+        }
+
+        let locked: Vec<_> = self
+            .failures
+            .iter()
+            .filter(|f| fs_util::is_locked_error(&f.error))
+            .collect();
+        if !locked.is_empty() {
+            eprintln!("\n{}", text(Msg::Locked, &[]));
+            for failure in locked {
+                eprintln!("  {}", display.show(&failure.path));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn test_check_records_or_propagates() {
+        let mut report = Report::new(false, false);
+        let result = report.check::<()>(Path::new("a"), Err(anyhow!("locked")));
+        assert!(matches!(result, Ok(None)));
+        assert!(report.has_failures());
+
+        let mut report = Report::new(true, false);
+        let result = report.check::<()>(Path::new("a"), Err(anyhow!("locked")));
+        assert!(result.is_err());
+        assert!(!report.has_failures());
+    }
+
+    #[test]
+    fn test_unreadable_is_fatal_in_strict_mode() {
+        let mut report = Report::new(false, false);
+        assert!(report.unreadable(Path::new("a"), "denied").is_ok());
+        assert_eq!(report.warnings[0].kind, WarningKind::Unreadable);
+
+        let mut report = Report::new(false, true);
+        assert!(report.unreadable(Path::new("a"), "denied").is_err());
+    }
+
+    #[test]
+    fn test_exit_codes() {
+        let aborted = anyhow::Error::from(Aborted("too many".into()));
+        assert_eq!(exit_code(&aborted), EXIT_ABORTED);
+        assert_eq!(
+            exit_code(&aborted.context("Failed to convert")),
+            EXIT_ABORTED
+        );
+        assert_eq!(
+            exit_code(&crate::cancel::Cancelled.into()),
+            crate::cancel::EXIT_CANCELLED
+        );
+        assert_eq!(exit_code(&anyhow!("disk full")), EXIT_ERROR);
+    }
+}
@@ -0,0 +1,482 @@
+use crate::aliases::Alias;
+use crate::pending::portable;
+use crate::plan::Plan;
+use crate::scanner::ScanOptions;
+use crate::ImportExtensions;
+use std::path::{Component, Path, PathBuf};
+
+/// Extensions tried, in order, when a specifier omits one
+pub const RESOLVE_EXTENSIONS: &[&str] = &[
+    "ts", "tsx", "js", "jsx", "mjs", "cjs", "svelte", "vue", "json",
+];
+
+/// Extensions `--import-extensions never` leaves out. Stylesheets, data
+/// and component files usually have to be imported with theirs.
+const SCRIPT_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx", "mjs", "cjs"];
+
+/// The extension a script compiles to, which native ESM imports name
+fn emitted_extension(extension: &str) -> &str {
+    match extension {
+        "ts" | "tsx" | "jsx" => "js",
+        "mts" => "mjs",
+        "cts" => "cjs",
+        other => other,
+    }
+}
+
+/// How a specifier mapped onto the filesystem
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolved {
+    /// The specifier names the file exactly
+    Exact(PathBuf),
+    /// The specifier omitted the file's extension
+    WithoutExtension(PathBuf),
+    /// The specifier names a directory resolved through its index file
+    Directory(PathBuf),
+    /// The specifier names the JavaScript a TypeScript file compiles to,
+    /// `./util.js` for `util.ts`, as native ESM imports in TypeScript do
+    Emitted(PathBuf),
+}
+
+impl Resolved {
+    pub fn path(&self) -> &Path {
+        match self {
+            Resolved::Exact(p)
+            | Resolved::WithoutExtension(p)
+            | Resolved::Directory(p)
+            | Resolved::Emitted(p) => p,
+        }
+    }
+}
+
+pub fn is_relative_specifier(spec: &str) -> bool {
+    spec == "."
+        || spec == ".."
+        || spec.starts_with("./")
+        || spec.starts_with("../")
+}
+
+/// Lexically normalizes `.` and `..` without touching the filesystem
+pub fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !out.pop() {
+                    out.push("..");
+                }
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Resolves a relative specifier from the directory of the importing file
+pub fn resolve_specifier(base_dir: &Path, spec: &str) -> Option<Resolved> {
+    if !is_relative_specifier(spec) {
+        return None;
+    }
+    let candidate = normalize(&base_dir.join(spec));
+
+    if candidate.is_file() {
+        return Some(Resolved::Exact(candidate));
+    }
+    for ext in RESOLVE_EXTENSIONS {
+        let mut with_ext = candidate.clone().into_os_string();
+        with_ext.push(".");
+        with_ext.push(ext);
+        let with_ext = PathBuf::from(with_ext);
+        if with_ext.is_file() {
+            return Some(Resolved::WithoutExtension(with_ext));
+        }
+    }
+    if candidate.is_dir() {
+        return Some(Resolved::Directory(candidate));
+    }
+    let sources: &[&str] = match candidate.extension().and_then(|e| e.to_str())
+    {
+        Some("js") => &["ts", "tsx"],
+        Some("mjs") => &["mts"],
+        Some("cjs") => &["cts"],
+        _ => &[],
+    };
+    sources
+        .iter()
+        .map(|ext| candidate.with_extension(ext))
+        .find(|source| source.is_file())
+        .map(Resolved::Emitted)
+}
+
+/// Builds a `./`-style relative specifier from `from_dir` to `to`
+pub fn relative_specifier(from_dir: &Path, to: &Path) -> String {
+    let (from_dir, to) = (normalize(from_dir), normalize(to));
+    let from: Vec<_> = from_dir.components().collect();
+    let target: Vec<_> = to.components().collect();
+    let common = from
+        .iter()
+        .zip(target.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut parts: Vec<String> = Vec::new();
+    for _ in common..from.len() {
+        parts.push("..".to_string());
+    }
+    for component in &target[common..] {
+        parts.push(component.as_os_str().to_string_lossy().into_owned());
+    }
+
+    if parts.first().map(String::as_str) == Some("..") {
+        parts.join("/")
+    } else if parts.is_empty() {
+        ".".to_string()
+    } else {
+        format!("./{}", parts.join("/"))
+    }
+}
+
+/// Rewrites a relative specifier in the style `scan` asks for, adding or
+/// leaving out its extension, writing an index file as its directory and
+/// taking the shortest path to it. A query or fragment is kept. Returns
+/// `None` when the specifier doesn't resolve or doesn't need to change.
+pub fn normalize_specifier(
+    dir: &Path,
+    spec: &str,
+    scan: &ScanOptions,
+) -> Option<String> {
+    let end = spec.find(['?', '#']).unwrap_or(spec.len());
+    let (file, suffix) = spec.split_at(end);
+    let resolved = resolve_specifier(dir, file)?;
+    let target = resolved.path();
+    let extension = target.extension().and_then(|e| e.to_str());
+    let name = file.rsplit(['/', '\\']).next().unwrap_or(file);
+    let is_index = target.file_stem().is_some_and(|s| s == "index")
+        && name.split('.').next() == Some("index");
+
+    // The directory, if it resolves to the same index file. `./lib` finds
+    // `lib.ts` before `lib/index.ts`.
+    let collapsed =
+        match file[..file.len() - name.len()].trim_end_matches(['/', '\\']) {
+            "" => ".",
+            dir => dir,
+        };
+    let collapses = scan.collapse_index
+        && is_index
+        && resolve_specifier(dir, collapsed)
+            .is_some_and(|r| r.path() == target.parent().unwrap_or(target));
+
+    let mut new_file = file.to_string();
+    let mut written = target.to_path_buf();
+    if collapses {
+        new_file = collapsed.to_string();
+        written = target.parent()?.to_path_buf();
+    } else {
+        match (&resolved, scan.import_extensions, extension) {
+            (
+                Resolved::WithoutExtension(_),
+                ImportExtensions::Always,
+                Some(ext),
+            ) => {
+                new_file = format!("{}.{}", file, ext);
+            }
+            (Resolved::Exact(_), ImportExtensions::Never, Some(ext))
+                if SCRIPT_EXTENSIONS.contains(&ext) =>
+            {
+                let without = &file[..file.len() - ext.len() - 1];
+                // Only if leaving it out still finds the same file
+                let same = resolve_specifier(dir, without)
+                    .is_some_and(|r| r.path() == target);
+                if same {
+                    new_file = without.to_string();
+                    written = target.with_extension("");
+                }
+            }
+            (
+                Resolved::WithoutExtension(_),
+                ImportExtensions::Esm,
+                Some(ext),
+            ) => {
+                let emitted = emitted_extension(ext);
+                new_file = format!("{}.{}", file, emitted);
+                written = target.with_extension(emitted);
+            }
+            (Resolved::Directory(_), ImportExtensions::Esm, _) => {
+                // Native ESM doesn't look for a directory's index file
+                let index = match resolve_specifier(target, "./index") {
+                    Some(Resolved::WithoutExtension(index)) => index,
+                    _ => return None,
+                };
+                let ext = index.extension().and_then(|e| e.to_str());
+                let emitted = emitted_extension(ext.unwrap_or(""));
+                let file = file.trim_end_matches(['/', '\\']);
+                new_file = format!("{}/index.{}", file, emitted);
+                written = index.with_extension(emitted);
+            }
+            (Resolved::WithoutExtension(_), _, _) => {
+                written = target.with_extension("");
+            }
+            (Resolved::Emitted(_), _, Some(ext)) => {
+                written = target.with_extension(emitted_extension(ext));
+            }
+            _ => {}
+        }
+    }
+    if scan.prefer_dot_slash {
+        new_file = relative_specifier(dir, &written);
+    }
+
+    let new_spec = format!("{}{}", new_file, suffix);
+    (new_spec != spec).then_some(new_spec)
+}
+
+/// Rewrites a specifier that climbs out of `dir`, like
+/// `../../components/UserCard`, through the alias for the directory it
+/// leads into, `@components/UserCard`. Imports from inside an alias's
+/// directory stay relative. This is lexical, so it works on imports that
+/// have been rewritten for renames that haven't happened yet, and names
+/// match whatever their case, so `src/Components` is `src/components`.
+pub fn alias_specifier(
+    dir: &Path,
+    spec: &str,
+    aliases: &[Alias],
+) -> Option<String> {
+    if !spec.starts_with("../") {
+        return None;
+    }
+    let end = spec.find(['?', '#']).unwrap_or(spec.len());
+    let (file, suffix) = spec.split_at(end);
+    let target = normalize(&dir.join(file));
+    let within = |path: &Path, dir: &Path| {
+        let mut parts = path.components();
+        dir.components().all(|part| {
+            parts.next().is_some_and(|p| {
+                p == part
+                    || crate::convert_name(&p.as_os_str().to_string_lossy())
+                        == crate::convert_name(
+                            &part.as_os_str().to_string_lossy(),
+                        )
+            })
+        })
+    };
+    let alias = aliases
+        .iter()
+        .filter(|a| within(&target, &a.dir) && !within(dir, &a.dir))
+        .max_by_key(|a| a.dir.components().count())?;
+    let rest: PathBuf = target
+        .components()
+        .skip(alias.dir.components().count())
+        .collect();
+    let rest = portable(&rest);
+    Some(match rest.as_str() {
+        "" => format!("{}{}", alias.prefix, suffix),
+        rest => format!("{}/{}{}", alias.prefix, rest, suffix),
+    })
+}
+
+/// Rewrites an import of another workspace package's file, like
+/// `@acme/ui/src/UserCard`, so it follows `plan`. `packages` maps package
+/// names to their directories.
+pub fn rewrite_package_specifier(
+    spec: &str,
+    packages: &[Alias],
+    plan: &Plan,
+) -> Option<String> {
+    let (package, rest) = packages.iter().find_map(|p| {
+        let rest = spec.strip_prefix(p.prefix.as_str())?.strip_prefix('/')?;
+        Some((p, rest))
+    })?;
+    let new_dir = plan.target_of(&package.dir).unwrap_or(package.dir.clone());
+    let new_spec = rewrite_specifier(
+        &format!("./{}", rest),
+        &package.dir,
+        &new_dir,
+        plan,
+    )?;
+    let new_rest = new_spec.strip_prefix("./")?;
+    Some(format!("{}/{}", package.prefix, new_rest))
+}
+
+/// Rewrites `spec` so it still points at the same file after `plan` is
+/// applied and the importing file itself moves from `old_dir` to `new_dir`.
+/// Returns `None` when the specifier doesn't need to change.
+pub fn rewrite_specifier(
+    spec: &str,
+    old_dir: &Path,
+    new_dir: &Path,
+    plan: &Plan,
+) -> Option<String> {
+    let resolved = resolve_specifier(old_dir, spec)?;
+    let old_target = resolved.path();
+    let new_target = plan
+        .target_of(old_target)
+        .unwrap_or_else(|| old_target.to_path_buf());
+
+    if new_target == old_target && new_dir == old_dir {
+        return None;
+    }
+
+    let new_spec = match resolved {
+        Resolved::Exact(_) | Resolved::Directory(_) => {
+            relative_specifier(new_dir, &new_target)
+        }
+        Resolved::WithoutExtension(_) => {
+            // Drop the extension again so the import keeps its style
+            relative_specifier(new_dir, &new_target.with_extension(""))
+        }
+        Resolved::Emitted(_) => {
+            let ext = new_target.extension().and_then(|e| e.to_str());
+            let emitted = emitted_extension(ext.unwrap_or(""));
+            relative_specifier(new_dir, &new_target.with_extension(emitted))
+        }
+    };
+
+    (new_spec != spec).then_some(new_spec)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_relative_specifier() {
+        assert_eq!(
+            relative_specifier(Path::new("/r/a"), Path::new("/r/a/b.ts")),
+            "./b.ts"
+        );
+        assert_eq!(
+            relative_specifier(Path::new("/r/a/c"), Path::new("/r/b/d.ts")),
+            "../../b/d.ts"
+        );
+        assert_eq!(
+            relative_specifier(Path::new("."), Path::new("./lib/b.ts")),
+            "./lib/b.ts"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_specifier() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir(root.join("Lib"))?;
+        fs::write(root.join("Lib/Button.ts"), "")?;
+        fs::write(root.join("App.ts"), "")?;
+
+        let mut plan = Plan::default();
+        plan.push(root.join("Lib"), root.join("ui"));
+        plan.push(root.join("App.ts"), root.join("src/app.ts"));
+
+        assert_eq!(
+            rewrite_specifier("./Lib/Button", root, root, &plan),
+            Some("./ui/Button".to_string())
+        );
+        assert_eq!(
+            rewrite_specifier(
+                "./Lib/Button.ts",
+                root,
+                &root.join("src"),
+                &plan
+            ),
+            Some("../ui/Button.ts".to_string())
+        );
+        assert_eq!(rewrite_specifier("svelte", root, root, &plan), None);
+        let packages = [Alias {
+            prefix: "@acme/app".to_string(),
+            dir: root.to_path_buf(),
+        }];
+        assert_eq!(
+            rewrite_package_specifier("@acme/app/Lib/Button", &packages, &plan),
+            Some("@acme/app/ui/Button".to_string())
+        );
+        assert_eq!(
+            rewrite_package_specifier("@acme/application", &packages, &plan),
+            None
+        );
+        assert_eq!(
+            rewrite_specifier("./Lib/Button.js", root, root, &plan),
+            Some("./ui/Button.js".to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_alias_specifier() {
+        let aliases = [
+            Alias {
+                prefix: "@".to_string(),
+                dir: PathBuf::from("/r/src"),
+            },
+            Alias {
+                prefix: "@components".to_string(),
+                dir: PathBuf::from("/r/src/components"),
+            },
+        ];
+        let pages = Path::new("/r/src/pages/users");
+        let alias = |dir, spec| alias_specifier(dir, spec, &aliases);
+        assert_eq!(
+            alias(pages, "../../components/UserCard?raw"),
+            Some("@components/UserCard?raw".to_string())
+        );
+        assert_eq!(alias(pages, "../Home"), None);
+        assert_eq!(alias(pages, "./Table"), None);
+        assert_eq!(
+            alias(Path::new("/r/test"), "../src/utils/format"),
+            Some("@/utils/format".to_string())
+        );
+        assert_eq!(alias(Path::new("/r/test"), "../scripts/seed"), None);
+    }
+
+    #[test]
+    fn test_normalize_specifier() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("Lib/Forms"))?;
+        fs::write(root.join("Lib/Button.ts"), "")?;
+        fs::write(root.join("Lib/Theme.css"), "")?;
+        fs::write(root.join("Lib/Forms/index.ts"), "")?;
+        fs::write(root.join("Lib/Icons.ts"), "")?;
+        fs::create_dir(root.join("Lib/Icons"))?;
+        fs::write(root.join("Lib/Icons/index.ts"), "")?;
+
+        let dir = &root.join("Lib");
+        let with = |extensions, collapse_index, prefer_dot_slash| {
+            let scan = ScanOptions {
+                import_extensions: extensions,
+                collapse_index,
+                prefer_dot_slash,
+                ..ScanOptions::default()
+            };
+            move |spec: &str| normalize_specifier(dir, spec, &scan)
+        };
+        let always = with(ImportExtensions::Always, false, false);
+        assert_eq!(always("./Button?raw"), Some("./Button.ts?raw".into()));
+        assert_eq!(always("./Forms"), None);
+        let never = with(ImportExtensions::Never, false, false);
+        assert_eq!(never("./Button.ts"), Some("./Button".into()));
+        assert_eq!(never("./Theme.css"), None);
+        // `./Icons` would find Icons.ts instead
+        assert_eq!(never("./Icons/index.ts"), Some("./Icons/index".into()));
+        let esm = with(ImportExtensions::Esm, false, false);
+        assert_eq!(esm("./Button"), Some("./Button.js".into()));
+        assert_eq!(esm("./Forms/"), Some("./Forms/index.js".into()));
+        assert_eq!(esm("./Button.js"), None);
+        assert_eq!(esm("./Theme.css"), None);
+        let collapse = with(ImportExtensions::Keep, true, false);
+        assert_eq!(collapse("./Forms/index"), Some("./Forms".into()));
+        assert_eq!(collapse("./Forms/index.ts"), Some("./Forms".into()));
+        assert_eq!(collapse("./Icons/index"), None);
+        let shortest = with(ImportExtensions::Keep, false, true);
+        assert_eq!(shortest("../Lib/Button"), Some("./Button".into()));
+        assert_eq!(
+            shortest("./Forms/../Button.ts"),
+            Some("./Button.ts".into())
+        );
+        assert_eq!(shortest("./Button"), None);
+        assert_eq!(shortest("lodash"), None);
+        Ok(())
+    }
+}
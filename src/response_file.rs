@@ -0,0 +1,172 @@
+use anyhow::{bail, Context, Result};
+use std::ffi::OsString;
+use std::fs;
+use std::path::Path;
+
+/// Deep enough for any sane setup, shallow enough to catch `@a` including `@a`
+const MAX_DEPTH: usize = 8;
+
+/// Replaces every `@file` argument with the arguments listed in that file.
+/// Each line is split like a shell would (quotes group words), blank lines
+/// and lines starting with `#` are ignored, and files may include others.
+/// Use `@@name` for a literal argument starting with `@`. The value of an
+/// option, like the `@frontend-team` of `--owned-by @frontend-team`, is
+/// left as it is; `takes_value` says which options have one.
+pub fn expand_args(
+    args: impl IntoIterator<Item = OsString>,
+    takes_value: impl Fn(&str) -> bool,
+) -> Result<Vec<OsString>> {
+    let mut expander = Expander {
+        takes_value,
+        value_next: false,
+        out: Vec::new(),
+    };
+    for arg in args {
+        expander.expand(arg, 0)?;
+    }
+    Ok(expander.out)
+}
+
+/// The arguments expanded so far, and whether the next one is the value
+/// of the option before it
+struct Expander<F> {
+    takes_value: F,
+    value_next: bool,
+    out: Vec<OsString>,
+}
+
+impl<F: Fn(&str) -> bool> Expander<F> {
+    fn expand(&mut self, arg: OsString, depth: usize) -> Result<()> {
+        let text = arg.to_str().unwrap_or_default();
+        let is_value = std::mem::take(&mut self.value_next);
+        let file = match text.strip_prefix('@') {
+            Some(file) if !is_value => file,
+            _ => {
+                // `--owned-by=@team` carries its value along
+                self.value_next = !is_value
+                    && !text.contains('=')
+                    && (self.takes_value)(text);
+                self.out.push(arg);
+                return Ok(());
+            }
+        };
+        if file.starts_with('@') {
+            self.out.push(file.into());
+            return Ok(());
+        }
+        self.read(file, depth)
+    }
+
+    fn read(&mut self, file: &str, depth: usize) -> Result<()> {
+        if depth >= MAX_DEPTH {
+            bail!("Response files nested too deeply at: {}", file);
+        }
+
+        let content =
+            fs::read_to_string(Path::new(file)).with_context(|| {
+                format!("Failed to read response file: {}", file)
+            })?;
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            for word in split_words(line)
+                .with_context(|| format!("Unterminated quote in: {}", file))?
+            {
+                self.expand(word.into(), depth + 1)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Splits a line into words, honouring single and double quotes
+fn split_words(line: &str) -> Option<Vec<String>> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut quote: Option<char> = None;
+
+    for c in line.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                in_word = true;
+            }
+            None if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            None => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+
+    if quote.is_some() {
+        return None;
+    }
+    if in_word {
+        words.push(current);
+    }
+    Some(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_split_words() {
+        assert_eq!(
+            split_words(r#"--exclude "My Dir/**" 'a b' c"#),
+            Some(vec![
+                "--exclude".to_string(),
+                "My Dir/**".to_string(),
+                "a b".to_string(),
+                "c".to_string()
+            ])
+        );
+        assert_eq!(split_words(r#"--exclude "open"#), None);
+    }
+
+    #[test]
+    fn test_expand_args() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let inner = temp_dir.path().join("inner.txt");
+        let outer = temp_dir.path().join("outer.txt");
+        fs::write(&inner, "--exclude dist\n")?;
+        fs::write(
+            &outer,
+            format!("# filters\n--include src/**\n@{}\n", inner.display()),
+        )?;
+
+        let args = expand_args(
+            vec![
+                OsString::from("kebabify"),
+                OsString::from(format!("@{}", outer.display())),
+                OsString::from("@@literal"),
+            ],
+            |_| false,
+        )?;
+        assert_eq!(
+            args,
+            vec![
+                "kebabify",
+                "--include",
+                "src/**",
+                "--exclude",
+                "dist",
+                "@literal"
+            ]
+        );
+        Ok(())
+    }
+}
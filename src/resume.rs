@@ -0,0 +1,153 @@
+use crate::journal::{self, ImportMode};
+use crate::plan::Plan;
+use crate::report::Report;
+use crate::scanner;
+use crate::Options;
+use anyhow::{bail, Result};
+use std::path::Path;
+
+/// Finishes the phases an interrupted run recorded in the journal in `dir`.
+/// Import edits already written and renames already made are skipped, so
+/// resuming twice is harmless. Returns every rename of the run.
+pub fn run(dir: &Path, opts: &Options, report: &mut Report) -> Result<Plan> {
+    let Some(phases) = journal::load(dir)? else {
+        bail!("Nothing to resume: no journal in {}", dir.display());
+    };
+    // The journal has absolute paths
+    let dir = &std::path::absolute(dir)?;
+
+    let mut all = Plan::default();
+    for (index, phase) in phases.into_iter().enumerate() {
+        // Steps are added to the same journal, so a resume can be resumed
+        report.journal.continue_phase(index);
+        let exceptions = phase.plan.exceptions();
+        if !phase.imports_done {
+            for entry in crate::source_files(dir, opts, report)? {
+                crate::cancel::checkpoint()?;
+                let path = entry.path();
+                if phase.written.contains(path) {
+                    continue;
+                }
+                let result = crate::process_file_imports(
+                    path,
+                    opts,
+                    &phase.scan,
+                    &phase.plan,
+                    report,
+                    |content, scan| match phase.imports {
+                        ImportMode::None => (content.to_string(), 0),
+                        ImportMode::Convert => scanner::convert_file(
+                            path,
+                            content,
+                            scan,
+                            &phase.plan,
+                            &exceptions,
+                        ),
+                        ImportMode::Plan => scanner::follow_plan_in_file(
+                            path,
+                            content,
+                            &phase.plan,
+                            scan,
+                        ),
+                    },
+                );
+                report.check(path, result)?;
+            }
+            crate::write_back::flush(opts, report)?;
+        }
+        report.journal.imports_done();
+
+        let links = crate::collect_symlinks(&crate::walk(dir, opts, report)?);
+        crate::rename_with_plan(&links, &phase.plan, opts, report)?;
+        all.extend(phase.plan);
+    }
+    Ok(all)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::Journal;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_resume_finishes_interrupted_run() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = &temp_dir.path().join("project");
+        fs::create_dir_all(root.join("Lib"))?;
+        fs::write(root.join("Lib/MyButton.ts"), "")?;
+        fs::write(root.join("main.ts"), "import b from './Lib/MyButton';")?;
+
+        // A run that was killed after renaming the directory
+        let opts = Options::default();
+        let plan = crate::plan_directory(root, &opts, &mut Report::default())?;
+        let mut journal = Journal::default();
+        journal.open(root, false)?;
+        journal.begin_phase(ImportMode::Convert, &opts.scan, &plan);
+        fs::rename(root.join("Lib"), root.join("lib"))?;
+        drop(journal);
+
+        for _ in 0..2 {
+            let mut report = Report::default();
+            report.journal.open(root, true)?;
+            run(root, &opts, &mut report)?;
+            assert!(!report.has_failures());
+        }
+
+        assert!(root.join("lib/my-button.ts").exists());
+        assert_eq!(
+            fs::read_to_string(root.join("main.ts"))?,
+            "import b from './lib/my-button';"
+        );
+        assert!(run(
+            &temp_dir.path().join("missing"),
+            &opts,
+            &mut Report::default()
+        )
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_resume_finishes_cancelled_run() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = &temp_dir.path().join("project");
+        fs::create_dir_all(root.join("Lib"))?;
+        fs::write(root.join("Lib/MyButton.ts"), "")?;
+        fs::write(root.join("Lib/MyCard.ts"), "")?;
+        fs::write(
+            root.join("main.ts"),
+            "import b from './Lib/MyButton';\nimport c from './Lib/MyCard';",
+        )?;
+
+        // Ctrl-C after the imports and the first rename
+        let opts = Options::default();
+        let mut report = Report::default();
+        report.journal.open(root, false)?;
+        crate::cancel::cancel_after(4);
+        let result =
+            crate::convert_directory(root, true, true, &opts, &mut report);
+        crate::cancel::reset();
+        assert!(crate::cancel::is_cancelled(&result.unwrap_err()));
+        drop(report);
+        let renamed = ["lib", "Lib/my-button.ts", "Lib/my-card.ts"]
+            .iter()
+            .filter(|path| root.join(path).exists())
+            .count();
+        assert_eq!(renamed, 1);
+
+        let mut report = Report::default();
+        report.journal.open(root, true)?;
+        let plan = run(root, &opts, &mut report)?;
+        assert!(!report.has_failures());
+        assert_eq!(plan.renames.len(), 3);
+        assert!(root.join("lib/my-button.ts").exists());
+        assert!(root.join("lib/my-card.ts").exists());
+        assert_eq!(
+            fs::read_to_string(root.join("main.ts"))?,
+            "import b from './lib/my-button';\nimport c from './lib/my-card';"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,158 @@
+use crate::plan::Plan;
+use crate::scanner::{self, rewrite_file_path, splice, ScanOptions, Scanner};
+use crate::NameStyle;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// Files Bundler and Rake look for by name
+const TOOL_FILES: [&str; 3] = ["Gemfile", "Gemfile.lock", "Rakefile"];
+
+/// Ruby sources. File names mirror the class they define in snake_case,
+/// `UserRecord` in `user_record.rb`.
+pub struct RubyScanner;
+
+impl Scanner for RubyScanner {
+    fn matches(&self, path: &Path) -> bool {
+        is_tool_file(path)
+            || matches!(
+                path.extension().and_then(|e| e.to_str()),
+                Some("rb" | "rake")
+            )
+    }
+
+    fn name_style(&self) -> NameStyle {
+        NameStyle::Snake
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_requires(path, content, scanner::converted_path)
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_requires(path, content, |found| plan.target_of(found))
+    }
+
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> Vec<String> {
+        let dir = path.parent().unwrap_or(Path::new(""));
+        require_regex()
+            .captures_iter(content)
+            .map(|caps| caps[1].to_string())
+            .filter(|file| {
+                !dir.join(file).is_file()
+                    && !dir.join(format!("{}.rb", file)).is_file()
+            })
+            .collect()
+    }
+
+    fn pins_name(&self, path: &Path) -> Option<&'static str> {
+        is_tool_file(path)
+            .then_some("Bundler or Rake looks for it by this name")
+    }
+}
+
+fn is_tool_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| TOOL_FILES.contains(&name))
+}
+
+fn require_regex() -> Regex {
+    Regex::new(r#"\brequire_relative\s*\(?\s*['"]([^'"]*)['"]"#).unwrap()
+}
+
+/// Rewrites `require_relative` paths, which are relative to the file and
+/// usually leave out `.rb`
+fn rewrite_requires(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut edits = Vec::new();
+    for caps in require_regex().captures_iter(content) {
+        let file = caps.get(1).unwrap();
+        edits.extend(rewrite_file_path(
+            dir,
+            file.as_str(),
+            file.start(),
+            &["rb"],
+            &rename,
+        ));
+    }
+    splice(content, edits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_require_relative() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("App/Models"))?;
+        fs::create_dir_all(root.join("App/Services"))?;
+        fs::write(root.join("App/Models/UserRecord.rb"), "")?;
+        fs::write(root.join("App/Models/Base.rb"), "")?;
+
+        let content = "\
+require 'json'
+require_relative '../Models/UserRecord'
+require_relative(\"../Models/Base.rb\")
+";
+        let path = root.join("App/Services/Sync.rb");
+        assert!(scanner::pinned(&root.join("Gemfile.lock"), false).is_some());
+        assert!(scanner::pinned(&root.join("Rakefile"), false).is_some());
+        let (new_content, changes) =
+            RubyScanner.convert(&path, content, &ScanOptions::default());
+        assert_eq!(
+            new_content,
+            "\
+require 'json'
+require_relative '../models/user_record'
+require_relative(\"../models/base.rb\")
+"
+        );
+        assert_eq!(changes, 4);
+
+        let mut plan = Plan::default();
+        plan.push(
+            root.join("App/Models/UserRecord.rb"),
+            root.join("App/Models/user.rb"),
+        );
+        let (new_content, _) = RubyScanner.follow_plan(
+            &path,
+            content,
+            &plan,
+            &ScanOptions::default(),
+        );
+        assert!(new_content.contains("require_relative '../Models/user'"));
+
+        let orphans = RubyScanner.orphans(
+            &path,
+            "require_relative '../Models/Base'\nrequire_relative 'gone'\n",
+            &ScanOptions::default(),
+        );
+        assert_eq!(orphans, ["gone"]);
+        Ok(())
+    }
+}
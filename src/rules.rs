@@ -0,0 +1,305 @@
+use crate::doctor::Finding;
+use crate::filter::compile_glob;
+use crate::long_names::LongNames;
+use crate::plan::Plan;
+use crate::{
+    convert_name_as, encoding, needs_conversion_as, relative_to, scanner,
+    NameStyle, Options,
+};
+use anyhow::{bail, Result};
+use globset::GlobMatcher;
+use std::fs;
+use std::path::Path;
+use walkdir::DirEntry;
+
+/// Filename policy checked by `kebabify check` on top of kebab-case
+#[derive(Debug, Default)]
+pub struct NamingRules {
+    pub max_length: Option<usize>,
+    pub forbidden_chars: Vec<char>,
+    affixes: Vec<Affix>,
+    /// Limits on the name as converted
+    long_names: LongNames,
+}
+
+/// A `DIR=TEXT` rule: files in directories matching `dir` must start (or
+/// end, before the extension) with `text`
+#[derive(Debug)]
+struct Affix {
+    dir: GlobMatcher,
+    pattern: String,
+    text: String,
+    prefix: bool,
+}
+
+impl NamingRules {
+    pub fn new(
+        max_length: Option<usize>,
+        forbidden_chars: &str,
+        prefixes: &[String],
+        suffixes: &[String],
+        long_names: LongNames,
+    ) -> Result<Self> {
+        let mut affixes = Vec::new();
+        for (rules, prefix) in [(prefixes, true), (suffixes, false)] {
+            for rule in rules {
+                let Some((dir, text)) = rule.split_once('=') else {
+                    bail!("Expected DIR=TEXT, got: {}", rule);
+                };
+                affixes.push(Affix {
+                    dir: compile_glob(dir)?.compile_matcher(),
+                    pattern: dir.to_string(),
+                    text: text.to_string(),
+                    prefix,
+                });
+            }
+        }
+        Ok(Self {
+            max_length,
+            forbidden_chars: forbidden_chars.chars().collect(),
+            affixes,
+            long_names,
+        })
+    }
+
+    /// Every rule `entry` breaks
+    pub fn violations(&self, entry: &DirEntry, root: &Path) -> Vec<Finding> {
+        let Some(name) = entry.file_name().to_str() else {
+            return Vec::new();
+        };
+        let finding = |problem: String, fix: String| Finding {
+            path: entry.path().to_path_buf(),
+            problem,
+            fix,
+        };
+        let mut found = Vec::new();
+
+        let style =
+            scanner::name_style(entry.path(), entry.file_type().is_dir());
+        let pinned =
+            scanner::pinned(entry.path(), entry.file_type().is_dir()).is_some();
+        let converted = match needs_conversion_as(name, style) && !pinned {
+            true => convert_name_as(name, style),
+            false => name.to_string(),
+        };
+        if converted != name {
+            let case = match style {
+                NameStyle::Kebab => "kebab-case",
+                NameStyle::Snake => "snake_case",
+            };
+            found.push(finding(
+                format!("'{}' isn't {}", name, case),
+                format!("rename to {}", converted),
+            ));
+        }
+        if let Some(problem) = self.long_names.problem(&converted) {
+            let fix = match self.long_names.abbreviate(&converted) {
+                Some(short) => format!("abbreviate it to {}", short),
+                None => "use a shorter name, or --abbreviate".to_string(),
+            };
+            found.push(finding(problem, fix));
+        }
+        if let Some(max) = self.max_length {
+            let length = name.chars().count();
+            if length > max {
+                found.push(finding(
+                    format!("name is {} characters long (max {})", length, max),
+                    "shorten the name".to_string(),
+                ));
+            }
+        }
+        let forbidden: String = name
+            .chars()
+            .filter(|c| self.forbidden_chars.contains(c))
+            .collect();
+        if !forbidden.is_empty() {
+            found.push(finding(
+                format!("name contains forbidden characters: {:?}", forbidden),
+                "remove or replace them".to_string(),
+            ));
+        }
+
+        if !entry.file_type().is_file() {
+            return found;
+        }
+        let dir = entry.path().parent().map(|p| relative_to(p, root));
+        let stem = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+        for affix in &self.affixes {
+            if !dir.is_some_and(|dir| affix.dir.is_match(dir)) {
+                continue;
+            }
+            let (ok, position) = if affix.prefix {
+                (name.starts_with(&affix.text), "start")
+            } else {
+                (stem.ends_with(&affix.text), "end")
+            };
+            if !ok {
+                found.push(finding(
+                    format!(
+                        "files in {} must {} with '{}'",
+                        affix.pattern, position, affix.text
+                    ),
+                    format!("rename it to {} with '{}'", position, affix.text),
+                ));
+            }
+        }
+        found
+    }
+}
+
+/// Checks every name under `dir` against kebab-case and `rules`, without
+/// changing anything
+pub fn check(
+    dir: &Path,
+    rules: &NamingRules,
+    opts: &Options,
+    report: &mut crate::report::Report,
+) -> Result<Vec<Finding>> {
+    Ok(crate::walk(dir, opts, report)?
+        .iter()
+        .filter(|e| e.depth() > 0)
+        .filter(|e| opts.filter.is_renamed(relative_to(e.path(), dir)))
+        .flat_map(|e| rules.violations(e, dir))
+        .collect())
+}
+
+/// Checks the references in the source files under `dir`, without changing
+/// anything: each line `-i` would rewrite, and each import that doesn't
+/// point at any file
+pub fn check_imports(
+    dir: &Path,
+    opts: &Options,
+    report: &mut crate::report::Report,
+) -> Result<Vec<Finding>> {
+    let mut findings = Vec::new();
+    let plan = Plan::default();
+    for entry in crate::source_files(dir, opts, report)? {
+        let path = entry.path();
+        let Some((content, _)) =
+            fs::read(path).ok().and_then(|b| encoding::decode(&b))
+        else {
+            continue;
+        };
+        let (converted, changes) =
+            scanner::convert_file(path, &content, &opts.scan, &plan, &plan);
+        if changes == 0 {
+            continue;
+        }
+        let lines = content.lines().zip(converted.lines()).enumerate();
+        for (i, (old, new)) in lines.filter(|(_, (old, new))| old != new) {
+            findings.push(Finding {
+                path: path.to_path_buf(),
+                problem: format!(
+                    "line {} references names that aren't converted: {}",
+                    i + 1,
+                    old.trim()
+                ),
+                fix: format!("run `kebabify -i` to make it: {}", new.trim()),
+            });
+        }
+    }
+    findings.extend(crate::orphans::find(dir, opts, report)?);
+    Ok(findings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+    use walkdir::WalkDir;
+
+    #[test]
+    fn test_violations() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src/hooks"))?;
+        fs::write(root.join("src/hooks/use-auth.ts"), "")?;
+        fs::write(root.join("src/hooks/fetch-user.ts"), "")?;
+        fs::write(root.join("src/UserCard.ts"), "")?;
+        fs::write(root.join("src/a very long name.ts"), "")?;
+
+        let rules = NamingRules::new(
+            Some(16),
+            " ",
+            &["hooks=use-".to_string()],
+            &[],
+            LongNames::new(60, 3, &[])?,
+        )?;
+        let mut problems: Vec<String> = WalkDir::new(root)
+            .min_depth(1)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .flat_map(|e| rules.violations(&e, root))
+            .map(|f| f.problem)
+            .collect();
+        problems.sort();
+
+        assert_eq!(
+            problems,
+            [
+                "'UserCard.ts' isn't kebab-case",
+                "'a very long name.ts' has 4 words (over 3)",
+                "files in hooks must start with 'use-'",
+                "name contains forbidden characters: \"   \"",
+                "name is 19 characters long (max 16)",
+            ]
+        );
+        assert!(NamingRules::new(
+            None,
+            "",
+            &["hooks".to_string()],
+            &[],
+            LongNames::default()
+        )
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_leaves_out_git_data() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join(".git/logs"))?;
+        fs::write(root.join(".git/HEAD"), "ref: refs/heads/main")?;
+        fs::write(root.join(".git/COMMIT_EDITMSG"), "")?;
+        fs::write(root.join(".git/logs/HEAD"), "")?;
+        fs::write(root.join("UserCard.ts"), "")?;
+
+        let mut report = crate::report::Report::default();
+        let rules = NamingRules::default();
+        let found = check(root, &rules, &Options::default(), &mut report)?;
+        let paths: Vec<_> = found.iter().map(|f| f.path.clone()).collect();
+        assert_eq!(paths, [root.join("UserCard.ts")]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_imports() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::write(root.join("user-card.ts"), "")?;
+        fs::write(
+            root.join("app.ts"),
+            "import card from './user-card';\n\
+             import api from './ApiClient';\n",
+        )?;
+
+        let mut report = crate::report::Report::default();
+        let mut problems: Vec<String> =
+            check_imports(root, &Options::default(), &mut report)?
+                .into_iter()
+                .map(|f| f.problem)
+                .collect();
+        problems.sort();
+        assert_eq!(
+            problems,
+            [
+                "'./ApiClient' doesn't point at any file",
+                "line 2 references names that aren't converted: import api \
+                 from './ApiClient';",
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,580 @@
+use crate::plan::Plan;
+use crate::scanner::{self, rewrite_file_path, splice, ScanOptions, Scanner};
+use regex::Regex;
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Rust sources, and the Cargo manifests that list them. Module names are
+/// identifiers, so module files and their directories are named in
+/// snake_case.
+pub struct RustScanner;
+
+impl Scanner for RustScanner {
+    fn matches(&self, path: &Path) -> bool {
+        path.extension().is_some_and(|e| e == "rs") || is_cargo_file(path)
+    }
+
+    fn name_style(&self) -> crate::NameStyle {
+        crate::NameStyle::Snake
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite(path, content, scanner::converted_path)
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite(path, content, |found| plan.target_of(found))
+    }
+
+    /// `mod` declarations whose file isn't there, and `#[path]`,
+    /// `include!` and manifest paths that lead nowhere. Paths like
+    /// `crate::Models::User` may name items as well as modules, so they
+    /// aren't checked.
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> Vec<String> {
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let leads_nowhere =
+            |file: &&str| scanner::leads_nowhere(dir, file, &[]);
+        match path.file_name().and_then(|n| n.to_str()) {
+            Some("Cargo.toml") => {
+                let files = manifest_paths(content);
+                let files = files.into_iter().map(|(file, _)| file);
+                return files
+                    .filter(leads_nowhere)
+                    .map(str::to_string)
+                    .collect();
+            }
+            Some("Cargo.lock") => return Vec::new(),
+            _ => {}
+        }
+        let module_dir = module_dir(path);
+        let mut orphans = Vec::new();
+        for caps in mod_regex().captures_iter(content) {
+            match caps.get(1) {
+                Some(file) if leads_nowhere(&file.as_str()) => {
+                    orphans.push(file.as_str().to_string());
+                }
+                Some(_) => {}
+                None if find_module(&module_dir, &caps[2]).is_none() => {
+                    orphans.push(format!("mod {}", &caps[2]));
+                }
+                None => {}
+            }
+        }
+        let includes = include_regex();
+        let files = includes.captures_iter(content).map(|caps| caps.get(1));
+        orphans.extend(
+            files
+                .flatten()
+                .map(|file| file.as_str())
+                .filter(leads_nowhere)
+                .map(str::to_string),
+        );
+        orphans
+    }
+
+    fn pins_name(&self, path: &Path) -> Option<&'static str> {
+        is_cargo_file(path).then_some("Cargo looks for it by this name")
+    }
+}
+
+/// `Cargo.toml` and `Cargo.lock`
+fn is_cargo_file(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name == "Cargo.toml" || name == "Cargo.lock")
+}
+
+fn rewrite(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    match path.file_name().and_then(|n| n.to_str()) {
+        Some("Cargo.toml") => rewrite_manifest_paths(path, content, rename),
+        // Only names packages and their versions
+        Some("Cargo.lock") => (content.to_string(), 0),
+        _ => rewrite_modules(path, content, rename),
+    }
+}
+
+/// Rewrites the paths in a `Cargo.toml`, relative to it: `path = "..."`
+/// of targets and path dependencies, and the workspace's `members`,
+/// `default-members` and `exclude`, which may be globs
+fn rewrite_manifest_paths(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut edits = Vec::new();
+    for (file, start) in manifest_paths(content) {
+        edits.extend(rewrite_file_path(dir, file, start, &[], &rename));
+    }
+    splice(content, edits)
+}
+
+/// Each path in a `Cargo.toml` and where it starts in the file
+fn manifest_paths(content: &str) -> Vec<(&str, usize)> {
+    let key = Regex::new(r#"\bpath\s*=\s*"([^"\n]+)""#).unwrap();
+    let list = Regex::new(
+        r"(?m)^[ \t]*(?:members|default-members|exclude)\s*=\s*\[([^\]]*)\]",
+    )
+    .unwrap();
+    let string = Regex::new(r#""([^"\n]+)""#).unwrap();
+
+    let mut files: Vec<(&str, usize)> = key
+        .captures_iter(content)
+        .map(|caps| caps.get(1).unwrap())
+        .map(|file| (file.as_str(), file.start()))
+        .collect();
+    for caps in list.captures_iter(content) {
+        let items = caps.get(1).unwrap();
+        files.extend(string.captures_iter(items.as_str()).map(|item| {
+            let item = item.get(1).unwrap();
+            (item.as_str(), items.start() + item.start())
+        }));
+    }
+    files
+}
+
+/// `mod Foo;`, with any visibility, and the `#[path]` attribute before it
+fn mod_regex() -> Regex {
+    Regex::new(
+        r#"(?m)^[ \t]*(?:#\[path\s*=\s*"([^"]*)"\]\s*)?(?:pub(?:\([^)]*\))?\s+)?mod\s+(\w+)\s*;"#,
+    )
+    .unwrap()
+}
+
+/// `include!`, `include_str!` and `include_bytes!` with a literal path
+fn include_regex() -> Regex {
+    Regex::new(r#"\binclude(?:_str|_bytes)?!\(\s*"([^"]*)"\s*\)"#).unwrap()
+}
+
+/// Where a file's child modules live: next to `lib.rs`, `main.rs` and
+/// `mod.rs`, and in a directory named after any other module file
+fn module_dir(path: &Path) -> PathBuf {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    match path.file_stem().and_then(|s| s.to_str()) {
+        Some("lib" | "main" | "mod") | None => dir.to_path_buf(),
+        Some(stem) => dir.join(stem),
+    }
+}
+
+/// The directory of the crate root, `src/` next to `Cargo.toml`
+fn crate_dir(path: &Path) -> Option<&Path> {
+    path.ancestors().skip(1).find(|dir| {
+        dir.file_name().is_some_and(|name| name == "src")
+            && dir.with_file_name("Cargo.toml").is_file()
+    })
+}
+
+/// The child module `name` of the module whose children live in `dir`.
+/// Returns the file or directory that carries its name, and where its own
+/// children live.
+fn find_module(dir: &Path, name: &str) -> Option<(PathBuf, PathBuf)> {
+    let child_dir = dir.join(name);
+    let file = dir.join(format!("{}.rs", name));
+    if file.is_file() {
+        Some((file, child_dir))
+    } else if child_dir.join("mod.rs").is_file() {
+        Some((child_dir.clone(), child_dir))
+    } else {
+        None
+    }
+}
+
+/// The new identifier of the module at `found`, if `rename` moves it
+fn new_ident(
+    found: &Path,
+    rename: &impl Fn(&Path) -> Option<PathBuf>,
+) -> Option<String> {
+    let new = rename(found)?;
+    let name = if found.is_dir() {
+        new.file_name()
+    } else {
+        new.file_stem()
+    };
+    Some(name?.to_str()?.to_string())
+}
+
+/// What a file knows about where module paths start
+struct Context<'a> {
+    module_dir: PathBuf,
+    crate_dir: Option<&'a Path>,
+    /// Modules declared with `mod` in this file
+    declared: HashSet<&'a str>,
+}
+
+impl Context<'_> {
+    /// Where the children of a path's first segment live, if it names a
+    /// module we can find. Queues an edit if the segment is a renamed
+    /// module declared in this file.
+    fn start(
+        &self,
+        segment: &str,
+        range: Range<usize>,
+        rename: &impl Fn(&Path) -> Option<PathBuf>,
+        edits: &mut Vec<(Range<usize>, String)>,
+    ) -> Option<PathBuf> {
+        match segment {
+            "crate" => self.crate_dir.map(Path::to_path_buf),
+            "self" => Some(self.module_dir.clone()),
+            "super" => self.module_dir.parent().map(Path::to_path_buf),
+            _ if self.declared.contains(segment) => {
+                step(&self.module_dir, segment, range, rename, edits)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Rewrites `mod` declarations, `#[path]` attributes, `include!` paths and
+/// module paths like `crate::Models::User` that name local module files.
+/// `rename` gives the new path of a file or directory, or `None` if it
+/// keeps its name.
+fn rewrite_modules(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut context = Context {
+        module_dir: module_dir(path),
+        crate_dir: crate_dir(path),
+        declared: HashSet::new(),
+    };
+    let mut edits = Vec::new();
+    let mut skip = Vec::new();
+
+    for caps in mod_regex().captures_iter(content) {
+        skip.push(caps.get(0).unwrap().range());
+        let ident = caps.get(2).unwrap();
+        context.declared.insert(ident.as_str());
+
+        // With `#[path]` the module's name doesn't depend on its file
+        if let Some(file) = caps.get(1) {
+            edits.extend(rewrite_file_path(
+                dir,
+                file.as_str(),
+                file.start(),
+                &[],
+                &rename,
+            ));
+            continue;
+        }
+        let Some((found, _)) = find_module(&context.module_dir, ident.as_str())
+        else {
+            continue;
+        };
+        if let Some(new) = new_ident(&found, &rename) {
+            if new != ident.as_str() {
+                edits.push((ident.range(), new));
+            }
+        }
+    }
+
+    for caps in include_regex().captures_iter(content) {
+        let file = caps.get(1).unwrap();
+        edits.extend(rewrite_file_path(
+            dir,
+            file.as_str(),
+            file.start(),
+            &[],
+            &rename,
+        ));
+    }
+
+    let use_regex = Regex::new(r"(?s)\buse\s+([^;]*);").unwrap();
+    for caps in use_regex.captures_iter(content) {
+        let tree = caps.get(1).unwrap();
+        skip.push(caps.get(0).unwrap().range());
+        let mut parser = UseTree {
+            text: tree.as_str(),
+            offset: tree.start(),
+            pos: 0,
+        };
+        parser.parse(true, None, &context, &rename, &mut edits);
+    }
+
+    let path_regex = Regex::new(r"\b\w+(?:\s*::\s*\w+)+").unwrap();
+    for found in path_regex.find_iter(content) {
+        if skip.iter().any(|s| s.contains(&found.start())) {
+            continue;
+        }
+        let segments = found.as_str().split("::").scan(0, |start, segment| {
+            let leading = segment.len() - segment.trim_start().len();
+            let range = *start + leading..*start + segment.trim_end().len();
+            *start += segment.len() + 2;
+            Some((
+                found.start() + range.start..found.start() + range.end,
+                range,
+            ))
+        });
+        let mut dir: Option<PathBuf> = None;
+        for (i, (range, local)) in segments.enumerate() {
+            let segment = &found.as_str()[local];
+            let next = if i == 0 {
+                context.start(segment, range, &rename, &mut edits)
+            } else if segment == "super" {
+                dir.as_deref().and_then(Path::parent).map(Path::to_path_buf)
+            } else {
+                dir.as_deref().and_then(|dir| {
+                    step(dir, segment, range, &rename, &mut edits)
+                })
+            };
+            let Some(next) = next else {
+                break;
+            };
+            dir = Some(next);
+        }
+    }
+
+    splice(content, edits)
+}
+
+/// Looks up the child module `segment` of `dir`, queueing an edit if it's
+/// renamed. Returns where the child's own children live.
+fn step(
+    dir: &Path,
+    segment: &str,
+    range: Range<usize>,
+    rename: &impl Fn(&Path) -> Option<PathBuf>,
+    edits: &mut Vec<(Range<usize>, String)>,
+) -> Option<PathBuf> {
+    let (found, child_dir) = find_module(dir, segment)?;
+    if let Some(new) = new_ident(&found, rename) {
+        if new != segment {
+            edits.push((range, new));
+        }
+    }
+    Some(child_dir)
+}
+
+/// A cursor over the tree of a `use` declaration, like
+/// `crate::Models::{User, Post as P}`
+struct UseTree<'a> {
+    text: &'a str,
+    /// Where `text` starts in the file
+    offset: usize,
+    pos: usize,
+}
+
+impl UseTree<'_> {
+    /// Parses one tree. `base` is where the children of the enclosing
+    /// group's prefix live, if it names a module we found.
+    fn parse(
+        &mut self,
+        top: bool,
+        base: Option<&Path>,
+        context: &Context,
+        rename: &impl Fn(&Path) -> Option<PathBuf>,
+        edits: &mut Vec<(Range<usize>, String)>,
+    ) {
+        let mut dir = base.map(Path::to_path_buf);
+        let mut first = top;
+        loop {
+            self.skip_whitespace();
+            if self.eat("{") {
+                loop {
+                    self.parse(false, dir.as_deref(), context, rename, edits);
+                    self.skip_whitespace();
+                    if !self.eat(",") {
+                        break;
+                    }
+                }
+                self.eat("}");
+                return;
+            }
+            let Some((segment, range)) = self.ident() else {
+                return;
+            };
+            if segment == "as" {
+                self.ident();
+                return;
+            }
+            dir = if first {
+                context.start(segment, range, rename, edits)
+            } else if segment == "self" {
+                dir
+            } else if segment == "super" {
+                dir.as_deref().and_then(Path::parent).map(Path::to_path_buf)
+            } else {
+                dir.as_deref()
+                    .and_then(|dir| step(dir, segment, range, rename, edits))
+            };
+            first = false;
+            self.skip_whitespace();
+            if !self.eat("::") {
+                // An alias, or the end of this tree
+                self.skip_whitespace();
+                if self.text[self.pos..].starts_with("as") {
+                    self.ident();
+                    self.ident();
+                }
+                return;
+            }
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.text[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        let found = self.text[self.pos..].starts_with(token);
+        if found {
+            self.pos += token.len();
+        }
+        found
+    }
+
+    /// The next identifier and its range in the file, or `None` at `*`
+    /// and other punctuation
+    fn ident(&mut self) -> Option<(&str, Range<usize>)> {
+        self.skip_whitespace();
+        let rest = &self.text[self.pos..];
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return None;
+        }
+        let start = self.pos;
+        self.pos += len;
+        let range = self.offset + start..self.offset + self.pos;
+        Some((&self.text[start..self.pos], range))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_convert_modules() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::write(root.join("Cargo.toml"), "")?;
+        fs::create_dir_all(root.join("src/DataModels"))?;
+        fs::create_dir_all(root.join("src/Generated"))?;
+        fs::write(root.join("src/DataModels.rs"), "pub mod UserRecord;")?;
+        fs::write(root.join("src/DataModels/UserRecord.rs"), "")?;
+        fs::write(root.join("src/Generated/Schema.rs"), "")?;
+        fs::write(root.join("src/Banner.txt"), "")?;
+
+        let content = r#"mod DataModels;
+#[path = "Generated/Schema.rs"]
+pub(crate) mod Schema;
+
+use crate::DataModels::{self, UserRecord::Record as R};
+use std::collections::HashMap;
+
+const BANNER: &str = include_str!("Banner.txt");
+
+fn main() {
+    let map: HashMap<u8, u8> = HashMap::new();
+    DataModels::UserRecord::load();
+    self::DataModels::UserRecord::Record::new();
+}
+"#;
+        let (new_content, changes) = RustScanner.convert(
+            &root.join("src/main.rs"),
+            content,
+            &ScanOptions::default(),
+        );
+        assert_eq!(
+            new_content,
+            r#"mod data_models;
+#[path = "generated/schema.rs"]
+pub(crate) mod Schema;
+
+use crate::data_models::{self, user_record::Record as R};
+use std::collections::HashMap;
+
+const BANNER: &str = include_str!("banner.txt");
+
+fn main() {
+    let map: HashMap<u8, u8> = HashMap::new();
+    data_models::user_record::load();
+    self::data_models::user_record::Record::new();
+}
+"#
+        );
+        assert_eq!(changes, 10);
+
+        let content = r#"mod DataModels;
+mod Removed;
+#[path = "Generated/Gone.rs"]
+mod gone;
+const BANNER: &str = include_str!("Banner.txt");
+const LOGO: &[u8] = include_bytes!("Logo.png");
+"#;
+        let orphans = RustScanner.orphans(
+            &root.join("src/main.rs"),
+            content,
+            &ScanOptions::default(),
+        );
+        assert_eq!(orphans, ["mod Removed", "Generated/Gone.rs", "Logo.png"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cargo_manifests() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("Crates/CoreLib"))?;
+        fs::create_dir_all(root.join("Tools/src/Bin"))?;
+        fs::write(root.join("Tools/src/Bin/GenDocs.rs"), "")?;
+
+        let content = r#"[workspace]
+members = ["Crates/*", "Tools"]
+
+[dependencies]
+core-lib = { path = "Crates/CoreLib", version = "0.1" }
+
+[[bin]]
+name = "gen-docs"
+path = "Tools/src/Bin/GenDocs.rs"
+"#;
+        let path = root.join("Cargo.toml");
+        assert!(scanner::pinned(&path, false).is_some());
+        assert!(RustScanner.pins_name(&root.join("Cargo.lock")).is_some());
+        let (new_content, changes) =
+            RustScanner.convert(&path, content, &ScanOptions::default());
+        assert_eq!(
+            new_content,
+            content
+                .replace("Crates/", "crates/")
+                .replace("\"Tools", "\"tools")
+                .replace("CoreLib", "core-lib")
+                .replace("Bin/GenDocs", "bin/gen_docs")
+        );
+        assert_eq!(changes, 7);
+
+        let content = "[dependencies]\ngone = { path = \"Crates/Gone\" }\n";
+        let orphans =
+            RustScanner.orphans(&path, content, &ScanOptions::default());
+        assert_eq!(orphans, ["Crates/Gone"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,1001 @@
+use crate::aliases::Alias;
+use crate::codeowners::CodeownersScanner;
+use crate::cpp::CppScanner;
+use crate::csharp::{CSharpScanner, MsBuildScanner};
+use crate::default_imports::{self, DefaultImports, ImportCase};
+use crate::describe::{self, DescribeBlocks};
+use crate::directives;
+use crate::docker::DockerScanner;
+use crate::docs::{DocsScanner, FrontMatterScanner};
+use crate::doctor::Finding;
+use crate::e2e::{self, E2eScanner};
+use crate::elixir::ElixirScanner;
+use crate::go::GoScanner;
+use crate::graphql::GraphqlScanner;
+use crate::helm::HelmScanner;
+use crate::i18n::I18nScanner;
+use crate::jvm::JvmScanner;
+use crate::lint::LintScanner;
+use crate::make::MakeScanner;
+use crate::monorepo::MonorepoScanner;
+use crate::package::PackageScanner;
+use crate::path_cache;
+use crate::php::PhpScanner;
+use crate::plan::Plan;
+use crate::proto::ProtoScanner;
+use crate::python::PythonScanner;
+use crate::redirects;
+use crate::ruby::RubyScanner;
+use crate::rust::RustScanner;
+use crate::schema::SchemaScanner;
+use crate::sfc::{SvelteScanner, VueScanner};
+use crate::shell::ShellScanner;
+use crate::string_refs::{
+    rewrite_comment_refs, rewrite_html_template_refs, rewrite_string_refs,
+};
+use crate::tailwind::TailwindScanner;
+use crate::terraform::TerraformScanner;
+use crate::tsconfig::{TsconfigScanner, ViteScanner};
+use crate::xml::XmlScanner;
+use crate::{
+    convert_name_as, resolve, Comments, ImportExtensions, NameStyle, Separators,
+};
+use regex::Regex;
+use std::collections::BTreeSet;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// How references are rewritten, beyond what each language's own syntax
+/// says
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScanOptions {
+    /// How to write path separators in rewritten imports
+    pub separators: Separators,
+    /// Normalizes relative imports while rewriting them: their extensions,
+    /// `./lib/index` as `./lib`, and the shortest `./` or `../` path
+    pub import_extensions: ImportExtensions,
+    pub collapse_index: bool,
+    pub prefer_dot_slash: bool,
+    /// Rewrites relative imports that climb out of their directory through
+    /// these aliases, when one covers the file they import
+    pub aliases: Vec<Alias>,
+    /// The workspace's packages by name, so imports of one package's files
+    /// from another follow the plan
+    pub packages: Vec<Alias>,
+    /// Packages installed from a registry, whose imports `-i` leaves alone
+    /// rather than converting them like paths
+    pub external: BTreeSet<String>,
+    /// The quote to write rewritten imports with, from the Prettier config.
+    /// Everything else around an import is always left as it was.
+    pub quote: Option<char>,
+    /// Whether imports in comments are rewritten
+    pub comments: Comments,
+    /// Also rewrite relative paths inside template literals, such as
+    /// `url('./Assets/Hero.png')` in styled-components and emotion styles
+    pub template_literals: bool,
+    /// Also rewrite quoted strings in any text file that resolve to a file
+    /// or directory, relative to the file or to one of `roots`
+    pub string_refs: bool,
+    pub roots: Vec<PathBuf>,
+    /// Also rewrite paths mentioned in comments, like `// see
+    /// ./Utils/Dates.ts`, that resolve the same way
+    pub comment_paths: bool,
+    /// Also rewrite attributes like `src` and `href` in lit-html and
+    /// hyperHTML templates that resolve the same way
+    pub html_templates: bool,
+    /// The attributes whose paths `XmlScanner` updates
+    pub xml_attributes: Vec<String>,
+    /// What to do with test blocks named after a renamed test file
+    pub describe: DescribeBlocks,
+    /// What to do with default imports of renamed files whose local name
+    /// isn't their export's, and the case to name them in
+    pub default_imports: DefaultImports,
+    pub import_case: ImportCase,
+}
+
+/// Finds and rewrites the file references in one kind of source file
+pub trait Scanner: Sync {
+    /// Whether this scanner reads the file at `path`
+    fn matches(&self, path: &Path) -> bool;
+
+    /// The case this language's own files and directories are named in
+    fn name_style(&self) -> NameStyle {
+        NameStyle::Kebab
+    }
+
+    /// Converts the case of every reference in `content`, as `-i` does.
+    /// Returns the new content and how many references changed.
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        scan: &ScanOptions,
+    ) -> (String, usize);
+
+    /// Rewrites references so they still point at the same files once
+    /// `plan` is applied, as `--mapping` does. Scanners that can't resolve
+    /// references leave the content alone.
+    fn follow_plan(
+        &self,
+        _path: &Path,
+        content: &str,
+        _plan: &Plan,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        (content.to_string(), 0)
+    }
+
+    /// References in `content` that can't be checked or rewritten, for
+    /// `kebabify doctor` to point out
+    fn findings(&self, _path: &Path, _content: &str) -> Vec<Finding> {
+        Vec::new()
+    }
+
+    /// Whether `convert` also converts references that don't resolve, so
+    /// references to renames that aren't plain conversions have to be
+    /// pointed at them first
+    fn converts_unresolved(&self) -> bool {
+        false
+    }
+
+    /// References in `content` that don't point at anything on disk, for
+    /// `kebabify orphans`: the ones `convert` would rewrite if they did.
+    /// Scanners that can't resolve references find none.
+    fn orphans(
+        &self,
+        _path: &Path,
+        _content: &str,
+        _scan: &ScanOptions,
+    ) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Why the file or directory at `path` has to keep its name, if this
+    /// language ties its name to something renaming would break. Asked
+    /// about this language's own files and about every directory.
+    fn pins_name(&self, _path: &Path) -> Option<&'static str> {
+        None
+    }
+
+    /// What renaming this language's file at `path` to `to` leaves to be
+    /// done by hand, if anything, like a class still named after the old
+    /// name
+    fn rename_caveat(&self, _path: &Path, _to: &Path) -> Option<String> {
+        None
+    }
+}
+
+/// JavaScript, TypeScript and component files with `import`/`require`
+pub struct JsScanner;
+
+impl Scanner for JsScanner {
+    fn matches(&self, path: &Path) -> bool {
+        crate::matches_source_file(path)
+    }
+
+    fn converts_unresolved(&self) -> bool {
+        true
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        scan: &ScanOptions,
+    ) -> (String, usize) {
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let masked = mask_comments(content, scan.comments);
+        let (content, normalized) = normalize_imports(path, &masked, scan);
+        let (content, changes) = crate::update_imports(&content, scan);
+        let (content, aliased) = alias_imports(dir, &content, &scan.aliases);
+        let (content, fixtures) =
+            e2e::rewrite_fixtures(path, &content, converted_path);
+        let changes = normalized + changes + aliased + fixtures;
+        let content = requote_changed(&masked, content, scan.quote);
+        if !scan.template_literals {
+            return (unmask_comments(content), changes);
+        }
+        let (content, more) =
+            rewrite_template_paths(&content, |file, start| {
+                if !dir.join(file).exists() {
+                    return Vec::new();
+                }
+                rewrite_file_path(dir, file, start, &[], &converted_path)
+            });
+        (unmask_comments(content), changes + more)
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        scan: &ScanOptions,
+    ) -> (String, usize) {
+        let old_dir = path.parent().unwrap_or(Path::new(""));
+        let new_path = plan.target_of(path).unwrap_or(path.to_path_buf());
+        let new_dir = new_path.parent().unwrap_or(Path::new(""));
+        let masked = mask_comments(content, scan.comments);
+        let (content, normalized) = normalize_imports(path, &masked, scan);
+        let (content, changes) =
+            crate::update_imports_with_plan(path, &content, plan);
+        let (content, crossed) = if scan.packages.is_empty() {
+            (content, 0)
+        } else {
+            let mut crossed = 0;
+            let content = crate::replace_specifiers(&content, |spec| {
+                let new_spec = resolve::rewrite_package_specifier(
+                    spec,
+                    &scan.packages,
+                    plan,
+                );
+                crossed += new_spec.is_some() as usize;
+                new_spec
+            });
+            (content, crossed)
+        };
+        // The imports now point into the renamed tree, so the aliases'
+        // directories have to be looked up there too
+        let aliases: Vec<Alias> = scan
+            .aliases
+            .iter()
+            .map(|alias| Alias {
+                prefix: alias.prefix.clone(),
+                dir: plan.target_of(&alias.dir).unwrap_or(alias.dir.clone()),
+            })
+            .collect();
+        let (content, aliased) = alias_imports(new_dir, &content, &aliases);
+        let (content, fixtures) =
+            e2e::rewrite_fixtures(path, &content, |found| {
+                plan.target_of(found)
+            });
+        let changes = normalized + changes + crossed + aliased + fixtures;
+        let content = requote_changed(&masked, content, scan.quote);
+        if !scan.template_literals {
+            return (unmask_comments(content), changes);
+        }
+        let (content, more) =
+            rewrite_template_paths(&content, |file, start| {
+                resolve::rewrite_specifier(file, old_dir, new_dir, plan)
+                    .map(|new| (start..start + file.len(), new))
+                    .into_iter()
+                    .collect()
+            });
+        (unmask_comments(content), changes + more)
+    }
+
+    /// Relative imports, and imports through one of `scan`'s aliases, that
+    /// don't resolve. A query or fragment, as in `'./Icon.svg?raw'`, is left
+    /// off before resolving.
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        scan: &ScanOptions,
+    ) -> Vec<String> {
+        let dir = path.parent().unwrap_or(Path::new(""));
+        crate::import_regex()
+            .captures_iter(content)
+            .map(|caps| caps[2].to_string())
+            .filter(|spec| {
+                let file = spec.split(['?', '#']).next().unwrap_or(spec);
+                let aliased = scan.aliases.iter().find_map(|alias| {
+                    let rest = file.strip_prefix(alias.prefix.as_str())?;
+                    let rest = rest
+                        .strip_prefix('/')
+                        .or(rest.is_empty().then_some(""))?;
+                    Some((alias.dir.as_path(), format!("./{}", rest)))
+                });
+                match aliased {
+                    Some((base, file)) => {
+                        resolve::resolve_specifier(base, &file).is_none()
+                    }
+                    None => {
+                        resolve::is_relative_specifier(file)
+                            && resolve::resolve_specifier(dir, file).is_none()
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+/// Stand-ins for the first letter of `import`, `export` and `require`,
+/// which hide an import from the rewriting passes. They're Unicode
+/// noncharacters, which text files don't contain.
+const MASKS: [(char, char); 3] =
+    [('i', '\u{FDD0}'), ('e', '\u{FDD1}'), ('r', '\u{FDD2}')];
+
+/// Hides the imports inside comments in `content` from the rewriting
+/// passes, unless `comments` says to rewrite them. `unmask_comments`
+/// brings them back.
+fn mask_comments(content: &str, comments: Comments) -> String {
+    if comments == Comments::Rewrite {
+        return content.to_string();
+    }
+    let ranges = comment_ranges(content);
+    let mut edits = Vec::new();
+    for found in crate::import_regex().find_iter(content) {
+        if !ranges.iter().any(|r| r.contains(&found.start())) {
+            continue;
+        }
+        let first = content[found.start()..].chars().next();
+        if let Some(&(_, mask)) = MASKS.iter().find(|(c, _)| Some(*c) == first)
+        {
+            edits.push((found.start()..found.start() + 1, mask.to_string()));
+        }
+    }
+    splice(content, edits).0
+}
+
+fn unmask_comments(content: String) -> String {
+    if !content.contains(MASKS.map(|(_, mask)| mask)) {
+        return content;
+    }
+    let mut content = content;
+    for (c, mask) in MASKS {
+        content = content.replace(mask, &c.to_string());
+    }
+    content
+}
+
+/// Where the `//`, `/* */` and `<!-- -->` comments in a script or
+/// component are, skipping over strings
+fn comment_ranges(content: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut at = 0;
+    while at < content.len() {
+        let rest = &content[at..];
+        let end = if rest.starts_with("//") {
+            rest.find('\n').unwrap_or(rest.len())
+        } else if let Some(body) = rest.strip_prefix("/*") {
+            body.find("*/").map_or(rest.len(), |end| end + 4)
+        } else if let Some(body) = rest.strip_prefix("<!--") {
+            body.find("-->").map_or(rest.len(), |end| end + 7)
+        } else if let Some(quote) =
+            rest.chars().next().filter(|c| "'\"`".contains(*c))
+        {
+            // Skip the string, so `"//"` isn't taken for a comment
+            let mut escaped = false;
+            let close = rest[1..].char_indices().find(|&(_, c)| {
+                let found = c == quote && !escaped || c == '\n' && quote != '`';
+                escaped = c == '\\' && !escaped;
+                found
+            });
+            at += close.map_or(rest.len(), |(i, _)| i + 2);
+            continue;
+        } else {
+            at += rest.chars().next().map_or(1, char::len_utf8);
+            continue;
+        };
+        ranges.push(at..at + end);
+        at += end;
+    }
+    ranges
+}
+
+/// The imports `held` leaves alone that `rewritten`, the same content with
+/// commented imports rewritten too, changes: each one's line, specifier
+/// and what it would have become
+pub fn held_back(held: &str, rewritten: &str) -> Vec<(usize, String, String)> {
+    let regex = crate::import_regex();
+    regex
+        .captures_iter(held)
+        .zip(regex.captures_iter(rewritten))
+        .filter(|(old, new)| old[2] != new[2])
+        .map(|(old, new)| {
+            let start = old.get(2).unwrap().start();
+            let line = held[..start].matches('\n').count() + 1;
+            (line, old[2].to_string(), new[2].to_string())
+        })
+        .collect()
+}
+
+/// Writes each import in `content` whose specifier differs from the same
+/// import in `original` with `quote`, unless the specifier contains it.
+/// Rewriting only changes specifiers, so the imports line up one to one.
+fn requote_changed(
+    original: &str,
+    content: String,
+    quote: Option<char>,
+) -> String {
+    let Some(quote) = quote else {
+        return content;
+    };
+    let regex = crate::import_regex();
+    let mut edits = Vec::new();
+    for (old, new) in regex
+        .captures_iter(original)
+        .zip(regex.captures_iter(&content))
+    {
+        let (spec, open, close) = (
+            new.get(2).unwrap(),
+            new.get(1).unwrap(),
+            new.get(3).unwrap(),
+        );
+        if old[2] == *spec.as_str() || spec.as_str().contains(quote) {
+            continue;
+        }
+        let quote = quote.to_string();
+        if !open.as_str().ends_with(quote.as_str()) {
+            edits.push((open.end() - 1..open.end(), quote.clone()));
+            edits.push((close.start()..close.start() + 1, quote));
+        }
+    }
+    splice(&content, edits).0
+}
+
+/// Normalizes the relative imports in `content` as `scan` asks, before
+/// they're renamed, while they still resolve on disk
+fn normalize_imports(
+    path: &Path,
+    content: &str,
+    scan: &ScanOptions,
+) -> (String, usize) {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut changes = 0;
+    let content = crate::replace_specifiers(content, |spec| {
+        let new_spec = resolve::normalize_specifier(dir, spec, scan);
+        changes += new_spec.is_some() as usize;
+        new_spec
+    });
+    (content, changes)
+}
+
+fn alias_imports(
+    dir: &Path,
+    content: &str,
+    aliases: &[Alias],
+) -> (String, usize) {
+    if aliases.is_empty() {
+        return (content.to_string(), 0);
+    }
+    let mut changes = 0;
+    let content = crate::replace_specifiers(content, |spec| {
+        let new_spec = resolve::alias_specifier(dir, spec, aliases);
+        changes += new_spec.is_some() as usize;
+        new_spec
+    });
+    (content, changes)
+}
+
+/// Applies `rewrite` to each relative path inside a template literal, like
+/// `url('./Assets/HeroBg.png')` or `'../Fonts/Inter.woff2'` in a
+/// styled-components or emotion style. Template literals hold arbitrary
+/// text, so only paths that resolve on disk may be touched.
+fn rewrite_template_paths(
+    content: &str,
+    rewrite: impl Fn(&str, usize) -> Vec<(Range<usize>, String)>,
+) -> (String, usize) {
+    let literal = Regex::new(r"`(?:[^`\\]|\\.)*`").unwrap();
+    let path =
+        Regex::new(r#"(?:url\(\s*['"]?|['"])(\.{1,2}/[^'"()\s`]+)"#).unwrap();
+
+    let mut edits = Vec::new();
+    for literal in literal.find_iter(content) {
+        for caps in path.captures_iter(literal.as_str()) {
+            let file = caps.get(1).unwrap();
+            edits
+                .extend(rewrite(file.as_str(), literal.start() + file.start()));
+        }
+    }
+    splice(content, edits)
+}
+
+static SCANNERS: &[&dyn Scanner] = &[
+    &TailwindScanner,
+    &E2eScanner,
+    &LintScanner,
+    &TsconfigScanner,
+    &ViteScanner,
+    &I18nScanner,
+    &DocsScanner,
+    &VueScanner,
+    &SvelteScanner,
+    &JsScanner,
+    &PythonScanner,
+    &RustScanner,
+    &GoScanner,
+    &PhpScanner,
+    &RubyScanner,
+    &ElixirScanner,
+    &ShellScanner,
+    &DockerScanner,
+    &TerraformScanner,
+    &HelmScanner,
+    &MakeScanner,
+    &CodeownersScanner,
+    &MonorepoScanner,
+    &PackageScanner,
+    &ProtoScanner,
+    &GraphqlScanner,
+    &FrontMatterScanner,
+    &SchemaScanner,
+    &XmlScanner,
+    &CppScanner,
+    &JvmScanner,
+    &CSharpScanner,
+    &MsBuildScanner,
+];
+
+/// The scanner that reads the file at `path`, if any does
+pub fn scanner_for(path: &Path) -> Option<&'static dyn Scanner> {
+    SCANNERS.iter().find(|s| s.matches(path)).copied()
+}
+
+/// Converts the references in the file at `path`, as `-i` does: those its
+/// language's scanner knows about, then verified string references if
+/// they're enabled. `plan` is the run's rename plan and `exceptions` its
+/// renames that aren't plain conversions (see `Plan::exceptions`), which
+/// references can't be converted to on their own.
+pub fn convert_file(
+    path: &Path,
+    content: &str,
+    scan: &ScanOptions,
+    plan: &Plan,
+    exceptions: &Plan,
+) -> (String, usize) {
+    let (renamed, imports) = rewrite_default_imports(path, content, scan, plan);
+    let (converted, changes) =
+        convert_references(path, &renamed, scan, plan, exceptions);
+    let converted = (converted, changes + imports);
+    directives::apply(content, rewrite_describe(path, converted, scan, plan))
+}
+
+fn convert_references(
+    path: &Path,
+    content: &str,
+    scan: &ScanOptions,
+    plan: &Plan,
+    exceptions: &Plan,
+) -> (String, usize) {
+    let scanner = scanner_for(path);
+    if !exceptions.renames.is_empty()
+        && !scanner.is_some_and(|s| s.converts_unresolved())
+    {
+        // Everything else only rewrites references that resolve, which
+        // following the whole plan does just as well
+        return follow_plan_references(path, content, plan, scan);
+    }
+    let (content, changes) = match scanner {
+        Some(scanner) => {
+            let (content, followed) = match exceptions.renames.is_empty() {
+                true => (content.to_string(), 0),
+                false => scanner.follow_plan(path, content, exceptions, scan),
+            };
+            let (content, changes) = scanner.convert(path, &content, scan);
+            (content, followed + changes)
+        }
+        None => (content.to_string(), 0),
+    };
+    let (content, more) =
+        rewrite_verified_refs(path, content, scan, converted_path);
+    (content, changes + more)
+}
+
+/// Rewrites the references in the file at `path` to follow `plan`, as
+/// `--mapping` does, including verified string references if they're
+/// enabled
+pub fn follow_plan_in_file(
+    path: &Path,
+    content: &str,
+    plan: &Plan,
+    scan: &ScanOptions,
+) -> (String, usize) {
+    let (renamed, imports) = rewrite_default_imports(path, content, scan, plan);
+    let (followed, changes) =
+        follow_plan_references(path, &renamed, plan, scan);
+    let followed = (followed, changes + imports);
+    directives::apply(content, rewrite_describe(path, followed, scan, plan))
+}
+
+/// Renames the default imports of files `plan` renames to their export's
+/// name, when `scan` asks for it. This runs before the references are
+/// rewritten, while the imports still resolve to the files on disk.
+fn rewrite_default_imports(
+    path: &Path,
+    content: &str,
+    scan: &ScanOptions,
+    plan: &Plan,
+) -> (String, usize) {
+    if scan.default_imports != DefaultImports::Rewrite {
+        return (content.to_string(), 0);
+    }
+    default_imports::rewrite(path, content, plan, scan.import_case)
+}
+
+/// Renames the test blocks named after the file at `path` in `content`,
+/// when `scan` asks for it, adding to `changes`
+fn rewrite_describe(
+    path: &Path,
+    (content, changes): (String, usize),
+    scan: &ScanOptions,
+    plan: &Plan,
+) -> (String, usize) {
+    if scan.describe != DescribeBlocks::Rewrite {
+        return (content, changes);
+    }
+    let (content, renamed) = describe::rewrite(path, &content, plan);
+    (content, changes + renamed)
+}
+
+fn follow_plan_references(
+    path: &Path,
+    content: &str,
+    plan: &Plan,
+    scan: &ScanOptions,
+) -> (String, usize) {
+    let (content, changes) = match scanner_for(path) {
+        Some(scanner) => scanner.follow_plan(path, content, plan, scan),
+        None => (content.to_string(), 0),
+    };
+    let (content, more) = rewrite_verified_refs(path, content, scan, |found| {
+        plan.target_of(found)
+    });
+    (content, changes + more)
+}
+
+/// Rewrites the string and comment references `scan` asks for, which are
+/// only touched when they resolve, with where `rename` says they end up
+fn rewrite_verified_refs(
+    path: &Path,
+    content: String,
+    scan: &ScanOptions,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let (content, strings) = match scan.string_refs {
+        true => rewrite_string_refs(path, &content, &scan.roots, &rename),
+        false => (content, 0),
+    };
+    let (content, comments) = match scan.comment_paths {
+        true => rewrite_comment_refs(path, &content, &scan.roots, &rename),
+        false => (content, 0),
+    };
+    let (content, templates) =
+        match scan.html_templates && crate::matches_source_file(path) {
+            true => {
+                rewrite_html_template_refs(path, &content, &scan.roots, &rename)
+            }
+            false => (content, 0),
+        };
+    (content, strings + comments + templates)
+}
+
+/// The case a file or directory should be named in. Files follow their
+/// language; directories follow the languages of the files directly in
+/// them, or of a module file with the same name next to them (`foo.rs` for
+/// `foo/`), so a Python package or Rust module gets a snake_case name.
+pub fn name_style(path: &Path, is_dir: bool) -> NameStyle {
+    let is_snake = |file: &Path| {
+        scanner_for(file).is_some_and(|s| s.name_style() == NameStyle::Snake)
+    };
+    if !is_dir {
+        return scanner_for(path).map_or(NameStyle::Kebab, |s| s.name_style());
+    }
+    let sibling = path.file_name().map(|name| {
+        path.with_file_name(format!("{}.rs", name.to_string_lossy()))
+    });
+    if sibling.is_some_and(|file| file.is_file() && is_snake(&file)) {
+        return NameStyle::Snake;
+    }
+    let Ok(children) = std::fs::read_dir(path) else {
+        return NameStyle::Kebab;
+    };
+    let snake = children.filter_map(|e| e.ok()).any(|child| {
+        child.file_type().is_ok_and(|t| t.is_file()) && is_snake(&child.path())
+    });
+    if snake {
+        NameStyle::Snake
+    } else {
+        NameStyle::Kebab
+    }
+}
+
+/// Why the file or directory at `path` has to keep its name, if it does
+pub fn pinned(path: &Path, is_dir: bool) -> Option<&'static str> {
+    path_cache::pinned(path, || {
+        if let Some(reason) = redirects::pins_name(path, is_dir) {
+            return Some(reason);
+        }
+        if is_dir {
+            SCANNERS.iter().find_map(|s| s.pins_name(path))
+        } else {
+            scanner_for(path)?.pins_name(path)
+        }
+    })
+}
+
+/// `path` made absolute, for looking at the directories above it. A
+/// relative root like `.` has no parent of its own, and `./src` doesn't
+/// start with `src`, so project files above it would go unnoticed.
+pub fn absolute(path: &Path) -> PathBuf {
+    std::path::absolute(path)
+        .map_or_else(|_| path.to_path_buf(), |path| resolve::normalize(&path))
+}
+
+/// What renaming the file at `path` to `to` leaves to be done by hand, if
+/// its language's scanner knows of anything
+pub fn rename_caveat(path: &Path, to: &Path) -> Option<String> {
+    scanner_for(path)?.rename_caveat(path, to)
+}
+
+/// The path `path` has once its name is converted, or `None` if it keeps
+/// its name. Scanners use it to rewrite references as `-i` does, so they
+/// agree with the renames on disk. Once the rename pass has planned a path,
+/// it's the name it planned.
+pub fn converted_path(path: &Path) -> Option<PathBuf> {
+    path_cache::renamed(path, || {
+        let is_dir = path.is_dir();
+        if pinned(path, is_dir).is_some() {
+            return None;
+        }
+        let name = path.file_name()?.to_str()?;
+        let converted = convert_name_as(name, name_style(path, is_dir));
+        (converted != name).then(|| path.with_file_name(converted))
+    })
+}
+
+/// Whether `file`, a path relative to `dir` that a scanner would rewrite,
+/// leads nowhere on disk, as written or with one of `extensions` added.
+/// Globs and interpolated paths can't be checked, so they never do.
+pub fn leads_nowhere(dir: &Path, file: &str, extensions: &[&str]) -> bool {
+    if file.is_empty() || file.contains(['*', '?', '[', '{', '$', '%']) {
+        return false;
+    }
+    let path = dir.join(file);
+    !path.exists()
+        && !extensions.iter().any(|ext| {
+            let mut with_ext = path.clone().into_os_string();
+            with_ext.push(format!(".{}", ext));
+            Path::new(&with_ext).exists()
+        })
+}
+
+/// Rewrites the segments of `file`, a path relative to `dir` found at byte
+/// `start` of a source file, that exist on disk and are renamed. A segment
+/// that doesn't exist as written is also tried with each of `extensions`
+/// added, for languages that leave them out.
+pub fn rewrite_file_path(
+    dir: &Path,
+    file: &str,
+    start: usize,
+    extensions: &[&str],
+    rename: &impl Fn(&Path) -> Option<PathBuf>,
+) -> Vec<(Range<usize>, String)> {
+    let mut edits = Vec::new();
+    let mut current = dir.to_path_buf();
+    let mut start = start;
+    for segment in file.split('/') {
+        let range = start..start + segment.len();
+        start += segment.len() + 1;
+        current.push(segment);
+        if matches!(segment, "" | "." | "..") {
+            continue;
+        }
+        let found = std::iter::once((current.clone(), ""))
+            .chain(extensions.iter().map(|ext| {
+                let name = format!("{}.{}", segment, ext);
+                (current.with_file_name(name), *ext)
+            }))
+            .find(|(path, _)| path.exists());
+        let Some((found, ext)) = found else {
+            continue;
+        };
+        let new = rename(&found);
+        let new = new.as_deref().and_then(Path::file_name);
+        let Some(new) = new.and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let new = match ext {
+            "" => new,
+            ext => new.strip_suffix(&format!(".{}", ext)).unwrap_or(new),
+        };
+        if new != segment {
+            edits.push((range, new.to_string()));
+        }
+    }
+    edits
+}
+
+/// Rewrites the strings in a tool's config that are paths or globs
+/// relative to it, like `'./src/**/*.{html,svelte}'`, `'!./src/Legacy/**'`
+/// or `"./Plugins/Tokens.js"`. Imports are left to `JsScanner`, and only
+/// the parts of a path that exist on disk are touched.
+pub fn rewrite_config_paths(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let string = Regex::new(
+        r#""((?:[^"\\\n]|\\.)*)"|'((?:[^'\\\n]|\\.)*)'|`([^`$\\]*)`"#,
+    )
+    .unwrap();
+    let imports: Vec<Range<usize>> = crate::import_regex()
+        .captures_iter(content)
+        .map(|caps| caps.get(2).unwrap().range())
+        .collect();
+
+    let mut edits = Vec::new();
+    for caps in string.captures_iter(content) {
+        let text = caps.get(1).or(caps.get(2)).or(caps.get(3)).unwrap();
+        if imports.iter().any(|import| import.contains(&text.start())) {
+            continue;
+        }
+        let value = text.as_str().trim_start_matches('!');
+        if !value.contains(['/', '.']) || value.starts_with('/') {
+            continue;
+        }
+        let start = text.end() - value.len();
+        edits.extend(rewrite_file_path(dir, value, start, &[], &rename));
+    }
+    splice(content, edits)
+}
+
+/// Replaces byte ranges of `content`. Returns the new content and how many
+/// ranges were replaced.
+pub fn splice(
+    content: &str,
+    mut edits: Vec<(Range<usize>, String)>,
+) -> (String, usize) {
+    edits.sort_by_key(|(range, _)| range.start);
+
+    let mut result = String::with_capacity(content.len());
+    let mut last = 0;
+    for (range, replacement) in &edits {
+        result.push_str(&content[last..range.start]);
+        result.push_str(replacement);
+        last = range.end;
+    }
+    result.push_str(&content[last..]);
+    (result, edits.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_name_style() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir(root.join("Models"))?;
+        fs::write(root.join("Models/__init__.py"), "")?;
+        fs::create_dir(root.join("Components"))?;
+        fs::write(root.join("Components/Button.tsx"), "")?;
+
+        assert_eq!(name_style(&root.join("Models"), true), NameStyle::Snake);
+        assert_eq!(
+            name_style(&root.join("Components"), true),
+            NameStyle::Kebab
+        );
+        assert_eq!(
+            name_style(Path::new("UserModel.py"), false),
+            NameStyle::Snake
+        );
+        assert_eq!(name_style(Path::new("README.md"), false), NameStyle::Kebab);
+        Ok(())
+    }
+
+    #[test]
+    fn test_absolute_paths_reach_above_the_root() -> Result<()> {
+        let cwd = std::env::current_dir()?;
+        assert_eq!(absolute(Path::new(".")), cwd);
+        assert_eq!(absolute(Path::new("./Server/../Cmd")), cwd.join("Cmd"));
+        let parent = cwd.parent().unwrap();
+        assert!(absolute(Path::new("./Server"))
+            .ancestors()
+            .any(|a| a == parent));
+        Ok(())
+    }
+
+    #[test]
+    fn test_rewrites_keep_formatting() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir(root.join("Lib"))?;
+        fs::write(root.join("Lib/MyButton.ts"), "")?;
+        fs::write(root.join("Lib/Theme.ts"), "")?;
+
+        let content = "import   B   from  \"./Lib/MyButton\"\r\n\
+                       import {\n  t,\n} from './Lib/Theme' ;\n\
+                       const c = require( \"lodash\" )\n";
+        let path = root.join("App.ts");
+        let mut scan = ScanOptions {
+            import_extensions: ImportExtensions::Always,
+            ..ScanOptions::default()
+        };
+        let (kept, _) = JsScanner.convert(&path, content, &scan);
+        assert_eq!(
+            kept,
+            "import   B   from  \"./lib/my-button.ts\"\r\n\
+             import {\n  t,\n} from './lib/theme.ts' ;\n\
+             const c = require( \"lodash\" )\n"
+        );
+
+        // Only the imports that were rewritten take Prettier's quotes
+        scan.quote = Some('\'');
+        let (requoted, _) = JsScanner.convert(&path, content, &scan);
+        assert!(
+            requoted.starts_with("import   B   from  './lib/my-button.ts'\r\n")
+        );
+        assert!(requoted.contains("require( \"lodash\" )"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_commented_imports() {
+        let content = "\
+import A from './Lib/MyButton';
+// import B from './Lib/MyButton';
+/* const c = require('./Lib/MyButton'); */
+const s = \"// no comment\"; import D from './Lib/MyButton';
+";
+        let path = Path::new("App.ts");
+        let convert = |comments| {
+            let scan = ScanOptions {
+                comments,
+                ..ScanOptions::default()
+            };
+            JsScanner.convert(path, content, &scan)
+        };
+        let (rewritten, changes) = convert(Comments::Rewrite);
+        assert_eq!(changes, 8);
+        let (held, changes) = convert(Comments::Skip);
+        assert_eq!(changes, 4);
+        assert_eq!(
+            held,
+            "\
+import A from './lib/my-button';
+// import B from './Lib/MyButton';
+/* const c = require('./Lib/MyButton'); */
+const s = \"// no comment\"; import D from './lib/my-button';
+"
+        );
+        let lines: Vec<usize> = held_back(&held, &rewritten)
+            .into_iter()
+            .map(|(line, _, _)| line)
+            .collect();
+        assert_eq!(lines, [2, 3]);
+    }
+
+    #[test]
+    fn test_template_literals() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir(root.join("Assets"))?;
+        fs::write(root.join("Assets/HeroBg.png"), "")?;
+
+        let content = "\
+const Hero = styled.div`
+  background: url('./Assets/HeroBg.png');
+  mask: url(./Assets/Missing.png);
+`;
+const label = 'Not a template ./Assets/HeroBg.png';
+";
+        let path = root.join("Hero.tsx");
+        let (kept, changes) =
+            JsScanner.convert(&path, content, &ScanOptions::default());
+        assert_eq!((kept.as_str(), changes), (content, 0));
+
+        let scan = ScanOptions {
+            template_literals: true,
+            ..ScanOptions::default()
+        };
+        let (new_content, changes) = JsScanner.convert(&path, content, &scan);
+        assert!(new_content.contains("url('./assets/hero-bg.png')"));
+        assert!(new_content.contains("url(./Assets/Missing.png)"));
+        assert!(new_content.contains("template ./Assets/HeroBg.png"));
+        assert_eq!(changes, 2);
+
+        let mut plan = Plan::default();
+        plan.push(root.join("Assets"), root.join("images"));
+        let (new_content, _) =
+            JsScanner.follow_plan(&path, content, &plan, &scan);
+        assert!(new_content.contains("url('./images/HeroBg.png')"));
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+use crate::plan::Plan;
+use crate::scanner::{self, rewrite_file_path, splice, ScanOptions, Scanner};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// OpenAPI documents and JSON Schemas, YAML or JSON, for the files their
+/// `$ref`s point into, like `./Schemas/UserResponse.yaml#/components/...`.
+/// Only the path before the `#` is touched; the JSON pointer after it
+/// names keys inside the file.
+pub struct SchemaScanner;
+
+impl Scanner for SchemaScanner {
+    fn matches(&self, path: &Path) -> bool {
+        path.extension()
+            .is_some_and(|e| e == "json" || e == "yaml" || e == "yml")
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_refs(path, content, scanner::converted_path)
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_refs(path, content, |found| plan.target_of(found))
+    }
+
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> Vec<String> {
+        let dir = path.parent().unwrap_or(Path::new(""));
+        file_refs(content)
+            .into_iter()
+            .filter(|(file, _)| scanner::leads_nowhere(dir, file, &[]))
+            .map(|(file, _)| file.to_string())
+            .collect()
+    }
+}
+
+/// Rewrites the file part of each `$ref`, relative to the file it's in
+fn rewrite_refs(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut edits = Vec::new();
+    for (file, start) in file_refs(content) {
+        edits.extend(rewrite_file_path(dir, file, start, &[], &rename));
+    }
+    splice(content, edits)
+}
+
+/// The file part of each `$ref`, quoted or not, with where it starts.
+/// References within the file (`#/...`) and URLs are left out.
+fn file_refs(content: &str) -> Vec<(&str, usize)> {
+    if !content.contains("$ref") {
+        return Vec::new();
+    }
+    let reference = Regex::new(
+        r##"["']?\$ref["']?[ \t]*:[ \t]*(?:"([^"#\n]*)|'([^'#\n]*)|([^\s"'#,}\]]+))"##,
+    )
+    .unwrap();
+    reference
+        .captures_iter(content)
+        .map(|caps| caps.get(1).or(caps.get(2)).or(caps.get(3)).unwrap())
+        .filter(|file| {
+            !file.as_str().is_empty() && !file.as_str().contains("://")
+        })
+        .map(|file| (file.as_str(), file.start()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_schema_refs() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("Schemas/Common"))?;
+        fs::write(root.join("Schemas/UserResponse.yaml"), "")?;
+        fs::write(root.join("Schemas/Common/ErrorModel.json"), "")?;
+
+        let openapi = "\
+paths:
+  /users/{id}:
+    get:
+      responses:
+        '200':
+          content:
+            application/json:
+              schema:
+                $ref: './Schemas/UserResponse.yaml#/components/schemas/User'
+        default:
+          $ref: Schemas/Common/ErrorModel.json
+        '404': { $ref: \"#/components/responses/NotFound\" }
+        '500': { $ref: 'https://example.com/Schemas/Error.yaml' }
+";
+        let path = root.join("openapi.yaml");
+        assert!(SchemaScanner.matches(&path));
+        let (new_openapi, changes) =
+            SchemaScanner.convert(&path, openapi, &ScanOptions::default());
+        assert_eq!(
+            new_openapi,
+            "\
+paths:
+  /users/{id}:
+    get:
+      responses:
+        '200':
+          content:
+            application/json:
+              schema:
+                $ref: './schemas/user-response.yaml#/components/schemas/User'
+        default:
+          $ref: schemas/common/error-model.json
+        '404': { $ref: \"#/components/responses/NotFound\" }
+        '500': { $ref: 'https://example.com/Schemas/Error.yaml' }
+"
+        );
+        assert_eq!(changes, 5);
+
+        let schema = r##"{ "properties": { "error": { "$ref": "Common/ErrorModel.json#/definitions/Error" } } }"##;
+        let (new_schema, _) = SchemaScanner.convert(
+            &root.join("Schemas/UserResponse.json"),
+            schema,
+            &ScanOptions::default(),
+        );
+        assert!(new_schema.contains(r#""common/error-model.json#/"#));
+
+        let orphans = SchemaScanner.orphans(
+            &path,
+            &openapi.replace("Common/ErrorModel", "Common/Gone"),
+            &ScanOptions::default(),
+        );
+        assert_eq!(orphans, ["Schemas/Common/Gone.json"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,354 @@
+use crate::lsp;
+use crate::path_cache;
+use crate::plan::Plan;
+use crate::report::Report;
+use crate::{cancel, convert_name, needs_conversion, Args, Command, Options};
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+
+/// An error response, sent back instead of a result
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// Answers newline-delimited JSON-RPC 2.0 requests from `input` until it
+/// closes, a `shutdown` request arrives or the server is interrupted. The
+/// scan of the served directory is cached between requests and refreshed
+/// by `apply` and `rescan`.
+pub fn run(
+    args: &Args,
+    opts: &Options,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> Result<()> {
+    let mut server = Server {
+        dir: args.root().to_path_buf(),
+        args,
+        opts,
+        plan: None,
+    };
+
+    for line in input.lines() {
+        let line = line.context("Failed to read request")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (response, done) = match serde_json::from_str::<Value>(&line) {
+            Ok(request) => server.handle(&request),
+            Err(err) => (
+                Some(error_response(
+                    Value::Null,
+                    RpcError::new(PARSE_ERROR, err.to_string()),
+                )),
+                false,
+            ),
+        };
+        if let Some(response) = response {
+            writeln!(output, "{}", response)?;
+            output.flush()?;
+        }
+        if done || cancel::checkpoint().is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+struct Server<'a> {
+    dir: PathBuf,
+    /// What `apply` does, from the command line
+    args: &'a Args,
+    opts: &'a Options,
+    /// Renames the project needs, kept until something changes on disk
+    plan: Option<Plan>,
+}
+
+impl Server<'_> {
+    /// Returns the response to send, if any, and whether to stop serving.
+    /// Notifications (requests without an id) never get a response.
+    fn handle(&mut self, request: &Value) -> (Option<Value>, bool) {
+        let id = request.get("id").cloned();
+        let Some(method) = request.get("method").and_then(Value::as_str) else {
+            let error = RpcError::new(INVALID_REQUEST, "missing method");
+            return (
+                Some(error_response(id.unwrap_or_default(), error)),
+                false,
+            );
+        };
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+        let done = matches!(method, "shutdown" | "exit");
+        // Files may have changed since the last request
+        path_cache::clear();
+        let result = self.call(method, &params);
+        let response = id.map(|id| match result {
+            Ok(result) => {
+                json!({ "jsonrpc": "2.0", "id": id, "result": result })
+            }
+            Err(error) => error_response(id, error),
+        });
+        (response, done)
+    }
+
+    fn call(
+        &mut self,
+        method: &str,
+        params: &Value,
+    ) -> Result<Value, RpcError> {
+        let opts = self.opts;
+        match method {
+            "plan" => {
+                Ok(json!({ "renames": renames_json(self.plan()?, opts) }))
+            }
+            "check" => {
+                let violations: Vec<Value> = self
+                    .plan()?
+                    .renames
+                    .iter()
+                    .filter_map(|r| {
+                        Some(json!({
+                            "path": opts.display.show(&r.from),
+                            "expected": r.to.file_name()?.to_string_lossy(),
+                        }))
+                    })
+                    .collect();
+                Ok(
+                    json!({ "ok": violations.is_empty(), "violations": violations }),
+                )
+            }
+            "convertString" => {
+                let text = params
+                    .get("text")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| {
+                        RpcError::new(
+                            INVALID_PARAMS,
+                            "expected { \"text\": string }",
+                        )
+                    })?;
+                Ok(json!({
+                    "text": convert_name(text),
+                    "changed": needs_conversion(text),
+                }))
+            }
+            "apply" => {
+                // A run with `-a`, checked, journaled and recorded like one
+                // from the command line
+                let args = Args {
+                    all: true,
+                    imports: false,
+                    command: Some(Command::Serve {
+                        path: self.dir.clone(),
+                    }),
+                    ..self.args.clone()
+                };
+                let mut report = Report::default();
+                let started = std::time::Instant::now();
+                let result = crate::apply(&args, opts, &mut report, started);
+                self.plan = None;
+                let plan = result.map_err(internal_error)?;
+                if let Some(dir) = plan.target_of(&self.dir) {
+                    self.dir = dir;
+                }
+
+                let failures: Vec<Value> = report
+                    .failures
+                    .iter()
+                    .map(|f| {
+                        json!({
+                            "path": opts.display.show(&f.path),
+                            "error": format!("{:#}", f.error),
+                        })
+                    })
+                    .collect();
+                Ok(json!({
+                    "renames": renames_json(&plan, opts),
+                    "failures": failures,
+                }))
+            }
+            "rescan" => {
+                self.plan = None;
+                let count = self.plan()?.renames.len();
+                Ok(json!({ "pending": count }))
+            }
+            "initialize" => Ok(lsp::capabilities()),
+            "workspace/willRenameFiles" => {
+                let plan = lsp::rename_plan(params).ok_or_else(|| {
+                    RpcError::new(
+                        INVALID_PARAMS,
+                        "expected { \"files\": [{ \"oldUri\", \"newUri\" }] }",
+                    )
+                })?;
+                let mut report = Report::default();
+                lsp::workspace_edit(&self.dir, &plan, opts, &mut report)
+                    .map_err(internal_error)
+            }
+            "shutdown" | "exit" => Ok(Value::Null),
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("unknown method: {}", method),
+            )),
+        }
+    }
+
+    /// The cached plan, scanning the project first if there isn't one
+    fn plan(&mut self) -> Result<&Plan, RpcError> {
+        if self.plan.is_none() {
+            let mut report = Report::default();
+            let plan = crate::plan_directory(&self.dir, self.opts, &mut report)
+                .map_err(internal_error)?;
+            self.plan = Some(plan);
+        }
+        Ok(self.plan.as_ref().expect("plan was just computed"))
+    }
+}
+
+fn renames_json(plan: &Plan, opts: &Options) -> Vec<Value> {
+    plan.renames
+        .iter()
+        .map(|r| {
+            json!({
+                "from": opts.display.show(&r.from),
+                "to": opts.display.show(&r.to),
+            })
+        })
+        .collect()
+}
+
+fn internal_error(error: anyhow::Error) -> RpcError {
+    RpcError::new(INTERNAL_ERROR, format!("{:#}", error))
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": error.code, "message": error.message },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use std::fs;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    fn serve(dir: &Path, requests: &[&str]) -> Result<Vec<Value>> {
+        let opts = Options {
+            quiet: true,
+            ..Default::default()
+        };
+        serve_with(dir, &opts, requests)
+    }
+
+    fn serve_with(
+        dir: &Path,
+        opts: &Options,
+        requests: &[&str],
+    ) -> Result<Vec<Value>> {
+        let args = Args::try_parse_from([
+            "kebabify".as_ref(),
+            "serve".as_ref(),
+            dir.as_os_str(),
+        ])?;
+        let mut output = Vec::new();
+        run(&args, opts, requests.join("\n").as_bytes(), &mut output)?;
+        String::from_utf8(output)?
+            .lines()
+            .map(|line| Ok(serde_json::from_str(line)?))
+            .collect()
+    }
+
+    #[test]
+    fn test_requests() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = &temp_dir.path().join("project");
+        fs::create_dir(root)?;
+        fs::write(root.join("UserCard.ts"), "")?;
+        fs::write(root.join("main.ts"), "import c from './UserCard';")?;
+
+        let responses = serve(
+            root,
+            &[
+                r#"{"jsonrpc":"2.0","id":1,"method":"check"}"#,
+                r#"{"jsonrpc":"2.0","id":2,"method":"convertString","params":{"text":"MyFile.ts"}}"#,
+                r#"{"jsonrpc":"2.0","method":"rescan"}"#,
+                r#"{"jsonrpc":"2.0","id":3,"method":"apply"}"#,
+                r#"{"jsonrpc":"2.0","id":4,"method":"plan"}"#,
+                r#"{"jsonrpc":"2.0","id":5,"method":"nope"}"#,
+                "not json",
+                r#"{"jsonrpc":"2.0","id":6,"method":"shutdown"}"#,
+                r#"{"jsonrpc":"2.0","id":7,"method":"plan"}"#,
+            ],
+        )?;
+
+        assert_eq!(responses.len(), 7);
+        assert_eq!(responses[0]["result"]["ok"], false);
+        assert_eq!(
+            responses[0]["result"]["violations"][0]["expected"],
+            "user-card.ts"
+        );
+        assert_eq!(responses[1]["result"]["text"], "my-file.ts");
+        assert_eq!(
+            responses[2]["result"]["renames"].as_array().unwrap().len(),
+            1
+        );
+        assert_eq!(responses[2]["result"]["failures"], json!([]));
+        assert_eq!(responses[3]["result"]["renames"], json!([]));
+        assert_eq!(responses[4]["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(responses[5]["error"]["code"], PARSE_ERROR);
+        assert_eq!(responses[6]["id"], 6);
+
+        assert!(root.join("user-card.ts").exists());
+        assert_eq!(
+            fs::read_to_string(root.join("main.ts"))?,
+            "import c from './user-card';"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_keeps_the_guardrails() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = &temp_dir.path().join("project");
+        fs::create_dir(root)?;
+        fs::write(root.join("UserCard.ts"), "")?;
+        fs::write(root.join("NavBar.ts"), "")?;
+
+        let opts = Options {
+            quiet: true,
+            max_changes: Some(1),
+            ..Default::default()
+        };
+        let responses = serve_with(
+            root,
+            &opts,
+            &[r#"{"jsonrpc":"2.0","id":1,"method":"apply"}"#],
+        )?;
+        assert_eq!(responses[0]["error"]["code"], INTERNAL_ERROR);
+        assert!(root.join("UserCard.ts").exists());
+        assert!(root.join("NavBar.ts").exists());
+        Ok(())
+    }
+}
@@ -0,0 +1,445 @@
+use crate::plan::Plan;
+use crate::resolve;
+use crate::scanner::{self, rewrite_file_path, splice, JsScanner};
+use crate::scanner::{ScanOptions, Scanner};
+use regex::Regex;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Extensions a stylesheet's `@import` may leave out
+const STYLE_EXTENSIONS: [&str; 4] = ["css", "scss", "sass", "less"];
+
+/// Vue single-file components. Each top-level block is handled on its own:
+/// `<script>` and `<script setup>` by `JsScanner`, `@import` and `url()` in
+/// `<style>`, and `src` attributes in `<template>` and on the blocks
+/// themselves, as in `<img src="./Assets/Logo.png">`.
+pub struct VueScanner;
+
+/// Svelte components, handled like Vue's except that the markup is
+/// everything outside `<script>` and `<style>`, and its `href` attributes
+/// are updated as well as its `src` ones
+pub struct SvelteScanner;
+
+/// Which framework's components a file is, which decides how it's split
+/// into blocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Component {
+    Vue,
+    Svelte,
+}
+
+impl Scanner for VueScanner {
+    fn matches(&self, path: &Path) -> bool {
+        path.extension().is_some_and(|e| e == "vue")
+    }
+
+    fn converts_unresolved(&self) -> bool {
+        true
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        scan: &ScanOptions,
+    ) -> (String, usize) {
+        convert(Component::Vue, path, content, scan)
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        scan: &ScanOptions,
+    ) -> (String, usize) {
+        follow_plan(Component::Vue, path, content, plan, scan)
+    }
+
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        scan: &ScanOptions,
+    ) -> Vec<String> {
+        orphans(Component::Vue, path, content, scan)
+    }
+}
+
+impl Scanner for SvelteScanner {
+    fn matches(&self, path: &Path) -> bool {
+        path.extension().is_some_and(|e| e == "svelte")
+    }
+
+    fn converts_unresolved(&self) -> bool {
+        true
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        scan: &ScanOptions,
+    ) -> (String, usize) {
+        convert(Component::Svelte, path, content, scan)
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        scan: &ScanOptions,
+    ) -> (String, usize) {
+        follow_plan(Component::Svelte, path, content, plan, scan)
+    }
+
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        scan: &ScanOptions,
+    ) -> Vec<String> {
+        orphans(Component::Svelte, path, content, scan)
+    }
+}
+
+fn convert(
+    component: Component,
+    path: &Path,
+    content: &str,
+    scan: &ScanOptions,
+) -> (String, usize) {
+    rewrite_blocks(
+        component,
+        path,
+        content,
+        |script| JsScanner.convert(path, script, scan),
+        scanner::converted_path,
+    )
+}
+
+fn follow_plan(
+    component: Component,
+    path: &Path,
+    content: &str,
+    plan: &Plan,
+    scan: &ScanOptions,
+) -> (String, usize) {
+    rewrite_blocks(
+        component,
+        path,
+        content,
+        |script| JsScanner.follow_plan(path, script, plan, scan),
+        |found| plan.target_of(found),
+    )
+}
+
+/// The imports in the component's scripts that don't resolve, and the
+/// files its markup and styles refer to that don't exist. Only paths
+/// starting with `./` or `../` are sure to be files next to the component;
+/// the rest may be served as they are or found on a load path. A Sass
+/// partial, `_variables.scss`, can be imported as `./variables`.
+fn orphans(
+    component: Component,
+    path: &Path,
+    content: &str,
+    scan: &ScanOptions,
+) -> Vec<String> {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut orphans = Vec::new();
+    for block in blocks(component, content) {
+        let tag = &content[block.tag.clone()];
+        let mut paths = asset_paths(component, tag, 0, Kind::Markup);
+        let body = &content[block.body.clone()];
+        match block.kind {
+            Kind::Script => {
+                orphans.extend(JsScanner.orphans(path, body, scan));
+            }
+            kind => paths.extend(asset_paths(component, body, 0, kind)),
+        }
+        let extensions: &[&str] = match block.kind {
+            Kind::Style => &STYLE_EXTENSIONS,
+            _ => &[],
+        };
+        let partial = |file: &str| match file.rsplit_once('/') {
+            Some((parent, name)) => format!("{}/_{}", parent, name),
+            None => format!("_{}", file),
+        };
+        orphans.extend(
+            paths
+                .into_iter()
+                .map(|(_, file)| file.split(['?', '#']).next().unwrap_or(file))
+                .filter(|file| {
+                    resolve::is_relative_specifier(file)
+                        && scanner::leads_nowhere(dir, file, extensions)
+                        && (block.kind != Kind::Style
+                            || scanner::leads_nowhere(
+                                dir,
+                                &partial(file),
+                                extensions,
+                            ))
+                })
+                .map(str::to_string),
+        );
+    }
+    orphans
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Script,
+    Style,
+    Markup,
+}
+
+/// A top-level block of a component: its opening tag and its body
+#[derive(Debug, PartialEq, Eq)]
+struct Block {
+    kind: Kind,
+    tag: Range<usize>,
+    body: Range<usize>,
+}
+
+/// The top-level blocks in `content`: `<script>`, `<style>` and, in Vue,
+/// `<template>`, which can hold `<template>` elements of its own, so it
+/// ends at the matching closing tag. In Svelte, the markup is what's left
+/// between the other blocks.
+fn blocks(component: Component, content: &str) -> Vec<Block> {
+    let open = match component {
+        Component::Vue => r"<(script|style|template)\b[^>]*>",
+        Component::Svelte => r"<(script|style)\b[^>]*>",
+    };
+    let open = Regex::new(open).unwrap();
+    let nested = Regex::new(r"<template\b[^>]*>|</template\s*>").unwrap();
+    let mut blocks = Vec::new();
+    let mut at = 0;
+    while let Some(caps) = open.captures_at(content, at) {
+        let tag = caps.get(0).unwrap();
+        let (kind, close) = match &caps[1] {
+            "script" => (Kind::Script, "</script"),
+            "style" => (Kind::Style, "</style"),
+            _ => (Kind::Markup, "</template"),
+        };
+        let start = tag.end();
+        if component == Component::Svelte {
+            blocks.push(Block {
+                kind: Kind::Markup,
+                tag: at..at,
+                body: at..tag.start(),
+            });
+        }
+        let end = match kind {
+            Kind::Markup => {
+                let mut depth = 1;
+                nested
+                    .find_iter(&content[start..])
+                    .find(|found| {
+                        depth += match found.as_str().starts_with("</") {
+                            true => -1,
+                            false => 1,
+                        };
+                        depth == 0
+                    })
+                    .map(|found| start + found.start())
+            }
+            _ => content[start..].find(close).map(|end| start + end),
+        };
+        let end = end.unwrap_or(content.len());
+        blocks.push(Block {
+            kind,
+            tag: tag.range(),
+            body: start..end,
+        });
+        at = content[end..]
+            .find('>')
+            .map_or(content.len(), |i| end + i + 1);
+    }
+    if component == Component::Svelte {
+        blocks.push(Block {
+            kind: Kind::Markup,
+            tag: at..at,
+            body: at.min(content.len())..content.len(),
+        });
+    }
+    blocks
+}
+
+/// The paths in `text`, found at byte `offset` of the component, that a
+/// block of `kind` refers to files by, with where each starts
+fn asset_paths(
+    component: Component,
+    text: &str,
+    offset: usize,
+    kind: Kind,
+) -> Vec<(usize, &str)> {
+    let pattern = match (kind, component) {
+        (Kind::Style, _) => concat!(
+            r#"@import\s+(?:url\(\s*)?["']([^"'\n]+)["']"#,
+            r#"|url\(\s*["']?([^"'()\s]+)"#,
+        ),
+        (_, Component::Vue) => r#"\bsrc\s*=\s*["']([^"'\s]+)["']"#,
+        (_, Component::Svelte) => {
+            r#"\b(?:src|href)\s*=\s*["']([^"'\s{}]+)["']"#
+        }
+    };
+    Regex::new(pattern)
+        .unwrap()
+        .captures_iter(text)
+        .filter_map(|caps| caps.iter().skip(1).flatten().next())
+        // URLs, root-relative paths and packages aren't relative to the
+        // component
+        .filter(|path| {
+            let path = path.as_str();
+            !path.contains(':') && !path.starts_with(['/', '~', '@', '#'])
+        })
+        .map(|path| (offset + path.start(), path.as_str()))
+        .collect()
+}
+
+/// Rewrites each block of the component at `path`: scripts with `script`,
+/// and the files the rest refer to that exist with where `rename` says they
+/// end up
+fn rewrite_blocks(
+    component: Component,
+    path: &Path,
+    content: &str,
+    script: impl Fn(&str) -> (String, usize),
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut edits = Vec::new();
+    let mut changes = 0;
+    for block in blocks(component, content) {
+        let tag = &content[block.tag.clone()];
+        let mut paths =
+            asset_paths(component, tag, block.tag.start, Kind::Markup);
+        let body = &content[block.body.clone()];
+        match block.kind {
+            Kind::Script => {
+                let (rewritten, changed) = script(body);
+                if changed > 0 {
+                    edits.push((block.body, rewritten));
+                    changes += changed;
+                }
+            }
+            kind => paths.extend(asset_paths(
+                component,
+                body,
+                block.body.start,
+                kind,
+            )),
+        }
+        let extensions: &[&str] = match block.kind {
+            Kind::Style => &STYLE_EXTENSIONS,
+            _ => &[],
+        };
+        for (start, file) in paths {
+            let found =
+                rewrite_file_path(dir, file, start, extensions, &rename);
+            changes += found.len();
+            edits.extend(found);
+        }
+    }
+    (splice(content, edits).0, changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_component_blocks() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("Assets"))?;
+        fs::create_dir_all(root.join("Styles"))?;
+        fs::write(root.join("Assets/HeroImage.png"), "")?;
+        fs::write(root.join("Assets/LogoIcon.svg"), "")?;
+        fs::write(root.join("Styles/BaseTheme.scss"), "")?;
+        fs::write(root.join("UserCard.vue"), "")?;
+        let content = r#"<template>
+  <template v-if="ready"><span>ready</span></template>
+  <img src="./Assets/LogoIcon.svg" alt="">
+  <img src="https://example.com/Logo.png">
+</template>
+
+<script setup lang="ts">
+import UserCard from './UserCard.vue';
+</script>
+
+<style lang="scss" scoped>
+@import './Styles/BaseTheme';
+.hero { background: url(./Assets/HeroImage.png); }
+</style>
+"#;
+        let (converted, changes) = VueScanner.convert(
+            &root.join("App.vue"),
+            content,
+            &ScanOptions::default(),
+        );
+        assert_eq!(changes, 7);
+        assert_eq!(
+            converted,
+            content
+                .replace("Assets/LogoIcon", "assets/logo-icon")
+                .replace("./UserCard.vue", "./user-card.vue")
+                .replace("Styles/BaseTheme", "styles/base-theme")
+                .replace("Assets/HeroImage", "assets/hero-image")
+        );
+
+        let content = r#"<script context="module">
+import UserCard from './UserCard.vue';
+</script>
+
+<a href="./Assets/LogoIcon.svg">{name}</a>
+<a href="https://example.com/Assets/LogoIcon.svg">Logo</a>
+<img src={hero} alt="">
+
+<style>
+div { background: url('./Assets/HeroImage.png'); }
+</style>
+"#;
+        let (converted, changes) = SvelteScanner.convert(
+            &root.join("App.svelte"),
+            content,
+            &ScanOptions::default(),
+        );
+        assert_eq!(changes, 5);
+        assert_eq!(
+            converted,
+            content
+                .replace("./UserCard.vue", "./user-card.vue")
+                .replace("./Assets/LogoIcon", "./assets/logo-icon")
+                .replace("Assets/HeroImage", "assets/hero-image")
+        );
+
+        fs::write(root.join("Styles/_Colors.scss"), "")?;
+        let content = r#"<template>
+  <img src="./Assets/LogoIcon.svg"><img src="./Assets/Gone.png">
+  <img src="images/served-as-is.png">
+</template>
+<script>
+import Gone from './Gone.vue';
+</script>
+<style lang="scss">
+@import './Styles/BaseTheme';
+@import './Styles/Colors';
+@import './Styles/Missing';
+</style>
+"#;
+        let orphans = VueScanner.orphans(
+            &root.join("App.vue"),
+            content,
+            &ScanOptions::default(),
+        );
+        assert_eq!(
+            orphans,
+            ["./Assets/Gone.png", "./Gone.vue", "./Styles/Missing"]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,175 @@
+use crate::plan::Plan;
+use crate::scanner::{self, rewrite_file_path, splice, ScanOptions, Scanner};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// Shell scripts, for the files they `source`
+pub struct ShellScanner;
+
+impl Scanner for ShellScanner {
+    fn matches(&self, path: &Path) -> bool {
+        matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("sh" | "bash" | "zsh")
+        )
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_sources(path, content, scanner::converted_path)
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_sources(path, content, |found| plan.target_of(found))
+    }
+
+    /// Sourced files that aren't in any directory they could be relative to
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> Vec<String> {
+        sources(path, content)
+            .into_iter()
+            .filter(|(file, _, bases)| {
+                bases
+                    .iter()
+                    .all(|base| scanner::leads_nowhere(base, file, &[]))
+            })
+            .map(|(file, _, _)| file.to_string())
+            .collect()
+    }
+}
+
+/// `source FILE` and `. FILE` in command position
+fn source_regex() -> Regex {
+    Regex::new(
+        r#"(?mx)
+        (?:^|[;&|(]|\bthen|\bdo|\belse)[\ \t]*(?:source|\.)[\ \t]+
+        # One shell word: quoted parts, command substitutions and plain text
+        ((?:
+            "(?:\$\([^)\n]*\)|[^"\n])*"
+            | '[^'\n]*'
+            | \$\([^)\n]*\)
+            | [^\s;&|)"']
+        )+)"#,
+    )
+    .unwrap()
+}
+
+/// Rewrites the paths of sourced files, in the first directory they're
+/// found in
+fn rewrite_sources(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let mut edits = Vec::new();
+    for (file, start, bases) in sources(path, content) {
+        let Some(base) =
+            bases.into_iter().find(|base| base.join(file).exists())
+        else {
+            continue;
+        };
+        edits.extend(rewrite_file_path(base, file, start, &[], &rename));
+    }
+    splice(content, edits)
+}
+
+/// The relative paths of sourced files, where each starts, and the
+/// directories it may be relative to. A path after a variable or command
+/// substitution, like `"$(dirname "$0")/Lib/Common.sh"` or
+/// `$SCRIPT_DIR/Lib/Common.sh`, is taken to be relative to the script.
+/// A plain relative path depends on where the script is run from, so it
+/// may be relative to the script's directory or any directory above it.
+fn sources<'a>(
+    path: &'a Path,
+    content: &'a str,
+) -> Vec<(&'a str, usize, Vec<&'a Path>)> {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let after_variable =
+        Regex::new(r#"(?:\$\{[^}]*\}|\$\([^)]*\)|\$\w+)"?/([^"'\s$]*)["']?$"#)
+            .unwrap();
+
+    let mut sources = Vec::new();
+    for caps in source_regex().captures_iter(content) {
+        let word = caps.get(1).unwrap();
+        let (file, start, bases) =
+            if let Some(tail) = after_variable.captures(word.as_str()) {
+                let tail = tail.get(1).unwrap();
+                (tail.as_str(), word.start() + tail.start(), vec![dir])
+            } else if word.as_str().contains('$') {
+                continue;
+            } else {
+                let quoted = word.as_str().starts_with(['"', '\'']);
+                let file = word.as_str().trim_matches(['"', '\'']);
+                let start = word.start() + quoted as usize;
+                (file, start, dir.ancestors().collect())
+            };
+        if file.starts_with(['/', '~']) {
+            continue;
+        }
+        sources.push((file, start, bases));
+    }
+    sources
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_sourced_files() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("Scripts/Lib"))?;
+        fs::write(root.join("Scripts/SetupEnv.sh"), "")?;
+        fs::write(root.join("Scripts/Lib/Common.sh"), "")?;
+
+        let content = r#"#!/bin/sh
+source ./Scripts/SetupEnv.sh
+. "$(dirname "$0")/Lib/Common.sh"
+if true; then . ${HERE}/Lib/Common.sh; fi
+source "$UNKNOWN"
+echo . ./Scripts/SetupEnv.sh
+"#;
+        let (new_content, changes) = ShellScanner.convert(
+            &root.join("Scripts/Deploy.sh"),
+            content,
+            &ScanOptions::default(),
+        );
+        assert_eq!(
+            new_content,
+            r#"#!/bin/sh
+source ./scripts/setup-env.sh
+. "$(dirname "$0")/lib/common.sh"
+if true; then . ${HERE}/lib/common.sh; fi
+source "$UNKNOWN"
+echo . ./Scripts/SetupEnv.sh
+"#
+        );
+        assert_eq!(changes, 6);
+
+        let orphans = ShellScanner.orphans(
+            &root.join("Scripts/Deploy.sh"),
+            "source ./Scripts/SetupEnv.sh\n. ./Lib/Gone.sh\n. ~/.profile\n",
+            &ScanOptions::default(),
+        );
+        assert_eq!(orphans, ["./Lib/Gone.sh"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,163 @@
+use crate::history::format_time;
+use crate::journal::Change;
+use crate::report::Report;
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Counts of what each run did, for following a migration over weeks.
+/// Kept only once turned on with `kebabify stats --enable`, and never sent
+/// anywhere.
+pub const STATS_FILE: &str = ".kebabify-stats.json";
+
+/// What's counted for each run and added up in the totals
+const COUNTS: [&str; 4] = ["renames", "files_updated", "warnings", "failures"];
+
+fn read(path: &Path) -> Result<Option<Value>> {
+    match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map(Some)
+            .with_context(|| format!("Malformed {}", path.display())),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err)
+            .with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+fn write(path: &Path, stats: &Value) -> Result<()> {
+    let content = serde_json::to_string_pretty(stats)?;
+    fs::write(path, content + "\n")
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Turns keeping stats under `root` on or off. Turning them off keeps the
+/// runs counted so far.
+pub fn set_enabled(root: &Path, enabled: bool) -> Result<()> {
+    let path = root.join(STATS_FILE);
+    let mut stats = read(&path)?.unwrap_or_else(|| json!({ "runs": [] }));
+    stats["enabled"] = json!(enabled);
+    write(&path, &stats)
+}
+
+/// Adds a run that took `elapsed` to the stats in `root`, if they're kept
+pub fn record(root: &Path, elapsed: Duration, report: &Report) -> Result<()> {
+    let path = root.join(STATS_FILE);
+    let Some(mut stats) = read(&path)? else {
+        return Ok(());
+    };
+    if stats.get("enabled").and_then(Value::as_bool) != Some(true) {
+        return Ok(());
+    }
+    let changes = &report.journal.changes;
+    let count =
+        |f: fn(&Change) -> bool| changes.iter().filter(|c| f(c)).count();
+    let run = json!({
+        "time": SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs()),
+        "seconds": (elapsed.as_secs_f64() * 1000.0).round() / 1000.0,
+        "renames": count(|c| matches!(c, Change::Renamed { .. })),
+        "files_updated": count(|c| matches!(c, Change::Wrote { .. })),
+        "warnings": report.warnings.len(),
+        "failures": report.failures.len(),
+    });
+
+    let Some(runs) = stats.get_mut("runs").and_then(Value::as_array_mut) else {
+        anyhow::bail!("Malformed {}: no \"runs\" list", path.display());
+    };
+    runs.push(run);
+    stats["totals"] = totals(runs);
+    write(&path, &stats)
+}
+
+/// Every count added up over `runs`, with how many there were and how long
+/// they took
+fn totals(runs: &[Value]) -> Value {
+    let sum = |key: &str| -> u64 {
+        runs.iter().filter_map(|r| r.get(key)?.as_u64()).sum()
+    };
+    let mut totals = json!({
+        "runs": runs.len(),
+        "seconds": runs
+            .iter()
+            .filter_map(|r| r.get("seconds")?.as_f64())
+            .sum::<f64>()
+            .round(),
+    });
+    for key in COUNTS {
+        totals[key] = json!(sum(key));
+    }
+    totals
+}
+
+/// Prints the totals kept under `root`, for `kebabify stats`
+pub fn print(root: &Path) -> Result<()> {
+    let stats = read(&root.join(STATS_FILE))?;
+    let enabled = stats
+        .as_ref()
+        .and_then(|s| s.get("enabled")?.as_bool())
+        .unwrap_or(false);
+    if !enabled {
+        println!("Stats are off (turn them on with `kebabify stats --enable`)");
+    }
+    let runs = stats
+        .as_ref()
+        .and_then(|s| s.get("runs")?.as_array().cloned())
+        .unwrap_or_default();
+    let (Some(first), Some(last)) = (runs.first(), runs.last()) else {
+        println!("No runs counted yet");
+        return Ok(());
+    };
+    let time = |run: &Value| {
+        format_time(run.get("time").and_then(Value::as_u64).unwrap_or(0))
+    };
+    let totals = totals(&runs);
+    println!(
+        "{} runs from {} to {} UTC",
+        runs.len(),
+        time(first),
+        time(last)
+    );
+    for key in COUNTS {
+        println!("  {}: {}", key.replace('_', " "), totals[key]);
+    }
+    println!("  seconds: {}", totals["seconds"]);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_stats_are_opt_in() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        let mut report = Report::default();
+        report.journal.record(Change::Renamed {
+            from: PathBuf::from("A.ts"),
+            to: PathBuf::from("a.ts"),
+        });
+
+        record(root, Duration::from_millis(1500), &report)?;
+        assert!(!root.join(STATS_FILE).exists());
+
+        set_enabled(root, true)?;
+        record(root, Duration::from_millis(1500), &report)?;
+        record(root, Duration::from_millis(1500), &Report::default())?;
+        let stats = read(&root.join(STATS_FILE))?.unwrap();
+        assert_eq!(stats["runs"].as_array().map(Vec::len), Some(2));
+        assert_eq!(stats["totals"]["renames"], 1);
+        assert_eq!(stats["totals"]["seconds"], 3.0);
+
+        set_enabled(root, false)?;
+        record(root, Duration::from_millis(1500), &report)?;
+        let stats = read(&root.join(STATS_FILE))?.unwrap();
+        assert_eq!(stats["totals"]["runs"], 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,247 @@
+use crate::scanner::{rewrite_file_path, splice};
+use regex::Regex;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Rewrites quoted strings in any text file that name an existing file or
+/// directory, like `'./Fixtures/UserList.json'` in a test or
+/// `"/Images/Logo.png"` in a router config. A string is tried relative to
+/// the file, then relative to each of `roots`; a string starting with `/`
+/// only against `roots`. Strings that don't resolve are left alone, so
+/// prose and identifiers that happen to look like paths aren't touched.
+pub fn rewrite_string_refs(
+    path: &Path,
+    content: &str,
+    roots: &[PathBuf],
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let string = Regex::new(r#""([^"\n]*)"|'([^'\n]*)'|`([^`\n]*)`"#).unwrap();
+
+    let mut edits = Vec::new();
+    for caps in string.captures_iter(content) {
+        let text = caps.iter().skip(1).flatten().next().unwrap();
+        edits.extend(rewrite_reference(
+            text.as_str(),
+            text.start(),
+            dir,
+            roots,
+            &rename,
+        ));
+    }
+    splice(content, edits)
+}
+
+/// Rewrites paths mentioned in comments, like `// see ./Utils/Dates.ts`,
+/// the same way: only when they name a file or directory that exists
+pub fn rewrite_comment_refs(
+    path: &Path,
+    content: &str,
+    roots: &[PathBuf],
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let comment = Regex::new(
+        r"(?s)//[^\n]*|/\*.*?\*/|<!--.*?-->|(?m:(?:^|[ \t])#[^\n]*)",
+    )
+    .unwrap();
+    let word = Regex::new(r"[\w./@~-]+").unwrap();
+
+    let mut edits = Vec::new();
+    for comment in comment.find_iter(content) {
+        for word in word.find_iter(comment.as_str()) {
+            // A path can end a sentence
+            let text = word.as_str().trim_end_matches('.');
+            edits.extend(rewrite_reference(
+                text,
+                comment.start() + word.start(),
+                dir,
+                roots,
+                &rename,
+            ));
+        }
+    }
+    splice(content, edits)
+}
+
+/// Rewrites path-bearing attributes like `src="./Assets/Logo.svg"` in
+/// lit-html and hyperHTML templates (`html\`...\``, `svg\`...\``,
+/// `wire()\`...\``, `bind(el)\`...\``) the same way: only when they name
+/// a file or directory that exists
+pub fn rewrite_html_template_refs(
+    path: &Path,
+    content: &str,
+    roots: &[PathBuf],
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let tag =
+        Regex::new(r"\b(?:html|svg|(?:wire|bind)\([^)`]*\))\s*`").unwrap();
+    let attribute = Regex::new(
+        r#"\b(?:src|href|poster|action)\s*=\s*(?:"([^"]*)"|'([^']*)')"#,
+    )
+    .unwrap();
+
+    let mut edits = Vec::new();
+    for tag in tag.find_iter(content) {
+        // Up to the next backtick, which ends the template or starts one
+        // nested in it, whose tag is found on its own
+        let rest = &content[tag.end()..];
+        let body = &rest[..rest.find('`').unwrap_or(rest.len())];
+        for caps in attribute.captures_iter(body) {
+            let text = caps.iter().skip(1).flatten().next().unwrap();
+            edits.extend(rewrite_reference(
+                text.as_str(),
+                tag.end() + text.start(),
+                dir,
+                roots,
+                &rename,
+            ));
+        }
+    }
+    splice(content, edits)
+}
+
+/// The edits renaming `text`, found at byte `start`, if it's a reference
+/// to something that exists relative to `dir` or one of `roots`
+fn rewrite_reference(
+    text: &str,
+    start: usize,
+    dir: &Path,
+    roots: &[PathBuf],
+    rename: &impl Fn(&Path) -> Option<PathBuf>,
+) -> Vec<(Range<usize>, String)> {
+    let Some((file, start)) = reference(text, start) else {
+        return Vec::new();
+    };
+    let bases = if file.len() < text.len() {
+        // `/Images/Logo.png` is only meaningful against a root
+        roots.iter().map(PathBuf::as_path).collect()
+    } else {
+        std::iter::once(dir)
+            .chain(roots.iter().map(PathBuf::as_path))
+            .collect::<Vec<_>>()
+    };
+    match bases.into_iter().find(|base| base.join(file).exists()) {
+        Some(base) => rewrite_file_path(base, file, start, &[], rename),
+        None => Vec::new(),
+    }
+}
+
+/// The path part of a string that could be a file reference, with where it
+/// starts. It needs a `/` or an extension, so bare words like `"Lib"`
+/// aren't taken for directories, and mustn't be a URL or a template.
+fn reference(text: &str, start: usize) -> Option<(&str, usize)> {
+    if text.contains("://") || text.contains(['$', '{', '\\']) {
+        return None;
+    }
+    let file = text.trim_start_matches('/');
+    let looks_like_path =
+        file.contains('/') || file.trim_start_matches('.').contains('.');
+    (looks_like_path && !file.is_empty())
+        .then_some((file, start + text.len() - file.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::converted_path;
+    use anyhow::Result;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_verified_string_refs() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("Tests/Fixtures"))?;
+        fs::create_dir_all(root.join("Public/Images"))?;
+        fs::write(root.join("Tests/Fixtures/UserList.json"), "")?;
+        fs::write(root.join("Public/Images/HeroBg.png"), "")?;
+
+        let content = r#"
+load('./Fixtures/UserList.json');
+{ "path": "/Images/HeroBg.png", "label": "Fixtures/Missing.json" }
+title = "See Fixtures"; url = "https://x.dev/Fixtures/UserList.json"
+"#;
+        let roots = vec![root.join("Public")];
+        let (new_content, changes) = rewrite_string_refs(
+            &root.join("Tests/routes.yml"),
+            content,
+            &roots,
+            converted_path,
+        );
+        assert_eq!(
+            new_content,
+            r#"
+load('./fixtures/user-list.json');
+{ "path": "/images/hero-bg.png", "label": "Fixtures/Missing.json" }
+title = "See Fixtures"; url = "https://x.dev/Fixtures/UserList.json"
+"#
+        );
+        assert_eq!(changes, 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_verified_comment_refs() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src/Utils"))?;
+        fs::write(root.join("src/Utils/DateHelpers.ts"), "")?;
+
+        let content = "\
+// TODO: see ./Utils/DateHelpers.ts for details.
+/* Utils/DateHelpers.ts and ./Utils/Missing.ts */
+const s = './Utils/DateHelpers.ts'; // Utils
+# FIXME Utils/DateHelpers.ts
+";
+        let (new_content, changes) = rewrite_comment_refs(
+            &root.join("src/main.ts"),
+            content,
+            &[],
+            converted_path,
+        );
+        assert_eq!(
+            new_content,
+            "\
+// TODO: see ./utils/date-helpers.ts for details.
+/* utils/date-helpers.ts and ./Utils/Missing.ts */
+const s = './Utils/DateHelpers.ts'; // Utils
+# FIXME utils/date-helpers.ts
+"
+        );
+        assert_eq!(changes, 6);
+        Ok(())
+    }
+
+    #[test]
+    fn test_verified_html_template_refs() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src/Assets"))?;
+        fs::write(root.join("src/Assets/LogoIcon.svg"), "")?;
+
+        let content = r#"render() {
+  return html`<img src="./Assets/LogoIcon.svg" alt="Assets/LogoIcon.svg">
+    ${this.open ? html`<a href='Assets/LogoIcon.svg'>` : ''}
+    <img src="./Assets/Missing.svg"><img src="${this.icon}">`;
+}
+const link = '<a href="./Assets/LogoIcon.svg">';
+"#;
+        let (new_content, changes) = rewrite_html_template_refs(
+            &root.join("src/my-element.ts"),
+            content,
+            &[],
+            converted_path,
+        );
+        assert_eq!(
+            new_content,
+            content
+                .replacen("./Assets/LogoIcon", "./assets/logo-icon", 1)
+                .replace("'Assets/LogoIcon", "'assets/logo-icon")
+        );
+        assert_eq!(changes, 4);
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+use crate::plan::Plan;
+use crate::scanner::{
+    self, rewrite_config_paths, JsScanner, ScanOptions, Scanner,
+};
+use std::path::Path;
+
+/// Tailwind and PostCSS configs, for the `content` globs that tell Tailwind
+/// which files to read class names from and the plugins they load by path.
+/// A stale `content` glob matches nothing and Tailwind quietly drops every
+/// style, so these are rewritten along with the config's imports.
+pub struct TailwindScanner;
+
+impl Scanner for TailwindScanner {
+    fn matches(&self, path: &Path) -> bool {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let Some((stem, extension)) = name.rsplit_once('.') else {
+            return false;
+        };
+        let script =
+            matches!(extension, "js" | "cjs" | "mjs" | "ts" | "cts" | "mts");
+        match stem {
+            "tailwind.config" | "postcss.config" => script,
+            ".postcssrc" => script || extension == "json",
+            _ => name == ".postcssrc",
+        }
+    }
+
+    fn converts_unresolved(&self) -> bool {
+        true
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        scan: &ScanOptions,
+    ) -> (String, usize) {
+        let (content, paths) =
+            rewrite_config_paths(path, content, scanner::converted_path);
+        let (content, imports) = JsScanner.convert(path, &content, scan);
+        (content, paths + imports)
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        scan: &ScanOptions,
+    ) -> (String, usize) {
+        let (content, paths) =
+            rewrite_config_paths(path, content, |found| plan.target_of(found));
+        let (content, imports) =
+            JsScanner.follow_plan(path, &content, plan, scan);
+        (content, paths + imports)
+    }
+
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        scan: &ScanOptions,
+    ) -> Vec<String> {
+        JsScanner.orphans(path, content, scan)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_tailwind_config_paths() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("Src/Legacy"))?;
+        fs::create_dir_all(root.join("Plugins"))?;
+        fs::write(root.join("Plugins/BrandColors.js"), "")?;
+        fs::write(root.join("Plugins/Tokens.js"), "")?;
+        fs::write(root.join("Index.html"), "")?;
+
+        let content = r#"const brand = require('./Plugins/BrandColors');
+
+module.exports = {
+  content: [
+    './Index.html',
+    './Src/**/*.{html,svelte}',
+    "!./Src/Legacy/**",
+  ],
+  theme: { spacing: { sm: '0.5rem' }, fontFamily: { sans: ['Inter var'] } },
+  plugins: [brand, require('@tailwindcss/forms')],
+};
+"#;
+        let path = root.join("tailwind.config.cjs");
+        assert!(TailwindScanner.matches(&path));
+        let (new_content, changes) =
+            TailwindScanner.convert(&path, content, &ScanOptions::default());
+        assert_eq!(
+            new_content,
+            r#"const brand = require('./plugins/brand-colors');
+
+module.exports = {
+  content: [
+    './index.html',
+    './src/**/*.{html,svelte}',
+    "!./src/legacy/**",
+  ],
+  theme: { spacing: { sm: '0.5rem' }, fontFamily: { sans: ['Inter var'] } },
+  plugins: [brand, require('@tailwindcss/forms')],
+};
+"#
+        );
+        assert_eq!(changes, 6);
+
+        // PostCSS plugins named by path, as keys
+        let content = r#"{ "plugins": { "./Plugins/Tokens.js": {}, "autoprefixer": {} } }"#;
+        let (new_content, _) = TailwindScanner.convert(
+            &root.join(".postcssrc.json"),
+            content,
+            &ScanOptions::default(),
+        );
+        assert_eq!(
+            new_content,
+            r#"{ "plugins": { "./plugins/tokens.js": {}, "autoprefixer": {} } }"#
+        );
+        Ok(())
+    }
+}
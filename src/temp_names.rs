@@ -0,0 +1,257 @@
+use anyhow::{Context, Result};
+use std::collections::hash_map::RandomState;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use walkdir::WalkDir;
+
+/// Every temporary name starts with this, so walks skip them and `kebabify
+/// cleanup` can find the ones a crashed run left behind
+pub const PREFIX: &str = ".kebabify-tmp-";
+
+/// What a temporary name is for, which decides how a leftover is cleaned up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// A file or directory halfway through a two-step rename, holding its
+    /// only copy: restored to its name
+    Rename,
+    /// New content not yet renamed over the file it replaces: deleted
+    Write,
+    /// A scratch directory for probing the filesystem: deleted
+    Probe,
+    /// The name a `Rename` is restored to, next to it: deleted with it
+    Note,
+}
+
+impl Kind {
+    fn tag(self) -> &'static str {
+        match self {
+            Kind::Rename => "mv",
+            Kind::Write => "wr",
+            Kind::Probe => "probe",
+            Kind::Note => "nm",
+        }
+    }
+}
+
+/// Hands out temporary names for one run:
+/// `.kebabify-tmp-<kind>-<pid>-<random>-<hash>`. The process ID keeps
+/// concurrent runs apart and the random part keeps names from colliding
+/// with real files. The hash of the name it stands in for only tells
+/// leftovers apart at a glance; the name itself is left out, so a
+/// temporary name is no longer than about 50 bytes however long the real
+/// one is. The sequence comes from a seed, so a run can be reproduced with
+/// `--temp-seed`.
+#[derive(Debug)]
+pub struct TempNames {
+    seed: u64,
+    counter: AtomicU64,
+}
+
+impl Default for TempNames {
+    fn default() -> Self {
+        Self::new(RandomState::new().build_hasher().finish())
+    }
+}
+
+impl TempNames {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    /// A fresh temporary name for `name`, to use next to it
+    pub fn next(&self, kind: Kind, name: &OsStr) -> OsString {
+        let n = self.counter.fetch_add(1, Ordering::Relaxed);
+        let hash = name.as_encoded_bytes().iter().fold(
+            0xcbf2_9ce4_8422_2325_u64,
+            |hash, &byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(0x100_0000_01b3)
+            },
+        );
+        OsString::from(format!(
+            "{PREFIX}{}-{}-{:016x}-{:08x}",
+            kind.tag(),
+            std::process::id(),
+            splitmix64(self.seed.wrapping_add(n)),
+            hash as u32
+        ))
+    }
+}
+
+/// Writes down, next to the `Rename` temporary `temp`, the name `cleanup`
+/// gives it back if the run crashes before it's renamed again
+pub fn note_name(temp: &Path, name: &OsStr) -> io::Result<()> {
+    fs::write(note_for(temp), name.to_string_lossy().as_bytes())
+}
+
+/// Removes the note `note_name` wrote for `temp`, once it's renamed
+pub fn forget_name(temp: &Path) {
+    let _ = fs::remove_file(note_for(temp));
+}
+
+/// The note next to a `Rename` temporary: its name, tagged as a note
+fn note_for(temp: &Path) -> PathBuf {
+    let name = temp.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    let id = parse(name).map_or("", |(_, id)| id);
+    temp.with_file_name(format!("{PREFIX}{}-{}", Kind::Note.tag(), id))
+}
+
+/// One step of the SplitMix64 generator, which spreads consecutive inputs
+/// over the whole range
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Whether `name` is one of ours
+pub fn is_temporary(name: &OsStr) -> bool {
+    name.to_str().is_some_and(|name| name.starts_with(PREFIX))
+}
+
+/// The kind of a temporary name and what tells it apart from the others,
+/// `<pid>-<random>-<hash>`
+fn parse(name: &str) -> Option<(Kind, &str)> {
+    let (tag, id) = name.strip_prefix(PREFIX)?.split_once('-')?;
+    let kind = match tag {
+        "mv" => Kind::Rename,
+        "wr" => Kind::Write,
+        "probe" => Kind::Probe,
+        "nm" => Kind::Note,
+        _ => return None,
+    };
+    Some((kind, id))
+}
+
+/// What `cleanup` did with a leftover
+#[derive(Debug, PartialEq, Eq)]
+pub enum Cleaned {
+    Restored(PathBuf),
+    Deleted,
+    /// Restoring it would replace a file that's there again
+    Kept(String),
+}
+
+/// Cleans up the temporary names crashed runs left under `dir`. Leftover
+/// renames get back the name noted next to them, anything else is deleted.
+/// Mustn't run while kebabify is working on `dir`.
+pub fn cleanup(dir: &Path) -> Result<Vec<(PathBuf, Cleaned)>> {
+    let leftovers: Vec<PathBuf> = WalkDir::new(dir)
+        .min_depth(1)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| is_temporary(e.file_name()))
+        .map(|e| e.into_path())
+        .collect();
+
+    let mut cleaned = Vec::new();
+    // Deepest first, so a directory's leftovers are handled before it moves
+    for path in leftovers.into_iter().rev() {
+        let Some((kind, _)) =
+            path.file_name().and_then(|n| n.to_str()).and_then(parse)
+        else {
+            continue;
+        };
+        let outcome = match kind {
+            Kind::Rename => {
+                let note = note_for(&path);
+                let Ok(name) = fs::read_to_string(&note) else {
+                    let lost = "its name wasn't noted, rename it by hand";
+                    cleaned.push((path, Cleaned::Kept(lost.to_string())));
+                    continue;
+                };
+                let original = path.with_file_name(name);
+                if fs::symlink_metadata(&original).is_ok() {
+                    Cleaned::Kept(format!(
+                        "{} exists again",
+                        original.display()
+                    ))
+                } else {
+                    fs::rename(&path, &original).with_context(|| {
+                        format!("Failed to restore {}", path.display())
+                    })?;
+                    forget_name(&path);
+                    Cleaned::Restored(original)
+                }
+            }
+            // Read with its rename, and gone once that's restored
+            Kind::Note if note_is_needed(&path) => continue,
+            Kind::Note if fs::symlink_metadata(&path).is_err() => continue,
+            Kind::Write | Kind::Probe | Kind::Note => {
+                match fs::symlink_metadata(&path)?.is_dir() {
+                    true => fs::remove_dir_all(&path),
+                    false => fs::remove_file(&path),
+                }
+                .with_context(|| {
+                    format!("Failed to delete {}", path.display())
+                })?;
+                Cleaned::Deleted
+            }
+        };
+        cleaned.push((path, outcome));
+    }
+    Ok(cleaned)
+}
+
+/// Whether the note at `path` still belongs to a leftover rename
+fn note_is_needed(path: &Path) -> bool {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    let Some((_, id)) = parse(name) else {
+        return false;
+    };
+    let rename = format!("{PREFIX}{}-{}", Kind::Rename.tag(), id);
+    fs::symlink_metadata(path.with_file_name(rename)).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_temporary_names_and_cleanup() -> Result<()> {
+        let names = TempNames::new(7);
+        let first = names.next(Kind::Rename, OsStr::new("UserCard.ts"));
+        assert!(is_temporary(&first));
+        assert_ne!(first, names.next(Kind::Rename, OsStr::new("UserCard.ts")));
+        assert_eq!(
+            TempNames::new(7).next(Kind::Rename, OsStr::new("UserCard.ts")),
+            first
+        );
+        let long = "A".repeat(250);
+        let short = names.next(Kind::Rename, OsStr::new(&long));
+        assert_eq!(short.len(), first.len());
+
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir(root.join("Lib"))?;
+        let moved = root.join("Lib").join(&first);
+        fs::write(&moved, "card")?;
+        note_name(&moved, OsStr::new("UserCard.ts"))?;
+        let unnoted = root.join(&short);
+        fs::write(&unnoted, "lost")?;
+        let written = names.next(Kind::Write, OsStr::new("main.ts"));
+        fs::write(root.join(&written), "half")?;
+        fs::write(root.join("main.ts"), "whole")?;
+
+        let cleaned = cleanup(root)?;
+        assert_eq!(cleaned.len(), 3);
+        assert!(cleaned.contains(&(
+            moved.clone(),
+            Cleaned::Restored(root.join("Lib/UserCard.ts"))
+        )));
+        assert_eq!(fs::read_to_string(root.join("Lib/UserCard.ts"))?, "card");
+        assert!(!note_for(&moved).exists());
+        assert!(unnoted.exists());
+        assert!(!root.join(written).exists());
+        assert_eq!(fs::read_to_string(root.join("main.ts"))?, "whole");
+        Ok(())
+    }
+}
@@ -0,0 +1,144 @@
+use crate::plan::Plan;
+use crate::scanner::{self, rewrite_file_path, splice, ScanOptions, Scanner};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// Terraform and OpenTofu configurations, for the local modules they use
+/// and the files they read through `path.module`
+pub struct TerraformScanner;
+
+impl Scanner for TerraformScanner {
+    fn matches(&self, path: &Path) -> bool {
+        path.extension().is_some_and(|e| e == "tf")
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_paths(path, content, scanner::converted_path)
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_paths(path, content, |found| plan.target_of(found))
+    }
+
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> Vec<String> {
+        let dir = path.parent().unwrap_or(Path::new(""));
+        local_paths(content)
+            .into_iter()
+            // Up to an interpolation, it's only the start of a path
+            .filter(|(file, start)| {
+                !content[start + file.len()..].starts_with("${")
+            })
+            .filter(|(file, _)| scanner::leads_nowhere(dir, file, &[]))
+            .map(|(file, _)| file.to_string())
+            .collect()
+    }
+}
+
+/// Rewrites local module sources, like `source = "./Modules/NetworkStack"`,
+/// and strings starting with `${path.module}/`, like
+/// `file("${path.module}/Scripts/Init.sh")`, relative to the file's
+/// directory. Registry and Git sources don't start with `./` or `../` and
+/// are left alone.
+fn rewrite_paths(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut edits = Vec::new();
+    for (file, start) in local_paths(content) {
+        edits.extend(rewrite_file_path(dir, file, start, &[], &rename));
+    }
+    splice(content, edits)
+}
+
+/// The local module sources and `path.module` paths in a configuration,
+/// up to any interpolation, with where each starts
+fn local_paths(content: &str) -> Vec<(&str, usize)> {
+    let source =
+        Regex::new(r#"(?m)^[ \t]*source[ \t]*=[ \t]*"(\.{1,2}/[^"\n]*)""#)
+            .unwrap();
+    let module_path = Regex::new(r#""\$\{path\.module\}/([^"\n]*)""#).unwrap();
+    let found = source
+        .captures_iter(content)
+        .chain(module_path.captures_iter(content));
+    found
+        .map(|caps| {
+            let file = caps.get(1).unwrap();
+            // Interpolated parts are only known to Terraform
+            let text = file.as_str().split("${").next().unwrap_or_default();
+            (text, file.start())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_terraform_paths() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("Modules/NetworkStack"))?;
+        fs::create_dir_all(root.join("Scripts"))?;
+        fs::write(root.join("Scripts/InitNode.sh"), "")?;
+
+        let content = r#"
+module "network" {
+  source = "./Modules/NetworkStack"
+}
+module "vpc" {
+  source = "terraform-aws-modules/vpc/aws"
+}
+resource "aws_instance" "web" {
+  user_data = file("${path.module}/Scripts/InitNode.sh")
+  tags = templatefile("${path.module}/Scripts/${var.name}.tpl", {})
+}
+"#;
+        let path = root.join("main.tf");
+        assert!(TerraformScanner.matches(&path));
+        let (new_content, changes) =
+            TerraformScanner.convert(&path, content, &ScanOptions::default());
+        assert_eq!(
+            new_content,
+            content
+                .replace("Modules/NetworkStack", "modules/network-stack")
+                .replace("Scripts/", "scripts/")
+                .replace("InitNode", "init-node")
+        );
+        assert_eq!(changes, 5);
+
+        let content = "\
+module \"db\" {
+  source = \"./Modules/Database\"
+}
+locals {
+  script = file(\"${path.module}/Scripts/Init-${var.env}.sh\")
+}
+";
+        let orphans =
+            TerraformScanner.orphans(&path, content, &ScanOptions::default());
+        assert_eq!(orphans, ["./Modules/Database"]);
+        Ok(())
+    }
+}
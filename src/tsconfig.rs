@@ -0,0 +1,271 @@
+use crate::aliases::strip_jsonc;
+use crate::plan::Plan;
+use crate::scanner::{
+    self, rewrite_config_paths, rewrite_file_path, splice, JsScanner,
+    ScanOptions, Scanner,
+};
+use regex::Regex;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// `tsconfig.json` and `jsconfig.json`, including variants like
+/// `tsconfig.app.json`, for their `paths` aliases, `baseUrl`, `extends` and
+/// `include` globs. An alias whose directory is renamed and left pointing
+/// at the old name breaks every import made through it.
+pub struct TsconfigScanner;
+
+impl Scanner for TsconfigScanner {
+    fn matches(&self, path: &Path) -> bool {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        ["tsconfig", "jsconfig"].iter().any(|stem| {
+            name.strip_prefix(stem)
+                .and_then(|rest| rest.strip_suffix(".json"))
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+        })
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_paths(path, content, scanner::converted_path)
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        _scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_paths(path, content, |found| plan.target_of(found))
+    }
+
+    /// The relative `extends`, `files`, `references` and `paths` targets
+    /// that aren't there. Other values, like `outDir` or `include` globs,
+    /// can name what doesn't exist yet.
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        _scan: &ScanOptions,
+    ) -> Vec<String> {
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let Ok(config) = serde_json::from_str::<Value>(&strip_jsonc(content))
+        else {
+            return Vec::new();
+        };
+        let options = config.get("compilerOptions");
+        let base_url = options
+            .and_then(|options| options.get("baseUrl")?.as_str())
+            .map_or(dir.to_path_buf(), |base| dir.join(base));
+
+        let mut found: Vec<(&str, &Path)> = Vec::new();
+        let extends = match config.get("extends") {
+            Some(Value::Array(all)) => all.iter().collect(),
+            Some(one) => vec![one],
+            None => Vec::new(),
+        };
+        let relative_extends = extends
+            .into_iter()
+            .filter_map(Value::as_str)
+            .filter(|file| file.starts_with('.'));
+        found.extend(relative_extends.map(|file| (file, dir)));
+        let files = config.get("files").and_then(Value::as_array);
+        let files = files.into_iter().flatten().filter_map(Value::as_str);
+        found.extend(files.map(|file| (file, dir)));
+        let references = config.get("references").and_then(Value::as_array);
+        let references = references
+            .into_iter()
+            .flatten()
+            .filter_map(|reference| reference.get("path")?.as_str());
+        found.extend(references.map(|file| (file, dir)));
+        let targets = options
+            .and_then(|options| options.get("paths")?.as_object())
+            .into_iter()
+            .flat_map(|paths| paths.values())
+            .filter_map(Value::as_array)
+            .flatten()
+            .filter_map(Value::as_str);
+        found.extend(targets.map(|file| (file, base_url.as_path())));
+
+        found
+            .into_iter()
+            .filter(|(file, base)| {
+                // `Components/*` maps onto the directory
+                let file = file.trim_end_matches('*').trim_end_matches('/');
+                scanner::leads_nowhere(base, file, &["json"])
+            })
+            .map(|(file, _)| file.to_string())
+            .collect()
+    }
+}
+
+/// Rewrites the string values in a tsconfig that are paths or globs. Most
+/// are relative to the file, but `paths` targets are relative to
+/// `baseUrl`, so a value that doesn't lead anywhere from the file is tried
+/// from there. Keys, like `"@components/*"`, are import prefixes and are
+/// left alone.
+fn rewrite_paths(
+    path: &Path,
+    content: &str,
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let base_url = serde_json::from_str::<Value>(&strip_jsonc(content))
+        .ok()
+        .and_then(|config| {
+            let base = config.get("compilerOptions")?.get("baseUrl")?;
+            base.as_str().map(|base| dir.join(base))
+        })
+        .unwrap_or(dir.to_path_buf());
+    let value = Regex::new(r#""((?:[^"\\\n]|\\.)*)"(\s*:)?"#).unwrap();
+
+    let mut edits = Vec::new();
+    for caps in value.captures_iter(content) {
+        let text = caps.get(1).unwrap();
+        let file = text.as_str();
+        if caps.get(2).is_some() || !file.contains(['/', '.']) {
+            continue;
+        }
+        let mut found =
+            rewrite_file_path(dir, file, text.start(), &[], &rename);
+        let leads_nowhere = !dir.join(first_segment(file)).exists();
+        if found.is_empty() && leads_nowhere {
+            found =
+                rewrite_file_path(&base_url, file, text.start(), &[], &rename);
+        }
+        edits.extend(found);
+    }
+    splice(content, edits)
+}
+
+/// The part of `file` before its first `/`, after any `./`
+fn first_segment(file: &str) -> &str {
+    let file = file.trim_start_matches("./");
+    file.split('/').next().unwrap_or(file)
+}
+
+/// Vite and Vitest configs, for the directories their `resolve.alias`
+/// entries point at, like `path.resolve(__dirname, 'src/Components')`.
+/// Their own imports are left to `JsScanner`.
+pub struct ViteScanner;
+
+impl Scanner for ViteScanner {
+    fn matches(&self, path: &Path) -> bool {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let Some((stem, extension)) = name.rsplit_once('.') else {
+            return false;
+        };
+        matches!(stem, "vite.config" | "vitest.config")
+            && matches!(extension, "js" | "cjs" | "mjs" | "ts" | "cts" | "mts")
+    }
+
+    fn converts_unresolved(&self) -> bool {
+        true
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        scan: &ScanOptions,
+    ) -> (String, usize) {
+        let (content, paths) =
+            rewrite_config_paths(path, content, scanner::converted_path);
+        let (content, imports) = JsScanner.convert(path, &content, scan);
+        (content, paths + imports)
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        scan: &ScanOptions,
+    ) -> (String, usize) {
+        let (content, paths) =
+            rewrite_config_paths(path, content, |found| plan.target_of(found));
+        let (content, imports) =
+            JsScanner.follow_plan(path, &content, plan, scan);
+        (content, paths + imports)
+    }
+
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        scan: &ScanOptions,
+    ) -> Vec<String> {
+        JsScanner.orphans(path, content, scan)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_tsconfig_and_vite_aliases() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("Src/Components"))?;
+        fs::write(root.join("tsconfig.Base.json"), "{}")?;
+
+        let tsconfig = r#"{
+  "extends": "./tsconfig.Base.json",
+  "compilerOptions": {
+    "baseUrl": "./Src",
+    "paths": {
+      "@components/*": ["Components/*"],
+      "Components/*": ["Components/*"],
+    },
+  },
+  "include": ["Src/**/*.ts"],
+}"#;
+        let path = root.join("tsconfig.json");
+        assert!(TsconfigScanner.matches(&path));
+        assert!(TsconfigScanner.matches(&root.join("tsconfig.app.json")));
+        assert!(!TsconfigScanner.matches(&root.join("tsconfigs.json")));
+        let (new_content, changes) =
+            TsconfigScanner.convert(&path, tsconfig, &ScanOptions::default());
+        assert_eq!(
+            new_content,
+            tsconfig
+                .replace("tsconfig.Base", "tsconfig.base")
+                .replace("./Src", "./src")
+                .replace(r#"["Components/*"]"#, r#"["components/*"]"#)
+                .replace("Src/**", "src/**")
+        );
+        assert_eq!(changes, 5);
+        let orphans = TsconfigScanner.orphans(
+            &path,
+            &tsconfig.replace("\"Components/*\"]", "\"Gone/*\"]"),
+            &ScanOptions::default(),
+        );
+        assert_eq!(orphans, ["Gone/*", "Gone/*"]);
+
+        let vite = "\
+import path from 'node:path';
+export default {
+  resolve: {
+    alias: { '@components': path.resolve(__dirname, 'Src/Components') },
+  },
+};
+";
+        let path = root.join("vite.config.ts");
+        assert!(ViteScanner.matches(&path));
+        let (new_content, _) =
+            ViteScanner.convert(&path, vite, &ScanOptions::default());
+        assert_eq!(
+            new_content,
+            vite.replace("'Src/Components'", "'src/components'")
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,330 @@
+use crate::display::PathDisplay;
+use crate::plan::Plan;
+use crate::report::Report;
+use crate::scanner::{self, ScanOptions};
+use crate::{encoding, import_regex, resolve, Options};
+use anyhow::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A planned rename the user can switch on or off
+struct Entry {
+    from: PathBuf,
+    new_name: OsString,
+    depth: usize,
+    enabled: bool,
+}
+
+/// An import line that changes, with the files its imports point at
+struct LineChange {
+    file: PathBuf,
+    line: usize,
+    old: String,
+    new: String,
+    targets: Vec<PathBuf>,
+}
+
+/// The plan under review, plus the source files it may edit
+struct Review {
+    entries: Vec<Entry>,
+    sources: Vec<(PathBuf, String)>,
+    changes: Vec<LineChange>,
+    state: ListState,
+    display: PathDisplay,
+    scan: ScanOptions,
+}
+
+/// Shows the rename plan for `dir` with the import changes each rename
+/// causes, lets the user switch individual renames off, and applies what's
+/// left if they confirm. Returns the renames it applied.
+pub fn run(dir: &Path, opts: &Options, report: &mut Report) -> Result<Plan> {
+    let plan = crate::plan_directory(dir, opts, report)?;
+    if plan.renames.is_empty() {
+        println!("Nothing to rename");
+        return Ok(plan);
+    }
+
+    let mut sources = Vec::new();
+    for entry in crate::source_files(dir, opts, report)? {
+        let content = fs::read(entry.path())
+            .ok()
+            .and_then(|bytes| encoding::decode(&bytes));
+        if let Some((content, _)) = content {
+            sources.push((entry.path().to_path_buf(), content));
+        }
+    }
+    let mut review = Review::new(dir, &plan, sources, opts.scan.clone());
+    review.display = opts.display.clone();
+
+    let mut terminal = ratatui::init();
+    let confirmed = review.run(&mut terminal);
+    ratatui::restore();
+
+    if !confirmed? {
+        println!("Nothing was changed");
+        return Ok(Plan::default());
+    }
+    let plan = review.plan();
+    crate::apply_plan(dir, &plan, opts, report)?;
+    Ok(plan)
+}
+
+impl Review {
+    fn new(
+        dir: &Path,
+        plan: &Plan,
+        sources: Vec<(PathBuf, String)>,
+        scan: ScanOptions,
+    ) -> Self {
+        let entries = plan
+            .renames
+            .iter()
+            .filter_map(|r| {
+                Some(Entry {
+                    from: r.from.clone(),
+                    new_name: r.to.file_name()?.to_os_string(),
+                    depth: crate::relative_to(&r.from, dir)
+                        .components()
+                        .count(),
+                    enabled: true,
+                })
+            })
+            .collect();
+        let mut review = Self {
+            entries,
+            sources,
+            changes: Vec::new(),
+            state: ListState::default().with_selected(Some(0)),
+            display: PathDisplay::default(),
+            scan,
+        };
+        review.refresh();
+        review
+    }
+
+    /// The enabled renames, with targets recomputed so a switched-off
+    /// directory keeps its name in its children's paths
+    fn plan(&self) -> Plan {
+        let mut plan = Plan::default();
+        for entry in self.entries.iter().filter(|e| e.enabled) {
+            let parent = entry.from.parent().unwrap_or(Path::new(""));
+            let final_parent = plan.target_of(parent).unwrap_or(parent.into());
+            plan.push(entry.from.clone(), final_parent.join(&entry.new_name));
+        }
+        plan
+    }
+
+    /// Recomputes the import changes for the current selection of renames
+    fn refresh(&mut self) {
+        let plan = self.plan();
+        self.changes.clear();
+
+        for (path, content) in &self.sources {
+            let old_dir = path.parent().unwrap_or(Path::new("."));
+            let new_path = plan.target_of(path).unwrap_or(path.clone());
+            let new_dir = new_path.parent().unwrap_or(Path::new("."));
+            let (new_content, changed) =
+                scanner::follow_plan_in_file(path, content, &plan, &self.scan);
+            if changed == 0 {
+                continue;
+            }
+
+            for (line, (old, new)) in
+                content.lines().zip(new_content.lines()).enumerate()
+            {
+                if old == new {
+                    continue;
+                }
+                let targets = import_regex()
+                    .captures_iter(old)
+                    .filter_map(|caps| {
+                        let spec = &caps[2];
+                        resolve::rewrite_specifier(
+                            spec, old_dir, new_dir, &plan,
+                        )?;
+                        let resolved =
+                            resolve::resolve_specifier(old_dir, spec)?;
+                        Some(resolved.path().to_path_buf())
+                    })
+                    .collect();
+                self.changes.push(LineChange {
+                    file: path.clone(),
+                    line: line + 1,
+                    old: old.to_string(),
+                    new: new.to_string(),
+                    targets,
+                });
+            }
+        }
+    }
+
+    /// Import changes in or pointing into the selected entry
+    fn related_changes(&self) -> impl Iterator<Item = &LineChange> {
+        let selected = self.state.selected().and_then(|i| self.entries.get(i));
+        self.changes.iter().filter(move |change| {
+            selected.is_some_and(|entry| {
+                change.file.starts_with(&entry.from)
+                    || change.targets.iter().any(|t| t.starts_with(&entry.from))
+            })
+        })
+    }
+
+    /// Event loop. Returns whether the user chose to apply the plan.
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<bool> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+                KeyCode::Enter => return Ok(true),
+                KeyCode::Down | KeyCode::Char('j') => self.state.select_next(),
+                KeyCode::Up | KeyCode::Char('k') => {
+                    self.state.select_previous()
+                }
+                KeyCode::Char(' ') => {
+                    if let Some(i) = self.state.selected() {
+                        if let Some(entry) = self.entries.get_mut(i) {
+                            entry.enabled = !entry.enabled;
+                        }
+                        self.refresh();
+                    }
+                }
+                KeyCode::Char('a') => {
+                    let enable = self.entries.iter().any(|e| !e.enabled);
+                    for entry in &mut self.entries {
+                        entry.enabled = enable;
+                    }
+                    self.refresh();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, help] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)])
+                .areas(frame.area());
+        let [left, right] = Layout::horizontal([
+            Constraint::Percentage(45),
+            Constraint::Percentage(55),
+        ])
+        .areas(main);
+
+        let items: Vec<ListItem> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let name = entry.from.file_name().unwrap_or_default();
+                let style = if entry.enabled {
+                    Style::default()
+                } else {
+                    Style::default().fg(Color::DarkGray)
+                };
+                ListItem::new(Line::from(vec![
+                    Span::raw(if entry.enabled { "[x] " } else { "[ ] " }),
+                    Span::raw("  ".repeat(entry.depth.saturating_sub(1))),
+                    Span::styled(name.to_string_lossy(), style),
+                    Span::styled(" -> ", Style::default().fg(Color::DarkGray)),
+                    Span::styled(entry.new_name.to_string_lossy(), style),
+                ]))
+            })
+            .collect();
+        let enabled = self.entries.iter().filter(|e| e.enabled).count();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(format!(
+                " Renames ({}/{}) ",
+                enabled,
+                self.entries.len()
+            )))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, left, &mut self.state);
+
+        let mut lines = Vec::new();
+        let mut last_file = None;
+        for change in self.related_changes() {
+            if last_file != Some(&change.file) {
+                lines.push(Line::styled(
+                    self.display.show(&change.file),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ));
+                last_file = Some(&change.file);
+            }
+            lines.push(Line::styled(
+                format!("{:>5} - {}", change.line, change.old.trim()),
+                Style::default().fg(Color::Red),
+            ));
+            lines.push(Line::styled(
+                format!("{:>5} + {}", change.line, change.new.trim()),
+                Style::default().fg(Color::Green),
+            ));
+        }
+        if lines.is_empty() {
+            lines.push(Line::raw("No import changes"));
+        }
+        let diff = Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Import changes "),
+        );
+        frame.render_widget(diff, right);
+
+        frame.render_widget(
+            Paragraph::new(
+                "↑/↓ move  space toggle  a toggle all  enter apply  q quit",
+            )
+            .style(Style::default().fg(Color::DarkGray)),
+            help,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_disabled_renames() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir(root.join("Lib"))?;
+        fs::write(root.join("Lib/Button.ts"), "")?;
+        let main = root.join("main.ts");
+        let content = "import b from './Lib/Button';\n".to_string();
+
+        let mut plan = Plan::default();
+        plan.push(root.join("Lib"), root.join("lib"));
+        plan.push(root.join("Lib/Button.ts"), root.join("lib/button.ts"));
+        let mut review = Review::new(
+            root,
+            &plan,
+            vec![(main, content)],
+            ScanOptions::default(),
+        );
+        assert_eq!(review.changes.len(), 1);
+        assert_eq!(review.changes[0].new, "import b from './lib/button';");
+        assert_eq!(review.related_changes().count(), 1);
+
+        review.entries[0].enabled = false;
+        review.refresh();
+        assert_eq!(
+            review.plan().target_of(&root.join("Lib/Button.ts")),
+            Some(root.join("Lib/button.ts"))
+        );
+        assert_eq!(review.changes[0].new, "import b from './Lib/button';");
+        Ok(())
+    }
+}
@@ -0,0 +1,182 @@
+use crate::plan::Plan;
+use crate::report::{Report, WarningKind};
+use crate::resolve::normalize;
+use crate::{hooks, mapping};
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// How many paths go to one `lsof` call, to stay under argument limits
+const LSOF_BATCH: usize = 500;
+
+/// Checks that can veto renames before a plan is applied, for policies
+/// kebabify can't know about
+#[derive(Debug, Default)]
+pub struct Validators {
+    /// `--veto-cmd` commands
+    pub commands: Vec<String>,
+    /// Whether files another process has open keep their names
+    pub open_files: bool,
+}
+
+impl Validators {
+    /// Asks every validator about `plan`, before anything changes, and
+    /// drops the renames they veto with a warning for each. Returns whether
+    /// any were.
+    pub fn review(
+        &self,
+        dir: &Path,
+        plan: &mut Plan,
+        report: &mut Report,
+    ) -> Result<bool> {
+        if plan.renames.is_empty() {
+            return Ok(false);
+        }
+        let mut vetoed = Vec::new();
+        if self.open_files {
+            vetoed.extend(open_files(plan)?);
+        }
+        for command in &self.commands {
+            vetoed.extend(run_command(command, dir, plan)?);
+        }
+        let mut any = false;
+        for (path, reason) in vetoed {
+            if plan.remove(&path) {
+                tracing::info!(path = %path.display(), %reason, "vetoed");
+                report.warn(&path, WarningKind::Vetoed, reason);
+                any = true;
+            }
+        }
+        Ok(any)
+    }
+}
+
+/// Runs `command` in `dir` with the plan as CSV, like `--mapping` takes, on
+/// its stdin. Each line it prints is a path to keep, relative to `dir`,
+/// optionally followed by a tab and the reason.
+fn run_command(
+    command: &str,
+    dir: &Path,
+    plan: &Plan,
+) -> Result<Vec<(PathBuf, String)>> {
+    let mut csv = Vec::new();
+    mapping::write_mapping(plan, dir, &mut csv)?;
+    let mut child = hooks::shell(command)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run: {}", command))?;
+    // Written from another thread so a command that prints before it has
+    // read everything can't block on a full pipe
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let writer = std::thread::spawn(move || stdin.write_all(&csv));
+    let output = child
+        .wait_with_output()
+        .with_context(|| format!("Failed to run: {}", command))?;
+    if let Ok(Err(err)) = writer.join() {
+        // A command that doesn't need the plan may exit without reading it
+        if err.kind() != std::io::ErrorKind::BrokenPipe {
+            return Err(err).context("Failed to send the plan to --veto-cmd");
+        }
+    }
+    if !output.status.success() {
+        bail!("Validator failed ({}): {}", output.status, command);
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (path, reason) = match line.split_once('\t') {
+                Some((path, reason)) => (path, reason.trim().to_string()),
+                None => (line, format!("vetoed by `{}`", command)),
+            };
+            (normalize(&dir.join(path.trim())), reason)
+        })
+        .collect())
+}
+
+/// The renamed files and directories another process has open, according
+/// to `lsof`
+fn open_files(plan: &Plan) -> Result<Vec<(PathBuf, String)>> {
+    if cfg!(windows) {
+        bail!("--veto-open-files needs lsof, which Windows doesn't have");
+    }
+    let by_real_path: HashMap<PathBuf, &Path> = plan
+        .renames
+        .iter()
+        .filter_map(|r| Some((fs::canonicalize(&r.from).ok()?, &*r.from)))
+        .collect();
+    let sources: Vec<&Path> = by_real_path.values().copied().collect();
+    let mut open = Vec::new();
+    for batch in sources.chunks(LSOF_BATCH) {
+        // lsof exits with 1 when none of the files are open, so only its
+        // output counts
+        let output = Command::new("lsof")
+            .args(["-F", "n", "--"])
+            .args(batch)
+            .stderr(Stdio::null())
+            .output()
+            .context("--veto-open-files needs lsof, which couldn't be run")?;
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            let Some(name) = line.strip_prefix('n') else {
+                continue;
+            };
+            if let Some(from) = by_real_path.get(Path::new(name)) {
+                open.push((
+                    from.to_path_buf(),
+                    "open in another process".to_string(),
+                ));
+            }
+        }
+    }
+    Ok(open)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_veto_command() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir(root.join("Legacy"))?;
+        fs::write(root.join("Legacy/OldApi.ts"), "")?;
+        fs::write(root.join("UserCard.ts"), "")?;
+        let mut plan = Plan::default();
+        plan.push(root.join("Legacy"), root.join("legacy"));
+        plan.push(
+            root.join("Legacy/OldApi.ts"),
+            root.join("legacy/old-api.ts"),
+        );
+        plan.push(root.join("UserCard.ts"), root.join("user-card.ts"));
+
+        let validators = Validators {
+            commands: vec![
+                "grep -q old_path && printf 'Legacy\\tfrozen\\n'".to_string()
+            ],
+            open_files: false,
+        };
+        let mut report = Report::default();
+        assert!(validators.review(root, &mut plan, &mut report)?);
+        assert_eq!(
+            plan.target_of(&root.join("Legacy/OldApi.ts")),
+            Some(root.join("Legacy/old-api.ts"))
+        );
+        assert_eq!(plan.renames.len(), 2);
+        assert_eq!(report.warnings[0].message, "frozen");
+
+        let failing = Validators {
+            commands: vec!["exit 3".to_string()],
+            open_files: false,
+        };
+        assert!(failing.review(root, &mut plan, &mut report).is_err());
+        Ok(())
+    }
+}
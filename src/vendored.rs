@@ -0,0 +1,108 @@
+use crate::filter::compile_glob;
+use anyhow::Result;
+use globset::{GlobSet, GlobSetBuilder};
+use std::path::Path;
+
+/// Directory names that hold someone else's code by convention
+const VENDOR_DIRS: [&str; 4] =
+    ["vendor", "third_party", "third-party", "bower_components"];
+
+/// Directory names that never hold the project's own files, skipped even
+/// with `--include-vendored`
+const NEVER_WALKED: [(&str, &str); 2] = [
+    (".git", "Git's own data"),
+    ("node_modules", "installed packages"),
+];
+
+/// Why the entry at `path` is never walked, if it's one of `NEVER_WALKED`.
+/// Renaming inside `.git` breaks the repository, and packages in
+/// `node_modules` are reinstalled under their own names.
+pub fn never_walked(path: &Path) -> Option<&'static str> {
+    let name = path.file_name()?;
+    NEVER_WALKED
+        .iter()
+        .find(|(never, _)| name == *never)
+        .map(|(_, reason)| *reason)
+}
+
+/// Spots vendored and third-party subtrees, which are skipped: renaming
+/// files in them breaks the next update from upstream.
+#[derive(Debug, Default)]
+pub struct Vendored {
+    /// Vendored directories to process anyway, from `--include-vendored`
+    keep: Option<GlobSet>,
+}
+
+impl Vendored {
+    pub fn new(include: &[String]) -> Result<Self> {
+        if include.is_empty() {
+            return Ok(Self::default());
+        }
+        let mut builder = GlobSetBuilder::new();
+        for pattern in include {
+            builder.add(compile_glob(pattern)?);
+        }
+        Ok(Self {
+            keep: Some(builder.build()?),
+        })
+    }
+
+    /// Why the directory at `path`, `rel_path` from the root, looks
+    /// vendored, unless it's been included anyway
+    pub fn reason(&self, path: &Path, rel_path: &Path) -> Option<&'static str> {
+        let name = path.file_name()?.to_str()?;
+        let git = path.join(".git");
+        let reason = if VENDOR_DIRS.contains(&name) {
+            "vendored code"
+        } else if git.is_file() {
+            "a Git submodule"
+        } else if git.is_dir() {
+            "a nested Git repository"
+        } else {
+            return None;
+        };
+        match &self.keep {
+            Some(keep) if keep.is_match(rel_path) => None,
+            _ => Some(reason),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_vendored_reasons() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("Libs/Upstream/.git"))?;
+        fs::create_dir_all(root.join("Libs/Module"))?;
+        fs::write(root.join("Libs/Module/.git"), "gitdir: ../../.git/modules")?;
+        fs::create_dir_all(root.join("web/vendor"))?;
+        fs::create_dir_all(root.join("Src"))?;
+
+        let vendored = Vendored::default();
+        let reason =
+            |rel: &str| vendored.reason(&root.join(rel), Path::new(rel));
+        assert_eq!(reason("Libs/Upstream"), Some("a nested Git repository"));
+        assert_eq!(reason("Libs/Module"), Some("a Git submodule"));
+        assert_eq!(reason("web/vendor"), Some("vendored code"));
+        assert_eq!(reason("Src"), None);
+        assert_eq!(
+            never_walked(&root.join("Libs/Upstream/.git")),
+            Some("Git's own data")
+        );
+        assert_eq!(never_walked(&root.join("Src")), None);
+
+        let vendored = Vendored::new(&["Libs/Module".to_string()])?;
+        assert_eq!(
+            vendored
+                .reason(&root.join("Libs/Module"), Path::new("Libs/Module")),
+            None
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,282 @@
+use crate::aliases::{strip_jsonc, Alias};
+use crate::plan::Plan;
+use crate::report::Report;
+use crate::resolve::normalize;
+use crate::{relative_to, Options};
+use anyhow::{Context, Result};
+use globset::Glob;
+use serde_json::Value;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use walkdir::{DirEntry, WalkDir};
+
+/// One package of a workspace, with the name other packages import it by
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Member {
+    pub name: Option<String>,
+    pub dir: PathBuf,
+}
+
+/// The member packages of the workspace at `root`, from `manifest` if
+/// given, otherwise from `pnpm-workspace.yaml` or the `workspaces` of
+/// `package.json`. A manifest lists one directory or glob per line,
+/// relative to the manifest, and may name directories outside `root`.
+pub fn members(root: &Path, manifest: Option<&Path>) -> Result<Vec<Member>> {
+    let (base, patterns) = match manifest {
+        Some(manifest) => {
+            let content = fs::read_to_string(manifest).with_context(|| {
+                format!("Failed to read {}", manifest.display())
+            })?;
+            let patterns = content
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(String::from)
+                .collect();
+            (manifest.parent().unwrap_or(Path::new("")), patterns)
+        }
+        None => (root, detect(root)?),
+    };
+    if patterns.is_empty() {
+        anyhow::bail!(
+            "No workspace packages found in {} (add pnpm-workspace.yaml, \
+             \"workspaces\" to package.json, or use --workspace-file)",
+            root.display()
+        );
+    }
+
+    let (excluded, included): (Vec<_>, Vec<_>) =
+        patterns.iter().partition(|p| p.starts_with('!'));
+    let excluded: Vec<_> = excluded
+        .iter()
+        .filter_map(|p| Glob::new(&p[1..]).ok())
+        .map(|g| g.compile_matcher())
+        .collect();
+
+    let mut dirs = Vec::new();
+    for pattern in included {
+        for dir in expand(base, pattern)? {
+            let rel = relative_to(&dir, base);
+            if !excluded.iter().any(|g| g.is_match(rel)) && !dirs.contains(&dir)
+            {
+                dirs.push(dir);
+            }
+        }
+    }
+    // A package inside another is processed with it
+    let outer: Vec<PathBuf> = dirs
+        .iter()
+        .filter(|dir| !dirs.iter().any(|d| d != *dir && dir.starts_with(d)))
+        .cloned()
+        .collect();
+    Ok(outer
+        .into_iter()
+        .map(|dir| Member {
+            name: package_name(&dir),
+            dir,
+        })
+        .collect())
+}
+
+/// The member patterns pnpm or npm/yarn workspaces declare
+fn detect(root: &Path) -> Result<Vec<String>> {
+    if let Ok(content) = fs::read_to_string(root.join("pnpm-workspace.yaml")) {
+        // Only the `packages:` list matters
+        let mut in_packages = false;
+        let mut patterns = Vec::new();
+        for line in content.lines() {
+            let trimmed = line.trim();
+            if !line.starts_with([' ', '\t', '-']) && !trimmed.is_empty() {
+                in_packages = trimmed.starts_with("packages:");
+            } else if let Some(item) = trimmed.strip_prefix('-') {
+                let item = item.split(" #").next().unwrap_or(item).trim();
+                if in_packages {
+                    patterns.push(item.trim_matches(['\'', '"']).to_string());
+                }
+            }
+        }
+        return Ok(patterns);
+    }
+
+    let path = root.join("package.json");
+    let Ok(content) = fs::read_to_string(&path) else {
+        return Ok(Vec::new());
+    };
+    let package: Value = serde_json::from_str(&strip_jsonc(&content))
+        .with_context(|| format!("Malformed {}", path.display()))?;
+    let workspaces = package.get("workspaces");
+    let list = workspaces
+        .and_then(Value::as_array)
+        .or_else(|| workspaces?.get("packages")?.as_array());
+    Ok(list
+        .into_iter()
+        .flatten()
+        .filter_map(|p| p.as_str().map(String::from))
+        .collect())
+}
+
+/// The directories with a `package.json` that `pattern` matches
+fn expand(base: &Path, pattern: &str) -> Result<Vec<PathBuf>> {
+    let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+    // Walk from the part of the pattern before its first wildcard, which
+    // may lead out of `base`
+    let literal: PathBuf = Path::new(pattern)
+        .components()
+        .take_while(|c| {
+            !c.as_os_str()
+                .to_string_lossy()
+                .contains(['*', '?', '[', '{'])
+        })
+        .collect();
+    let start = normalize(&base.join(&literal));
+    if literal == Path::new(pattern) {
+        return Ok(vec![start].into_iter().filter(|d| d.is_dir()).collect());
+    }
+    let rest = Path::new(pattern).strip_prefix(&literal)?;
+    let matcher = Glob::new(&rest.to_string_lossy())
+        .with_context(|| format!("Invalid workspace pattern '{}'", pattern))?
+        .compile_matcher();
+
+    Ok(WalkDir::new(&start)
+        .min_depth(1)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| !in_node_modules(e.path()))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_dir())
+        .filter(|e| matcher.is_match(relative_to(e.path(), &start)))
+        .filter(|e| e.path().join("package.json").is_file())
+        .map(|e| e.into_path())
+        .collect())
+}
+
+fn package_name(dir: &Path) -> Option<String> {
+    let content = fs::read_to_string(dir.join("package.json")).ok()?;
+    let package: Value = serde_json::from_str(&content).ok()?;
+    package.get("name")?.as_str().map(String::from)
+}
+
+fn in_node_modules(path: &Path) -> bool {
+    path.components()
+        .any(|c| c == Component::Normal("node_modules".as_ref()))
+}
+
+/// Package names other packages import by, for rewriting imports like
+/// `@acme/ui/src/UserCard` that cross from one package into another
+pub fn packages(members: &[Member]) -> Vec<Alias> {
+    members
+        .iter()
+        .filter_map(|m| {
+            Some(Alias {
+                prefix: m.name.clone()?,
+                dir: m.dir.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Converts every member of the workspace as one run, like
+/// `convert_directory` does for a single directory: one plan across all of
+/// them, so imports from one package into another follow renames in both.
+/// The references in files directly in `root`, like a root `turbo.json`,
+/// are updated too.
+/// `node_modules`, where packages link to each other, isn't renamed.
+pub fn convert(
+    root: &Path,
+    members: &[Member],
+    imports: bool,
+    renames: bool,
+    opts: &Options,
+    report: &mut Report,
+) -> Result<Plan> {
+    let mut plan = Plan::default();
+    let mut links = Vec::new();
+    let mut files = Vec::new();
+    // A root that's a member itself is walked with the members
+    if imports && !members.iter().any(|m| m.dir == root) {
+        let sources = crate::source_files(root, opts, report)?;
+        let top = |e: &DirEntry| e.path().parent() == Some(root);
+        files.extend(sources.into_iter().filter(top));
+    }
+    for member in members {
+        let entries = crate::walk(&member.dir, opts, report)?;
+        // Links between packages in node_modules follow their renames
+        links.extend(crate::collect_symlinks(&entries));
+        if renames {
+            let planned =
+                crate::plan_entries(&member.dir, &entries, opts, report);
+            plan.extend(planned);
+        }
+        if imports {
+            let sources = crate::source_files(&member.dir, opts, report)?;
+            files.extend(sources);
+        }
+    }
+    let vetoed = opts.validators.review(root, &mut plan, report)?;
+    crate::check_plan_size(&plan, opts)?;
+    crate::ambiguity::review(&plan, opts)?;
+
+    let mode = crate::import_mode(imports, vetoed);
+    report.journal.begin_phase(mode, &opts.scan, &plan);
+    crate::update_references(&files, mode, &plan, opts, report)?;
+    report.journal.imports_done();
+
+    crate::rename_with_plan(&links, &plan, opts, report)?;
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_workspace_members() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        for dir in [
+            "packages/ui",
+            "packages/ui/node_modules/dep",
+            "apps/web",
+            "apps/Legacy",
+        ] {
+            fs::create_dir_all(root.join(dir))?;
+            fs::write(root.join(dir).join("package.json"), "{}")?;
+        }
+        fs::write(
+            root.join("packages/ui/package.json"),
+            r#"{ "name": "@acme/ui" }"#,
+        )?;
+        fs::write(
+            root.join("pnpm-workspace.yaml"),
+            "packages:\n  - 'packages/*'\n  - apps/*  # apps\n  - '!apps/Legacy'\ncatalog:\n  - nope\n",
+        )?;
+
+        let found = members(root, None)?;
+        assert_eq!(
+            found,
+            [
+                Member {
+                    name: Some("@acme/ui".into()),
+                    dir: root.join("packages/ui"),
+                },
+                Member {
+                    name: None,
+                    dir: root.join("apps/web"),
+                },
+            ]
+        );
+
+        // A manifest can reach outside the root
+        let other = TempDir::new()?;
+        fs::write(other.path().join("package.json"), "{}")?;
+        let manifest = root.join("workspace.txt");
+        fs::write(
+            &manifest,
+            format!("# Packages\npackages/ui\n{}\n", other.path().display()),
+        )?;
+        let found = members(root, Some(&manifest))?;
+        assert_eq!(found[1].dir, other.path());
+        Ok(())
+    }
+}
@@ -0,0 +1,212 @@
+use crate::journal::Change;
+use crate::report::Report;
+use crate::temp_names::Kind as TempKind;
+use crate::{fs_util, Options};
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+
+/// How many rewritten files are held back and then written together
+const CHUNK: usize = 64;
+
+/// How far writes are flushed to disk before a run carries on, from
+/// `--fsync`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Fsync {
+    /// Leave it to the operating system; a crash can lose the last writes
+    #[default]
+    None,
+    /// Flush each file before it replaces the original
+    File,
+    /// Flush each file, then each directory files were replaced in, so the
+    /// replacements themselves survive a crash
+    Dir,
+}
+
+/// A rewritten file waiting to be written
+#[derive(Debug)]
+pub struct Queued {
+    path: PathBuf,
+    original: Vec<u8>,
+    bytes: Vec<u8>,
+    /// Named up front, so `--temp-seed` names the same file the same way
+    /// whichever thread writes it
+    temp: PathBuf,
+}
+
+/// Queues `bytes` to be written over the file at `path`, whose contents
+/// were `original`. Every `CHUNK` files, the queue is written out.
+pub fn queue(
+    path: &Path,
+    original: Vec<u8>,
+    bytes: Vec<u8>,
+    opts: &Options,
+    report: &mut Report,
+) -> Result<()> {
+    let name = path.file_name().unwrap_or_default();
+    report.writes.push(Queued {
+        path: path.to_path_buf(),
+        original,
+        bytes,
+        temp: path.with_file_name(opts.temp.next(TempKind::Write, name)),
+    });
+    if report.writes.len() >= CHUNK {
+        flush(opts, report)?;
+    }
+    Ok(())
+}
+
+/// Writes out every queued file, spread over a few threads, and records
+/// each one written in the journal. With `--fsync dir`, each directory
+/// written in is flushed once, however many files it got.
+pub fn flush(opts: &Options, report: &mut Report) -> Result<()> {
+    let queued = std::mem::take(&mut report.writes);
+    if queued.is_empty() {
+        return Ok(());
+    }
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let per_thread = queued.len().div_ceil(threads);
+    let results: Vec<io::Result<()>> = thread::scope(|scope| {
+        let workers: Vec<_> = queued
+            .chunks(per_thread)
+            .map(|files| {
+                scope.spawn(move || {
+                    files
+                        .iter()
+                        .map(|file| {
+                            fs_util::with_retry(opts.retries, || {
+                                write_file(file, opts)
+                            })
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
+    });
+
+    // Everything written goes in the journal before any failure can stop
+    // the run, so a rollback finds it
+    let mut failures = Vec::new();
+    let mut dirs = BTreeSet::new();
+    for (file, result) in queued.into_iter().zip(results) {
+        match result {
+            Ok(()) => {
+                if let Some(dir) = file.path.parent() {
+                    dirs.insert(dir.to_path_buf());
+                }
+                report.journal.record(Change::Wrote {
+                    path: file.path,
+                    original: file.original,
+                });
+            }
+            Err(err) => failures.push((file.path, err)),
+        }
+    }
+    for (path, err) in failures {
+        let result = Err(err).with_context(|| {
+            format!("Failed to write file: {}", opts.display.show(&path))
+        });
+        report.check::<()>(&path, result)?;
+    }
+    if opts.fsync == Fsync::Dir {
+        for dir in dirs {
+            let result = sync_dir(&dir).with_context(|| {
+                format!(
+                    "Failed to flush directory: {}",
+                    opts.display.show(&dir)
+                )
+            });
+            report.check(&dir, result)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes a queued file over the original through its temporary file,
+/// renamed into place, so a crash never leaves it half-written. Hard-linked
+/// files are written in place to keep their links, as is everything on
+/// filesystems where a rename can't replace a file.
+fn write_file(file: &Queued, opts: &Options) -> io::Result<()> {
+    let sync = opts.fsync != Fsync::None;
+    let meta = fs::metadata(&file.path)?;
+    #[cfg(unix)]
+    let linked = std::os::unix::fs::MetadataExt::nlink(&meta) > 1;
+    #[cfg(not(unix))]
+    let linked = false;
+    if linked || !opts.fs.replacing_rename {
+        return write(&file.path, &file.bytes, sync);
+    }
+
+    write(&file.temp, &file.bytes, sync)
+        .and_then(|()| {
+            fs_util::copy_metadata(&file.path, &file.temp, &meta);
+            fs::rename(&file.temp, &file.path)
+        })
+        .inspect_err(|_| {
+            let _ = fs::remove_file(&file.temp);
+        })
+}
+
+/// Writes `bytes` to `path`, flushing them to disk if `sync` is set
+fn write(path: &Path, bytes: &[u8], sync: bool) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(bytes)?;
+    if sync {
+        file.sync_all()?;
+    }
+    Ok(())
+}
+
+/// Flushes the entries of `dir`, so files renamed into it stay renamed
+/// after a crash. Windows can't open a directory to flush it, and its
+/// filesystems journal renames anyway.
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    if cfg!(windows) {
+        return Ok(());
+    }
+    File::open(dir)?.sync_all()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_flush_writes_and_journals() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        let opts = Options {
+            fsync: Fsync::Dir,
+            ..Options::default()
+        };
+        let mut report = Report::default();
+        for i in 0..CHUNK + 3 {
+            let path = root.join(format!("file-{i}.ts"));
+            fs::write(&path, "old")?;
+            let bytes = format!("new {i}").into_bytes();
+            queue(&path, b"old".to_vec(), bytes, &opts, &mut report)?;
+        }
+        assert_eq!(report.writes.len(), 3);
+        assert_eq!(fs::read_to_string(root.join("file-0.ts"))?, "new 0");
+        assert_eq!(fs::read_to_string(root.join("file-65.ts"))?, "old");
+
+        let missing = root.join("missing.ts");
+        queue(&missing, Vec::new(), b"new".to_vec(), &opts, &mut report)?;
+        flush(&opts, &mut report)?;
+        let last = format!("new {}", CHUNK + 2);
+        assert_eq!(fs::read_to_string(root.join("file-66.ts"))?, last);
+        assert!(!missing.exists());
+        assert_eq!(report.journal.changes.len(), CHUNK + 3);
+        assert_eq!(report.failures.len(), 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+use crate::plan::Plan;
+use crate::scanner::{self, rewrite_file_path, splice, ScanOptions, Scanner};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// The attributes `--xml-attributes` lists unless it's given
+pub const DEFAULT_ATTRIBUTES: [&str; 3] = ["src", "href", "xlink:href"];
+
+/// XML templates: `.xml`, MJML emails and XSLT stylesheets, whose
+/// references to other files are attributes like `<img src="...">`,
+/// `<xsl:include href="...">` and `<use xlink:href="...">`
+pub struct XmlScanner;
+
+impl Scanner for XmlScanner {
+    fn matches(&self, path: &Path) -> bool {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|ext| ["xml", "mjml", "xsl", "xslt"].contains(&ext))
+    }
+
+    fn convert(
+        &self,
+        path: &Path,
+        content: &str,
+        scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_attributes(
+            path,
+            content,
+            &scan.xml_attributes,
+            scanner::converted_path,
+        )
+    }
+
+    fn follow_plan(
+        &self,
+        path: &Path,
+        content: &str,
+        plan: &Plan,
+        scan: &ScanOptions,
+    ) -> (String, usize) {
+        rewrite_attributes(path, content, &scan.xml_attributes, |found| {
+            plan.target_of(found)
+        })
+    }
+
+    fn orphans(
+        &self,
+        path: &Path,
+        content: &str,
+        scan: &ScanOptions,
+    ) -> Vec<String> {
+        let dir = path.parent().unwrap_or(Path::new(""));
+        attribute_paths(content, &scan.xml_attributes)
+            .into_iter()
+            .filter(|(file, _)| scanner::leads_nowhere(dir, file, &[]))
+            .map(|(file, _)| file.to_string())
+            .collect()
+    }
+}
+
+/// Rewrites the relative paths in `attributes` that name a file or
+/// directory, relative to the file they're in
+fn rewrite_attributes(
+    path: &Path,
+    content: &str,
+    attributes: &[String],
+    rename: impl Fn(&Path) -> Option<PathBuf>,
+) -> (String, usize) {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut edits = Vec::new();
+    for (file, start) in attribute_paths(content, attributes) {
+        edits.extend(rewrite_file_path(dir, file, start, &[], &rename));
+    }
+    splice(content, edits)
+}
+
+/// The relative paths in `attributes`, with where each starts. URLs,
+/// absolute paths, fragments and values with template expressions are
+/// left out.
+fn attribute_paths<'a>(
+    content: &'a str,
+    attributes: &[String],
+) -> Vec<(&'a str, usize)> {
+    if attributes.is_empty() {
+        return Vec::new();
+    }
+    let names: Vec<String> =
+        attributes.iter().map(|name| regex::escape(name)).collect();
+    let attribute = Regex::new(&format!(
+        r#"\s(?:{})\s*=\s*(?:"([^"]*)"|'([^']*)')"#,
+        names.join("|")
+    ))
+    .unwrap();
+
+    let mut paths = Vec::new();
+    for caps in attribute.captures_iter(content) {
+        let value = caps.iter().skip(1).flatten().next().unwrap();
+        let file = value.as_str();
+        if file.contains([':', '{', '$'])
+            || file.starts_with(['/', '#', '@', '~'])
+        {
+            continue;
+        }
+        // `Assets/Logo.png#icon` names a part of the file
+        let file = file.split(['#', '?']).next().unwrap_or(file);
+        paths.push((file, value.start()));
+    }
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_xml_attributes() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("Emails/Partials"))?;
+        fs::create_dir_all(root.join("Emails/Images"))?;
+        fs::write(root.join("Emails/Partials/HeaderBar.mjml"), "")?;
+        fs::write(root.join("Emails/Images/BrandLogo.png"), "")?;
+        fs::write(root.join("Emails/Images/IconSprite.svg"), "")?;
+
+        let content = r##"<mjml>
+  <mj-include path="./Partials/HeaderBar.mjml" />
+  <mj-image src="Images/BrandLogo.png" href="https://example.com/Images" />
+  <svg><use xlink:href='Images/IconSprite.svg#Close'/></svg>
+  <mj-image src="{{ LogoUrl }}" title="Images/BrandLogo.png" />
+</mjml>
+"##;
+        let mut attributes: Vec<String> =
+            DEFAULT_ATTRIBUTES.map(String::from).into();
+        let (converted, changes) = rewrite_attributes(
+            &root.join("Emails/Welcome.mjml"),
+            content,
+            &attributes,
+            scanner::converted_path,
+        );
+        assert_eq!(
+            converted,
+            content
+                .replace("src=\"Images/BrandLogo", "src=\"images/brand-logo")
+                .replace("Images/IconSprite", "images/icon-sprite")
+        );
+        assert_eq!(changes, 4);
+
+        attributes.push("path".to_string());
+        let (converted, _) = rewrite_attributes(
+            &root.join("Emails/Welcome.mjml"),
+            content,
+            &attributes,
+            scanner::converted_path,
+        );
+        assert!(converted.contains("./partials/header-bar.mjml"));
+
+        let scan = ScanOptions {
+            xml_attributes: attributes,
+            ..ScanOptions::default()
+        };
+        let orphans = XmlScanner.orphans(
+            &root.join("Emails/Welcome.mjml"),
+            &content.replace("HeaderBar", "FooterBar"),
+            &scan,
+        );
+        assert_eq!(orphans, ["./Partials/FooterBar.mjml"]);
+        Ok(())
+    }
+}